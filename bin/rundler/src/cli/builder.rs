@@ -11,7 +11,7 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 
 use anyhow::{bail, Context};
 use clap::Args;
@@ -115,6 +115,26 @@ pub struct BuilderArgs {
     )]
     max_bundle_size: u64,
 
+    /// Maximum number of distinct senders to include in one bundle.
+    /// Unlimited if not set.
+    #[arg(
+        long = "builder.max_bundle_senders",
+        name = "builder.max_bundle_senders",
+        env = "BUILDER_MAX_BUNDLE_SENDERS"
+    )]
+    max_bundle_senders: Option<u64>,
+
+    /// Percentage to add to the base fee when setting the bundle max fee, so that a
+    /// bundle is still priced high enough if the base fee rises before it is mined.
+    /// Not applied on chains without EIP-1559.
+    #[arg(
+        long = "builder.base_fee_headroom_percent",
+        name = "builder.base_fee_headroom_percent",
+        env = "BUILDER_BASE_FEE_HEADROOM_PERCENT",
+        default_value = "0"
+    )]
+    base_fee_headroom_percent: u64,
+
     /// Choice of what sender type to to use for transaction submission.
     /// Defaults to the value of `raw`. Other options include `flashbots`,
     /// `conditional` and `bloxroute`
@@ -245,6 +265,46 @@ pub struct BuilderArgs {
     )]
     max_replacement_underpriced_blocks: u64,
 
+    /// If set, bundling is triggered every this many new blocks, for chains with variable
+    /// block times. Otherwise bundling is triggered on every new block and on the chain's
+    /// max send interval.
+    #[arg(
+        long = "builder.bundle_block_interval",
+        name = "builder.bundle_block_interval",
+        env = "BUILDER_BUNDLE_BLOCK_INTERVAL"
+    )]
+    bundle_block_interval: Option<u64>,
+
+    /// If set, bundle transactions include an EIP-2930 access list of the storage
+    /// accessed during simulation, which can reduce gas on some chains.
+    #[arg(
+        long = "builder.use_access_list",
+        name = "builder.use_access_list",
+        env = "BUILDER_USE_ACCESS_LIST",
+        default_value = "false"
+    )]
+    use_access_list: bool,
+
+    /// Maximum number of bundle transactions in flight at once across all bundle
+    /// builders. Unlimited if not set.
+    #[arg(
+        long = "builder.max_bundles_in_flight",
+        name = "builder.max_bundles_in_flight",
+        env = "BUILDER_MAX_BUNDLES_IN_FLIGHT",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    max_bundles_in_flight: Option<u64>,
+
+    /// The minimum number of seconds that must remain before an operation's
+    /// valid until timestamp for it to be included in a bundle.
+    #[arg(
+        long = "builder.time_range_buffer_secs",
+        name = "builder.time_range_buffer_secs",
+        env = "BUILDER_TIME_RANGE_BUFFER_SECS",
+        default_value = "60"
+    )]
+    time_range_buffer_secs: u64,
+
     /// The index offset to apply to the builder index
     #[arg(
         long = "builder_index_offset",
@@ -264,6 +324,10 @@ impl BuilderArgs {
         common: &CommonArgs,
         remote_address: Option<SocketAddr>,
     ) -> anyhow::Result<BuilderTaskArgs> {
+        if self.use_access_list && !chain_spec.eip1559_enabled {
+            bail!("builder.use_access_list requires a chain with EIP-1559 enabled");
+        }
+
         let priority_fee_mode = PriorityFeeMode::try_from(
             common.priority_fee_mode_kind.as_str(),
             common.priority_fee_mode_value,
@@ -340,6 +404,7 @@ impl BuilderArgs {
             entry_points,
             chain_spec,
             unsafe_mode: common.unsafe_mode,
+            allow_trace_fallback: common.allow_trace_fallback,
             rpc_url,
             private_keys,
             aws_kms_key_ids: self.aws_kms_key_ids.clone(),
@@ -350,15 +415,22 @@ impl BuilderArgs {
             redis_uri: self.redis_uri.clone(),
             redis_lock_ttl_millis: self.redis_lock_ttl_millis,
             max_bundle_size: self.max_bundle_size,
+            max_bundle_senders: self.max_bundle_senders,
+            max_bundle_pre_verification_gas: common.max_bundle_pre_verification_gas,
             max_bundle_gas: common.max_bundle_gas,
             bundle_priority_fee_overhead_percent: common.bundle_priority_fee_overhead_percent,
+            bundle_base_fee_headroom_percent: self.base_fee_headroom_percent,
             priority_fee_mode,
+            time_range_buffer: Duration::from_secs(self.time_range_buffer_secs),
             sender_args,
             sim_settings: common.try_into()?,
             max_blocks_to_wait_for_mine: self.max_blocks_to_wait_for_mine,
             replacement_fee_percent_increase: self.replacement_fee_percent_increase,
             max_cancellation_fee_increases: self.max_cancellation_fee_increases,
             max_replacement_underpriced_blocks: self.max_replacement_underpriced_blocks,
+            bundle_block_interval: self.bundle_block_interval,
+            use_access_list: self.use_access_list,
+            max_bundles_in_flight: self.max_bundles_in_flight.map(|max| max as usize),
             remote_address,
         })
     }
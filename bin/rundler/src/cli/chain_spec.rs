@@ -88,6 +88,39 @@ pub fn resolve_chain_spec(network: &Option<String>, file: &Option<String>) -> Ch
     c.try_deserialize().expect("should deserialize config")
 }
 
+/// Parse the network flag, accepting either a hardcoded network name or the
+/// chain ID of a hardcoded network, and return the network name
+pub fn parse_network(network: &str) -> Result<String, String> {
+    let network = network.to_lowercase();
+    if HARDCODED_CHAIN_SPECS.contains(&network.as_str()) {
+        return Ok(network);
+    }
+
+    let id = network.parse::<u64>().map_err(|_| {
+        format!(
+            "unknown network: {network}, expected a chain ID or one of {}",
+            HARDCODED_CHAIN_SPECS.join(", ")
+        )
+    })?;
+    get_hardcoded_network_by_id(id)
+        .map(str::to_string)
+        .ok_or_else(|| format!("no hardcoded network with chain ID {id}"))
+}
+
+fn get_hardcoded_network_by_id(id: u64) -> Option<&'static str> {
+    HARDCODED_CHAIN_SPECS.iter().copied().find(|network| {
+        Config::builder()
+            .add_source(File::from_str(
+                get_hardcoded_chain_spec(network),
+                FileFormat::Toml,
+            ))
+            .build()
+            .expect("should build hardcoded config")
+            .get::<u64>("id")
+            .is_ok_and(|network_id| network_id == id)
+    })
+}
+
 macro_rules! define_hardcoded_chain_specs {
     ($($network:ident),+) => {
         paste! {
@@ -123,5 +156,133 @@ define_hardcoded_chain_specs!(
     polygon_amoy,
     avax,
     bera_bartio,
-    avax_fuji
+    avax_fuji,
+    scroll,
+    scroll_sepolia
 );
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::U256;
+    use rundler_provider::MockEntryPointV0_6;
+    use rundler_sim::gas;
+    use rundler_types::{chain::PaymasterGasPolicy, v0_6, UserOperation};
+
+    use super::*;
+
+    fn static_pvg(network: &str) -> U256 {
+        let chain_spec = resolve_chain_spec(&Some(network.to_string()), &None);
+        v0_6::UserOperation::default().calc_static_pre_verification_gas(&chain_spec, true)
+    }
+
+    // Required PVG of an op on the given network, with the L1 gas oracle reporting `l1_gas`
+    async fn required_pvg(network: &str, l1_gas: u64) -> U256 {
+        let chain_spec = resolve_chain_spec(&Some(network.to_string()), &None);
+        let mut entry_point = MockEntryPointV0_6::new();
+        entry_point
+            .expect_address()
+            .return_const(chain_spec.entry_point_address_v0_6);
+        entry_point
+            .expect_calc_l1_gas()
+            .returning(move |_, _, _| Ok(l1_gas.into()));
+        let op = v0_6::UserOperation {
+            max_fee_per_gas: 1.into(),
+            ..Default::default()
+        };
+
+        gas::calc_required_pre_verification_gas(&chain_spec, &entry_point, &op, 1.into())
+            .await
+            .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_hardcoded_chain_specs() {
+        for network in HARDCODED_CHAIN_SPECS {
+            let chain_spec = resolve_chain_spec(&Some(network.to_string()), &None);
+            assert_ne!(chain_spec.id, 0);
+        }
+    }
+
+    #[test]
+    fn test_scroll_chain_spec() {
+        for network in ["scroll", "scroll_sepolia"] {
+            let chain_spec = resolve_chain_spec(&Some(network.to_string()), &None);
+            assert!(chain_spec.name.starts_with("Scroll"));
+            assert!(chain_spec.calldata_pre_verification_gas);
+            assert!(!chain_spec.l1_gas_oracle_contract_address.is_zero());
+        }
+    }
+
+    #[test]
+    fn test_parse_network_by_chain_id() {
+        for network in HARDCODED_CHAIN_SPECS {
+            let chain_spec = resolve_chain_spec(&Some(network.to_string()), &None);
+            assert_eq!(parse_network(&chain_spec.id.to_string()).unwrap(), *network);
+        }
+        assert_eq!(parse_network("Scroll").unwrap(), "scroll");
+        assert!(parse_network("unknown").is_err());
+        assert!(parse_network("999999999").is_err());
+    }
+
+    #[test]
+    fn test_chain_spec_overheads_change_pvg() {
+        assert_ne!(static_pvg("ethereum"), static_pvg("avax"));
+    }
+
+    #[test]
+    fn test_overhead_presets_have_distinct_pvg() {
+        let presets = ["ethereum", "arbitrum", "optimism", "scroll", "base"];
+        let pvgs = presets
+            .iter()
+            .map(|network| {
+                let chain_spec = resolve_chain_spec(&Some(parse_network(network).unwrap()), &None);
+                let id = chain_spec.id.to_string();
+                // selecting the preset by chain ID resolves the same overheads
+                assert_eq!(
+                    static_pvg(&parse_network(&id).unwrap()),
+                    static_pvg(network)
+                );
+                static_pvg(network)
+            })
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(pvgs.len(), presets.len());
+    }
+
+    #[tokio::test]
+    async fn test_l1_data_fee_changes_pvg() {
+        let l1_gas = 10_000;
+        let ethereum = required_pvg("ethereum", l1_gas).await;
+        assert_eq!(ethereum, required_pvg("ethereum", 0).await);
+        let scroll = required_pvg("scroll", l1_gas).await;
+        assert_eq!(scroll, required_pvg("scroll", 0).await + l1_gas);
+    }
+
+    #[test]
+    fn test_resolve_paymaster_gas_policy() {
+        let chain_spec = resolve_chain_spec(&Some("ethereum".to_string()), &None);
+        assert_eq!(
+            chain_spec.paymaster_gas_policy_v0_6,
+            PaymasterGasPolicy::default()
+        );
+
+        let path = std::env::temp_dir().join(format!("chain_spec_{}.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            "[paymaster_gas_policy_v0_6]\nmax_gas_cost_multiplier = 4\n",
+        )
+        .unwrap();
+        let chain_spec = resolve_chain_spec(
+            &Some("ethereum".to_string()),
+            &Some(path.to_str().unwrap().to_string()),
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            chain_spec.paymaster_gas_policy_v0_6,
+            PaymasterGasPolicy {
+                max_gas_cost_multiplier: 4,
+                ..Default::default()
+            }
+        );
+    }
+}
@@ -17,14 +17,15 @@ use itertools::Itertools;
 use metrics::gauge;
 use metrics_exporter_prometheus::PrometheusBuilder;
 use metrics_process::Collector;
-use metrics_util::layers::{PrefixLayer, Stack};
+use metrics_util::layers::{FanoutBuilder, Layer, PrefixLayer};
+use rundler_rpc::MetricsSnapshot;
 
 pub fn initialize<'a>(
     sample_interval_millis: u64,
     listen_addr: SocketAddr,
     tags: impl IntoIterator<Item = &'a String>,
     buckets: &[f64],
-) -> anyhow::Result<()> {
+) -> anyhow::Result<MetricsSnapshot> {
     let mut builder = PrometheusBuilder::new().with_http_listener(listen_addr);
 
     let tags: Vec<(&str, &str)> = tags
@@ -39,9 +40,14 @@ pub fn initialize<'a>(
 
     let (recorder, exporter) = builder.build()?;
     tokio::spawn(exporter);
-    Stack::new(recorder)
-        .push(PrefixLayer::new("rundler"))
-        .install()?;
+    // the snapshot served over RPC sees the unprefixed metric names
+    let snapshot = MetricsSnapshot::default();
+    let fanout = FanoutBuilder::default()
+        .add_recorder(PrefixLayer::new("rundler").layer(recorder))
+        .add_recorder(snapshot.clone())
+        .build();
+    metrics::set_global_recorder(fanout)
+        .map_err(|_| anyhow::anyhow!("metrics recorder should only be installed once"))?;
 
     tokio::spawn(async move {
         let collector = Collector::default();
@@ -62,7 +68,7 @@ pub fn initialize<'a>(
         }
     });
 
-    Ok(())
+    Ok(snapshot)
 }
 
 const TOKIO_PREFIX: &str = "tokio_rt_";
@@ -11,6 +11,8 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
+use std::collections::HashMap;
+
 use anyhow::{bail, Context};
 use clap::{builder::PossibleValuesParser, Args, Parser, Subcommand};
 
@@ -27,10 +29,12 @@ use builder::BuilderCliArgs;
 use node::NodeCliArgs;
 use pool::PoolCliArgs;
 use rpc::RpcCliArgs;
-use rundler_rpc::{EthApiSettings, RundlerApiSettings};
+use rundler_rpc::{ErrorCompatMode, EthApiSettings, RundlerApiSettings};
 use rundler_sim::{
-    EstimationSettings, PrecheckSettings, PriorityFeeMode, SimulationSettings, MIN_CALL_GAS_LIMIT,
+    simulation::default_allowed_precompiles, EstimationSettings, PrecheckSettings, PriorityFeeMode,
+    SimulationSettings, StakeRequirement, MIN_CALL_GAS_LIMIT,
 };
+use rundler_types::EntityType;
 
 /// Main entry point for the CLI
 ///
@@ -42,7 +46,7 @@ pub async fn run() -> anyhow::Result<()> {
     tracing::info!("Parsed CLI options: {:#?}", opt);
 
     let metrics_addr = format!("{}:{}", opt.metrics.host, opt.metrics.port).parse()?;
-    metrics::initialize(
+    let metrics_snapshot = metrics::initialize(
         opt.metrics.sample_interval_millis,
         metrics_addr,
         &opt.metrics.tags,
@@ -54,9 +58,9 @@ pub async fn run() -> anyhow::Result<()> {
     tracing::info!("Chain spec: {:#?}", cs);
 
     match opt.command {
-        Command::Node(args) => node::run(cs, *args, opt.common).await?,
+        Command::Node(args) => node::run(cs, *args, opt.common, metrics_snapshot).await?,
         Command::Pool(args) => pool::run(cs, args, opt.common).await?,
-        Command::Rpc(args) => rpc::run(cs, args, opt.common).await?,
+        Command::Rpc(args) => rpc::run(cs, args, opt.common, metrics_snapshot).await?,
         Command::Builder(args) => builder::run(cs, args, opt.common).await?,
     }
 
@@ -96,12 +100,12 @@ enum Command {
 #[derive(Debug, Args)]
 #[command(next_help_heading = "Common")]
 pub struct CommonArgs {
-    /// Network flag
+    /// Network flag, a hardcoded network name or chain ID
     #[arg(
         long = "network",
         name = "network",
         env = "NETWORK",
-        value_parser = PossibleValuesParser::new(chain_spec::HARDCODED_CHAIN_SPECS),
+        value_parser = chain_spec::parse_network,
         global = true)
     ]
     network: Option<String>,
@@ -115,7 +119,7 @@ pub struct CommonArgs {
     )]
     chain_spec: Option<String>,
 
-    /// ETH Node HTTP URL to connect to
+    /// ETH Node HTTP URL to connect to, or a comma-separated list of URLs to fail over across
     #[arg(
         long = "node_http",
         name = "node_http",
@@ -128,6 +132,15 @@ pub struct CommonArgs {
     #[arg(long = "unsafe", env = "UNSAFE", global = true)]
     unsafe_mode: bool,
 
+    /// Fall back to unsafe mode if the node does not support `debug_traceCall` with custom tracers
+    #[arg(
+        long = "allow_trace_fallback",
+        name = "allow_trace_fallback",
+        env = "ALLOW_TRACE_FALLBACK",
+        global = true
+    )]
+    allow_trace_fallback: bool,
+
     #[arg(
         long = "max_verification_gas",
         name = "max_verification_gas",
@@ -146,6 +159,14 @@ pub struct CommonArgs {
     )]
     max_bundle_gas: u64,
 
+    #[arg(
+        long = "max_bundle_pre_verification_gas",
+        name = "max_bundle_pre_verification_gas",
+        env = "MAX_BUNDLE_PRE_VERIFICATION_GAS",
+        global = true
+    )]
+    max_bundle_pre_verification_gas: Option<u64>,
+
     #[arg(
         long = "min_stake_value",
         name = "min_stake_value",
@@ -164,6 +185,18 @@ pub struct CommonArgs {
     )]
     min_unstake_delay: u32,
 
+    /// Per entity type minimum stake and unstake delay, as a comma separated list of
+    /// `entity=stake:delay`, i.e. `paymaster=2000000000000000000:172800`. Entity types
+    /// without an entry use `min_stake_value` and `min_unstake_delay`.
+    #[arg(
+        long = "entity_stake_requirements",
+        name = "entity_stake_requirements",
+        env = "ENTITY_STAKE_REQUIREMENTS",
+        value_delimiter = ',',
+        global = true
+    )]
+    entity_stake_requirements: Vec<String>,
+
     /// String representation of the timeout of a custom tracer in a format that is parsable by the
     /// `ParseDuration` function on the ethereum node. See Docs: https://pkg.go.dev/time#ParseDuration
     #[arg(
@@ -175,6 +208,57 @@ pub struct CommonArgs {
     )]
     tracer_timeout: String,
 
+    /// Maximum number of seconds the simulation block may lag behind wall-clock time.
+    /// If unset, the simulation block age is not checked.
+    #[arg(
+        long = "max_sim_block_age_seconds",
+        name = "max_sim_block_age_seconds",
+        env = "MAX_SIM_BLOCK_AGE_SECONDS",
+        global = true
+    )]
+    max_sim_block_age_seconds: Option<u64>,
+
+    /// Comma separated list of precompile addresses that may be called during validation.
+    /// Defaults to the ERC-4337 allowed precompiles.
+    #[arg(
+        long = "allowed_precompiles",
+        name = "allowed_precompiles",
+        env = "ALLOWED_PRECOMPILES",
+        value_delimiter = ',',
+        global = true
+    )]
+    allowed_precompiles: Option<Vec<String>>,
+
+    /// Size in bytes above which a validation trace, and its operation, are rejected.
+    /// Checked after the trace is received, so this does not bound memory use.
+    #[arg(
+        long = "reject_trace_size_bytes",
+        name = "reject_trace_size_bytes",
+        env = "REJECT_TRACE_SIZE_BYTES",
+        default_value = "10485760",
+        global = true
+    )]
+    reject_trace_size_bytes: usize,
+
+    /// If set, only accept operations whose paymaster is staked.
+    #[arg(
+        long = "require_staked_paymaster",
+        name = "require_staked_paymaster",
+        env = "REQUIRE_STAKED_PAYMASTER",
+        default_value = "false",
+        global = true
+    )]
+    require_staked_paymaster: bool,
+
+    /// Maximum number of seconds in the future an operation's `validAfter` may be.
+    #[arg(
+        long = "max_valid_after_horizon_seconds",
+        name = "max_valid_after_horizon_seconds",
+        env = "MAX_VALID_AFTER_HORIZON_SECONDS",
+        global = true
+    )]
+    max_valid_after_horizon_seconds: Option<u64>,
+
     /// Amount of blocks to search when calling eth_getUserOperationByHash.
     /// Defaults from 0 to latest block
     #[arg(
@@ -185,6 +269,18 @@ pub struct CommonArgs {
     )]
     user_operation_event_block_distance: Option<u64>,
 
+    /// Error response convention for the `eth_` namespace.
+    /// `standard` follows ERC-4337, `vendor` follows Alchemy/Infura conventions
+    #[arg(
+        long = "error_compat_mode",
+        name = "error_compat_mode",
+        env = "ERROR_COMPAT_MODE",
+        value_parser = PossibleValuesParser::new(["standard", "vendor"]),
+        default_value = "standard",
+        global = true
+    )]
+    error_compat_mode: String,
+
     #[arg(
         long = "max_simulate_handle_ops_gas",
         name = "max_simulate_handle_ops_gas",
@@ -203,6 +299,24 @@ pub struct CommonArgs {
     )]
     verification_estimation_gas_fee: u64,
 
+    #[arg(
+        long = "estimation_with_op_fees",
+        name = "estimation_with_op_fees",
+        env = "ESTIMATION_WITH_OP_FEES",
+        default_value = "false",
+        global = true
+    )]
+    estimation_with_op_fees: bool,
+
+    #[arg(
+        long = "paymaster_post_op_gas",
+        name = "paymaster_post_op_gas",
+        env = "PAYMASTER_POST_OP_GAS",
+        default_value = "10000",
+        global = true
+    )]
+    paymaster_post_op_gas: u64,
+
     #[arg(
         long = "bundle_priority_fee_overhead_percent",
         name = "bundle_priority_fee_overhead_percent",
@@ -249,6 +363,49 @@ pub struct CommonArgs {
     )]
     pre_verification_gas_accept_percent: u64,
 
+    #[arg(
+        long = "min_call_gas_limit",
+        name = "min_call_gas_limit",
+        env = "MIN_CALL_GAS_LIMIT",
+        default_value = "9100",
+        global = true
+    )]
+    min_call_gas_limit: u64,
+
+    #[arg(
+        long = "max_init_code_bytes",
+        name = "max_init_code_bytes",
+        env = "MAX_INIT_CODE_BYTES",
+        default_value = "32768",
+        global = true
+    )]
+    max_init_code_bytes: usize,
+
+    #[arg(
+        long = "max_verification_gas_to_call_gas_ratio",
+        name = "max_verification_gas_to_call_gas_ratio",
+        env = "MAX_VERIFICATION_GAS_TO_CALL_GAS_RATIO",
+        global = true
+    )]
+    max_verification_gas_to_call_gas_ratio: Option<u64>,
+
+    #[arg(
+        long = "reject_duplicate_entities",
+        name = "reject_duplicate_entities",
+        env = "REJECT_DUPLICATE_ENTITIES",
+        default_value = "false",
+        global = true
+    )]
+    reject_duplicate_entities: bool,
+
+    #[arg(
+        long = "max_nonce_gap",
+        name = "max_nonce_gap",
+        env = "MAX_NONCE_GAP",
+        global = true
+    )]
+    max_nonce_gap: Option<u64>,
+
     #[arg(
         long = "aws_region",
         name = "aws_region",
@@ -334,9 +491,11 @@ impl TryFrom<&CommonArgs> for EstimationSettings {
             max_call_gas,
             max_paymaster_verification_gas: value.max_verification_gas,
             max_paymaster_post_op_gas: max_call_gas,
+            paymaster_post_op_gas: value.paymaster_post_op_gas,
             max_total_execution_gas: value.max_bundle_gas,
             max_simulate_handle_ops_gas: value.max_simulate_handle_ops_gas,
             verification_estimation_gas_fee: value.verification_estimation_gas_fee,
+            simulate_with_op_fees: value.estimation_with_op_fees,
         })
     }
 }
@@ -355,6 +514,12 @@ impl TryFrom<&CommonArgs> for PrecheckSettings {
             )?,
             base_fee_accept_percent: value.base_fee_accept_percent,
             pre_verification_gas_accept_percent: value.pre_verification_gas_accept_percent,
+            min_call_gas_limit: value.min_call_gas_limit.into(),
+            max_init_code_bytes: value.max_init_code_bytes,
+            max_verification_gas_to_call_gas_ratio: value.max_verification_gas_to_call_gas_ratio,
+            max_pre_verification_gas: value.max_bundle_pre_verification_gas.map(Into::into),
+            reject_duplicate_entities: value.reject_duplicate_entities,
+            max_nonce_gap: value.max_nonce_gap,
         })
     }
 }
@@ -367,19 +532,62 @@ impl TryFrom<&CommonArgs> for SimulationSettings {
             bail!("Invalid value for tracer_timeout, must be parsable by the ParseDuration function. See docs https://pkg.go.dev/time#ParseDuration")
         }
 
+        let allowed_precompiles = match &value.allowed_precompiles {
+            Some(addresses) => addresses
+                .iter()
+                .map(|a| a.parse())
+                .collect::<Result<Vec<_>, _>>()
+                .context("Invalid value for allowed_precompiles, must be a list of addresses")?,
+            None => default_allowed_precompiles(),
+        };
+
+        let entity_stake_requirements = value
+            .entity_stake_requirements
+            .iter()
+            .map(|entry| {
+                let (kind, requirement) = entry
+                    .split_once('=')
+                    .with_context(|| format!("invalid entity stake requirement: {entry}"))?;
+                let kind: EntityType = kind.parse()?;
+                let (stake, delay) = requirement
+                    .split_once(':')
+                    .with_context(|| format!("invalid entity stake requirement: {entry}"))?;
+                let requirement = StakeRequirement {
+                    min_stake_value: stake
+                        .parse()
+                        .with_context(|| format!("invalid entity stake requirement: {entry}"))?,
+                    min_unstake_delay: delay
+                        .parse()
+                        .with_context(|| format!("invalid entity stake requirement: {entry}"))?,
+                };
+                Ok((kind, requirement))
+            })
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+
         Ok(Self::new(
             value.min_unstake_delay,
             value.min_stake_value,
             value.max_simulate_handle_ops_gas,
             value.max_verification_gas,
             value.tracer_timeout.clone(),
+            value.max_sim_block_age_seconds,
+            allowed_precompiles,
+            value.reject_trace_size_bytes,
+            value.require_staked_paymaster,
+            value.max_valid_after_horizon_seconds,
+            entity_stake_requirements,
         ))
     }
 }
 
-impl From<&CommonArgs> for EthApiSettings {
-    fn from(value: &CommonArgs) -> Self {
-        Self::new(value.user_operation_event_block_distance)
+impl TryFrom<&CommonArgs> for EthApiSettings {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &CommonArgs) -> Result<Self, Self::Error> {
+        Ok(Self::new(
+            value.user_operation_event_block_distance,
+            value.error_compat_mode.parse::<ErrorCompatMode>()?,
+        ))
     }
 }
 
@@ -14,7 +14,7 @@
 use clap::Args;
 use rundler_builder::{BuilderEvent, BuilderTask, LocalBuilderBuilder};
 use rundler_pool::{LocalPoolBuilder, PoolEvent, PoolTask};
-use rundler_rpc::RpcTask;
+use rundler_rpc::{MetricsSnapshot, RpcTask};
 use rundler_task::spawn_tasks_with_shutdown;
 use rundler_types::chain::ChainSpec;
 use rundler_utils::emit::{self, WithEntryPoint, EVENT_CHANNEL_CAPACITY};
@@ -48,6 +48,7 @@ pub async fn run(
     chain_spec: ChainSpec,
     bundler_args: NodeCliArgs,
     common_args: CommonArgs,
+    metrics_snapshot: MetricsSnapshot,
 ) -> anyhow::Result<()> {
     let NodeCliArgs {
         pool: pool_args,
@@ -65,9 +66,10 @@ pub async fn run(
         chain_spec,
         &common_args,
         (&common_args).try_into()?,
-        (&common_args).into(),
         (&common_args).try_into()?,
         (&common_args).try_into()?,
+        (&common_args).try_into()?,
+        metrics_snapshot,
     )?;
 
     let (event_sender, event_rx) =
@@ -11,12 +11,12 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::{collections::HashMap, net::SocketAddr, time::Duration};
+use std::{collections::HashMap, net::SocketAddr, path::PathBuf, time::Duration};
 
 use anyhow::Context;
 use clap::Args;
 use ethers::types::Address;
-use rundler_pool::{LocalPoolBuilder, PoolConfig, PoolTask, PoolTaskArgs};
+use rundler_pool::{LocalPoolBuilder, PoolConfig, PoolOrderingMode, PoolTask, PoolTaskArgs};
 use rundler_sim::MempoolConfigs;
 use rundler_task::spawn_tasks_with_shutdown;
 use rundler_types::{chain::ChainSpec, EntryPointVersion};
@@ -59,6 +59,15 @@ pub struct PoolArgs {
     )]
     pub max_size_in_bytes: usize,
 
+    #[arg(
+        long = "pool.size_headroom_percent",
+        name = "pool.size_headroom_percent",
+        env = "POOL_SIZE_HEADROOM_PERCENT",
+        default_value = "5",
+        value_parser = clap::value_parser!(u64).range(0..=100)
+    )]
+    pub size_headroom_percent: u64,
+
     #[arg(
         long = "pool.same_sender_mempool_count",
         name = "pool.same_sender_mempool_count",
@@ -164,6 +173,56 @@ pub struct PoolArgs {
     )]
     pub reputation_tracking_enabled: bool,
 
+    #[arg(
+        long = "pool.reputation_decay_interval_secs",
+        name = "pool.reputation_decay_interval_secs",
+        env = "POOL_REPUTATION_DECAY_INTERVAL_SECS",
+        default_value = "3600",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub reputation_decay_interval_secs: u64,
+
+    #[arg(
+        long = "pool.reputation_decay_factor",
+        name = "pool.reputation_decay_factor",
+        env = "POOL_REPUTATION_DECAY_FACTOR",
+        default_value = "24",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub reputation_decay_factor: u64,
+
+    #[arg(
+        long = "pool.reputation_min_inclusion_rate_denominator",
+        name = "pool.reputation_min_inclusion_rate_denominator",
+        env = "POOL_REPUTATION_MIN_INCLUSION_RATE_DENOMINATOR",
+        default_value = "10",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub reputation_min_inclusion_rate_denominator: u64,
+
+    #[arg(
+        long = "pool.reputation_throttling_slack",
+        name = "pool.reputation_throttling_slack",
+        env = "POOL_REPUTATION_THROTTLING_SLACK",
+        default_value = "10"
+    )]
+    pub reputation_throttling_slack: u64,
+
+    #[arg(
+        long = "pool.reputation_ban_slack",
+        name = "pool.reputation_ban_slack",
+        env = "POOL_REPUTATION_BAN_SLACK",
+        default_value = "50"
+    )]
+    pub reputation_ban_slack: u64,
+
+    #[arg(
+        long = "pool.reputation_persist_dir",
+        name = "pool.reputation_persist_dir",
+        env = "POOL_REPUTATION_PERSIST_DIR"
+    )]
+    pub reputation_persist_dir: Option<String>,
+
     #[arg(
         long = "pool.drop_min_num_blocks",
         name = "pool.drop_min_num_blocks",
@@ -171,6 +230,51 @@ pub struct PoolArgs {
         default_value = "10"
     )]
     pub drop_min_num_blocks: u64,
+
+    #[arg(
+        long = "pool.revalidation_interval_blocks",
+        name = "pool.revalidation_interval_blocks",
+        env = "POOL_REVALIDATION_INTERVAL_BLOCKS"
+    )]
+    pub revalidation_interval_blocks: Option<u64>,
+
+    #[arg(
+        long = "pool.revalidation_max_ops",
+        name = "pool.revalidation_max_ops",
+        env = "POOL_REVALIDATION_MAX_OPS",
+        default_value = "100"
+    )]
+    pub revalidation_max_ops: usize,
+
+    #[arg(
+        long = "pool.ordering_mode",
+        name = "pool.ordering_mode",
+        env = "POOL_ORDERING_MODE",
+        default_value = "priority_fee"
+    )]
+    pub ordering_mode: PoolOrderingMode,
+
+    #[arg(
+        long = "pool.max_ops_scanned_per_bundle",
+        name = "pool.max_ops_scanned_per_bundle",
+        env = "POOL_MAX_OPS_SCANNED_PER_BUNDLE"
+    )]
+    pub max_ops_scanned_per_bundle: Option<usize>,
+
+    #[arg(
+        long = "pool.max_ops_per_fingerprint",
+        name = "pool.max_ops_per_fingerprint",
+        env = "POOL_MAX_OPS_PER_FINGERPRINT"
+    )]
+    pub max_ops_per_fingerprint: Option<usize>,
+
+    #[arg(
+        long = "pool.untraced_admission",
+        name = "pool.untraced_admission",
+        env = "POOL_UNTRACED_ADMISSION",
+        default_value = "false"
+    )]
+    pub untraced_admission: bool,
 }
 
 impl PoolArgs {
@@ -201,6 +305,10 @@ impl PoolArgs {
         };
         tracing::info!("Mempool channel configs: {:?}", mempool_channel_configs);
 
+        if let Some(dir) = &self.reputation_persist_dir {
+            std::fs::create_dir_all(dir).context("should create reputation persist dir")?;
+        }
+
         let chain_id = chain_spec.id;
         let pool_config_base = PoolConfig {
             // update per entry point
@@ -213,6 +321,7 @@ impl PoolArgs {
             same_sender_mempool_count: self.same_sender_mempool_count,
             min_replacement_fee_increase_percentage: self.min_replacement_fee_increase_percentage,
             max_size_of_pool_bytes: self.max_size_in_bytes,
+            size_headroom_percent: self.size_headroom_percent,
             blocklist: blocklist.clone(),
             allowlist: allowlist.clone(),
             precheck_settings: common.try_into()?,
@@ -222,7 +331,20 @@ impl PoolArgs {
             paymaster_tracking_enabled: self.paymaster_tracking_enabled,
             paymaster_cache_length: self.paymaster_cache_length,
             reputation_tracking_enabled: self.reputation_tracking_enabled,
+            reputation_decay_interval_secs: self.reputation_decay_interval_secs,
+            reputation_decay_factor: self.reputation_decay_factor,
+            reputation_min_inclusion_rate_denominator: self
+                .reputation_min_inclusion_rate_denominator,
+            reputation_throttling_slack: self.reputation_throttling_slack,
+            reputation_ban_slack: self.reputation_ban_slack,
+            // update per entry point
+            reputation_persist_path: None,
             drop_min_num_blocks: self.drop_min_num_blocks,
+            revalidation_interval_blocks: self.revalidation_interval_blocks,
+            revalidation_max_ops: self.revalidation_max_ops,
+            ordering_mode: self.ordering_mode,
+            max_ops_scanned_per_bundle: self.max_ops_scanned_per_bundle,
+            max_ops_per_fingerprint: self.max_ops_per_fingerprint,
         };
 
         let mut pool_configs = vec![];
@@ -234,6 +356,8 @@ impl PoolArgs {
                 num_shards: common.num_builders_v0_6,
                 mempool_channel_configs: mempool_channel_configs
                     .get_for_entry_point(chain_spec.entry_point_address_v0_6),
+                reputation_persist_path: self
+                    .reputation_persist_path(chain_spec.entry_point_address_v0_6),
                 ..pool_config_base.clone()
             });
         }
@@ -244,6 +368,8 @@ impl PoolArgs {
                 num_shards: common.num_builders_v0_7,
                 mempool_channel_configs: mempool_channel_configs
                     .get_for_entry_point(chain_spec.entry_point_address_v0_7),
+                reputation_persist_path: self
+                    .reputation_persist_path(chain_spec.entry_point_address_v0_7),
                 ..pool_config_base.clone()
             });
         }
@@ -251,6 +377,8 @@ impl PoolArgs {
         Ok(PoolTaskArgs {
             chain_spec,
             unsafe_mode: common.unsafe_mode,
+            allow_trace_fallback: common.allow_trace_fallback,
+            untraced_admission: self.untraced_admission,
             http_url: common
                 .node_http
                 .clone()
@@ -262,6 +390,13 @@ impl PoolArgs {
             chain_update_channel_capacity: self.chain_update_channel_capacity.unwrap_or(1024),
         })
     }
+
+    // Each entry point has its own reputation, persisted to its own file
+    fn reputation_persist_path(&self, entry_point: Address) -> Option<PathBuf> {
+        self.reputation_persist_dir
+            .as_ref()
+            .map(|dir| PathBuf::from(dir).join(format!("reputation_{entry_point:?}.json")))
+    }
 }
 
 /// CLI options for the Pool server standalone
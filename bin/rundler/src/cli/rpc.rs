@@ -11,13 +11,17 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::time::Duration;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use anyhow::Context;
 use clap::Args;
+use ethers::types::{Address, Bytes};
 use rundler_builder::RemoteBuilderClient;
 use rundler_pool::RemotePoolClient;
-use rundler_rpc::{EthApiSettings, RpcTask, RpcTaskArgs, RundlerApiSettings};
+use rundler_rpc::{
+    AdmissionHook, AdmissionHooks, EthApiSettings, MetricsSnapshot, RejectEmptySignature, RpcTask,
+    RpcTaskArgs, RundlerApiSettings,
+};
 use rundler_sim::{EstimationSettings, PrecheckSettings};
 use rundler_task::{server::connect_with_retries_shutdown, spawn_tasks_with_shutdown};
 use rundler_types::chain::ChainSpec;
@@ -66,6 +70,54 @@ pub struct RpcArgs {
     )]
     timeout_seconds: String,
 
+    /// Per-method timeouts for RPC requests, as a comma separated list of
+    /// `method=seconds`, i.e. `eth_estimateUserOperationGas=10`
+    #[arg(
+        long = "rpc.method_timeouts",
+        name = "rpc.method_timeouts",
+        env = "RPC_METHOD_TIMEOUTS",
+        value_delimiter = ','
+    )]
+    method_timeouts: Vec<String>,
+
+    /// Dummy signatures used by `rundler_estimateUserOperationGas` when a user operation
+    /// omits its signature, as a comma separated list of `factory=signature`
+    #[arg(
+        long = "rpc.dummy_signatures",
+        name = "rpc.dummy_signatures",
+        env = "RPC_DUMMY_SIGNATURES",
+        value_delimiter = ','
+    )]
+    dummy_signatures: Vec<String>,
+
+    /// Token paymasters whose paymaster data starts with the ERC-20 token they charge,
+    /// reported as `paymasterToken` by `rundler_getPendingUserOperations`
+    #[arg(
+        long = "rpc.token_paymasters",
+        name = "rpc.token_paymasters",
+        env = "RPC_TOKEN_PAYMASTERS",
+        value_delimiter = ','
+    )]
+    token_paymasters: Vec<Address>,
+
+    /// Maximum number of receipts looked up concurrently by `rundler_getUserOperationReceipts`
+    #[arg(
+        long = "rpc.receipt_lookup_concurrency",
+        name = "rpc.receipt_lookup_concurrency",
+        env = "RPC_RECEIPT_LOOKUP_CONCURRENCY",
+        default_value = "8"
+    )]
+    receipt_lookup_concurrency: usize,
+
+    /// Maximum number of hashes accepted per `rundler_getUserOperationReceipts` call
+    #[arg(
+        long = "rpc.max_receipt_lookup_hashes",
+        name = "rpc.max_receipt_lookup_hashes",
+        env = "RPC_MAX_RECEIPT_LOOKUP_HASHES",
+        default_value = "100"
+    )]
+    max_receipt_lookup_hashes: usize,
+
     /// Maximum number of concurrent connections
     #[arg(
         long = "rpc.max_connections",
@@ -74,6 +126,44 @@ pub struct RpcArgs {
         default_value = "100"
     )]
     max_connections: u32,
+
+    /// Allow gas estimation against entry point addresses that are not configured,
+    /// i.e. for testing against a custom or forked entry point
+    #[arg(
+        long = "rpc.allow_custom_entrypoints",
+        name = "rpc.allow_custom_entrypoints",
+        env = "RPC_ALLOW_CUSTOM_ENTRYPOINTS",
+        default_value = "false"
+    )]
+    allow_custom_entrypoints: bool,
+
+    /// Log the full JSON of rejected user operations at debug level
+    #[arg(
+        long = "rpc.log_rejected_ops",
+        name = "rpc.log_rejected_ops",
+        env = "RPC_LOG_REJECTED_OPS",
+        default_value = "false"
+    )]
+    log_rejected_ops: bool,
+
+    /// Number of accepted user operation hashes remembered to report dropped operations
+    /// from `rundler_getUserOperationStatus`. Tracked separately by each RPC server.
+    #[arg(
+        long = "rpc.submitted_ops_capacity",
+        name = "rpc.submitted_ops_capacity",
+        env = "RPC_SUBMITTED_OPS_CAPACITY",
+        default_value = "10000"
+    )]
+    submitted_ops_capacity: u32,
+
+    /// Reject user operations sent with an empty signature
+    #[arg(
+        long = "rpc.reject_empty_signature",
+        name = "rpc.reject_empty_signature",
+        env = "RPC_REJECT_EMPTY_SIGNATURE",
+        default_value = "false"
+    )]
+    reject_empty_signature: bool,
 }
 
 impl RpcArgs {
@@ -88,6 +178,7 @@ impl RpcArgs {
         eth_api_settings: EthApiSettings,
         rundler_api_settings: RundlerApiSettings,
         estimation_settings: EstimationSettings,
+        metrics_snapshot: MetricsSnapshot,
     ) -> anyhow::Result<RpcTaskArgs> {
         let apis = self
             .api
@@ -95,9 +186,47 @@ impl RpcArgs {
             .map(|api| api.parse())
             .collect::<Result<Vec<_>, _>>()?;
 
+        let method_timeouts = self
+            .method_timeouts
+            .iter()
+            .map(|entry| {
+                let (method, secs) = entry
+                    .split_once('=')
+                    .with_context(|| format!("invalid rpc method timeout: {entry}"))?;
+                let secs = secs
+                    .parse()
+                    .with_context(|| format!("invalid rpc method timeout: {entry}"))?;
+                Ok((method.to_string(), Duration::from_secs(secs)))
+            })
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+
+        let dummy_signatures = self
+            .dummy_signatures
+            .iter()
+            .map(|entry| {
+                let (factory, signature) = entry
+                    .split_once('=')
+                    .with_context(|| format!("invalid rpc dummy signature: {entry}"))?;
+                let factory: Address = factory
+                    .parse()
+                    .with_context(|| format!("invalid rpc dummy signature factory: {entry}"))?;
+                let signature: Bytes = signature
+                    .parse()
+                    .with_context(|| format!("invalid rpc dummy signature: {entry}"))?;
+                Ok((factory, signature))
+            })
+            .collect::<anyhow::Result<HashMap<_, _>>>()?;
+
+        let mut hooks: Vec<Arc<dyn AdmissionHook>> = vec![];
+        if self.reject_empty_signature {
+            hooks.push(Arc::new(RejectEmptySignature));
+        }
+        let admission_hooks = AdmissionHooks::new(hooks);
+
         Ok(RpcTaskArgs {
             chain_spec,
             unsafe_mode: common.unsafe_mode,
+            allow_trace_fallback: common.allow_trace_fallback,
             port: self.port,
             host: self.host.clone(),
             rpc_url: common
@@ -110,9 +239,19 @@ impl RpcArgs {
             rundler_api_settings,
             estimation_settings,
             rpc_timeout: Duration::from_secs(self.timeout_seconds.parse()?),
+            method_timeouts,
+            dummy_signatures,
+            token_paymasters: self.token_paymasters.iter().copied().collect(),
+            receipt_lookup_concurrency: self.receipt_lookup_concurrency,
+            max_receipt_lookup_hashes: self.max_receipt_lookup_hashes,
             max_connections: self.max_connections,
             entry_point_v0_6_enabled: !common.disable_entry_point_v0_6,
             entry_point_v0_7_enabled: !common.disable_entry_point_v0_7,
+            allow_custom_entrypoints: self.allow_custom_entrypoints,
+            admission_hooks,
+            log_rejected_ops: self.log_rejected_ops,
+            submitted_ops_capacity: self.submitted_ops_capacity,
+            metrics_snapshot,
         })
     }
 }
@@ -146,6 +285,7 @@ pub async fn run(
     chain_spec: ChainSpec,
     rpc_args: RpcCliArgs,
     common_args: CommonArgs,
+    metrics_snapshot: MetricsSnapshot,
 ) -> anyhow::Result<()> {
     let RpcCliArgs {
         rpc: rpc_args,
@@ -157,9 +297,10 @@ pub async fn run(
         chain_spec.clone(),
         &common_args,
         (&common_args).try_into()?,
-        (&common_args).into(),
         (&common_args).try_into()?,
         (&common_args).try_into()?,
+        (&common_args).try_into()?,
+        metrics_snapshot,
     )?;
 
     let pool = connect_with_retries_shutdown(
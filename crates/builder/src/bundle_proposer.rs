@@ -18,6 +18,7 @@ use std::{
     mem,
     pin::Pin,
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::Context;
@@ -40,7 +41,7 @@ use rundler_types::{
     pool::{Pool, PoolOperation, SimulationViolation},
     Entity, EntityInfo, EntityInfos, EntityType, EntityUpdate, EntityUpdateType, GasFees,
     Timestamp, UserOperation, UserOperationVariant, UserOpsPerAggregator, BUNDLE_BYTE_OVERHEAD,
-    TIME_RANGE_BUFFER, USER_OP_OFFSET_WORD_SIZE,
+    USER_OP_OFFSET_WORD_SIZE,
 };
 use rundler_utils::{emit::WithEntryPoint, math};
 use tokio::{sync::broadcast, try_join};
@@ -58,6 +59,8 @@ pub(crate) struct Bundle<UO: UserOperation> {
     pub(crate) gas_fees: GasFees,
     pub(crate) expected_storage: ExpectedStorage,
     pub(crate) rejected_ops: Vec<UO>,
+    /// Hashes of the rejected ops along with the reason each was dropped
+    pub(crate) dropped_ops: Vec<(H256, String)>,
     pub(crate) entity_updates: Vec<EntityUpdate>,
 }
 
@@ -69,6 +72,7 @@ impl<UO: UserOperation> Default for Bundle<UO> {
             gas_fees: GasFees::default(),
             expected_storage: ExpectedStorage::default(),
             rejected_ops: Vec::new(),
+            dropped_ops: Vec::new(),
             entity_updates: Vec::new(),
         }
     }
@@ -151,10 +155,14 @@ pub(crate) struct BundleProposerImpl<UO, S, E, P, M> {
 pub(crate) struct Settings {
     pub(crate) chain_spec: ChainSpec,
     pub(crate) max_bundle_size: u64,
+    pub(crate) max_bundle_senders: Option<u64>,
+    pub(crate) max_bundle_pre_verification_gas: Option<u64>,
     pub(crate) max_bundle_gas: u64,
     pub(crate) beneficiary: Address,
     pub(crate) bundle_priority_fee_overhead_percent: u64,
+    pub(crate) bundle_base_fee_headroom_percent: u64,
     pub(crate) priority_fee_mode: PriorityFeeMode,
+    pub(crate) time_range_buffer: Duration,
 }
 
 #[async_trait]
@@ -208,6 +216,15 @@ where
         } else {
             self.fee_estimator.required_op_fees(bundle_fees)
         };
+        // The base fee may rise while the bundle is proposed and sent, raise the bundle's
+        // max fee so that it is still covered. Ops are filtered on the fees above, the
+        // priority fee paid by the bundle is unchanged.
+        let bundle_fees = with_base_fee_headroom(
+            bundle_fees,
+            base_fee,
+            self.settings.bundle_base_fee_headroom_percent,
+            self.settings.chain_spec.eip1559_enabled,
+        );
         let all_paymaster_addresses = ops
             .iter()
             .filter_map(|op| op.uo.paymaster())
@@ -287,6 +304,7 @@ where
                     gas_fees: bundle_fees,
                     expected_storage,
                     rejected_ops: context.rejected_ops.iter().map(|po| po.0.clone()).collect(),
+                    dropped_ops: self.dropped_ops(&context),
                     entity_updates: context.entity_updates.into_values().collect(),
                 });
             }
@@ -294,6 +312,7 @@ where
         }
         Ok(Bundle {
             rejected_ops: context.rejected_ops.iter().map(|po| po.0.clone()).collect(),
+            dropped_ops: self.dropped_ops(&context),
             entity_updates: context.entity_updates.into_values().collect(),
             gas_fees: bundle_fees,
             ..Default::default()
@@ -430,7 +449,7 @@ where
             .simulate_validation(
                 op.uo.clone().into(),
                 Some(block_hash),
-                Some(op.expected_code_hash),
+                op.expected_code_hash,
             )
             .await;
         let result = match result {
@@ -472,6 +491,7 @@ where
         let mut paymasters_to_reject = Vec::<EntityInfo>::new();
 
         let mut gas_spent = self.settings.chain_spec.transaction_intrinsic_gas;
+        let mut pre_verification_gas_spent = U256::zero();
         let mut constructed_bundle_size = BUNDLE_BYTE_OVERHEAD;
         for (po, simulation) in ops_with_simulations {
             let op = po.clone().uo;
@@ -490,19 +510,23 @@ where
                         entity_infos,
                     } = error
                     {
+                        let reason = format!("failed revalidation: {violations:?}");
                         // try to use EntityInfos from the latest simulation, but if it doesn't exist use the EntityInfos from the previous simulation
                         let infos = entity_infos.map_or(po.entity_infos, |e| e);
                         context.process_simulation_violations(violations, infos);
-                        context.rejected_ops.push((op.into(), po.entity_infos));
+                        context
+                            .rejected_ops
+                            .push((op.into(), po.entity_infos, reason));
                     }
                     continue;
                 }
             };
 
-            // filter time range
+            // filter time range, skipping ops that will expire before they are likely to be included.
+            // the simulated time range is the intersection of the account and paymaster windows.
             if !simulation
                 .valid_time_range
-                .contains(Timestamp::now(), TIME_RANGE_BUFFER)
+                .contains(Timestamp::now(), self.settings.time_range_buffer)
             {
                 self.emit(BuilderEvent::skipped_op(
                     self.builder_index,
@@ -511,7 +535,11 @@ where
                         valid_range: simulation.valid_time_range,
                     },
                 ));
-                context.rejected_ops.push((op.into(), po.entity_infos));
+                context.rejected_ops.push((
+                    op.into(),
+                    po.entity_infos,
+                    format!("invalid time range: {:?}", simulation.valid_time_range),
+                ));
                 continue;
             }
 
@@ -532,6 +560,30 @@ where
                 continue;
             }
 
+            // Skip ops that would push the bundle's total preVerificationGas, which covers the L1
+            // data cost fronted by the bundler, over the cap. They stay in the pool for a later
+            // bundle, unless their preVerificationGas alone exceeds the cap.
+            if let Some(max_pvg) = self.settings.max_bundle_pre_verification_gas {
+                let max_pvg = U256::from(max_pvg);
+                if pre_verification_gas_spent + op.pre_verification_gas() > max_pvg {
+                    self.emit(BuilderEvent::skipped_op(
+                        self.builder_index,
+                        self.op_hash(&op),
+                        SkipReason::PreVerificationGasLimit { max_pvg },
+                    ));
+                    if op.pre_verification_gas() > max_pvg {
+                        let reason = format!(
+                            "preVerificationGas {} exceeds the bundle maximum {max_pvg}",
+                            op.pre_verification_gas()
+                        );
+                        context
+                            .rejected_ops
+                            .push((op.into(), po.entity_infos, reason));
+                    }
+                    continue;
+                }
+            }
+
             if let Some(&other_sender) = simulation
                 .accessed_addresses
                 .iter()
@@ -552,7 +604,7 @@ where
                     error!("Op had paymaster with unknown balance, but balances should have been loaded for all paymasters in bundle.");
                     continue;
                 };
-                let max_cost = op.max_gas_cost();
+                let max_cost = op.max_gas_cost(&self.settings.chain_spec);
                 if *balance < max_cost {
                     info!("Rejected paymaster {paymaster:?} because its balance {balance:?} was too low.");
                     paymasters_to_reject.push(po.entity_infos.paymaster.unwrap());
@@ -565,6 +617,7 @@ where
             // Update the running gas that would need to be be spent to execute the bundle so far.
             gas_spent +=
                 gas::user_operation_execution_gas_limit(&self.settings.chain_spec, &op, false);
+            pre_verification_gas_spent += op.pre_verification_gas();
 
             constructed_bundle_size =
                 constructed_bundle_size.saturating_add(op_size_with_offset_word);
@@ -604,9 +657,10 @@ where
             self.emit(BuilderEvent::rejected_op(
                 self.builder_index,
                 self.op_hash(&context.get_op_at(index)?.op),
-                OpRejectionReason::ConditionNotMet(reason),
+                OpRejectionReason::ConditionNotMet(reason.clone()),
             ));
-            self.reject_index(context, index).await;
+            self.reject_index(context, index, format!("condition not met: {reason:?}"))
+                .await;
         }
 
         Ok(())
@@ -654,8 +708,8 @@ where
         None
     }
 
-    async fn reject_index(&self, context: &mut ProposalContext<UO>, i: usize) {
-        let changed_aggregator = context.reject_index(i);
+    async fn reject_index(&self, context: &mut ProposalContext<UO>, i: usize, reason: String) {
+        let changed_aggregator = context.reject_index(i, reason);
         self.compute_aggregator_signatures(context, &changed_aggregator)
             .await;
     }
@@ -852,7 +906,7 @@ where
                 info!(
                     "Rejected op because it failed during gas estimation with message {message}."
                 );
-                self.reject_index(context, index).await;
+                self.reject_index(context, index, message).await;
                 return Ok(());
             }
         };
@@ -904,15 +958,16 @@ where
         }
 
         // iterate in reverse so that we can remove ops without affecting the index of the next op to remove
+        let message = "post op reverted leading to entry point revert".to_owned();
         for index in to_remove.into_iter().rev() {
             self.emit(BuilderEvent::rejected_op(
                 self.builder_index,
                 self.op_hash(&context.get_op_at(index)?.op),
                 OpRejectionReason::FailedInBundle {
-                    message: Arc::new("post op reverted leading to entry point revert".to_owned()),
+                    message: Arc::new(message.clone()),
                 },
             ));
-            self.reject_index(context, index).await;
+            self.reject_index(context, index, message.clone()).await;
         }
 
         Ok(())
@@ -994,7 +1049,22 @@ where
         // Make the bundle gas limit 10% higher here so that we simulate more UOs than we need in case that we end up dropping some UOs later so we can still pack a full bundle
         let mut gas_left = math::increase_by_percent(U256::from(self.settings.max_bundle_gas), 10);
         let mut ops_in_bundle = Vec::new();
+        // Senders are capped before simulation to bound its cost. Ops removed later only
+        // shrink the set of senders, so the bundle stays within the cap.
+        let mut senders = HashSet::<Address>::new();
         for op in ops {
+            let sender = op.uo.sender();
+            if let Some(max_bundle_senders) = self.settings.max_bundle_senders {
+                if !senders.contains(&sender) && senders.len() as u64 >= max_bundle_senders {
+                    self.emit(BuilderEvent::skipped_op(
+                        self.builder_index,
+                        self.op_hash(&op.uo),
+                        SkipReason::SenderLimit,
+                    ));
+                    continue;
+                }
+            }
+
             // Here we use optimistic gas limits for the UOs by assuming none of the paymaster UOs use postOp calls.
             // This way after simulation once we have determined if each UO actually uses a postOp call or not we can still pack a full bundle
             let gas =
@@ -1008,6 +1078,7 @@ where
                 continue;
             }
             gas_left -= gas;
+            senders.insert(sender);
             ops_in_bundle.push(op);
         }
         (
@@ -1031,6 +1102,14 @@ where
     {
         op.hash(self.entry_point.address(), self.settings.chain_spec.id)
     }
+
+    fn dropped_ops(&self, context: &ProposalContext<UO>) -> Vec<(H256, String)> {
+        context
+            .rejected_ops
+            .iter()
+            .map(|(op, _, reason)| (self.op_hash(op), reason.clone()))
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -1057,7 +1136,7 @@ impl<UO: UserOperation> OpWithSimulation<UO> {
 #[derive(Debug)]
 struct ProposalContext<UO> {
     groups_by_aggregator: LinkedHashMap<Option<Address>, AggregatorGroup<UO>>,
-    rejected_ops: Vec<(UO, EntityInfos)>,
+    rejected_ops: Vec<(UO, EntityInfos, String)>,
     // This is a BTreeMap so that the conversion to a Vec<EntityUpdate> is deterministic, mainly for tests
     entity_updates: BTreeMap<Address, EntityUpdate>,
 }
@@ -1081,7 +1160,7 @@ impl<UO: UserOperation> ProposalContext<UO> {
     fn new() -> Self {
         Self {
             groups_by_aggregator: LinkedHashMap::<Option<Address>, AggregatorGroup<UO>>::new(),
-            rejected_ops: Vec::<(UO, EntityInfos)>::new(),
+            rejected_ops: Vec::<(UO, EntityInfos, String)>::new(),
             entity_updates: BTreeMap::new(),
         }
     }
@@ -1119,14 +1198,14 @@ impl<UO: UserOperation> ProposalContext<UO> {
     /// Returns the address of the op's aggregator if the aggregator's signature
     /// may need to be recomputed.
     #[must_use = "rejected op but did not update aggregator signatures"]
-    fn reject_index(&mut self, i: usize) -> Option<Address> {
+    fn reject_index(&mut self, i: usize, reason: String) -> Option<Address> {
         let mut remaining_i = i;
         let mut found_aggregator: Option<Option<Address>> = None;
         for (&aggregator, group) in &mut self.groups_by_aggregator {
             if remaining_i < group.ops_with_simulations.len() {
                 let rejected = group.ops_with_simulations.remove(remaining_i);
                 self.rejected_ops
-                    .push((rejected.op, rejected.simulation.entity_infos));
+                    .push((rejected.op, rejected.simulation.entity_infos, reason));
                 found_aggregator = Some(aggregator);
                 break;
             }
@@ -1409,10 +1488,31 @@ impl<UO: UserOperation> ProposalContext<UO> {
     }
 }
 
+// Raises the max fee of the bundle fees to cover the base fee increased by
+// `headroom_percent`, leaving the priority fee unchanged.
+//
+// On chains without EIP-1559 the max fee is the gas price paid rather than a cap, so the
+// headroom would be paid in full and is not applied.
+fn with_base_fee_headroom(
+    bundle_fees: GasFees,
+    base_fee: U256,
+    headroom_percent: u64,
+    eip1559_enabled: bool,
+) -> GasFees {
+    if !eip1559_enabled {
+        return bundle_fees;
+    }
+    GasFees {
+        max_fee_per_gas: bundle_fees.max_fee_per_gas.max(
+            math::increase_by_percent(base_fee, headroom_percent)
+                + bundle_fees.max_priority_fee_per_gas,
+        ),
+        max_priority_fee_per_gas: bundle_fees.max_priority_fee_per_gas,
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
-
     use anyhow::anyhow;
     use ethers::{
         types::{H160, U64},
@@ -1423,10 +1523,11 @@ mod tests {
     use rundler_types::{
         pool::{MockPool, SimulationViolation},
         v0_6::{UserOperation, ENTRY_POINT_INNER_GAS_OVERHEAD},
-        UserOperation as UserOperationTrait, ValidTimeRange,
+        UserOperation as UserOperationTrait, ValidTimeRange, TIME_RANGE_BUFFER,
     };
 
     use super::*;
+    use crate::emit::BuilderEventKind;
 
     #[tokio::test]
     async fn test_singleton_valid_bundle() {
@@ -1480,6 +1581,9 @@ mod tests {
         .await;
         assert!(bundle.ops_per_aggregator.is_empty());
         assert_eq!(bundle.rejected_ops, vec![op]);
+        assert_eq!(bundle.dropped_ops.len(), 1);
+        assert_eq!(bundle.dropped_ops[0].0, op.hash(address(123), 0));
+        assert!(bundle.dropped_ops[0].1.starts_with("failed revalidation"));
     }
 
     #[tokio::test]
@@ -1541,6 +1645,48 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_skips_op_expiring_within_time_range_buffer() {
+        // expires before the buffer elapses, so would likely expire before inclusion
+        let expiring = op_with_sender(address(1));
+        let valid = op_with_sender(address(2));
+        let bundle = simple_make_bundle(vec![
+            MockOp {
+                op: expiring.clone(),
+                simulation_result: Box::new(|| {
+                    Ok(SimulationResult {
+                        valid_time_range: ValidTimeRange::new(
+                            Timestamp::MIN,
+                            Timestamp::now() + TIME_RANGE_BUFFER / 2,
+                        ),
+                        ..Default::default()
+                    })
+                }),
+            },
+            MockOp {
+                op: valid.clone(),
+                simulation_result: Box::new(|| {
+                    Ok(SimulationResult {
+                        valid_time_range: ValidTimeRange::new(
+                            Timestamp::MIN,
+                            Timestamp::now() + TIME_RANGE_BUFFER * 2,
+                        ),
+                        ..Default::default()
+                    })
+                }),
+            },
+        ])
+        .await;
+        assert_eq!(
+            bundle.ops_per_aggregator,
+            vec![UserOpsPerAggregator {
+                user_ops: vec![valid],
+                ..Default::default()
+            }]
+        );
+        assert_eq!(bundle.rejected_ops, vec![expiring]);
+    }
+
     #[tokio::test]
     async fn test_drops_but_not_rejects_op_accessing_another_sender() {
         let op1 = op_with_sender(address(1));
@@ -1657,6 +1803,83 @@ mod tests {
         assert!(bundle.rejected_ops.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_zero_priority_fee() {
+        let base_fee = U256::from(1000);
+        let max_priority_fee_per_gas = U256::from(50);
+        let op = op_with_sender_and_fees(address(1), 1000.into(), 0.into());
+        let bundle = mock_make_bundle_with_settings(
+            vec![MockOp {
+                op: op.clone(),
+                simulation_result: Box::new(|| Ok(SimulationResult::default())),
+            }],
+            vec![],
+            vec![HandleOpsOut::Success],
+            vec![],
+            base_fee,
+            max_priority_fee_per_gas,
+            false,
+            ExpectedStorage::default(),
+            None,
+            None,
+            ChainSpec {
+                zero_priority_fee: true,
+                ..Default::default()
+            },
+        )
+        .await;
+        // the network priority fee is ignored, and ops aren't required to pay one
+        assert_eq!(
+            bundle.gas_fees,
+            GasFees {
+                max_fee_per_gas: 1000.into(),
+                max_priority_fee_per_gas: 0.into(),
+            }
+        );
+        assert_eq!(
+            bundle.ops_per_aggregator,
+            vec![UserOpsPerAggregator {
+                user_ops: vec![op],
+                ..Default::default()
+            }],
+        );
+    }
+
+    #[test]
+    fn test_base_fee_headroom() {
+        let bundle_fees = GasFees {
+            max_fee_per_gas: 1050.into(),
+            max_priority_fee_per_gas: 50.into(),
+        };
+        // covers the base fee rising by 13%, the priority fee is unchanged
+        assert_eq!(
+            with_base_fee_headroom(bundle_fees, 1000.into(), 13, true),
+            GasFees {
+                max_fee_per_gas: 1180.into(),
+                max_priority_fee_per_gas: 50.into(),
+            }
+        );
+        // not applied without EIP-1559, where the max fee is the price paid
+        assert_eq!(
+            with_base_fee_headroom(bundle_fees, 1000.into(), 13, false),
+            bundle_fees
+        );
+        // already covered, e.g. a replacement with higher required fees
+        let bundle_fees = GasFees {
+            max_fee_per_gas: 2000.into(),
+            max_priority_fee_per_gas: 50.into(),
+        };
+        assert_eq!(
+            with_base_fee_headroom(bundle_fees, 1000.into(), 13, true),
+            bundle_fees
+        );
+        // no headroom
+        assert_eq!(
+            with_base_fee_headroom(bundle_fees, 1000.into(), 0, true),
+            bundle_fees
+        );
+    }
+
     #[tokio::test]
     async fn test_drops_but_not_rejects_op_with_too_low_pvg() {
         let base_fee = U256::from(1000);
@@ -2044,6 +2267,183 @@ mod tests {
         assert_eq!(gas_limit, expected_gas_limit);
     }
 
+    #[tokio::test]
+    async fn test_bundle_max_senders() {
+        let ops: Vec<_> = (1..=4)
+            .map(|i| op_with_sender_call_gas_limit(address(i), U256::from(1_000_000)))
+            .collect();
+        let deposit = parse_units("1", "ether").unwrap().into();
+
+        let bundle = mock_make_bundle_with_settings(
+            ops.iter()
+                .enumerate()
+                .map(|(i, op)| MockOp {
+                    op: op.clone(),
+                    simulation_result: Box::new(move || {
+                        assert!(i < 2, "ops past the sender cap should not be simulated");
+                        Ok(SimulationResult::default())
+                    }),
+                })
+                .collect(),
+            vec![],
+            vec![HandleOpsOut::Success],
+            vec![deposit; 4],
+            U256::zero(),
+            U256::zero(),
+            false,
+            ExpectedStorage::default(),
+            Some(2),
+            None,
+            ChainSpec::default(),
+        )
+        .await;
+
+        // ops from senders past the cap are left out, but not rejected
+        assert_eq!(bundle.rejected_ops, vec![]);
+        assert_eq!(
+            bundle.ops_per_aggregator,
+            vec![UserOpsPerAggregator {
+                user_ops: ops[..2].to_vec(),
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bundle_max_senders_skipped_op_frees_sender() {
+        // the first op doesn't fit the bundle gas limit, so its sender doesn't count
+        let ops = vec![
+            op_with_sender_call_gas_limit(address(1), U256::from(20_000_000)),
+            op_with_sender_call_gas_limit(address(2), U256::from(1_000_000)),
+            op_with_sender_call_gas_limit(address(3), U256::from(1_000_000)),
+        ];
+        let deposit = parse_units("1", "ether").unwrap().into();
+
+        let bundle = mock_make_bundle_with_settings(
+            ops.iter()
+                .map(|op| MockOp {
+                    op: op.clone(),
+                    simulation_result: Box::new(|| Ok(SimulationResult::default())),
+                })
+                .collect(),
+            vec![],
+            vec![HandleOpsOut::Success],
+            vec![deposit; 3],
+            U256::zero(),
+            U256::zero(),
+            false,
+            ExpectedStorage::default(),
+            Some(2),
+            None,
+            ChainSpec::default(),
+        )
+        .await;
+
+        assert_eq!(
+            bundle.ops_per_aggregator,
+            vec![UserOpsPerAggregator {
+                user_ops: ops[1..].to_vec(),
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bundle_max_pre_verification_gas() {
+        let ops: Vec<_> = [100_000, 200_000, 100_000, 50_000, 300_000]
+            .into_iter()
+            .enumerate()
+            .map(|(i, pvg)| UserOperation {
+                sender: address(i as u8 + 1),
+                pre_verification_gas: U256::from(pvg),
+                ..Default::default()
+            })
+            .collect();
+        let deposit = parse_units("1", "ether").unwrap().into();
+
+        let bundle = mock_make_bundle_with_settings(
+            ops.iter()
+                .map(|op| MockOp {
+                    op: op.clone(),
+                    simulation_result: Box::new(|| Ok(SimulationResult::default())),
+                })
+                .collect(),
+            vec![],
+            vec![HandleOpsOut::Success],
+            vec![deposit; 5],
+            U256::zero(),
+            U256::zero(),
+            false,
+            ExpectedStorage::default(),
+            None,
+            Some(250_000),
+            ChainSpec::default(),
+        )
+        .await;
+
+        // the op that would exceed the cap is skipped and stays in the pool, smaller ops still fit.
+        // the op that exceeds the cap on its own can never be bundled and is rejected.
+        assert_eq!(bundle.rejected_ops, vec![ops[4].clone()]);
+        assert_eq!(
+            bundle.ops_per_aggregator,
+            vec![UserOpsPerAggregator {
+                user_ops: vec![ops[0].clone(), ops[2].clone(), ops[3].clone()],
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bundle_max_pre_verification_gas_emits_skipped_op() {
+        let ops: Vec<_> = [200_000, 100_000]
+            .into_iter()
+            .enumerate()
+            .map(|(i, pvg)| UserOperation {
+                sender: address(i as u8 + 1),
+                pre_verification_gas: U256::from(pvg),
+                ..Default::default()
+            })
+            .collect();
+        let deposit = parse_units("1", "ether").unwrap().into();
+        let (event_sender, mut event_receiver) = broadcast::channel(16);
+
+        mock_make_bundle_with_events(
+            ops.iter()
+                .map(|op| MockOp {
+                    op: op.clone(),
+                    simulation_result: Box::new(|| Ok(SimulationResult::default())),
+                })
+                .collect(),
+            vec![],
+            vec![HandleOpsOut::Success],
+            vec![deposit; 2],
+            U256::zero(),
+            U256::zero(),
+            false,
+            ExpectedStorage::default(),
+            None,
+            Some(250_000),
+            ChainSpec::default(),
+            event_sender,
+        )
+        .await;
+
+        let skipped_hash = ops[1].hash(address(123), 0);
+        let mut skipped = false;
+        while let Ok(WithEntryPoint { event, .. }) = event_receiver.try_recv() {
+            if let BuilderEventKind::SkippedOp {
+                op_hash,
+                reason: SkipReason::PreVerificationGasLimit { max_pvg },
+            } = event.kind
+            {
+                assert_eq!(op_hash, skipped_hash);
+                assert_eq!(max_pvg, U256::from(250_000));
+                skipped = true;
+            }
+        }
+        assert!(skipped);
+    }
+
     #[tokio::test]
     async fn test_post_op_revert() {
         let op1 = op_with_sender(address(1));
@@ -2104,6 +2504,13 @@ mod tests {
                 ..Default::default()
             }]
         );
+        assert_eq!(
+            bundle.dropped_ops,
+            vec![(
+                op1.hash(address(123), 0),
+                "post op reverted leading to entry point revert".to_owned()
+            )]
+        );
     }
 
     #[tokio::test]
@@ -2281,6 +2688,69 @@ mod tests {
         max_priority_fee_per_gas: U256,
         notify_condition_not_met: bool,
         actual_storage: ExpectedStorage,
+    ) -> Bundle<UserOperation> {
+        mock_make_bundle_with_settings(
+            mock_ops,
+            mock_aggregators,
+            mock_handle_ops_call_results,
+            mock_paymaster_deposits,
+            base_fee,
+            max_priority_fee_per_gas,
+            notify_condition_not_met,
+            actual_storage,
+            None,
+            None,
+            ChainSpec::default(),
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn mock_make_bundle_with_settings(
+        mock_ops: Vec<MockOp>,
+        mock_aggregators: Vec<MockAggregator>,
+        mock_handle_ops_call_results: Vec<HandleOpsOut>,
+        mock_paymaster_deposits: Vec<U256>,
+        base_fee: U256,
+        max_priority_fee_per_gas: U256,
+        notify_condition_not_met: bool,
+        actual_storage: ExpectedStorage,
+        max_bundle_senders: Option<u64>,
+        max_bundle_pre_verification_gas: Option<u64>,
+        chain_spec: ChainSpec,
+    ) -> Bundle<UserOperation> {
+        let (event_sender, _) = broadcast::channel(16);
+        mock_make_bundle_with_events(
+            mock_ops,
+            mock_aggregators,
+            mock_handle_ops_call_results,
+            mock_paymaster_deposits,
+            base_fee,
+            max_priority_fee_per_gas,
+            notify_condition_not_met,
+            actual_storage,
+            max_bundle_senders,
+            max_bundle_pre_verification_gas,
+            chain_spec,
+            event_sender,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn mock_make_bundle_with_events(
+        mock_ops: Vec<MockOp>,
+        mock_aggregators: Vec<MockAggregator>,
+        mock_handle_ops_call_results: Vec<HandleOpsOut>,
+        mock_paymaster_deposits: Vec<U256>,
+        base_fee: U256,
+        max_priority_fee_per_gas: U256,
+        notify_condition_not_met: bool,
+        actual_storage: ExpectedStorage,
+        max_bundle_senders: Option<u64>,
+        max_bundle_pre_verification_gas: Option<u64>,
+        chain_spec: ChainSpec,
+        event_sender: broadcast::Sender<WithEntryPoint<BuilderEvent>>,
     ) -> Bundle<UserOperation> {
         let entry_point_address = address(123);
         let beneficiary = address(124);
@@ -2291,11 +2761,12 @@ mod tests {
             .iter()
             .map(|MockOp { op, .. }| PoolOperation {
                 uo: op.clone().into(),
-                expected_code_hash,
+                expected_code_hash: Some(expected_code_hash),
                 entry_point: entry_point_address,
                 sim_block_hash: current_block_hash,
                 sim_block_number: 0,
                 account_is_staked: false,
+                no_replacement: false,
                 valid_time_range: ValidTimeRange::default(),
                 entity_infos: EntityInfos::default(),
                 aggregator: None,
@@ -2364,7 +2835,6 @@ mod tests {
         entry_point
             .expect_aggregate_signatures()
             .returning(move |address, _| Ok(signatures_by_aggregator[&address]().unwrap()));
-        let (event_sender, _) = broadcast::channel(16);
         let mut proposer = BundleProposerImpl::new(
             0,
             pool_client,
@@ -2372,12 +2842,16 @@ mod tests {
             entry_point,
             Arc::new(provider),
             Settings {
-                chain_spec: ChainSpec::default(),
+                chain_spec,
                 max_bundle_size,
+                max_bundle_senders,
+                max_bundle_pre_verification_gas,
                 max_bundle_gas: 10_000_000,
                 beneficiary,
                 priority_fee_mode: PriorityFeeMode::PriorityFeeIncreasePercent(10),
                 bundle_priority_fee_overhead_percent: 0,
+                bundle_base_fee_headroom_percent: 0,
+                time_range_buffer: TIME_RANGE_BUFFER,
             },
             event_sender,
         );
@@ -15,14 +15,17 @@ use std::{marker::PhantomData, sync::Arc, time::Duration};
 
 use anyhow::{bail, Context};
 use async_trait::async_trait;
-use ethers::types::{transaction::eip2718::TypedTransaction, Address, H256, U256};
+use ethers::types::{
+    transaction::{eip2718::TypedTransaction, eip2930::AccessList},
+    Address, H256, U256,
+};
 use futures_util::StreamExt;
 #[cfg(test)]
 use mockall::automock;
 use rundler_provider::{BundleHandler, EntryPoint};
 use rundler_sim::ExpectedStorage;
 use rundler_types::{
-    builder::BundlingMode,
+    builder::{BundlePreview, BundlingMode},
     chain::ChainSpec,
     pool::{NewHead, Pool},
     EntityUpdate, UserOperation,
@@ -30,7 +33,7 @@ use rundler_types::{
 use rundler_utils::emit::WithEntryPoint;
 use tokio::{
     join,
-    sync::{broadcast, mpsc, mpsc::UnboundedReceiver, oneshot},
+    sync::{broadcast, mpsc, mpsc::UnboundedReceiver, oneshot, OwnedSemaphorePermit, Semaphore},
 };
 use tracing::{debug, error, info, instrument, warn};
 
@@ -50,6 +53,15 @@ pub(crate) struct Settings {
     pub(crate) max_replacement_underpriced_blocks: u64,
     pub(crate) max_cancellation_fee_increases: u64,
     pub(crate) max_blocks_to_wait_for_mine: u64,
+    /// If set, auto bundling is triggered every this many new blocks instead of on
+    /// every new block and on the send interval timer
+    pub(crate) bundle_block_interval: Option<u64>,
+    /// If set, attach an EIP-2930 access list of the storage accessed during simulation
+    /// to bundle transactions
+    pub(crate) use_access_list: bool,
+    /// If set, bounds the number of bundle transactions in flight across all bundle senders.
+    /// A permit is held from assembling a bundle until its transaction is mined, dropped or abandoned.
+    pub(crate) in_flight_limiter: Option<Arc<Semaphore>>,
 }
 
 #[derive(Debug)]
@@ -73,17 +85,27 @@ struct BundleTx {
     tx: TypedTransaction,
     expected_storage: ExpectedStorage,
     op_hashes: Vec<H256>,
+    dropped_ops: Vec<(H256, String)>,
 }
 
 pub enum BundleSenderAction {
     SendBundle(SendBundleRequest),
     ChangeMode(BundlingMode),
+    PreviewBundle(PreviewBundleRequest),
 }
 
 pub struct SendBundleRequest {
     pub responder: oneshot::Sender<SendBundleResult>,
 }
 
+/// Request to build the next bundle without sending it.
+///
+/// Only handled while the sender is waiting to build a bundle, not while a bundle
+/// transaction is pending.
+pub struct PreviewBundleRequest {
+    pub responder: oneshot::Sender<anyhow::Result<BundlePreview>>,
+}
+
 /// Response to a `SendBundleRequest` after
 /// going through a full cycle of bundling, sending,
 /// and waiting for the transaction to be mined.
@@ -99,10 +121,22 @@ pub enum SendBundleResult {
     Error(anyhow::Error),
 }
 
+/// Outcome of a single successful bundle send attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleResult {
+    /// Hash of the sent bundle transaction
+    pub tx_hash: H256,
+    /// Hashes of the operations included in the bundle
+    pub included_ops: Vec<H256>,
+    /// Hashes of the operations dropped while forming the bundle, along with the reason
+    pub dropped_ops: Vec<(H256, String)>,
+}
+
 // Internal result of attempting to send a bundle.
+#[derive(Debug)]
 enum SendBundleAttemptResult {
     // The bundle was successfully sent
-    Success,
+    Success(BundleResult),
     // There are no operations available to bundle
     NoOperationsInitially,
     // There were no operations after the fee was increased
@@ -115,6 +149,41 @@ enum SendBundleAttemptResult {
     ConditionNotMet,
     // Nonce too low
     NonceTooLow,
+    // The bundle reverted on submission
+    Reverted,
+    // The builder account can't pay for the bundle
+    InsufficientFunds,
+}
+
+/// How the sender should react to a failed bundle submission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SubmissionErrorAction {
+    /// The nonce is stale, refetch it and retry
+    RefetchNonce,
+    /// The replacement is underpriced, bump fees and retry
+    BumpFees,
+    /// A storage condition of the bundle is not met, recheck conditions and retry
+    RecheckConditions,
+    /// Some ops in the bundle are no longer valid, trim them and retry
+    TrimAndRetry,
+    /// Retrying can't succeed until the builder account is funded. Record the insufficient
+    /// funds metric and abandon the attempt until the next trigger, without stopping the sender.
+    AlertAndWait,
+    /// Unknown error, fail this bundle attempt
+    Fail,
+}
+
+impl SubmissionErrorAction {
+    fn classify(error: &TransactionTrackerError) -> Self {
+        match error {
+            TransactionTrackerError::NonceTooLow => Self::RefetchNonce,
+            TransactionTrackerError::ReplacementUnderpriced => Self::BumpFees,
+            TransactionTrackerError::ConditionNotMet => Self::RecheckConditions,
+            TransactionTrackerError::Reverted => Self::TrimAndRetry,
+            TransactionTrackerError::InsufficientFunds => Self::AlertAndWait,
+            TransactionTrackerError::Other(_) => Self::Fail,
+        }
+    }
 }
 
 #[async_trait]
@@ -136,6 +205,7 @@ where
             &self.pool,
             self.bundle_action_receiver.take().unwrap(),
             Duration::from_millis(self.chain_spec.bundle_max_send_interval_millis),
+            self.settings.bundle_block_interval,
         )
         .await?;
 
@@ -199,6 +269,14 @@ where
     ) -> anyhow::Result<()> {
         let tracker_update = state.wait_for_trigger().await?;
 
+        if let Some(responder) = state.preview_bundle_response.take() {
+            let preview = self.preview_bundle().await;
+            if responder.send(preview).is_err() {
+                error!("Failed to send bundle preview to caller");
+            }
+            return Ok(());
+        }
+
         match state.inner {
             InnerState::Building(building_state) => {
                 self.handle_building_state(state, building_state).await?;
@@ -227,14 +305,30 @@ where
     ) -> anyhow::Result<()> {
         // send bundle
         let block_number = state.block_number();
+        if !self.acquire_in_flight_permit(state) {
+            debug!("Maximum bundle transactions in flight, waiting for next trigger");
+            self.metrics.increment_bundle_txns_in_flight_limited();
+            state.complete(Some(SendBundleResult::Error(anyhow::anyhow!(
+                "maximum bundle transactions in flight"
+            ))));
+            return Ok(());
+        }
         debug!("Building bundle on block {}", block_number);
         let result = self.send_bundle(state, inner.fee_increase_count).await;
 
         // handle result
         match result {
-            Ok(SendBundleAttemptResult::Success) => {
+            Ok(SendBundleAttemptResult::Success(result)) => {
                 // sent the bundle
-                info!("Bundle sent successfully");
+                info!(
+                    "Bundle sent successfully with tx hash {:?}, {} included op(s) and {} dropped op(s)",
+                    result.tx_hash,
+                    result.included_ops.len(),
+                    result.dropped_ops.len()
+                );
+                for (op_hash, reason) in &result.dropped_ops {
+                    debug!("Dropped op {op_hash:?} from bundle: {reason}");
+                }
                 state.update(InnerState::Pending(inner.to_pending(
                     block_number + self.settings.max_blocks_to_wait_for_mine,
                 )));
@@ -295,6 +389,21 @@ where
                 self.proposer.notify_condition_not_met();
                 state.update(InnerState::Building(inner.retry()));
             }
+            Ok(SendBundleAttemptResult::Reverted) => {
+                // the next attempt re-simulates the bundle, dropping the reverting ops. Wait for
+                // the next trigger so that a bundle that keeps reverting isn't resent in a loop.
+                info!("Bundle reverted, starting new bundle attempt on next trigger");
+                state.update(InnerState::Building(inner.retry_on_trigger()));
+            }
+            Ok(SendBundleAttemptResult::InsufficientFunds) => {
+                error!(
+                    "Builder account has insufficient funds to send bundle, abandoning bundle attempt until next trigger"
+                );
+                self.metrics.increment_bundle_txns_failed();
+                state.complete(Some(SendBundleResult::Error(anyhow::anyhow!(
+                    "builder account has insufficient funds"
+                ))));
+            }
             Err(error) => {
                 error!("Bundle send error {error:?}");
                 self.metrics.increment_bundle_txns_failed();
@@ -495,6 +604,38 @@ where
         Ok(())
     }
 
+    /// Returns false if the in flight limit is reached. A sender already holding a permit
+    /// keeps it while replacing or cancelling its transaction.
+    fn acquire_in_flight_permit<TRIG: Trigger>(
+        &self,
+        state: &mut SenderMachineState<T, TRIG>,
+    ) -> bool {
+        let Some(limiter) = &self.settings.in_flight_limiter else {
+            return true;
+        };
+        if state.in_flight_permit.is_none() {
+            state.in_flight_permit = Arc::clone(limiter).try_acquire_owned().ok();
+        }
+        state.in_flight_permit.is_some()
+    }
+
+    /// Constructs a bundle without sending it, leaving rejected ops in the pool.
+    async fn preview_bundle(&mut self) -> anyhow::Result<BundlePreview> {
+        let bundle = match self.proposer.make_bundle(None, false).await {
+            Ok(bundle) => bundle,
+            Err(
+                BundleProposerError::NoOperationsInitially
+                | BundleProposerError::NoOperationsAfterFeeFilter,
+            ) => return Ok(BundlePreview::default()),
+            Err(e) => bail!("Failed to make bundle: {e:?}"),
+        };
+
+        Ok(BundlePreview {
+            user_op_hashes: bundle.iter_ops().map(|op| self.op_hash(op)).collect(),
+            gas_estimate: bundle.gas_estimate,
+        })
+    }
+
     /// Constructs a bundle and sends it to the entry point as a transaction.
     ///
     /// Returns empty if:
@@ -537,6 +678,7 @@ where
             tx,
             expected_storage,
             op_hashes,
+            dropped_ops,
         } = bundle_tx;
 
         self.metrics.increment_bundle_txns_sent();
@@ -553,34 +695,49 @@ where
                     Some(BundleTxDetails {
                         tx_hash,
                         tx,
-                        op_hashes: Arc::new(op_hashes),
+                        op_hashes: Arc::new(op_hashes.clone()),
                     }),
                     nonce.low_u64(),
                     fee_increase_count,
                     required_fees,
                 ));
 
-                Ok(SendBundleAttemptResult::Success)
-            }
-            Err(TransactionTrackerError::NonceTooLow) => {
-                self.metrics.increment_bundle_txn_nonce_too_low();
-                warn!("Bundle attempt nonce too low");
-                Ok(SendBundleAttemptResult::NonceTooLow)
-            }
-            Err(TransactionTrackerError::ReplacementUnderpriced) => {
-                self.metrics.increment_bundle_txn_replacement_underpriced();
-                warn!("Bundle attempt replacement transaction underpriced");
-                Ok(SendBundleAttemptResult::ReplacementUnderpriced)
-            }
-            Err(TransactionTrackerError::ConditionNotMet) => {
-                self.metrics.increment_bundle_txn_condition_not_met();
-                warn!("Bundle attempt condition not met");
-                Ok(SendBundleAttemptResult::ConditionNotMet)
-            }
-            Err(e) => {
-                error!("Failed to send bundle with unexpected error: {e:?}");
-                Err(e.into())
+                Ok(SendBundleAttemptResult::Success(BundleResult {
+                    tx_hash,
+                    included_ops: op_hashes,
+                    dropped_ops,
+                }))
             }
+            Err(e) => match SubmissionErrorAction::classify(&e) {
+                SubmissionErrorAction::RefetchNonce => {
+                    self.metrics.increment_bundle_txn_nonce_too_low();
+                    warn!("Bundle attempt nonce too low");
+                    Ok(SendBundleAttemptResult::NonceTooLow)
+                }
+                SubmissionErrorAction::BumpFees => {
+                    self.metrics.increment_bundle_txn_replacement_underpriced();
+                    warn!("Bundle attempt replacement transaction underpriced");
+                    Ok(SendBundleAttemptResult::ReplacementUnderpriced)
+                }
+                SubmissionErrorAction::RecheckConditions => {
+                    self.metrics.increment_bundle_txn_condition_not_met();
+                    warn!("Bundle attempt condition not met");
+                    Ok(SendBundleAttemptResult::ConditionNotMet)
+                }
+                SubmissionErrorAction::TrimAndRetry => {
+                    self.metrics.increment_bundle_txn_reverted();
+                    warn!("Bundle attempt reverted");
+                    Ok(SendBundleAttemptResult::Reverted)
+                }
+                SubmissionErrorAction::AlertAndWait => {
+                    self.metrics.increment_bundle_txn_insufficient_funds();
+                    Ok(SendBundleAttemptResult::InsufficientFunds)
+                }
+                SubmissionErrorAction::Fail => {
+                    error!("Failed to send bundle with unexpected error: {e:?}");
+                    Err(e.into())
+                }
+            },
         }
     }
 
@@ -632,17 +789,30 @@ where
             bundle.entity_updates.len()
         );
         let op_hashes: Vec<_> = bundle.iter_ops().map(|op| self.op_hash(op)).collect();
+        let access_list = self
+            .settings
+            .use_access_list
+            .then(|| bundle.expected_storage.access_list());
+        // the access list is charged as intrinsic gas on top of the estimated bundle gas
+        let gas_limit = bundle.gas_estimate
+            + access_list
+                .as_ref()
+                .map_or(U256::zero(), access_list_intrinsic_gas);
         let mut tx = self.entry_point.get_send_bundle_transaction(
             bundle.ops_per_aggregator,
             self.beneficiary,
-            bundle.gas_estimate,
+            gas_limit,
             bundle.gas_fees,
         );
         tx.set_nonce(nonce);
+        if let Some(access_list) = access_list {
+            tx.set_access_list(access_list);
+        }
         Ok(Some(BundleTx {
             tx,
             expected_storage: bundle.expected_storage,
             op_hashes,
+            dropped_ops: bundle.dropped_ops,
         }))
     }
 
@@ -677,12 +847,30 @@ where
     }
 }
 
+/// EIP-2930 intrinsic gas charged per address in an access list
+const ACCESS_LIST_ADDRESS_GAS: u64 = 2400;
+/// EIP-2930 intrinsic gas charged per storage key in an access list
+const ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1900;
+
+fn access_list_intrinsic_gas(access_list: &AccessList) -> U256 {
+    access_list
+        .0
+        .iter()
+        .map(|item| {
+            ACCESS_LIST_ADDRESS_GAS + ACCESS_LIST_STORAGE_KEY_GAS * item.storage_keys.len() as u64
+        })
+        .sum::<u64>()
+        .into()
+}
+
 struct SenderMachineState<T, TRIG> {
     trigger: TRIG,
     transaction_tracker: T,
     send_bundle_response: Option<oneshot::Sender<SendBundleResult>>,
+    preview_bundle_response: Option<oneshot::Sender<anyhow::Result<BundlePreview>>>,
     inner: InnerState,
     requires_reset: bool,
+    in_flight_permit: Option<OwnedSemaphorePermit>,
 }
 
 impl<T: TransactionTracker, TRIG: Trigger> SenderMachineState<T, TRIG> {
@@ -691,8 +879,10 @@ impl<T: TransactionTracker, TRIG: Trigger> SenderMachineState<T, TRIG> {
             trigger,
             transaction_tracker,
             send_bundle_response: None,
+            preview_bundle_response: None,
             inner: InnerState::new(),
             requires_reset: false,
+            in_flight_permit: None,
         }
     }
 
@@ -703,6 +893,7 @@ impl<T: TransactionTracker, TRIG: Trigger> SenderMachineState<T, TRIG> {
     // resets the state machine to the initial state, doesn't wait for next trigger
     fn reset(&mut self) {
         self.requires_reset = true;
+        self.in_flight_permit = None;
         let building_state = BuildingState {
             wait_for_trigger: false,
             fee_increase_count: 0,
@@ -726,6 +917,7 @@ impl<T: TransactionTracker, TRIG: Trigger> SenderMachineState<T, TRIG> {
 
     fn abandon(&mut self) {
         self.transaction_tracker.abandon();
+        self.in_flight_permit = None;
         self.inner = InnerState::new();
     }
 
@@ -737,6 +929,7 @@ impl<T: TransactionTracker, TRIG: Trigger> SenderMachineState<T, TRIG> {
                 }
             }
         }
+        self.in_flight_permit = None;
         self.inner = InnerState::new();
     }
 
@@ -752,7 +945,13 @@ impl<T: TransactionTracker, TRIG: Trigger> SenderMachineState<T, TRIG> {
                     return Ok(None);
                 }
 
-                self.send_bundle_response = self.trigger.wait_for_trigger().await?;
+                match self.trigger.wait_for_trigger().await? {
+                    TriggerAction::SendBundle(r) => self.send_bundle_response = r,
+                    TriggerAction::PreviewBundle(r) => {
+                        self.preview_bundle_response = Some(r);
+                        return Ok(None);
+                    }
+                }
                 self.transaction_tracker
                     .check_for_update()
                     .await
@@ -831,6 +1030,12 @@ impl BuildingState {
         self
     }
 
+    // Retry the build after the next trigger
+    fn retry_on_trigger(mut self) -> Self {
+        self.wait_for_trigger = true;
+        self
+    }
+
     // Mark a replacement as underpriced
     //
     // The next state will wait for a trigger to reduce bundle building loops
@@ -918,12 +1123,18 @@ impl CancelPendingState {
     }
 }
 
+// Action to take once the trigger fires
+enum TriggerAction {
+    // Build and send a bundle, responding to the manual caller if set
+    SendBundle(Option<oneshot::Sender<SendBundleResult>>),
+    // Build a bundle without sending it, responding with its preview
+    PreviewBundle(oneshot::Sender<anyhow::Result<BundlePreview>>),
+}
+
 #[async_trait]
 #[cfg_attr(test, automock)]
 trait Trigger {
-    async fn wait_for_trigger(
-        &mut self,
-    ) -> anyhow::Result<Option<oneshot::Sender<SendBundleResult>>>;
+    async fn wait_for_trigger(&mut self) -> anyhow::Result<TriggerAction>;
 
     async fn wait_for_block(&mut self) -> anyhow::Result<NewHead>;
 
@@ -936,24 +1147,28 @@ struct BundleSenderTrigger {
     bundle_action_receiver: mpsc::Receiver<BundleSenderAction>,
     timer: tokio::time::Interval,
     last_block: NewHead,
+    // If set, auto mode triggers every this many blocks, ignoring the timer
+    block_interval: Option<u64>,
+    // Block number of the last auto trigger
+    last_trigger_block: Option<u64>,
 }
 
 #[async_trait]
 impl Trigger for BundleSenderTrigger {
-    async fn wait_for_trigger(
-        &mut self,
-    ) -> anyhow::Result<Option<oneshot::Sender<SendBundleResult>>> {
+    async fn wait_for_trigger(&mut self) -> anyhow::Result<TriggerAction> {
         let mut send_bundle_response: Option<oneshot::Sender<SendBundleResult>> = None;
 
         loop {
             // 3 triggers for loop logic:
             // 1 - new block
-            //      - If auto mode, send next bundle
+            //      - If auto mode, send next bundle. If bundling every N blocks,
+            //        only once N blocks have passed since the last trigger
             // 2 - timer tick
-            //      - If auto mode, send next bundle
+            //      - If auto mode and not bundling every N blocks, send next bundle
             // 3 - action recv
             //      - If change mode, change and restart loop
             //      - If send bundle and manual mode, send next bundle
+            //      - If preview bundle, build the next bundle without sending it
             tokio::select! {
                 b = self.block_rx.recv() => {
                     let Some(b) = b else {
@@ -965,13 +1180,18 @@ impl Trigger for BundleSenderTrigger {
 
                     match self.bundling_mode {
                         BundlingMode::Manual => continue,
-                        BundlingMode::Auto => break,
+                        BundlingMode::Auto => {
+                            if self.block_interval_elapsed() {
+                                break;
+                            }
+                            continue;
+                        }
                     }
                 },
                 _ = self.timer.tick() => {
-                    match self.bundling_mode {
-                        BundlingMode::Manual => continue,
-                        BundlingMode::Auto => break,
+                    match (self.bundling_mode, self.block_interval) {
+                        (BundlingMode::Auto, None) => break,
+                        _ => continue,
                     }
                 },
                 a = self.bundle_action_receiver.recv() => {
@@ -993,6 +1213,9 @@ impl Trigger for BundleSenderTrigger {
                                 }
                             }
                         },
+                        Some(BundleSenderAction::PreviewBundle(r)) => {
+                            return Ok(TriggerAction::PreviewBundle(r.responder));
+                        },
                         None => {
                             error!("Bundle action recv closed");
                             bail!("Bundle action recv closed");
@@ -1003,8 +1226,9 @@ impl Trigger for BundleSenderTrigger {
         }
 
         self.consume_blocks()?;
+        self.last_trigger_block = Some(self.last_block.block_number);
 
-        Ok(send_bundle_response)
+        Ok(TriggerAction::SendBundle(send_bundle_response))
     }
 
     async fn wait_for_block(&mut self) -> anyhow::Result<NewHead> {
@@ -1027,6 +1251,7 @@ impl BundleSenderTrigger {
         pool_client: &P,
         bundle_action_receiver: mpsc::Receiver<BundleSenderAction>,
         timer_interval: Duration,
+        block_interval: Option<u64>,
     ) -> anyhow::Result<Self> {
         let block_rx = Self::start_block_stream(pool_client).await?;
 
@@ -1039,9 +1264,21 @@ impl BundleSenderTrigger {
                 block_hash: H256::zero(),
                 block_number: 0,
             },
+            block_interval,
+            last_trigger_block: None,
         })
     }
 
+    // Whether enough blocks have passed since the last trigger to trigger again
+    fn block_interval_elapsed(&self) -> bool {
+        match (self.block_interval, self.last_trigger_block) {
+            (Some(interval), Some(last)) => {
+                self.last_block.block_number >= last.saturating_add(interval)
+            }
+            _ => true,
+        }
+    }
+
     async fn start_block_stream<P: Pool>(
         pool_client: &P,
     ) -> anyhow::Result<UnboundedReceiver<NewHead>> {
@@ -1147,6 +1384,14 @@ impl BuilderMetrics {
         metrics::counter!("builder_bundle_condition_not_met", "entry_point" => self.entry_point.to_string(), "builder_index" => self.builder_index.to_string()).increment(1);
     }
 
+    fn increment_bundle_txn_reverted(&self) {
+        metrics::counter!("builder_bundle_reverted", "entry_point" => self.entry_point.to_string(), "builder_index" => self.builder_index.to_string()).increment(1);
+    }
+
+    fn increment_bundle_txn_insufficient_funds(&self) {
+        metrics::counter!("builder_bundle_insufficient_funds", "entry_point" => self.entry_point.to_string(), "builder_index" => self.builder_index.to_string()).increment(1);
+    }
+
     fn increment_cancellation_txns_sent(&self) {
         metrics::counter!("builder_cancellation_txns_sent", "entry_point" => self.entry_point.to_string(), "builder_index" => self.builder_index.to_string()).increment(1);
     }
@@ -1171,6 +1416,10 @@ impl BuilderMetrics {
         metrics::counter!("builder_cancellation_txns_failed", "entry_point" => self.entry_point.to_string(), "builder_index" => self.builder_index.to_string()).increment(1);
     }
 
+    fn increment_bundle_txns_in_flight_limited(&self) {
+        metrics::counter!("builder_bundle_txns_in_flight_limited", "entry_point" => self.entry_point.to_string(), "builder_index" => self.builder_index.to_string()).increment(1);
+    }
+
     fn increment_state_machine_errors(&self) {
         metrics::counter!("builder_state_machine_errors", "entry_point" => self.entry_point.to_string(), "builder_index" => self.builder_index.to_string()).increment(1);
     }
@@ -1178,7 +1427,8 @@ impl BuilderMetrics {
 
 #[cfg(test)]
 mod tests {
-    use ethers::types::Bytes;
+    use ethers::types::{transaction::eip2930::AccessListItem, Bytes, Eip1559TransactionRequest};
+    use futures_util::FutureExt;
     use mockall::Sequence;
     use rundler_provider::MockEntryPointV0_6;
     use rundler_types::{
@@ -1293,6 +1543,241 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_send_paused_at_in_flight_cap() {
+        let Mocks {
+            mut mock_proposer,
+            mut mock_entry_point,
+            mut mock_tracker,
+            mut mock_trigger,
+        } = new_mocks();
+
+        // blocks 0 and 1
+        let mut seq = Sequence::new();
+        add_trigger_no_update_last_block(&mut mock_trigger, &mut mock_tracker, &mut seq, 0);
+        add_trigger_no_update_last_block(&mut mock_trigger, &mut mock_tracker, &mut seq, 1);
+
+        mock_tracker
+            .expect_get_nonce_and_required_fees()
+            .returning(|| Ok((U256::zero(), None)));
+
+        // only assembled once the cap frees up
+        mock_proposer
+            .expect_make_bundle()
+            .times(1)
+            .returning(|_, _| Box::pin(async { Ok(bundle()) }));
+        mock_entry_point
+            .expect_get_send_bundle_transaction()
+            .returning(|_, _, _, _| TypedTransaction::default());
+        mock_tracker
+            .expect_send_transaction()
+            .returning(|_, _| Box::pin(async { Ok(H256::zero()) }));
+
+        let limiter = Arc::new(Semaphore::new(1));
+        let mut sender = new_sender(mock_proposer, mock_entry_point);
+        sender.settings.in_flight_limiter = Some(Arc::clone(&limiter));
+
+        // another sender's bundle is in flight
+        let other_permit = Arc::clone(&limiter).try_acquire_owned().unwrap();
+
+        let mut state = SenderMachineState::new(mock_trigger, mock_tracker);
+        sender.step_state(&mut state).await.unwrap();
+
+        // assembly is skipped, waiting for the next trigger
+        assert!(matches!(
+            state.inner,
+            InnerState::Building(BuildingState {
+                wait_for_trigger: true,
+                ..
+            })
+        ));
+        assert!(state.in_flight_permit.is_none());
+
+        // the other bundle is mined, this sender assembles and sends its bundle
+        drop(other_permit);
+        sender.step_state(&mut state).await.unwrap();
+        assert!(matches!(state.inner, InnerState::Pending(_)));
+        assert!(state.in_flight_permit.is_some());
+        assert_eq!(limiter.available_permits(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_access_list() {
+        let Mocks {
+            mut mock_proposer,
+            mut mock_entry_point,
+            mut mock_tracker,
+            mut mock_trigger,
+        } = new_mocks();
+
+        add_trigger_no_update_last_block(
+            &mut mock_trigger,
+            &mut mock_tracker,
+            &mut Sequence::new(),
+            0,
+        );
+        mock_tracker
+            .expect_get_nonce_and_required_fees()
+            .returning(|| Ok((U256::zero(), None)));
+
+        // bundle whose simulation read two slots of one contract and one of another
+        let (address0, address1) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let mut expected_storage = ExpectedStorage::default();
+        expected_storage.insert(address0, 1.into(), 10.into());
+        expected_storage.insert(address0, 2.into(), 20.into());
+        expected_storage.insert(address1, 3.into(), 30.into());
+        mock_proposer.expect_make_bundle().returning(move |_, _| {
+            let expected_storage = expected_storage.clone();
+            Box::pin(async move {
+                Ok(Bundle {
+                    expected_storage,
+                    ..bundle()
+                })
+            })
+        });
+
+        // 100k bundle gas plus 2 addresses and 3 storage keys of intrinsic access list gas
+        mock_entry_point
+            .expect_get_send_bundle_transaction()
+            .withf(|_, _, &gas, _| gas == U256::from(100_000 + 2 * 2400 + 3 * 1900))
+            .returning(|_, _, _, _| Eip1559TransactionRequest::new().into());
+
+        let expected_access_list = AccessList(vec![
+            AccessListItem {
+                address: address0,
+                storage_keys: vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)],
+            },
+            AccessListItem {
+                address: address1,
+                storage_keys: vec![H256::from_low_u64_be(3)],
+            },
+        ]);
+        mock_tracker
+            .expect_send_transaction()
+            .withf(move |tx, _| tx.access_list() == Some(&expected_access_list))
+            .once()
+            .returning(|_, _| Box::pin(async { Ok(H256::zero()) }));
+
+        let mut sender = new_sender(mock_proposer, mock_entry_point);
+        sender.settings.use_access_list = true;
+        let mut state = SenderMachineState::new(mock_trigger, mock_tracker);
+
+        sender.step_state(&mut state).await.unwrap();
+        assert!(matches!(state.inner, InnerState::Pending(_)));
+    }
+
+    #[tokio::test]
+    async fn test_preview_matches_sent_bundle() {
+        let Mocks {
+            mut mock_proposer,
+            mut mock_entry_point,
+            mut mock_tracker,
+            mut mock_trigger,
+        } = new_mocks();
+        let mut seq = Sequence::new();
+
+        // preview first, then a manual send
+        let (preview_tx, mut preview_rx) = oneshot::channel();
+        let mut preview_tx = Some(preview_tx);
+        mock_trigger
+            .expect_wait_for_trigger()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(move || {
+                let r = preview_tx.take().unwrap();
+                Box::pin(async move { Ok(TriggerAction::PreviewBundle(r)) })
+            });
+        add_trigger_no_update_last_block(&mut mock_trigger, &mut mock_tracker, &mut seq, 0);
+
+        mock_tracker
+            .expect_get_nonce_and_required_fees()
+            .returning(|| Ok((U256::zero(), None)));
+        mock_proposer
+            .expect_make_bundle()
+            .times(2)
+            .returning(|_, _| Box::pin(async { Ok(bundle()) }));
+
+        let expected_ops = bundle().ops_per_aggregator;
+        mock_entry_point
+            .expect_get_send_bundle_transaction()
+            .withf(move |ops, _, _, _| *ops == expected_ops)
+            .once()
+            .returning(|_, _, _, _| TypedTransaction::default());
+        mock_tracker
+            .expect_send_transaction()
+            .once()
+            .returning(|_, _| Box::pin(async { Ok(H256::zero()) }));
+
+        let mut sender = new_sender(mock_proposer, mock_entry_point);
+        let mut state = SenderMachineState::new(mock_trigger, mock_tracker);
+
+        // preview doesn't send or leave the building state
+        sender.step_state(&mut state).await.unwrap();
+        let preview = preview_rx.try_recv().unwrap().unwrap();
+        let expected_hashes = bundle()
+            .iter_ops()
+            .map(|op| sender.op_hash(op))
+            .collect::<Vec<_>>();
+        assert_eq!(preview.user_op_hashes, expected_hashes);
+        assert_eq!(preview.gas_estimate, bundle().gas_estimate);
+        assert!(matches!(state.inner, InnerState::Building(_)));
+
+        // the sent bundle contains the previewed ops
+        sender.step_state(&mut state).await.unwrap();
+        assert!(matches!(state.inner, InnerState::Pending(_)));
+    }
+
+    #[tokio::test]
+    async fn test_send_returns_bundle_result() {
+        let Mocks {
+            mut mock_proposer,
+            mut mock_entry_point,
+            mut mock_tracker,
+            mock_trigger,
+        } = new_mocks();
+
+        mock_tracker
+            .expect_get_nonce_and_required_fees()
+            .returning(|| Ok((U256::zero(), None)));
+
+        // bundle with one op, after another was trimmed during proposal
+        mock_proposer
+            .expect_make_bundle()
+            .times(1)
+            .returning(|_, _| {
+                Box::pin(async {
+                    Ok(Bundle {
+                        dropped_ops: vec![(H256::repeat_byte(1), "condition not met".to_owned())],
+                        ..bundle()
+                    })
+                })
+            });
+
+        mock_entry_point
+            .expect_get_send_bundle_transaction()
+            .returning(|_, _, _, _| TypedTransaction::default());
+
+        mock_tracker
+            .expect_send_transaction()
+            .returning(|_, _| Box::pin(async { Ok(H256::repeat_byte(2)) }));
+
+        let mut sender = new_sender(mock_proposer, mock_entry_point);
+        let mut state = SenderMachineState::new(mock_trigger, mock_tracker);
+        let included_op_hash = sender.op_hash(&UserOperation::default());
+
+        let result = sender.send_bundle(&mut state, 0).await.unwrap();
+
+        let SendBundleAttemptResult::Success(result) = result else {
+            panic!("expected successful send, got {result:?}");
+        };
+        assert_eq!(result.tx_hash, H256::repeat_byte(2));
+        assert_eq!(result.included_ops, vec![included_op_hash]);
+        assert_eq!(
+            result.dropped_ops,
+            vec![(H256::repeat_byte(1), "condition not met".to_owned())]
+        );
+    }
+
     #[tokio::test]
     async fn test_wait_for_mine_success() {
         let Mocks {
@@ -1349,11 +1834,13 @@ mod tests {
             trigger: mock_trigger,
             transaction_tracker: mock_tracker,
             send_bundle_response: None,
+            preview_bundle_response: None,
             inner: InnerState::Pending(PendingState {
                 until: 3,
                 fee_increase_count: 0,
             }),
             requires_reset: false,
+            in_flight_permit: None,
         };
 
         // first step has no update
@@ -1401,11 +1888,13 @@ mod tests {
             trigger: mock_trigger,
             transaction_tracker: mock_tracker,
             send_bundle_response: None,
+            preview_bundle_response: None,
             inner: InnerState::Pending(PendingState {
                 until: 3,
                 fee_increase_count: 0,
             }),
             requires_reset: false,
+            in_flight_permit: None,
         };
 
         // first and second step has no update
@@ -1461,6 +1950,7 @@ mod tests {
             trigger: mock_trigger,
             transaction_tracker: mock_tracker,
             send_bundle_response: None,
+            preview_bundle_response: None,
             inner: InnerState::Building(BuildingState {
                 wait_for_trigger: true,
                 fee_increase_count: 0,
@@ -1470,6 +1960,7 @@ mod tests {
                 }),
             }),
             requires_reset: false,
+            in_flight_permit: None,
         };
 
         // step state, block number should trigger move to cancellation
@@ -1510,10 +2001,12 @@ mod tests {
             trigger: mock_trigger,
             transaction_tracker: mock_tracker,
             send_bundle_response: None,
+            preview_bundle_response: None,
             inner: InnerState::Cancelling(CancellingState {
                 fee_increase_count: 0,
             }),
             requires_reset: false,
+            in_flight_permit: None,
         };
 
         let mut sender = new_sender(mock_proposer, mock_entry_point);
@@ -1551,11 +2044,13 @@ mod tests {
             trigger: mock_trigger,
             transaction_tracker: mock_tracker,
             send_bundle_response: None,
+            preview_bundle_response: None,
             inner: InnerState::CancelPending(CancelPendingState {
                 until: 3,
                 fee_increase_count: 0,
             }),
             requires_reset: false,
+            in_flight_permit: None,
         };
 
         let mut sender = new_sender(mock_proposer, mock_entry_point);
@@ -1623,12 +2118,14 @@ mod tests {
             trigger: mock_trigger,
             transaction_tracker: mock_tracker,
             send_bundle_response: None,
+            preview_bundle_response: None,
             inner: InnerState::Building(BuildingState {
                 wait_for_trigger: true,
                 fee_increase_count: 0,
                 underpriced_info: None,
             }),
             requires_reset: false,
+            in_flight_permit: None,
         };
 
         let mut sender = new_sender(mock_proposer, mock_entry_point);
@@ -1646,6 +2143,101 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_classify_submission_errors() {
+        let cases = [
+            (
+                TransactionTrackerError::NonceTooLow,
+                SubmissionErrorAction::RefetchNonce,
+            ),
+            (
+                TransactionTrackerError::ReplacementUnderpriced,
+                SubmissionErrorAction::BumpFees,
+            ),
+            (
+                TransactionTrackerError::ConditionNotMet,
+                SubmissionErrorAction::RecheckConditions,
+            ),
+            (
+                TransactionTrackerError::Reverted,
+                SubmissionErrorAction::TrimAndRetry,
+            ),
+            (
+                TransactionTrackerError::InsufficientFunds,
+                SubmissionErrorAction::AlertAndWait,
+            ),
+            (
+                TransactionTrackerError::Other(anyhow::anyhow!("connection reset")),
+                SubmissionErrorAction::Fail,
+            ),
+        ];
+
+        for (error, expected) in cases {
+            assert_eq!(
+                SubmissionErrorAction::classify(&error),
+                expected,
+                "{error:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reverted_retries() {
+        let Mocks {
+            mut mock_proposer,
+            mut mock_entry_point,
+            mut mock_tracker,
+            mut mock_trigger,
+        } = new_mocks();
+
+        let mut seq = Sequence::new();
+        add_trigger_no_update_last_block(&mut mock_trigger, &mut mock_tracker, &mut seq, 1);
+
+        mock_tracker
+            .expect_get_nonce_and_required_fees()
+            .returning(|| Ok((U256::zero(), None)));
+        mock_proposer
+            .expect_make_bundle()
+            .times(1)
+            .returning(|_, _| Box::pin(async { Ok(bundle()) }));
+        mock_entry_point
+            .expect_get_send_bundle_transaction()
+            .returning(|_, _, _, _| TypedTransaction::default());
+
+        // should send the bundle txn, returns reverted
+        mock_tracker
+            .expect_send_transaction()
+            .returning(|_, _| Box::pin(async { Err(TransactionTrackerError::Reverted) }));
+
+        let mut state = SenderMachineState {
+            trigger: mock_trigger,
+            transaction_tracker: mock_tracker,
+            send_bundle_response: None,
+            preview_bundle_response: None,
+            inner: InnerState::Building(BuildingState {
+                wait_for_trigger: true,
+                fee_increase_count: 0,
+                underpriced_info: None,
+            }),
+            requires_reset: false,
+            in_flight_permit: None,
+        };
+
+        let mut sender = new_sender(mock_proposer, mock_entry_point);
+
+        sender.step_state(&mut state).await.unwrap();
+
+        // back in the building state, rebuilding a trimmed bundle on the next trigger
+        assert!(matches!(
+            state.inner,
+            InnerState::Building(BuildingState {
+                wait_for_trigger: true,
+                fee_increase_count: 0,
+                underpriced_info: None,
+            })
+        ));
+    }
+
     struct Mocks {
         mock_proposer: MockBundleProposer,
         mock_entry_point: MockEntryPointV0_6,
@@ -1690,11 +2282,52 @@ mod tests {
                 max_cancellation_fee_increases: 3,
                 max_blocks_to_wait_for_mine: 3,
                 max_replacement_underpriced_blocks: 3,
+                bundle_block_interval: None,
+                use_access_list: false,
+                in_flight_limiter: None,
             },
             broadcast::channel(1000).0,
         )
     }
 
+    #[tokio::test]
+    async fn test_trigger_every_n_blocks() {
+        let (block_tx, block_rx) = mpsc::unbounded_channel();
+        let (_action_tx, bundle_action_receiver) = mpsc::channel(1);
+        let mut trigger = BundleSenderTrigger {
+            bundling_mode: BundlingMode::Auto,
+            block_rx,
+            bundle_action_receiver,
+            // only the immediate first tick fires during the test, so triggers are driven by
+            // blocks alone
+            timer: tokio::time::interval(Duration::from_secs(3600)),
+            last_block: NewHead {
+                block_hash: H256::zero(),
+                block_number: 0,
+            },
+            block_interval: Some(3),
+            last_trigger_block: None,
+        };
+        let send_block = |block_number: u64| {
+            block_tx
+                .send(NewHead {
+                    block_hash: H256::random(),
+                    block_number,
+                })
+                .unwrap();
+        };
+
+        let mut triggered_at = vec![];
+        for block_number in 1..=10 {
+            send_block(block_number);
+            if trigger.wait_for_trigger().now_or_never().is_some() {
+                triggered_at.push(trigger.last_block().block_number);
+            }
+        }
+
+        assert_eq!(triggered_at, vec![1, 4, 7, 10]);
+    }
+
     fn add_trigger_no_update_last_block(
         mock_trigger: &mut MockTrigger,
         mock_tracker: &mut MockTransactionTracker,
@@ -1705,7 +2338,7 @@ mod tests {
             .expect_wait_for_trigger()
             .once()
             .in_sequence(seq)
-            .returning(move || Box::pin(async move { Ok(None) }));
+            .returning(move || Box::pin(async move { Ok(TriggerAction::SendBundle(None)) }));
         mock_tracker
             .expect_check_for_update()
             .returning(|| Box::pin(async { Ok(None) }));
@@ -1752,6 +2385,7 @@ mod tests {
             gas_fees: GasFees::default(),
             expected_storage: Default::default(),
             rejected_ops: vec![],
+            dropped_ops: vec![],
             entity_updates: vec![],
             ops_per_aggregator: vec![UserOpsPerAggregator {
                 aggregator: Address::zero(),
@@ -185,6 +185,10 @@ pub enum SkipReason {
     },
     /// Bundle ran out of space by gas limit to include the operation
     GasLimit,
+    /// Bundle ran out of space by total preVerificationGas to include the operation
+    PreVerificationGasLimit { max_pvg: U256 },
+    /// Bundle reached its maximum number of distinct senders
+    SenderLimit,
     /// Other reason, typically internal errors
     Other { reason: Arc<String> },
 }
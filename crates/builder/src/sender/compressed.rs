@@ -0,0 +1,264 @@
+// This file is part of Rundler.
+//
+// Rundler is free software: you can redistribute it and/or modify it under the
+// terms of the GNU Lesser General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// Rundler is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Rundler.
+// If not, see https://www.gnu.org/licenses/.
+
+use async_trait::async_trait;
+use ethers::{
+    providers::JsonRpcClient,
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, H256, U256},
+};
+use ethers_signers::Signer;
+use rundler_sim::ExpectedStorage;
+use rundler_types::GasFees;
+
+use super::{CancelTxInfo, Result, TransactionSenderEnum};
+use crate::sender::{SentTxInfo, TransactionSender, TxStatus};
+
+// Longest run of 0x00 or 0xff bytes that fits in a single run marker
+const MAX_RUN_LENGTH: usize = 0x80;
+
+// Gas used by the decompressor on top of the forwarded call: the cold call into the
+// decompressor, its call to the target and copying the return data
+const DECOMPRESSOR_BASE_GAS: u64 = 10_000;
+
+// Upper bound of the gas used by the decompressor per decompressed byte
+const DECOMPRESSOR_GAS_PER_BYTE: u64 = 100;
+
+/// Sends bundle transactions through a calldata decompressor contract.
+///
+/// The decompressor is called with the compressed encoding of the original target
+/// address followed by the original calldata. It decompresses the payload and forwards
+/// the calldata to the target, so the entry point sees the same `handleOps` call. The
+/// decompressor is `contracts/src/utils/CalldataDecompressor.sol` in the types crate.
+///
+/// Cancellations are sent uncompressed by the inner sender.
+pub(crate) struct CompressedTransactionSender<C, S, FS>
+where
+    C: JsonRpcClient + 'static,
+    S: Signer + 'static,
+    FS: Signer + 'static,
+{
+    inner: Box<TransactionSenderEnum<C, S, FS>>,
+    decompressor: Address,
+}
+
+#[async_trait]
+impl<C, S, FS> TransactionSender for CompressedTransactionSender<C, S, FS>
+where
+    C: JsonRpcClient + 'static,
+    S: Signer + 'static,
+    FS: Signer + 'static,
+{
+    async fn send_transaction(
+        &self,
+        tx: TypedTransaction,
+        expected_storage: &ExpectedStorage,
+    ) -> Result<SentTxInfo> {
+        let tx = compress_transaction(tx, self.decompressor)?;
+        self.inner.send_transaction(tx, expected_storage).await
+    }
+
+    async fn cancel_transaction(
+        &self,
+        tx_hash: H256,
+        nonce: U256,
+        to: Address,
+        gas_fees: GasFees,
+    ) -> Result<CancelTxInfo> {
+        self.inner
+            .cancel_transaction(tx_hash, nonce, to, gas_fees)
+            .await
+    }
+
+    async fn get_transaction_status(&self, tx_hash: H256) -> Result<TxStatus> {
+        self.inner.get_transaction_status(tx_hash).await
+    }
+
+    fn address(&self) -> Address {
+        self.inner.address()
+    }
+}
+
+impl<C, S, FS> CompressedTransactionSender<C, S, FS>
+where
+    C: JsonRpcClient + 'static,
+    S: Signer + 'static,
+    FS: Signer + 'static,
+{
+    pub(crate) fn new(inner: TransactionSenderEnum<C, S, FS>, decompressor: Address) -> Self {
+        Self {
+            inner: Box::new(inner),
+            decompressor,
+        }
+    }
+}
+
+/// Rewrite a transaction to call the decompressor with the compressed target and calldata.
+///
+/// The gas limit is raised by the gas the decompressor uses.
+fn compress_transaction(
+    mut tx: TypedTransaction,
+    decompressor: Address,
+) -> anyhow::Result<TypedTransaction> {
+    let target = *tx
+        .to_addr()
+        .ok_or_else(|| anyhow::anyhow!("compressed transaction must have a target address"))?;
+    let mut payload = target.as_bytes().to_vec();
+    if let Some(data) = tx.data() {
+        payload.extend_from_slice(data);
+    }
+
+    if let Some(&gas) = tx.gas() {
+        tx.set_gas(gas + decompressor_gas_overhead(payload.len()));
+    }
+    tx.set_to(decompressor);
+    tx.set_data(compress(&payload));
+    Ok(tx)
+}
+
+/// Gas used by the decompressor to decompress and forward a payload of the given size
+fn decompressor_gas_overhead(payload_len: usize) -> U256 {
+    U256::from(DECOMPRESSOR_BASE_GAS + DECOMPRESSOR_GAS_PER_BYTE * payload_len as u64)
+}
+
+/// Compress calldata by run-length encoding its runs of 0x00 and 0xff bytes.
+///
+/// Any other byte is copied as is. A run is encoded as a 0x00 marker followed by a
+/// length byte: bit 7 selects the fill byte (0x00 if unset, 0xff if set) and the low
+/// 7 bits hold the run length minus one.
+pub(crate) fn compress(data: &[u8]) -> Bytes {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let byte = data[i];
+        if byte != 0x00 && byte != 0xff {
+            out.push(byte);
+            i += 1;
+            continue;
+        }
+
+        let run = data[i..]
+            .iter()
+            .take(MAX_RUN_LENGTH)
+            .take_while(|&&b| b == byte)
+            .count();
+        // a single 0xff is cheaper as a literal
+        if byte == 0xff && run == 1 {
+            out.push(byte);
+        } else {
+            let fill_flag = if byte == 0xff { 0x80 } else { 0x00 };
+            out.push(0x00);
+            out.push(fill_flag | (run - 1) as u8);
+        }
+        i += run;
+    }
+    out.into()
+}
+
+/// Decompress calldata produced by `compress`, mirroring the decompressor contract.
+///
+/// The contract itself is tested against the vectors of `test_compress_vectors`.
+#[cfg(test)]
+fn decompress(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() * 2);
+    let mut bytes = data.iter();
+    while let Some(&byte) = bytes.next() {
+        if byte != 0x00 {
+            out.push(byte);
+            continue;
+        }
+
+        let &marker = bytes
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("run marker missing its length byte"))?;
+        let fill = if marker & 0x80 == 0 { 0x00 } else { 0xff };
+        let len = (marker & 0x7f) as usize + 1;
+        out.extend(std::iter::repeat(fill).take(len));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{abi::AbiEncode, types::Eip1559TransactionRequest};
+    use rundler_types::{contracts::v0_6::i_entry_point::HandleOpsCall, v0_6::UserOperation};
+
+    use super::*;
+
+    #[test]
+    fn test_compress_round_trip() {
+        let cases: [&[u8]; 6] = [
+            &[],
+            &[0x00],
+            &[0xff],
+            &[0x12, 0x00, 0x00, 0xff, 0xff, 0xff, 0x34],
+            &[0x00; 300],
+            &[0xff; 129],
+        ];
+        for data in cases {
+            let compressed = compress(data);
+            assert_eq!(decompress(&compressed).unwrap(), data);
+        }
+    }
+
+    // Shared with contracts/test/CalldataDecompressor.t.sol in the types crate, which
+    // checks that the decompressor contract forwards the original data
+    #[test]
+    fn test_compress_vectors() {
+        let cases = [("0x120000ffffff34", "0x120001008234"), ("0xff", "0xff")];
+        for (data, compressed) in cases {
+            let data: Bytes = data.parse().unwrap();
+            let compressed: Bytes = compressed.parse().unwrap();
+            assert_eq!(compress(&data), compressed);
+        }
+
+        let compressed: Bytes = "0x007f007f002b".parse().unwrap();
+        assert_eq!(compress(&[0x00; 300]), compressed);
+    }
+
+    #[test]
+    fn test_compressed_transaction_decompresses_to_original_calldata() {
+        let entry_point = Address::random();
+        let decompressor = Address::random();
+        let calldata: Bytes = HandleOpsCall {
+            ops: vec![UserOperation {
+                sender: Address::random(),
+                call_gas_limit: 100_000.into(),
+                verification_gas_limit: 200_000.into(),
+                max_fee_per_gas: U256::MAX,
+                ..Default::default()
+            }],
+            beneficiary: Address::random(),
+        }
+        .encode()
+        .into();
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(entry_point)
+            .data(calldata.clone())
+            .gas(1_000_000)
+            .into();
+
+        let compressed = compress_transaction(tx, decompressor).unwrap();
+        assert_eq!(compressed.to_addr(), Some(&decompressor));
+        assert_eq!(
+            compressed.gas(),
+            Some(&(U256::from(1_000_000) + decompressor_gas_overhead(20 + calldata.len())))
+        );
+
+        let compressed_data = compressed.data().unwrap();
+        assert!(compressed_data.len() < calldata.len());
+
+        let payload = decompress(compressed_data).unwrap();
+        assert_eq!(&payload[..20], entry_point.as_bytes());
+        assert_eq!(&payload[20..], calldata.as_ref());
+    }
+}
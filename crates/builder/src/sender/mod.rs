@@ -12,6 +12,7 @@
 // If not, see https://www.gnu.org/licenses/.
 
 mod bloxroute;
+mod compressed;
 mod flashbots;
 mod raw;
 use std::sync::Arc;
@@ -19,6 +20,7 @@ use std::sync::Arc;
 use anyhow::Context;
 use async_trait::async_trait;
 pub(crate) use bloxroute::PolygonBloxrouteTransactionSender;
+pub(crate) use compressed::CompressedTransactionSender;
 use enum_dispatch::enum_dispatch;
 use ethers::{
     prelude::SignerMiddleware,
@@ -69,6 +71,12 @@ pub(crate) enum TxSenderError {
     /// Conditional value not met
     #[error("storage slot value condition not met")]
     ConditionNotMet,
+    /// Sender account can't cover the transaction cost
+    #[error("insufficient funds")]
+    InsufficientFunds,
+    /// Transaction reverted when it was submitted
+    #[error("execution reverted")]
+    Reverted,
     /// Soft cancellation failed
     #[error("soft cancel failed")]
     SoftCancelFailed,
@@ -112,6 +120,7 @@ where
     Raw(RawTransactionSender<C, S>),
     Flashbots(FlashbotsTransactionSender<C, S, FS>),
     PolygonBloxroute(PolygonBloxrouteTransactionSender<C, S>),
+    Compressed(CompressedTransactionSender<C, S, FS>),
 }
 
 /// Transaction sender types
@@ -293,6 +302,11 @@ impl From<ProviderError> for TxSenderError {
                         .contains("storage slot value condition not met")
                     {
                         return TxSenderError::ConditionNotMet;
+                    // geth, erigon, reth
+                    } else if e.message.contains("insufficient funds") {
+                        return TxSenderError::InsufficientFunds;
+                    } else if e.message.contains("execution reverted") {
+                        return TxSenderError::Reverted;
                     }
                 }
                 TxSenderError::Other(value.into())
@@ -14,14 +14,16 @@
 use async_trait::async_trait;
 use ethers::types::{Address, H256};
 use rundler_task::server::{HealthCheck, ServerStatus};
-use rundler_types::builder::{Builder, BuilderError, BuilderResult, BundlingMode};
+use rundler_types::builder::{Builder, BuilderError, BuilderResult, BundlePreview, BundlingMode};
 use tokio::{
     sync::{mpsc, oneshot},
     task::JoinHandle,
 };
 use tokio_util::sync::CancellationToken;
 
-use crate::bundle_sender::{BundleSenderAction, SendBundleRequest, SendBundleResult};
+use crate::bundle_sender::{
+    BundleSenderAction, PreviewBundleRequest, SendBundleRequest, SendBundleResult,
+};
 
 /// Local builder server builder
 #[derive(Debug)]
@@ -48,9 +50,12 @@ impl LocalBuilderBuilder {
     }
 
     /// Run the local builder server, consuming the builder
+    ///
+    /// `bundle_sender_actions` holds the action channel of each bundle sender, along with
+    /// the entry point it bundles for.
     pub fn run(
         self,
-        bundle_sender_actions: Vec<mpsc::Sender<BundleSenderAction>>,
+        bundle_sender_actions: Vec<(Address, mpsc::Sender<BundleSenderAction>)>,
         entry_points: Vec<Address>,
         shutdown_token: CancellationToken,
     ) -> JoinHandle<anyhow::Result<()>> {
@@ -68,7 +73,7 @@ pub struct LocalBuilderHandle {
 
 struct LocalBuilderServerRunner {
     req_receiver: mpsc::Receiver<ServerRequest>,
-    bundle_sender_actions: Vec<mpsc::Sender<BundleSenderAction>>,
+    bundle_sender_actions: Vec<(Address, mpsc::Sender<BundleSenderAction>)>,
     entry_points: Vec<Address>,
 }
 
@@ -117,6 +122,15 @@ impl Builder for LocalBuilderHandle {
             _ => Err(BuilderError::UnexpectedResponse),
         }
     }
+
+    async fn debug_preview_bundle(&self, entry_point: Address) -> BuilderResult<BundlePreview> {
+        let req = ServerRequestKind::DebugPreviewBundle { entry_point };
+        let resp = self.send(req).await?;
+        match resp {
+            ServerResponse::DebugPreviewBundle { preview } => Ok(preview),
+            _ => Err(BuilderError::UnexpectedResponse),
+        }
+    }
 }
 
 #[async_trait]
@@ -137,7 +151,7 @@ impl HealthCheck for LocalBuilderHandle {
 impl LocalBuilderServerRunner {
     fn new(
         req_receiver: mpsc::Receiver<ServerRequest>,
-        bundle_sender_actions: Vec<mpsc::Sender<BundleSenderAction>>,
+        bundle_sender_actions: Vec<(Address, mpsc::Sender<BundleSenderAction>)>,
         entry_points: Vec<Address>,
     ) -> Self {
         Self {
@@ -167,7 +181,7 @@ impl LocalBuilderServerRunner {
                                 }
 
                                 let (tx, rx) = oneshot::channel();
-                                match self.bundle_sender_actions[0].send(BundleSenderAction::SendBundle(SendBundleRequest{
+                                match self.bundle_sender_actions[0].1.send(BundleSenderAction::SendBundle(SendBundleRequest{
                                     responder: tx
                                 })).await {
                                     Ok(()) => {},
@@ -195,13 +209,33 @@ impl LocalBuilderServerRunner {
                                     break 'a Err(anyhow::anyhow!("more than 1 bundle builder not supported in debug mode").into())
                                 }
 
-                                match self.bundle_sender_actions[0].send(BundleSenderAction::ChangeMode(mode)).await {
+                                match self.bundle_sender_actions[0].1.send(BundleSenderAction::ChangeMode(mode)).await {
                                     Ok(()) => {},
                                     Err(e) => break 'a Err(anyhow::anyhow!("failed to change bundler mode: {}", e.to_string()).into())
                                 }
 
                                 Ok(ServerResponse::DebugSetBundlingMode)
                             },
+                            ServerRequestKind::DebugPreviewBundle { entry_point } => {
+                                let mut senders = self.bundle_sender_actions.iter().filter(|(ep, _)| *ep == entry_point);
+                                let (Some((_, sender)), None) = (senders.next(), senders.next()) else {
+                                    break 'a Err(anyhow::anyhow!("preview requires exactly 1 bundle builder for entry point {entry_point:?}").into())
+                                };
+
+                                let (tx, rx) = oneshot::channel();
+                                match sender.send(BundleSenderAction::PreviewBundle(PreviewBundleRequest{
+                                    responder: tx
+                                })).await {
+                                    Ok(()) => {},
+                                    Err(e) => break 'a Err(anyhow::anyhow!("failed to send preview bundle request: {}", e.to_string()).into())
+                                }
+
+                                match rx.await {
+                                    Ok(Ok(preview)) => Ok(ServerResponse::DebugPreviewBundle { preview }),
+                                    Ok(Err(e)) => Err(anyhow::anyhow!("preview bundle error: {e:?}").into()),
+                                    Err(e) => Err(anyhow::anyhow!("failed to receive bundle preview: {e:?}").into()),
+                                }
+                            },
                         }
                     };
 
@@ -219,6 +253,7 @@ enum ServerRequestKind {
     GetSupportedEntryPoints,
     DebugSendBundleNow,
     DebugSetBundlingMode { mode: BundlingMode },
+    DebugPreviewBundle { entry_point: Address },
 }
 
 #[derive(Debug)]
@@ -232,4 +267,5 @@ enum ServerResponse {
     GetSupportedEntryPoints { entry_points: Vec<Address> },
     DebugSendBundleNow { hash: H256, block_number: u64 },
     DebugSetBundlingMode,
+    DebugPreviewBundle { preview: BundlePreview },
 }
@@ -15,10 +15,10 @@ use std::str::FromStr;
 
 use ethers::types::{Address, H256};
 use rundler_task::{
-    grpc::protos::{from_bytes, ConversionError},
+    grpc::protos::{from_bytes, ConversionError, ToProtoBytes},
     server::{HealthCheck, ServerStatus},
 };
-use rundler_types::builder::{Builder, BuilderError, BuilderResult, BundlingMode};
+use rundler_types::builder::{Builder, BuilderError, BuilderResult, BundlePreview, BundlingMode};
 use tonic::{
     async_trait,
     transport::{Channel, Uri},
@@ -29,9 +29,9 @@ use tonic_health::{
 };
 
 use super::protos::{
-    builder_client::BuilderClient, debug_send_bundle_now_response,
-    debug_set_bundling_mode_response, BundlingMode as ProtoBundlingMode, DebugSendBundleNowRequest,
-    DebugSetBundlingModeRequest, GetSupportedEntryPointsRequest,
+    builder_client::BuilderClient, debug_preview_bundle_response, debug_send_bundle_now_response,
+    debug_set_bundling_mode_response, BundlingMode as ProtoBundlingMode, DebugPreviewBundleRequest,
+    DebugSendBundleNowRequest, DebugSetBundlingModeRequest, GetSupportedEntryPointsRequest,
 };
 
 /// Remote builder client, used for communicating with a remote builder server
@@ -112,6 +112,35 @@ impl Builder for RemoteBuilderClient {
             )))?,
         }
     }
+
+    async fn debug_preview_bundle(&self, entry_point: Address) -> BuilderResult<BundlePreview> {
+        let res = self
+            .grpc_client
+            .clone()
+            .debug_preview_bundle(DebugPreviewBundleRequest {
+                entry_point: entry_point.to_proto_bytes(),
+            })
+            .await
+            .map_err(anyhow::Error::from)?
+            .into_inner()
+            .result;
+
+        match res {
+            Some(debug_preview_bundle_response::Result::Success(s)) => Ok(BundlePreview {
+                user_op_hashes: s
+                    .user_op_hashes
+                    .iter()
+                    .map(|hash| from_bytes(hash))
+                    .collect::<Result<_, ConversionError>>()
+                    .map_err(anyhow::Error::from)?,
+                gas_estimate: from_bytes(&s.gas_estimate).map_err(anyhow::Error::from)?,
+            }),
+            Some(debug_preview_bundle_response::Result::Failure(f)) => Err(f.try_into()?),
+            None => Err(BuilderError::Other(anyhow::anyhow!(
+                "should have received result from builder"
+            )))?,
+        }
+    }
 }
 
 #[async_trait]
@@ -13,6 +13,7 @@
 
 use std::net::SocketAddr;
 
+use rundler_task::grpc::protos::{from_bytes, ToProtoBytes};
 use rundler_types::builder::Builder;
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
@@ -20,10 +21,12 @@ use tonic::{async_trait, transport::Server, Request, Response, Status};
 
 use super::protos::{
     builder_server::{Builder as GrpcBuilder, BuilderServer as GrpcBuilderServer},
-    debug_send_bundle_now_response, debug_set_bundling_mode_response, BundlingMode,
-    DebugSendBundleNowRequest, DebugSendBundleNowResponse, DebugSetBundlingModeRequest,
-    DebugSetBundlingModeResponse, DebugSetBundlingModeSuccess, GetSupportedEntryPointsRequest,
-    GetSupportedEntryPointsResponse, BUILDER_FILE_DESCRIPTOR_SET,
+    debug_preview_bundle_response, debug_send_bundle_now_response,
+    debug_set_bundling_mode_response, BundlingMode, DebugPreviewBundleRequest,
+    DebugPreviewBundleResponse, DebugPreviewBundleSuccess, DebugSendBundleNowRequest,
+    DebugSendBundleNowResponse, DebugSetBundlingModeRequest, DebugSetBundlingModeResponse,
+    DebugSetBundlingModeSuccess, GetSupportedEntryPointsRequest, GetSupportedEntryPointsResponse,
+    BUILDER_FILE_DESCRIPTOR_SET,
 };
 use crate::server::{local::LocalBuilderHandle, remote::protos::DebugSendBundleNowSuccess};
 
@@ -143,4 +146,32 @@ impl GrpcBuilder for GrpcBuilderServerImpl {
 
         Ok(Response::new(resp))
     }
+
+    async fn debug_preview_bundle(
+        &self,
+        request: Request<DebugPreviewBundleRequest>,
+    ) -> tonic::Result<Response<DebugPreviewBundleResponse>> {
+        let entry_point = from_bytes(&request.into_inner().entry_point)
+            .map_err(|e| Status::invalid_argument(format!("Invalid entry point: {e}")))?;
+
+        let resp = match self.local_builder.debug_preview_bundle(entry_point).await {
+            Ok(preview) => DebugPreviewBundleResponse {
+                result: Some(debug_preview_bundle_response::Result::Success(
+                    DebugPreviewBundleSuccess {
+                        user_op_hashes: preview
+                            .user_op_hashes
+                            .iter()
+                            .map(|hash| hash.as_bytes().to_vec())
+                            .collect(),
+                        gas_estimate: preview.gas_estimate.to_proto_bytes(),
+                    },
+                )),
+            },
+            Err(e) => {
+                return Err(Status::internal(format!("Failed to preview bundle: {e}")));
+            }
+        };
+
+        Ok(Response::new(resp))
+    }
 }
@@ -140,3 +140,113 @@ async fn try_lock<'a>(lm: &'a LockManager, lock_id: &str, ttl_millis: usize) ->
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        core::k256::ecdsa::{signature::hazmat::PrehashSigner, Signature, SigningKey},
+        types::{transaction::eip2718::TypedTransaction, Address, Eip1559TransactionRequest, U256},
+    };
+    use ethers_signers::LocalWallet;
+    use rusoto_core::{
+        credential::StaticProvider,
+        request::{DispatchSignedRequestFuture, HttpResponse},
+        signature::{SignedRequest, SignedRequestPayload},
+        DispatchSignedRequest,
+    };
+    use rusoto_kms::{GetPublicKeyResponse, SignRequest, SignResponse};
+
+    use super::*;
+    use crate::signer::BundlerSigner;
+
+    // Hardhat's first default account
+    const PRIVATE_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    // DER prefix of a secp256k1 SubjectPublicKeyInfo with an uncompressed point
+    const SPKI_PREFIX: &str = "3056301006072a8648ce3d020106052b8104000a034200";
+
+    // Answers the KMS requests made by `AwsSigner` with a local key
+    struct MockKms(SigningKey);
+
+    impl DispatchSignedRequest for MockKms {
+        fn dispatch(
+            &self,
+            request: SignedRequest,
+            _timeout: Option<Duration>,
+        ) -> DispatchSignedRequestFuture {
+            let body = match request.headers["x-amz-target"][0].as_slice() {
+                b"TrentService.GetPublicKey" => {
+                    let mut public_key = ethers::utils::hex::decode(SPKI_PREFIX).unwrap();
+                    public_key.extend_from_slice(
+                        self.0.verifying_key().to_encoded_point(false).as_bytes(),
+                    );
+                    serde_json::to_vec(&GetPublicKeyResponse {
+                        public_key: Some(public_key.into()),
+                        ..Default::default()
+                    })
+                }
+                b"TrentService.Sign" => {
+                    let Some(SignedRequestPayload::Buffer(payload)) = request.payload else {
+                        panic!("sign request should have a payload");
+                    };
+                    let request: SignRequest = serde_json::from_slice(&payload).unwrap();
+                    let signature: Signature = self.0.sign_prehash(&request.message).unwrap();
+                    serde_json::to_vec(&SignResponse {
+                        signature: Some(signature.to_der().as_bytes().to_vec().into()),
+                        ..Default::default()
+                    })
+                }
+                target => panic!("unexpected KMS request {}", String::from_utf8_lossy(target)),
+            }
+            .unwrap();
+
+            Box::pin(async move {
+                Ok(HttpResponse {
+                    status: Default::default(),
+                    body: body.into(),
+                    headers: Default::default(),
+                })
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_kms_signer_signs_transaction() {
+        let wallet = PRIVATE_KEY
+            .parse::<LocalWallet>()
+            .unwrap()
+            .with_chain_id(1u64);
+        let client = KmsClient::new_with(
+            MockKms(wallet.signer().clone()),
+            StaticProvider::new_minimal("key".to_string(), "secret".to_string()),
+            Region::UsEast1,
+        );
+        let signer = BundlerSigner::Kms(KmsSigner {
+            signer: AwsSigner::new(client, "key-id".to_string(), 1)
+                .await
+                .unwrap(),
+            _kms_guard: None,
+            _monitor_guard: SpawnGuard::spawn_with_guard(async {}),
+        });
+        assert_eq!(signer.address(), wallet.address());
+        assert_eq!(signer.chain_id(), 1);
+
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(Address::random())
+            .nonce(7)
+            .gas(U256::from(1_000_000))
+            .max_fee_per_gas(U256::from(100))
+            .max_priority_fee_per_gas(U256::from(10))
+            .chain_id(1)
+            .into();
+
+        let signature = signer.sign_transaction(&tx).await.unwrap();
+        let local_signature = wallet.sign_transaction(&tx).await.unwrap();
+
+        assert_eq!(
+            (signature.r, signature.s),
+            (local_signature.r, local_signature.s)
+        );
+        assert_eq!(signature.recover(tx.sighash()).unwrap(), wallet.address());
+    }
+}
@@ -150,3 +150,41 @@ impl Signer for BundlerSigner {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{Eip1559TransactionRequest, U256};
+
+    use super::*;
+
+    // Hardhat's first default account
+    const PRIVATE_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    #[tokio::test]
+    async fn test_local_signer_signs_transaction_deterministically() {
+        let wallet = PRIVATE_KEY
+            .parse::<LocalWallet>()
+            .unwrap()
+            .with_chain_id(1u64);
+        let signer = BundlerSigner::Local(LocalSigner {
+            signer: wallet.clone(),
+            _monitor_abort_handle: SpawnGuard::spawn_with_guard(async {}),
+        });
+
+        let tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .to(Address::random())
+            .nonce(7)
+            .gas(U256::from(1_000_000))
+            .max_fee_per_gas(U256::from(100))
+            .max_priority_fee_per_gas(U256::from(10))
+            .chain_id(1)
+            .into();
+
+        let signature = signer.sign_transaction(&tx).await.unwrap();
+
+        assert_eq!(signature, signer.sign_transaction(&tx).await.unwrap());
+        assert_eq!(signature, wallet.sign_transaction(&tx).await.unwrap());
+        assert_eq!(signature.recover(tx.sighash()).unwrap(), signer.address());
+        assert_eq!(signer.chain_id(), 1);
+    }
+}
@@ -35,7 +35,7 @@ use rundler_types::{
 use rundler_utils::{emit::WithEntryPoint, handle};
 use rusoto_core::Region;
 use tokio::{
-    sync::{broadcast, mpsc},
+    sync::{broadcast, mpsc, Semaphore},
     task::JoinHandle,
     time, try_join,
 };
@@ -46,7 +46,7 @@ use crate::{
     bundle_proposer::{self, BundleProposerImpl},
     bundle_sender::{self, BundleSender, BundleSenderAction, BundleSenderImpl},
     emit::BuilderEvent,
-    sender::TransactionSenderArgs,
+    sender::{CompressedTransactionSender, TransactionSenderArgs, TransactionSenderEnum},
     server::{spawn_remote_builder_server, LocalBuilderBuilder},
     signer::{BundlerSigner, KmsSigner, LocalSigner},
     transaction_tracker::{self, TransactionTrackerImpl},
@@ -61,6 +61,8 @@ pub struct Args {
     pub rpc_url: String,
     /// True if using unsafe mode
     pub unsafe_mode: bool,
+    /// True if unsafe mode should be used when the node does not support custom tracers
+    pub allow_trace_fallback: bool,
     /// Private key to use for signing transactions
     /// If empty, AWS KMS will be used
     pub private_keys: Vec<String>,
@@ -75,12 +77,21 @@ pub struct Args {
     pub redis_lock_ttl_millis: u64,
     /// Maximum bundle size in number of operations
     pub max_bundle_size: u64,
+    /// Maximum number of distinct senders in a bundle, unlimited if not set
+    pub max_bundle_senders: Option<u64>,
+    /// Maximum total pre-verification gas of the ops in a bundle, unlimited if not set
+    pub max_bundle_pre_verification_gas: Option<u64>,
     /// Maximum bundle size in gas limit
     pub max_bundle_gas: u64,
     /// Percentage to add to the network priority fee for the bundle priority fee
     pub bundle_priority_fee_overhead_percent: u64,
+    /// Percentage to add to the base fee when setting the bundle max fee, so that the
+    /// bundle is still priced high enough if the base fee rises before it is mined
+    pub bundle_base_fee_headroom_percent: u64,
     /// Priority fee mode to use for operation priority fee minimums
     pub priority_fee_mode: PriorityFeeMode,
+    /// Minimum time remaining before an operation's valid until timestamp for it to be bundled
+    pub time_range_buffer: Duration,
     /// Sender to be used by the builder
     pub sender_args: TransactionSenderArgs,
     /// Operation simulation settings
@@ -93,6 +104,13 @@ pub struct Args {
     pub max_cancellation_fee_increases: u64,
     /// Maximum amount of blocks to spend in a replacement underpriced state before moving to cancel
     pub max_replacement_underpriced_blocks: u64,
+    /// If set, trigger bundling every this many new blocks instead of on every block and timer tick
+    pub bundle_block_interval: Option<u64>,
+    /// If set, attach an EIP-2930 access list derived from simulation to bundle transactions
+    pub use_access_list: bool,
+    /// Maximum number of bundle transactions in flight at once across all bundle builders,
+    /// unlimited if not set
+    pub max_bundles_in_flight: Option<usize>,
     /// Address to bind the remote builder server to, if any. If none, no server is starter.
     pub remote_address: Option<SocketAddr>,
     /// Entry points to start builders for
@@ -121,6 +139,7 @@ pub struct BuilderTask<P> {
     event_sender: broadcast::Sender<WithEntryPoint<BuilderEvent>>,
     builder_builder: LocalBuilderBuilder,
     pool: P,
+    in_flight_limiter: Option<Arc<Semaphore>>,
 }
 
 #[async_trait]
@@ -130,6 +149,12 @@ where
 {
     async fn run(mut self: Box<Self>, shutdown_token: CancellationToken) -> anyhow::Result<()> {
         let provider = rundler_provider::new_provider(&self.args.rpc_url, None)?;
+        self.args.unsafe_mode = simulation::resolve_unsafe_mode(
+            &*provider,
+            self.args.unsafe_mode,
+            self.args.allow_trace_fallback,
+        )
+        .await?;
         let submit_provider = if let TransactionSenderArgs::Raw(args) = &self.args.sender_args {
             Some(rundler_provider::new_provider(&args.submit_url, None)?)
         } else {
@@ -166,7 +191,7 @@ where
                         )
                         .await?;
                     sender_handles.extend(handles);
-                    bundle_sender_actions.extend(actions);
+                    bundle_sender_actions.extend(actions.into_iter().map(|a| (ep.address, a)));
                 }
                 EntryPointVersion::V0_7 => {
                     let (handles, actions) = self
@@ -179,7 +204,7 @@ where
                         )
                         .await?;
                     sender_handles.extend(handles);
-                    bundle_sender_actions.extend(actions);
+                    bundle_sender_actions.extend(actions.into_iter().map(|a| (ep.address, a)));
                 }
                 EntryPointVersion::Unspecified => {
                     panic!("Unspecified entry point version")
@@ -244,11 +269,15 @@ where
         builder_builder: LocalBuilderBuilder,
         pool: P,
     ) -> Self {
+        let in_flight_limiter = args
+            .max_bundles_in_flight
+            .map(|max| Arc::new(Semaphore::new(max)));
         Self {
             args,
             event_sender,
             builder_builder,
             pool,
+            in_flight_limiter,
         }
     }
 
@@ -298,6 +327,7 @@ where
                     submit_provider.clone(),
                     ep_v0_6.clone(),
                     simulation::new_v0_6_simulator(
+                        self.args.chain_spec.clone(),
                         Arc::clone(&provider),
                         ep_v0_6.clone(),
                         self.args.sim_settings.clone(),
@@ -429,17 +459,26 @@ where
         let proposer_settings = bundle_proposer::Settings {
             chain_spec: self.args.chain_spec.clone(),
             max_bundle_size: self.args.max_bundle_size,
+            max_bundle_senders: self.args.max_bundle_senders,
+            max_bundle_pre_verification_gas: self.args.max_bundle_pre_verification_gas,
             max_bundle_gas: self.args.max_bundle_gas,
             beneficiary,
             priority_fee_mode: self.args.priority_fee_mode,
             bundle_priority_fee_overhead_percent: self.args.bundle_priority_fee_overhead_percent,
+            bundle_base_fee_headroom_percent: self.args.bundle_base_fee_headroom_percent,
+            time_range_buffer: self.args.time_range_buffer,
         };
 
-        let transaction_sender = self.args.sender_args.clone().into_sender(
+        let mut transaction_sender = self.args.sender_args.clone().into_sender(
             Arc::clone(&provider),
             submit_provider,
             signer,
         )?;
+        if let Some(decompressor) = self.args.chain_spec.calldata_decompressor_address {
+            transaction_sender = TransactionSenderEnum::Compressed(
+                CompressedTransactionSender::new(transaction_sender, decompressor),
+            );
+        }
 
         let tracker_settings = transaction_tracker::Settings {
             replacement_fee_percent_increase: self.args.replacement_fee_percent_increase,
@@ -457,6 +496,9 @@ where
             max_replacement_underpriced_blocks: self.args.max_replacement_underpriced_blocks,
             max_cancellation_fee_increases: self.args.max_cancellation_fee_increases,
             max_blocks_to_wait_for_mine: self.args.max_blocks_to_wait_for_mine,
+            bundle_block_interval: self.args.bundle_block_interval,
+            use_access_list: self.args.use_access_list,
+            in_flight_limiter: self.in_flight_limiter.clone(),
         };
 
         let proposer = BundleProposerImpl::new(
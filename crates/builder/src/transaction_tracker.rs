@@ -89,6 +89,10 @@ pub(crate) enum TransactionTrackerError {
     ReplacementUnderpriced,
     #[error("storage slot value condition not met")]
     ConditionNotMet,
+    #[error("insufficient funds")]
+    InsufficientFunds,
+    #[error("execution reverted")]
+    Reverted,
     /// All other errors
     #[error(transparent)]
     Other(#[from] anyhow::Error),
@@ -466,6 +470,8 @@ impl From<TxSenderError> for TransactionTrackerError {
                 TransactionTrackerError::ReplacementUnderpriced
             }
             TxSenderError::ConditionNotMet => TransactionTrackerError::ConditionNotMet,
+            TxSenderError::InsufficientFunds => TransactionTrackerError::InsufficientFunds,
+            TxSenderError::Reverted => TransactionTrackerError::Reverted,
             TxSenderError::SoftCancelFailed => {
                 TransactionTrackerError::Other(anyhow::anyhow!("soft cancel failed"))
             }
@@ -128,6 +128,11 @@ pub enum OpRemovalReason {
         /// Op was valid until this timestamp
         valid_until: Timestamp,
     },
+    /// Op was removed because it failed re-validation
+    FailedRevalidation {
+        /// Op failed re-validation at this block number
+        block_number: u64,
+    },
 }
 
 impl EntitySummary {
@@ -25,6 +25,7 @@ pub(crate) use paymaster::{PaymasterConfig, PaymasterTracker};
 mod uo_pool;
 use std::{
     collections::{HashMap, HashSet},
+    path::PathBuf,
     sync::Arc,
 };
 
@@ -36,7 +37,7 @@ use rundler_types::{
     pool::{
         MempoolError, PaymasterMetadata, PoolOperation, Reputation, ReputationStatus, StakeStatus,
     },
-    EntityUpdate, EntryPointVersion, UserOperationId, UserOperationVariant,
+    EntityType, EntityUpdate, EntryPointVersion, UserOperationId, UserOperationVariant,
 };
 use tonic::async_trait;
 pub(crate) use uo_pool::UoPool;
@@ -52,6 +53,12 @@ pub trait Mempool: Send + Sync + 'static {
     /// Call to update the mempool with a new chain update
     async fn on_chain_update(&self, update: &ChainUpdate);
 
+    /// Re-simulates the next batch of pending operations if a re-validation pass is due at
+    /// this block, dropping those that are no longer valid.
+    ///
+    /// Called off the chain update path, as a pass can run many simulations.
+    async fn revalidate_operations(&self, block_number: u64);
+
     /// Returns the entry point address this pool targets.
     fn entry_point(&self) -> Address;
 
@@ -94,6 +101,9 @@ pub trait Mempool: Send + Sync + 'static {
     /// Looks up a user operation by hash, returns None if not found
     fn get_user_operation_by_hash(&self, hash: H256) -> Option<Arc<PoolOperation>>;
 
+    /// Returns all operations in the pool for a sender, sorted by nonce
+    fn get_user_operations_by_sender(&self, sender: Address) -> Vec<Arc<PoolOperation>>;
+
     /// Debug methods
 
     /// Clears the mempool of UOs or reputation of all addresses
@@ -111,8 +121,13 @@ pub trait Mempool: Send + Sync + 'static {
     /// Overwrites the mempool's reputation for an address
     fn set_reputation(&self, address: Address, ops_seen: u64, ops_included: u64);
 
-    /// Get stake status for address
-    async fn get_stake_status(&self, address: Address) -> MempoolResult<StakeStatus>;
+    /// Get stake status for address, checked against the stake requirement of the given
+    /// entity type, or the default requirement if none is given
+    async fn get_stake_status(
+        &self,
+        address: Address,
+        entity_type: Option<EntityType>,
+    ) -> MempoolResult<StakeStatus>;
 
     /// Reset paymaster state
     async fn reset_confirmed_paymaster_balances(&self) -> MempoolResult<()>;
@@ -137,6 +152,9 @@ pub struct PoolConfig {
     pub min_replacement_fee_increase_percentage: u64,
     /// After this threshold is met, we will start to drop the worst userops from the mempool
     pub max_size_of_pool_bytes: usize,
+    /// Percentage of the maximum pool size held back as headroom. Within the headroom, new
+    /// operations that would be the first evicted are rejected instead of admitted.
+    pub size_headroom_percent: u64,
     /// Operations that are always banned from the mempool
     pub blocklist: Option<HashSet<Address>>,
     /// Operations that are always allowed in the mempool, regardless of reputation
@@ -162,8 +180,46 @@ pub struct PoolConfig {
     pub paymaster_cache_length: u32,
     /// Boolean field used to toggle the operation of the reputation tracker
     pub reputation_tracking_enabled: bool,
+    /// The number of seconds between decays of the reputation counters
+    pub reputation_decay_interval_secs: u64,
+    /// Each decay removes 1/N of an entity's `ops_seen` and `ops_included` counters
+    pub reputation_decay_factor: u64,
+    /// An entity is expected to have 1/N of its seen operations included
+    pub reputation_min_inclusion_rate_denominator: u64,
+    /// The number of included operations an entity may fall short of expected before it is throttled
+    pub reputation_throttling_slack: u64,
+    /// The number of included operations an entity may fall short of expected before it is banned
+    pub reputation_ban_slack: u64,
+    /// File to persist the entity reputation counters to, restored at startup. If unset,
+    /// reputation is not persisted and resets on restart.
+    pub reputation_persist_path: Option<PathBuf>,
     /// The minimum number of blocks a user operation must be in the mempool before it can be dropped
     pub drop_min_num_blocks: u64,
+    /// The number of blocks between re-validation passes over the operations in the mempool.
+    /// Operations that fail re-simulation are dropped. If unset, re-validation is disabled.
+    pub revalidation_interval_blocks: Option<u64>,
+    /// The maximum number of operations re-simulated during a single re-validation pass
+    pub revalidation_max_ops: usize,
+    /// The order in which operations are returned as the best operations for bundling
+    pub ordering_mode: PoolOrderingMode,
+    /// The maximum number of the best operations considered when selecting operations for a
+    /// bundle, bounding the cost of each pass over large pools. If unset, all operations are considered.
+    pub max_ops_scanned_per_bundle: Option<usize>,
+    /// The maximum number of operations in the mempool sharing a fingerprint, derived from the
+    /// sender, call data and paymaster. Throttles spam variants of the same operation. If unset, no limit.
+    pub max_ops_per_fingerprint: Option<usize>,
+}
+
+/// Order in which operations are returned as the best operations for bundling
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum PoolOrderingMode {
+    /// Order by max fee per gas, highest first
+    #[default]
+    PriorityFee,
+    /// Order by arrival in the mempool, oldest first, regardless of fees.
+    /// Useful for permissioned or test networks.
+    Fifo,
 }
 
 /// Origin of an operation.
@@ -177,6 +233,9 @@ pub enum OperationOrigin {
     /// The operation was returned to the pool when the block it was in was
     /// reorged away.
     ReturnedAfterReorg,
+    /// The operation was injected by the bundler operator. It may only be
+    /// replaced by another operator operation.
+    Operator,
 }
 
 #[cfg(test)]
@@ -205,10 +264,11 @@ mod tests {
             entry_point: Address::random(),
             aggregator: Some(aggregator),
             valid_time_range: ValidTimeRange::all_time(),
-            expected_code_hash: H256::random(),
+            expected_code_hash: Some(H256::random()),
             sim_block_hash: H256::random(),
             sim_block_number: 0,
             account_is_staked: true,
+            no_replacement: false,
             entity_infos: EntityInfos {
                 factory: Some(EntityInfo {
                     entity: Entity::factory(factory),
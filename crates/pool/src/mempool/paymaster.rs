@@ -19,14 +19,16 @@ use anyhow::Context;
 use ethers::{abi::Address, types::U256};
 use parking_lot::RwLock;
 use rundler_provider::EntryPoint;
+use rundler_sim::StakeRequirement;
 use rundler_types::{
+    chain::ChainSpec,
     pool::{MempoolError, PaymasterMetadata, PoolOperation, StakeStatus},
     StakeInfo, UserOperation, UserOperationId, UserOperationVariant,
 };
 use rundler_utils::cache::LruMap;
 
 use super::MempoolResult;
-use crate::chain::MinedOp;
+use crate::chain::{BalanceUpdate, MinedOp};
 
 /// Keeps track of current and pending paymaster balances
 #[derive(Debug)]
@@ -38,22 +40,13 @@ pub(crate) struct PaymasterTracker<E> {
 
 #[derive(Debug)]
 pub(crate) struct PaymasterConfig {
-    min_stake_value: u128,
-    min_unstake_delay: u32,
     tracker_enabled: bool,
     cache_length: u32,
 }
 
 impl PaymasterConfig {
-    pub(crate) fn new(
-        min_stake_value: u128,
-        min_unstake_delay: u32,
-        tracker_enabled: bool,
-        cache_length: u32,
-    ) -> Self {
+    pub(crate) fn new(tracker_enabled: bool, cache_length: u32) -> Self {
         Self {
-            min_stake_value,
-            min_unstake_delay,
             tracker_enabled,
             cache_length,
         }
@@ -64,10 +57,11 @@ impl<E> PaymasterTracker<E>
 where
     E: EntryPoint,
 {
-    pub(crate) fn new(entry_point: E, config: PaymasterConfig) -> Self {
+    pub(crate) fn new(chain_spec: ChainSpec, entry_point: E, config: PaymasterConfig) -> Self {
         Self {
             entry_point,
             state: RwLock::new(PaymasterTrackerInner::new(
+                chain_spec,
                 config.tracker_enabled,
                 config.cache_length,
             )),
@@ -75,13 +69,17 @@ where
         }
     }
 
-    pub(crate) async fn get_stake_status(&self, address: Address) -> MempoolResult<StakeStatus> {
+    pub(crate) async fn get_stake_status(
+        &self,
+        address: Address,
+        requirement: StakeRequirement,
+    ) -> MempoolResult<StakeStatus> {
         let deposit_info = self.entry_point.get_deposit_info(address).await?;
 
-        let is_staked = deposit_info.stake.ge(&self.config.min_stake_value)
+        let is_staked = deposit_info.stake.ge(&requirement.min_stake_value)
             && deposit_info
                 .unstake_delay_sec
-                .ge(&self.config.min_unstake_delay);
+                .ge(&requirement.min_unstake_delay);
 
         let stake_status = StakeStatus {
             stake_info: StakeInfo {
@@ -186,6 +184,12 @@ where
             .update_paymaster_balance_from_mined_op(mined_op);
     }
 
+    pub(crate) fn update_paymaster_balance_from_event(&self, update: &BalanceUpdate) {
+        self.state
+            .write()
+            .update_paymaster_balance_from_event(update);
+    }
+
     pub(crate) fn remove_operation(&self, id: &UserOperationId) {
         self.state.write().remove_operation(id);
     }
@@ -222,11 +226,14 @@ struct PaymasterTrackerInner {
     paymaster_balances: LruMap<Address, PaymasterBalance>,
     // boolean for operation of tracker
     tracker_enabled: bool,
+    // chain spec used to compute the max gas cost of operations
+    chain_spec: ChainSpec,
 }
 
 impl PaymasterTrackerInner {
-    fn new(tracker_enabled: bool, cache_size: u32) -> Self {
+    fn new(chain_spec: ChainSpec, tracker_enabled: bool, cache_size: u32) -> Self {
         Self {
+            chain_spec,
             user_op_fees: HashMap::new(),
             tracker_enabled,
             paymaster_balances: LruMap::new(cache_size),
@@ -246,7 +253,7 @@ impl PaymasterTrackerInner {
         op: &UserOperationVariant,
         paymaster_metadata: &PaymasterMetadata,
     ) -> MempoolResult<()> {
-        let max_op_cost = op.max_gas_cost();
+        let max_op_cost = op.max_gas_cost(&self.chain_spec);
 
         if let Some(prev) = self.user_op_fees.get(&op.id()) {
             let reset_balance = paymaster_metadata
@@ -299,6 +306,17 @@ impl PaymasterTrackerInner {
         }
     }
 
+    // `Deposited` events carry the new total deposit, `Withdrawn` events the amount withdrawn
+    fn update_paymaster_balance_from_event(&mut self, update: &BalanceUpdate) {
+        if let Some(paymaster_balance) = self.paymaster_balances.get(&update.address) {
+            paymaster_balance.confirmed = if update.is_addition {
+                update.amount
+            } else {
+                paymaster_balance.confirmed.saturating_sub(update.amount)
+            };
+        }
+    }
+
     fn remove_operation(&mut self, id: &UserOperationId) {
         if let Some(op_fee) = self.user_op_fees.get(id) {
             if let Some(paymaster_balance) = self.paymaster_balances.get(&op_fee.paymaster) {
@@ -351,7 +369,7 @@ impl PaymasterTrackerInner {
         paymaster_metadata: &PaymasterMetadata,
     ) -> MempoolResult<()> {
         let id = po.uo.id();
-        let max_op_cost = po.uo.max_gas_cost();
+        let max_op_cost = po.uo.max_gas_cost(&self.chain_spec);
 
         // Only return an error if tracking is enabled
         if paymaster_metadata.pending_balance.lt(&max_op_cost) && self.tracker_enabled {
@@ -515,9 +533,10 @@ mod tests {
             entry_point: Address::random(),
             aggregator: None,
             valid_time_range: ValidTimeRange::all_time(),
-            expected_code_hash: H256::random(),
+            expected_code_hash: Some(H256::random()),
             sim_block_hash: H256::random(),
             account_is_staked: true,
+            no_replacement: false,
             entity_infos: EntityInfos::default(),
             sim_block_number: 0,
         }
@@ -539,7 +558,7 @@ mod tests {
             ..Default::default()
         };
 
-        let uo_max_cost = uo.clone().max_gas_cost();
+        let uo_max_cost = uo.clone().max_gas_cost(&ChainSpec::default());
 
         let po = demo_pool_op(uo);
 
@@ -721,7 +740,7 @@ mod tests {
             ..Default::default()
         };
 
-        let uo_max_cost = uo.clone().max_gas_cost();
+        let uo_max_cost = uo.clone().max_gas_cost(&ChainSpec::default());
 
         let po = demo_pool_op(uo);
         let res = paymaster_tracker.add_or_update_balance(&po).await;
@@ -766,8 +785,8 @@ mod tests {
         uo_1.max_fee_per_gas = 2.into();
         uo_1.paymaster_and_data = paymaster_1.as_bytes().to_vec().into();
 
-        let max_op_cost_0 = uo.max_gas_cost();
-        let max_op_cost_1 = uo_1.max_gas_cost();
+        let max_op_cost_0 = uo.max_gas_cost(&ChainSpec::default());
+        let max_op_cost_1 = uo_1.max_gas_cost(&ChainSpec::default());
 
         paymaster_tracker.add_new_paymaster(paymaster_0, paymaster_balance_0, 0.into());
         paymaster_tracker.add_new_paymaster(paymaster_1, paymaster_balance_1, 0.into());
@@ -863,7 +882,7 @@ mod tests {
             ..Default::default()
         };
 
-        let max_op_cost = uo.clone().max_gas_cost();
+        let max_op_cost = uo.clone().max_gas_cost(&ChainSpec::default());
 
         let po = demo_pool_op(uo);
 
@@ -893,14 +912,21 @@ mod tests {
     async fn test_stake_status_staked() {
         let tracker = new_paymaster_tracker();
 
-        let status = tracker.get_stake_status(Address::random()).await.unwrap();
+        let requirement = StakeRequirement {
+            min_stake_value: 1001,
+            min_unstake_delay: 99,
+        };
+        let status = tracker
+            .get_stake_status(Address::random(), requirement)
+            .await
+            .unwrap();
 
         assert!(status.is_staked);
     }
 
     #[test]
     fn test_inner_cache_full() {
-        let mut inner = PaymasterTrackerInner::new(true, 2);
+        let mut inner = PaymasterTrackerInner::new(ChainSpec::default(), true, 2);
 
         let paymaster_0 = Address::random();
         let paymaster_1 = Address::random();
@@ -940,9 +966,9 @@ mod tests {
             .expect_balance_of()
             .returning(|_, _| Ok(U256::from(1000)));
 
-        let config = PaymasterConfig::new(1001, 99, true, u32::MAX);
+        let config = PaymasterConfig::new(true, u32::MAX);
 
-        PaymasterTracker::new(entrypoint, config)
+        PaymasterTracker::new(ChainSpec::default(), entrypoint, config)
     }
 
     impl PaymasterTracker<MockEntryPointV0_6> {
@@ -14,14 +14,19 @@
 use std::{
     cmp::{self, Ordering},
     collections::{hash_map::Entry, BTreeSet, HashMap, HashSet},
-    sync::Arc,
+    ops::Bound,
+    sync::{
+        atomic::{self, AtomicU64},
+        Arc,
+    },
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::Context;
 use ethers::{
-    abi::Address,
+    abi::{self, Address, Token},
     types::{H256, U256},
+    utils::keccak256,
 };
 use rundler_types::{
     pool::{MempoolError, PoolOperation},
@@ -30,7 +35,9 @@ use rundler_types::{
 use rundler_utils::math;
 use tracing::{info, warn};
 
-use super::{entity_tracker::EntityCounter, size::SizeTracker, MempoolResult, PoolConfig};
+use super::{
+    entity_tracker::EntityCounter, size::SizeTracker, MempoolResult, PoolConfig, PoolOrderingMode,
+};
 use crate::chain::MinedOp;
 
 #[derive(Debug, Clone)]
@@ -38,9 +45,11 @@ pub(crate) struct PoolInnerConfig {
     entry_point: Address,
     chain_id: u64,
     max_size_of_pool_bytes: usize,
+    size_headroom_percent: u64,
     min_replacement_fee_increase_percentage: u64,
     throttled_entity_mempool_count: u64,
     throttled_entity_live_blocks: u64,
+    ordering_mode: PoolOrderingMode,
 }
 
 impl From<PoolConfig> for PoolInnerConfig {
@@ -49,9 +58,11 @@ impl From<PoolConfig> for PoolInnerConfig {
             entry_point: config.entry_point,
             chain_id: config.chain_id,
             max_size_of_pool_bytes: config.max_size_of_pool_bytes,
+            size_headroom_percent: config.size_headroom_percent,
             min_replacement_fee_increase_percentage: config.min_replacement_fee_increase_percentage,
             throttled_entity_mempool_count: config.throttled_entity_mempool_count,
             throttled_entity_live_blocks: config.throttled_entity_live_blocks,
+            ordering_mode: config.ordering_mode,
         }
     }
 }
@@ -65,7 +76,7 @@ pub(crate) struct PoolInner {
     by_hash: HashMap<H256, OrderedPoolOperation>,
     /// Operations by operation ID
     by_id: HashMap<UserOperationId, OrderedPoolOperation>,
-    /// Best operations, sorted by gas price
+    /// Best operations, sorted by the configured ordering mode
     best: BTreeSet<OrderedPoolOperation>,
     /// Time to mine info
     time_to_mine: HashMap<H256, TimeToMineInfo>,
@@ -78,12 +89,19 @@ pub(crate) struct PoolInner {
     mined_hashes_with_block_numbers: BTreeSet<(u64, H256)>,
     /// Count of operations by entity address
     count_by_address: HashMap<Address, EntityCounter>,
-    /// Submission ID counter
-    submission_id: u64,
+    /// Count of operations by fingerprint
+    count_by_fingerprint: HashMap<H256, usize>,
+    /// Submission ID counter, a monotonic sequence assigned to operations as they are
+    /// admitted. Used for arrival ordering instead of wall-clock time, which can skew.
+    submission_id: AtomicU64,
     /// keeps track of the size of the pool in bytes
     pool_size: SizeTracker,
     /// keeps track of the size of the removed cache in bytes
     cache_size: SizeTracker,
+    /// Number of operations in the pool by the entity types they use
+    composition: PoolComposition,
+    /// The last operation handed out for re-validation, the next batch starts after it
+    revalidation_cursor: Option<OrderedPoolOperation>,
     /// The time of the previous block
     prev_sys_block_time: Duration,
     /// The number of the previous block
@@ -101,18 +119,24 @@ impl PoolInner {
             mined_at_block_number_by_hash: HashMap::new(),
             mined_hashes_with_block_numbers: BTreeSet::new(),
             count_by_address: HashMap::new(),
-            submission_id: 0,
+            count_by_fingerprint: HashMap::new(),
+            submission_id: AtomicU64::new(0),
             pool_size: SizeTracker::default(),
             cache_size: SizeTracker::default(),
+            composition: PoolComposition::default(),
+            revalidation_cursor: None,
             prev_sys_block_time: Duration::default(),
             prev_block_number: 0,
         }
     }
 
     /// Returns hash of operation to replace if operation is a replacement
+    ///
+    /// Operations added by the operator can only be replaced by another operator operation.
     pub(crate) fn check_replacement(
         &self,
         op: &UserOperationVariant,
+        from_operator: bool,
     ) -> MempoolResult<Option<H256>> {
         // Check if operation already known
         if self
@@ -123,6 +147,10 @@ impl PoolInner {
         }
 
         if let Some(pool_op) = self.by_id.get(&op.id()) {
+            if pool_op.po.no_replacement && !from_operator {
+                return Err(MempoolError::ReplacementNotAllowed);
+            }
+
             let (replacement_priority_fee, replacement_fee) =
                 self.get_min_replacement_fees(pool_op.uo());
 
@@ -145,14 +173,88 @@ impl PoolInner {
         }
     }
 
+    /// Returns an error if the pool is within its headroom of the size limit and the operation
+    /// would be the first evicted to make room for it, so admitting it can only churn the pool.
+    ///
+    /// Operations that outbid the current worst operation are still admitted and evict it.
+    pub(crate) fn check_capacity(&self, op: &UserOperationVariant) -> MempoolResult<()> {
+        let max_size = self.config.max_size_of_pool_bytes;
+        let headroom =
+            (max_size as u128 * self.config.size_headroom_percent as u128 / 100) as usize;
+        if self.pool_size < max_size.saturating_sub(headroom) {
+            return Ok(());
+        }
+        let Some(worst) = self.best.last() else {
+            return Ok(());
+        };
+
+        let would_be_evicted = match self.config.ordering_mode {
+            // A new operation is always the last to arrive
+            PoolOrderingMode::Fifo => true,
+            // Ties go to the operations already in the pool
+            PoolOrderingMode::PriorityFee => op.max_fee_per_gas() <= worst.uo().max_fee_per_gas(),
+        };
+        if would_be_evicted {
+            return Err(MempoolError::PoolBusy);
+        }
+        Ok(())
+    }
+
     pub(crate) fn add_operation(&mut self, op: PoolOperation) -> MempoolResult<H256> {
         let ret = self.add_operation_internal(Arc::new(op), None);
         self.update_metrics();
         ret
     }
 
-    pub(crate) fn best_operations(&self) -> impl Iterator<Item = Arc<PoolOperation>> {
-        self.best.clone().into_iter().map(|v| v.po)
+    /// Adds an operation with the submission ID it was assigned when admitted.
+    ///
+    /// A replacement keeps the submission ID of the operation it replaces instead.
+    pub(crate) fn add_operation_with_submission_id(
+        &mut self,
+        op: PoolOperation,
+        submission_id: u64,
+    ) -> MempoolResult<H256> {
+        let ret = self.add_operation_internal(Arc::new(op), Some(submission_id));
+        self.update_metrics();
+        ret
+    }
+
+    /// Returns the next submission ID, only requires shared access so that IDs can be
+    /// assigned in arrival order before operations are simulated
+    pub(crate) fn next_submission_id(&self) -> u64 {
+        self.submission_id.fetch_add(1, atomic::Ordering::Relaxed)
+    }
+
+    pub(crate) fn best_operations(&self) -> impl Iterator<Item = Arc<PoolOperation>> + '_ {
+        self.best.iter().map(|v| Arc::clone(&v.po))
+    }
+
+    /// Returns the next batch of up to `max` operations to re-validate.
+    ///
+    /// Batches rotate through the pool in order, wrapping around at the end, so that every
+    /// operation is eventually re-validated rather than only the best ones.
+    pub(crate) fn next_revalidation_batch(&mut self, max: usize) -> Vec<Arc<PoolOperation>> {
+        let batch = match &self.revalidation_cursor {
+            Some(cursor) => {
+                let mut batch = self
+                    .best
+                    .range((Bound::Excluded(cursor), Bound::Unbounded))
+                    .take(max)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let remaining = max - batch.len();
+                batch.extend(
+                    self.best
+                        .range((Bound::Unbounded, Bound::Included(cursor)))
+                        .take(remaining)
+                        .cloned(),
+                );
+                batch
+            }
+            None => self.best.iter().take(max).cloned().collect(),
+        };
+        self.revalidation_cursor = batch.last().cloned();
+        batch.into_iter().map(|op| op.po).collect()
     }
 
     /// Does maintenance on the pool.
@@ -180,7 +282,7 @@ impl PoolInner {
         let mut num_candidates = 0;
 
         for (hash, op) in &mut self.by_hash {
-            if op.po.valid_time_range.valid_until < block_timestamp {
+            if op.po.valid_time_range.is_expired(block_timestamp) {
                 expired.push((*hash, op.po.valid_time_range.valid_until));
             }
 
@@ -218,6 +320,13 @@ impl PoolInner {
         0
     }
 
+    pub(crate) fn fingerprint_count(&self, op: &UserOperationVariant) -> usize {
+        self.count_by_fingerprint
+            .get(&fingerprint(op))
+            .copied()
+            .unwrap_or_default()
+    }
+
     pub(crate) fn get_operation_by_hash(&self, hash: H256) -> Option<Arc<PoolOperation>> {
         self.by_hash.get(&hash).map(|o| o.po.clone())
     }
@@ -226,6 +335,17 @@ impl PoolInner {
         self.by_id.get(id).map(|o| o.po.clone())
     }
 
+    pub(crate) fn get_operations_by_sender(&self, sender: Address) -> Vec<Arc<PoolOperation>> {
+        let mut ops = self
+            .by_id
+            .iter()
+            .filter(|(id, _)| id.sender == sender)
+            .map(|(_, o)| o.po.clone())
+            .collect::<Vec<_>>();
+        ops.sort_by_key(|o| o.uo.nonce());
+        ops
+    }
+
     pub(crate) fn remove_operation_by_hash(&mut self, hash: H256) -> Option<Arc<PoolOperation>> {
         let ret = self.remove_operation_internal(hash, None);
         self.update_metrics();
@@ -397,8 +517,11 @@ impl PoolInner {
         self.mined_at_block_number_by_hash.clear();
         self.mined_hashes_with_block_numbers.clear();
         self.count_by_address.clear();
+        self.count_by_fingerprint.clear();
         self.pool_size = SizeTracker::default();
         self.cache_size = SizeTracker::default();
+        self.composition = PoolComposition::default();
+        self.revalidation_cursor = None;
         self.update_metrics();
     }
 
@@ -407,9 +530,7 @@ impl PoolInner {
 
         while self.pool_size > self.config.max_size_of_pool_bytes {
             if let Some(worst) = self.best.pop_last() {
-                let hash = worst
-                    .uo()
-                    .hash(self.config.entry_point, self.config.chain_id);
+                let hash = worst.hash;
 
                 let _ = self
                     .remove_operation_internal(hash, None)
@@ -433,13 +554,22 @@ impl PoolInner {
     ) -> MempoolResult<H256> {
         // Check if operation already known or replacing an existing operation
         // if replacing, remove the existing operation
-        if let Some(hash) = self.check_replacement(&op.uo)? {
+        let mut replaced_submission_id = None;
+        if let Some(hash) = self.check_replacement(&op.uo, op.no_replacement)? {
+            // the replacement keeps the arrival order of the operation it replaces
+            // so that a sender can't jump the FIFO queue by repeatedly bumping fees
+            replaced_submission_id = self.by_hash.get(&hash).map(|op| op.submission_id);
             self.remove_operation_by_hash(hash);
         }
 
+        let hash = op.uo.hash(self.config.entry_point, self.config.chain_id);
         let pool_op = OrderedPoolOperation {
             po: op,
-            submission_id: submission_id.unwrap_or_else(|| self.next_submission_id()),
+            hash,
+            submission_id: replaced_submission_id
+                .or(submission_id)
+                .unwrap_or_else(|| self.next_submission_id()),
+            ordering_mode: self.config.ordering_mode,
         };
 
         // update counts
@@ -448,12 +578,14 @@ impl PoolInner {
                 .entry(e.address)
                 .or_default()
                 .increment_entity_count(&e.kind);
+            self.composition.increment(&e.kind);
         }
+        *self
+            .count_by_fingerprint
+            .entry(fingerprint(&pool_op.po.uo))
+            .or_default() += 1;
 
         // create and insert ordered operation
-        let hash = pool_op
-            .uo()
-            .hash(self.config.entry_point, self.config.chain_id);
         self.pool_size += pool_op.mem_size();
         self.by_hash.insert(hash, pool_op.clone());
         self.by_id.insert(pool_op.uo().id(), pool_op.clone());
@@ -466,7 +598,7 @@ impl PoolInner {
             .context("should have succeeded in resizing the pool")?;
 
         if removed.contains(&hash) {
-            Err(MempoolError::DiscardedOnInsert)?;
+            Err(MempoolError::PoolFull)?;
         }
 
         Ok(hash)
@@ -493,6 +625,15 @@ impl PoolInner {
 
         for e in op.po.entities() {
             self.decrement_address_count(e.address, &e.kind);
+            self.composition.decrement(&e.kind);
+        }
+        if let Entry::Occupied(mut count_entry) =
+            self.count_by_fingerprint.entry(fingerprint(&op.po.uo))
+        {
+            *count_entry.get_mut() -= 1;
+            if *count_entry.get() == 0 {
+                count_entry.remove_entry();
+            }
         }
 
         self.pool_size -= op.mem_size();
@@ -508,12 +649,6 @@ impl PoolInner {
         }
     }
 
-    fn next_submission_id(&mut self) -> u64 {
-        let id = self.submission_id;
-        self.submission_id += 1;
-        id
-    }
-
     fn get_min_replacement_fees(&self, op: &UserOperationVariant) -> (U256, U256) {
         let replacement_priority_fee = math::increase_by_percent(
             op.max_priority_fee_per_gas(),
@@ -537,6 +672,50 @@ impl PoolInner {
             self.cache_size.0,
             self.config.entry_point,
         );
+        PoolMetrics::set_composition_metrics(&self.composition, self.config.entry_point);
+    }
+}
+
+/// Fingerprint of an operation, shared by ops from the same sender with the same
+/// call data and paymaster. Used to rate limit spam variants that only differ in
+/// fields such as the nonce, gas limits, or signature.
+fn fingerprint(uo: &UserOperationVariant) -> H256 {
+    keccak256(abi::encode(&[
+        Token::Address(uo.sender()),
+        Token::FixedBytes(keccak256(uo.call_data()).to_vec()),
+        Token::Address(uo.paymaster().unwrap_or_default()),
+    ]))
+    .into()
+}
+
+/// Number of operations in the pool that use each optional entity type
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct PoolComposition {
+    with_paymaster: usize,
+    with_factory: usize,
+    aggregated: usize,
+}
+
+impl PoolComposition {
+    fn increment(&mut self, kind: &EntityType) {
+        if let Some(count) = self.count_mut(kind) {
+            *count += 1;
+        }
+    }
+
+    fn decrement(&mut self, kind: &EntityType) {
+        if let Some(count) = self.count_mut(kind) {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    fn count_mut(&mut self, kind: &EntityType) -> Option<&mut usize> {
+        match kind {
+            EntityType::Paymaster => Some(&mut self.with_paymaster),
+            EntityType::Factory => Some(&mut self.with_factory),
+            EntityType::Aggregator => Some(&mut self.aggregated),
+            EntityType::Account => None,
+        }
     }
 }
 
@@ -545,7 +724,9 @@ impl PoolInner {
 #[derive(Debug, Clone)]
 struct OrderedPoolOperation {
     po: Arc<PoolOperation>,
+    hash: H256,
     submission_id: u64,
+    ordering_mode: PoolOrderingMode,
 }
 
 impl OrderedPoolOperation {
@@ -562,12 +743,22 @@ impl Eq for OrderedPoolOperation {}
 
 impl Ord for OrderedPoolOperation {
     fn cmp(&self, other: &Self) -> Ordering {
-        // Sort by gas price descending then by id ascending
+        if self.ordering_mode == PoolOrderingMode::Fifo {
+            // Sort by arrival in the pool, oldest first
+            return self
+                .submission_id
+                .cmp(&other.submission_id)
+                .then_with(|| self.hash.cmp(&other.hash));
+        }
+
+        // Sort by gas price descending then by id ascending, with the hash as a final
+        // tie-break so that operations sharing a submission ID have a deterministic order
         other
             .uo()
             .max_fee_per_gas()
             .cmp(&self.uo().max_fee_per_gas())
             .then_with(|| self.submission_id.cmp(&other.submission_id))
+            .then_with(|| self.hash.cmp(&other.hash))
     }
 }
 
@@ -620,6 +811,15 @@ impl PoolMetrics {
             .set(size_bytes as f64);
     }
 
+    fn set_composition_metrics(composition: &PoolComposition, entry_point: Address) {
+        metrics::gauge!("op_pool_num_ops_with_paymaster", "entry_point" => entry_point.to_string())
+            .set(composition.with_paymaster as f64);
+        metrics::gauge!("op_pool_num_ops_with_factory", "entry_point" => entry_point.to_string())
+            .set(composition.with_factory as f64);
+        metrics::gauge!("op_pool_num_aggregated_ops", "entry_point" => entry_point.to_string())
+            .set(composition.aggregated as f64);
+    }
+
     // Set the number of candidates in the pool, only changes on block boundaries
     fn set_num_candidates(num_candidates: usize, entry_point: Address) {
         metrics::gauge!("op_pool_num_candidates", "entry_point" => entry_point.to_string())
@@ -642,9 +842,10 @@ impl PoolMetrics {
 
 #[cfg(test)]
 mod tests {
+    use ethers::types::Bytes;
     use rundler_types::{
         v0_6::UserOperation, EntityInfo, EntityInfos, UserOperation as UserOperationTrait,
-        ValidTimeRange,
+        ValidTimeRange, ValidationReturnInfo,
     };
 
     use super::*;
@@ -690,6 +891,54 @@ mod tests {
         assert_eq!(pool.get_operation_by_id(&bad_id), None);
     }
 
+    #[test]
+    fn test_get_by_sender() {
+        let mut pool = PoolInner::new(conf());
+        let sender = Address::random();
+        let op1 = create_op(sender, 1, 1);
+        let op0 = create_op(sender, 0, 1);
+        let other = create_op(Address::random(), 0, 1);
+        pool.add_operation(op1.clone()).unwrap();
+        pool.add_operation(op0.clone()).unwrap();
+        pool.add_operation(other).unwrap();
+
+        let ops = pool.get_operations_by_sender(sender);
+        assert_eq!(ops.len(), 2);
+        assert_eq!(*ops[0], op0);
+        assert_eq!(*ops[1], op1);
+
+        assert!(pool.get_operations_by_sender(Address::random()).is_empty());
+    }
+
+    #[test]
+    fn test_revalidation_batches_rotate() {
+        let mut pool = PoolInner::new(conf());
+        let ops = (1..=5)
+            .map(|fee| create_op(Address::random(), 0, fee))
+            .collect::<Vec<_>>();
+        for op in &ops {
+            pool.add_operation(op.clone()).unwrap();
+        }
+        let fees = |batch: Vec<Arc<PoolOperation>>| {
+            batch
+                .iter()
+                .map(|op| op.uo.max_fee_per_gas().as_u64())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(fees(pool.next_revalidation_batch(2)), vec![5, 4]);
+        assert_eq!(fees(pool.next_revalidation_batch(2)), vec![3, 2]);
+        // wraps around to the start of the pool
+        assert_eq!(fees(pool.next_revalidation_batch(2)), vec![1, 5]);
+
+        // a removed cursor operation doesn't reset the rotation
+        pool.remove_operation_by_hash(ops[4].uo.hash(pool.config.entry_point, 1));
+        assert_eq!(fees(pool.next_revalidation_batch(2)), vec![4, 3]);
+
+        // never returns an operation twice in one batch
+        assert_eq!(fees(pool.next_revalidation_batch(10)), vec![2, 1, 4, 3]);
+    }
+
     #[test]
     fn add_multiple_ops() {
         let mut pool = PoolInner::new(conf());
@@ -737,6 +986,124 @@ mod tests {
         check_map_entry(pool.best.iter().nth(2), Some(&ops[2]));
     }
 
+    #[test]
+    fn best_fifo() {
+        let mut pool = PoolInner::new(PoolInnerConfig {
+            ordering_mode: PoolOrderingMode::Fifo,
+            ..conf()
+        });
+        let ops = vec![
+            create_op(Address::random(), 0, 1),
+            create_op(Address::random(), 0, 3),
+            create_op(Address::random(), 0, 2),
+        ];
+        for op in ops.iter() {
+            pool.add_operation(op.clone()).unwrap();
+        }
+
+        // best should be in insertion order, regardless of fees
+        let best = pool.best_operations().collect::<Vec<_>>();
+        assert_eq!(best.len(), 3);
+        for (best, op) in best.iter().zip(ops.iter()) {
+            assert_eq!(best.uo, op.uo);
+        }
+    }
+
+    #[test]
+    fn best_fifo_replacement_keeps_arrival_order() {
+        let mut pool = PoolInner::new(PoolInnerConfig {
+            ordering_mode: PoolOrderingMode::Fifo,
+            ..conf()
+        });
+        let sender = Address::random();
+        let mut first = create_op(sender, 0, 10);
+        let uo: &mut UserOperation = first.uo.as_mut();
+        uo.max_priority_fee_per_gas = 10.into();
+        let second = create_op(Address::random(), 0, 10);
+        pool.add_operation(first).unwrap();
+        pool.add_operation(second.clone()).unwrap();
+
+        // bump the fees of the first op
+        let mut replacement = create_op(sender, 0, 20);
+        let uo: &mut UserOperation = replacement.uo.as_mut();
+        uo.max_priority_fee_per_gas = 20.into();
+        pool.add_operation(replacement.clone()).unwrap();
+
+        // the replacement is still ahead of the second op
+        let best = pool.best_operations().collect::<Vec<_>>();
+        assert_eq!(best.len(), 2);
+        assert_eq!(best[0].uo, replacement.uo);
+        assert_eq!(best[1].uo, second.uo);
+    }
+
+    #[test]
+    fn best_fifo_orders_by_admission() {
+        let mut pool = PoolInner::new(PoolInnerConfig {
+            ordering_mode: PoolOrderingMode::Fifo,
+            ..conf()
+        });
+        let first = create_op(Address::random(), 0, 1);
+        let second = create_op(Address::random(), 0, 1);
+        let first_id = pool.next_submission_id();
+        let second_id = pool.next_submission_id();
+
+        // the first op is admitted first but finishes simulation last
+        pool.add_operation_with_submission_id(second.clone(), second_id)
+            .unwrap();
+        pool.add_operation_with_submission_id(first.clone(), first_id)
+            .unwrap();
+
+        let best = pool.best_operations().collect::<Vec<_>>();
+        assert_eq!(best[0].uo, first.uo);
+        assert_eq!(best[1].uo, second.uo);
+    }
+
+    #[test]
+    fn submission_ids_monotonic_under_concurrency() {
+        let pool = Arc::new(PoolInner::new(conf()));
+        let threads = (0..8)
+            .map(|_| {
+                let pool = Arc::clone(&pool);
+                std::thread::spawn(move || {
+                    (0..1000)
+                        .map(|_| pool.next_submission_id())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut all = vec![];
+        for thread in threads {
+            let ids = thread.join().unwrap();
+            // each thread sees strictly increasing ids
+            assert!(ids.windows(2).all(|w| w[0] < w[1]));
+            all.extend(ids);
+        }
+
+        // no id is assigned twice or skipped
+        all.sort_unstable();
+        assert_eq!(all, (0..8000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn best_ties_same_submission_id_by_hash() {
+        let ordered = |hash: H256| OrderedPoolOperation {
+            po: Arc::new(create_op(Address::random(), 0, 1)),
+            hash,
+            submission_id: 0,
+            ordering_mode: PoolOrderingMode::PriorityFee,
+        };
+        let low = ordered(H256::from_low_u64_be(1));
+        let high = ordered(H256::from_low_u64_be(2));
+
+        assert_eq!(low.cmp(&high), Ordering::Less);
+        assert_eq!(high.cmp(&low), Ordering::Greater);
+
+        let best = BTreeSet::from([high.clone(), low.clone()]);
+        assert_eq!(best.len(), 2);
+        assert_eq!(best.first().unwrap().hash, low.hash);
+    }
+
     #[test]
     fn remove_op() {
         let mut pool = PoolInner::new(conf());
@@ -974,7 +1341,10 @@ mod tests {
         }
 
         let op = create_op(Address::random(), 4, 1);
-        assert!(pool.add_operation(op).is_err());
+        assert!(matches!(
+            pool.add_operation(op),
+            Err(MempoolError::PoolFull)
+        ));
 
         // on equal gas, worst should remain because it came first
         let op = create_op(Address::random(), 4, 2);
@@ -982,6 +1352,50 @@ mod tests {
         assert!(result.is_ok(), "{:?}", result.err());
     }
 
+    #[test]
+    fn pool_near_capacity_is_busy() {
+        let args = conf();
+        let mut pool = PoolInner::new(args.clone());
+        // headroom is 5% of 20 ops, so the pool is near capacity at 19 ops
+        for i in 0..18 {
+            let op = create_op(Address::random(), i, i + 2);
+            pool.add_operation(op).unwrap();
+        }
+        let cheap = create_op(Address::random(), 0, 1);
+        assert!(pool.check_capacity(&cheap.uo).is_ok());
+
+        let op = create_op(Address::random(), 18, 20);
+        pool.add_operation(op).unwrap();
+        // the worst op in the pool pays 2, an op that would be evicted first is rejected
+        assert!(matches!(
+            pool.check_capacity(&cheap.uo),
+            Err(MempoolError::PoolBusy)
+        ));
+        let equal = create_op(Address::random(), 0, 2);
+        assert!(matches!(
+            pool.check_capacity(&equal.uo),
+            Err(MempoolError::PoolBusy)
+        ));
+        // an op that outbids the worst op is admitted and evicts it
+        let better = create_op(Address::random(), 0, 3);
+        assert!(pool.check_capacity(&better.uo).is_ok());
+    }
+
+    #[test]
+    fn pool_near_capacity_headroom_disabled() {
+        let args = PoolInnerConfig {
+            size_headroom_percent: 0,
+            ..conf()
+        };
+        let mut pool = PoolInner::new(args);
+        for i in 0..19 {
+            let op = create_op(Address::random(), i, i + 2);
+            pool.add_operation(op).unwrap();
+        }
+        let cheap = create_op(Address::random(), 0, 1);
+        assert!(pool.check_capacity(&cheap.uo).is_ok());
+    }
+
     #[test]
     fn replace_op_underpriced() {
         let mut pool = PoolInner::new(conf());
@@ -1009,12 +1423,43 @@ mod tests {
             pool.pool_size,
             OrderedPoolOperation {
                 po: Arc::new(po1),
+                hash: H256::zero(),
                 submission_id: 0,
+                ordering_mode: PoolOrderingMode::default(),
             }
             .mem_size()
         );
     }
 
+    #[test]
+    fn replace_operator_op_not_allowed() {
+        let mut pool = PoolInner::new(conf());
+        let sender = Address::random();
+        let mut po1 = create_op(sender, 0, 100);
+        po1.no_replacement = true;
+        let uo1: &mut UserOperation = po1.uo.as_mut();
+        uo1.max_priority_fee_per_gas = 100.into();
+        let hash1 = pool.add_operation(po1).unwrap();
+
+        // an external bump at the same id is rejected regardless of its fees
+        let mut po2 = create_op(sender, 0, 200);
+        let uo2: &mut UserOperation = po2.uo.as_mut();
+        uo2.max_priority_fee_per_gas = 200.into();
+        let res = pool.add_operation(po2);
+        assert!(matches!(res, Err(MempoolError::ReplacementNotAllowed)));
+        assert!(pool.get_operation_by_hash(hash1).is_some());
+
+        // the operator may still replace its own op
+        let mut po3 = create_op(sender, 0, 200);
+        po3.no_replacement = true;
+        let uo3: &mut UserOperation = po3.uo.as_mut();
+        uo3.max_priority_fee_per_gas = 200.into();
+        let hash3 = pool.add_operation(po3).unwrap();
+        assert!(pool.get_operation_by_hash(hash1).is_none());
+        assert!(pool.get_operation_by_hash(hash3).is_some());
+        assert_eq!(pool.address_count(&sender), 1);
+    }
+
     #[test]
     fn replace_op() {
         let mut pool = PoolInner::new(conf());
@@ -1049,7 +1494,9 @@ mod tests {
             pool.pool_size,
             OrderedPoolOperation {
                 po: Arc::new(po2),
+                hash: H256::zero(),
                 submission_id: 0,
+                ordering_mode: PoolOrderingMode::default(),
             }
             .mem_size()
         );
@@ -1087,6 +1534,22 @@ mod tests {
         assert_eq!(res[0].1, Timestamp::from(1));
     }
 
+    #[test]
+    fn test_zero_valid_until_not_expired() {
+        let conf = conf();
+        let mut pool = PoolInner::new(conf.clone());
+        let mut po1 = create_op(Address::random(), 0, 10);
+        // a zero validUntil returned by validation is decoded as no expiry
+        let return_info =
+            ValidationReturnInfo::from((U256::zero(), U256::zero(), false, 0, 0, Bytes::default()));
+        po1.valid_time_range.valid_until = return_info.valid_until;
+        let _ = pool.add_operation(po1.clone()).unwrap();
+
+        let res = pool.do_maintenance(0, Timestamp::now(), GasFees::default(), 0.into());
+        assert!(res.is_empty());
+        assert_eq!(pool.best_operations().count(), 1);
+    }
+
     #[test]
     fn test_multiple_expired() {
         let conf = conf();
@@ -1110,21 +1573,75 @@ mod tests {
         assert!(res.contains(&(po3.uo.hash(conf.entry_point, conf.chain_id), 9.into())));
     }
 
+    #[test]
+    fn test_pool_composition() {
+        let mut pool = PoolInner::new(conf());
+
+        let plain = create_op(Address::random(), 0, 10);
+        let mut paymaster = create_op(Address::random(), 0, 10);
+        paymaster
+            .entity_infos
+            .set_paymaster(Address::random(), false);
+        let mut factory_and_paymaster = create_op(Address::random(), 0, 10);
+        factory_and_paymaster
+            .entity_infos
+            .set_factory(Address::random(), false);
+        factory_and_paymaster
+            .entity_infos
+            .set_paymaster(Address::random(), false);
+        let mut aggregated = create_op(Address::random(), 0, 10);
+        aggregated
+            .entity_infos
+            .set_aggregator(Address::random(), true);
+
+        for op in [&plain, &paymaster, &factory_and_paymaster, &aggregated] {
+            pool.add_operation(op.clone()).unwrap();
+        }
+        assert_eq!(
+            pool.composition,
+            PoolComposition {
+                with_paymaster: 2,
+                with_factory: 1,
+                aggregated: 1,
+            }
+        );
+
+        let hash = factory_and_paymaster
+            .uo
+            .hash(pool.config.entry_point, pool.config.chain_id);
+        pool.remove_operation_by_hash(hash).unwrap();
+        assert_eq!(
+            pool.composition,
+            PoolComposition {
+                with_paymaster: 1,
+                with_factory: 0,
+                aggregated: 1,
+            }
+        );
+
+        pool.clear();
+        assert_eq!(pool.composition, PoolComposition::default());
+    }
+
     fn conf() -> PoolInnerConfig {
         PoolInnerConfig {
             entry_point: Address::random(),
             chain_id: 1,
             min_replacement_fee_increase_percentage: 10,
             max_size_of_pool_bytes: 20 * mem_size_of_ordered_pool_op(),
+            size_headroom_percent: 5,
             throttled_entity_mempool_count: 4,
             throttled_entity_live_blocks: 10,
+            ordering_mode: PoolOrderingMode::PriorityFee,
         }
     }
 
     fn mem_size_of_ordered_pool_op() -> usize {
         OrderedPoolOperation {
             po: Arc::new(create_op(Address::random(), 1, 1)),
+            hash: H256::zero(),
             submission_id: 1,
+            ordering_mode: PoolOrderingMode::default(),
         }
         .mem_size()
     }
@@ -1150,10 +1667,11 @@ mod tests {
             entry_point: Address::random(),
             valid_time_range: ValidTimeRange::default(),
             aggregator: None,
-            expected_code_hash: H256::random(),
+            expected_code_hash: Some(H256::random()),
             sim_block_hash: H256::random(),
             sim_block_number: 0,
             account_is_staked: false,
+            no_replacement: false,
         }
     }
 
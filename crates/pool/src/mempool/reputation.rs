@@ -13,13 +13,21 @@
 
 use std::{
     collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
     time::Duration,
 };
 
+use anyhow::Context;
 use ethers::types::Address;
 use parking_lot::RwLock;
 use rundler_types::pool::{Reputation, ReputationStatus};
-use tokio::time::interval;
+use serde::{Deserialize, Serialize};
+use tokio::time::{interval, interval_at, Instant};
+use tokio_util::sync::CancellationToken;
+
+/// How often reputation counters are written to the persistence file, if enabled
+const PERSIST_INTERVAL: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct ReputationParams {
@@ -53,13 +61,32 @@ impl Default for ReputationParams {
 }
 
 impl ReputationParams {
-    pub(crate) fn new(tracking_enabled: bool) -> Self {
+    pub(crate) fn new(tracking_enabled: bool, decay_interval_secs: u64, decay_factor: u64) -> Self {
         Self {
             tracking_enabled,
+            decay_interval_secs,
+            decay_factor,
             ..Default::default()
         }
     }
 
+    /// Sets the thresholds at which an entity is throttled or banned. An entity is expected
+    /// to have 1/`min_inclusion_rate_denominator` of its seen operations included, and is
+    /// throttled or banned once it falls short by more than the respective slack.
+    pub(crate) fn with_thresholds(
+        self,
+        min_inclusion_rate_denominator: u64,
+        throttling_slack: u64,
+        ban_slack: u64,
+    ) -> Self {
+        Self {
+            min_inclusion_rate_denominator,
+            throttling_slack,
+            ban_slack,
+            ..self
+        }
+    }
+
     #[allow(dead_code)]
     pub(crate) fn bundler_default() -> Self {
         Self::default()
@@ -85,6 +112,7 @@ impl ReputationParams {
 
 pub(crate) struct AddressReputation {
     state: RwLock<AddressReputationInner>,
+    persist_path: Option<PathBuf>,
 }
 
 impl AddressReputation {
@@ -99,19 +127,68 @@ impl AddressReputation {
                     .with_blocklist(blocklist)
                     .with_allowlist(allowlist),
             ),
+            persist_path: None,
         }
     }
 
-    pub(crate) async fn run(&self) {
-        let mut tick = interval(Duration::from_secs(
-            self.state.read().params.decay_interval_secs,
-        ));
+    /// Persists the reputation counters to the file at `path`, restoring any counters
+    /// previously saved there.
+    pub(crate) fn with_persistence(mut self, path: PathBuf) -> Self {
+        if path.exists() {
+            match load_counts(&path) {
+                Ok(counts) => {
+                    tracing::info!(
+                        "Restored reputation of {} entities from {path:?}",
+                        counts.len()
+                    );
+                    self.state.get_mut().counts = counts;
+                }
+                Err(e) => tracing::error!("Failed to restore reputation from {path:?}: {e:?}"),
+            }
+        }
+        self.persist_path = Some(path);
+        self
+    }
+
+    pub(crate) async fn run(&self, shutdown_token: CancellationToken) {
+        let decay_interval = Duration::from_secs(self.state.read().params.decay_interval_secs);
+        // Restored counters shouldn't decay on every restart, so when persisting start the
+        // decay after a full interval
+        let decay_start = if self.persist_path.is_some() {
+            Instant::now() + decay_interval
+        } else {
+            Instant::now()
+        };
+        let mut decay_tick = interval_at(decay_start, decay_interval);
+        let mut persist_tick = interval(PERSIST_INTERVAL);
         loop {
-            tick.tick().await;
-            self.state.write().update();
+            tokio::select! {
+                _ = decay_tick.tick() => self.state.write().update(),
+                _ = persist_tick.tick(), if self.persist_path.is_some() => {
+                    if let Err(e) = self.persist().await {
+                        tracing::error!("Failed to persist reputation: {e:?}");
+                    }
+                }
+                _ = shutdown_token.cancelled() => {
+                    if let Err(e) = self.persist().await {
+                        tracing::error!("Failed to persist reputation on shutdown: {e:?}");
+                    }
+                    break;
+                }
+            }
         }
     }
 
+    async fn persist(&self) -> anyhow::Result<()> {
+        let Some(path) = self.persist_path.clone() else {
+            return Ok(());
+        };
+        let counts = self.state.read().counts.clone();
+        tokio::task::spawn_blocking(move || save_counts(&path, &counts))
+            .await
+            .context("reputation persist task should not panic")?
+    }
+
     pub(crate) fn status(&self, address: Address) -> ReputationStatus {
         self.state.read().status(address)
     }
@@ -291,12 +368,42 @@ impl AddressReputationInner {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct AddressCount {
     ops_seen: u64,
     ops_included: u64,
 }
 
+#[derive(Serialize, Deserialize)]
+struct PersistedCount {
+    address: Address,
+    #[serde(flatten)]
+    count: AddressCount,
+}
+
+fn load_counts(path: &Path) -> anyhow::Result<HashMap<Address, AddressCount>> {
+    let file = fs::File::open(path).context("should open reputation file")?;
+    let counts: Vec<PersistedCount> =
+        serde_json::from_reader(file).context("should parse reputation file")?;
+    Ok(counts.into_iter().map(|c| (c.address, c.count)).collect())
+}
+
+// Writes to a temporary file first so a crash mid-write can't corrupt the saved counters
+fn save_counts(path: &Path, counts: &HashMap<Address, AddressCount>) -> anyhow::Result<()> {
+    let counts: Vec<PersistedCount> = counts
+        .iter()
+        .map(|(address, count)| PersistedCount {
+            address: *address,
+            count: count.clone(),
+        })
+        .collect();
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, serde_json::to_vec(&counts)?).context("should write reputation file")?;
+    fs::rename(&tmp_path, path).context("should replace reputation file")?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -377,7 +484,10 @@ mod tests {
     #[test]
     fn reputation_banned_tracking_disabled() {
         let addr = Address::random();
-        let params = ReputationParams::new(false);
+        let params = ReputationParams {
+            tracking_enabled: false,
+            ..ReputationParams::default()
+        };
         let mut reputation = AddressReputationInner::new(params);
 
         let ops_seen = 1000;
@@ -386,6 +496,77 @@ mod tests {
         assert_eq!(reputation.status(addr), ReputationStatus::Ok);
     }
 
+    #[test]
+    fn reputation_custom_thresholds() {
+        let addr = Address::random();
+        let mut default_reputation =
+            AddressReputationInner::new(ReputationParams::bundler_default());
+        let mut custom_reputation = AddressReputationInner::new(
+            ReputationParams::bundler_default().with_thresholds(10, 5, 20),
+        );
+
+        // 100 expected included, short by 30
+        default_reputation.set_reputation(addr, 1000, 70);
+        custom_reputation.set_reputation(addr, 1000, 70);
+        assert_eq!(default_reputation.status(addr), ReputationStatus::Throttled);
+        assert_eq!(custom_reputation.status(addr), ReputationStatus::Banned);
+
+        // short by 10, within the default throttling slack
+        default_reputation.set_reputation(addr, 1000, 90);
+        custom_reputation.set_reputation(addr, 1000, 90);
+        assert_eq!(default_reputation.status(addr), ReputationStatus::Ok);
+        assert_eq!(custom_reputation.status(addr), ReputationStatus::Throttled);
+    }
+
+    #[tokio::test]
+    async fn persist_round_trip() {
+        let path = std::env::temp_dir().join(format!("reputation_{:?}.json", Address::random()));
+        let seen_addr = Address::random();
+        let included_addr = Address::random();
+
+        let reputation = AddressReputation::new(
+            ReputationParams::bundler_default(),
+            HashSet::new(),
+            HashSet::new(),
+        )
+        .with_persistence(path.clone());
+        reputation.set_reputation(seen_addr, 1000, 10);
+        reputation.set_reputation(included_addr, 20, 15);
+        reputation.persist().await.unwrap();
+
+        let restored = AddressReputation::new(
+            ReputationParams::bundler_default(),
+            HashSet::new(),
+            HashSet::new(),
+        )
+        .with_persistence(path.clone());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.state.read().counts, reputation.state.read().counts);
+        assert_eq!(restored.status(seen_addr), ReputationStatus::Banned);
+        assert_eq!(restored.status(included_addr), ReputationStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn persist_on_shutdown() {
+        let path = std::env::temp_dir().join(format!("reputation_{:?}.json", Address::random()));
+        let reputation = AddressReputation::new(
+            ReputationParams::bundler_default(),
+            HashSet::new(),
+            HashSet::new(),
+        )
+        .with_persistence(path.clone());
+        reputation.set_reputation(Address::random(), 1000, 10);
+
+        let shutdown_token = CancellationToken::new();
+        shutdown_token.cancel();
+        reputation.run(shutdown_token).await;
+
+        let counts = load_counts(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(counts, reputation.state.read().counts);
+    }
+
     #[test]
     fn hourly_update() {
         let addr = Address::random();
@@ -408,6 +589,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn configured_decay() {
+        let addr = Address::random();
+        let mut reputation = AddressReputationInner::new(ReputationParams::new(true, 60, 2));
+        reputation.set_reputation(addr, 1000, 100);
+
+        // each interval removes half of the counts
+        reputation.update();
+        let counts = reputation.counts.get(&addr).unwrap();
+        assert_eq!(counts.ops_seen, 500);
+        assert_eq!(counts.ops_included, 50);
+
+        reputation.update();
+        let counts = reputation.counts.get(&addr).unwrap();
+        assert_eq!(counts.ops_seen, 250);
+        assert_eq!(counts.ops_included, 25);
+    }
+
+    #[test]
+    fn banned_recovers_after_decay() {
+        let addr = Address::random();
+        let mut reputation = AddressReputationInner::new(ReputationParams::new(true, 60, 2));
+        reputation.set_reputation(addr, 1000, 0);
+        assert_eq!(reputation.status(addr), ReputationStatus::Banned);
+
+        for _ in 0..4 {
+            reputation.update();
+        }
+        assert_eq!(reputation.status(addr), ReputationStatus::Ok);
+    }
+
     #[test]
     fn test_blocklist() {
         let addr = Address::random();
@@ -467,4 +679,31 @@ mod tests {
             assert!(addrs.contains(&rep.address));
         }
     }
+
+    #[test]
+    fn concurrent_seen_included() {
+        let reputation = AddressReputation::new(
+            ReputationParams::bundler_default(),
+            HashSet::new(),
+            HashSet::new(),
+        );
+        let addr = Address::random();
+
+        std::thread::scope(|s| {
+            for _ in 0..16 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        reputation.add_seen(addr);
+                        reputation.add_included(addr);
+                    }
+                });
+            }
+        });
+
+        let reps = reputation.dump_reputation();
+        assert_eq!(reps.len(), 1);
+        assert_eq!(reps[0].ops_seen, 16_000);
+        assert_eq!(reps[0].ops_included, 16_000);
+        assert_eq!(reputation.status(addr), ReputationStatus::Ok);
+    }
 }
@@ -11,7 +11,14 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::{collections::HashSet, marker::PhantomData, sync::Arc};
+use std::{
+    collections::HashSet,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 
 use ethers::{
     types::{Address, H256, U256},
@@ -20,12 +27,12 @@ use ethers::{
 use itertools::Itertools;
 use parking_lot::RwLock;
 use rundler_provider::EntryPoint;
-use rundler_sim::{Prechecker, Simulator};
+use rundler_sim::{Prechecker, SimulationError, Simulator, StakeRequirement, ViolationError};
 use rundler_types::{
     pool::{
         MempoolError, PaymasterMetadata, PoolOperation, Reputation, ReputationStatus, StakeStatus,
     },
-    Entity, EntityUpdate, EntityUpdateType, EntryPointVersion, GasFees, UserOperation,
+    Entity, EntityType, EntityUpdate, EntityUpdateType, EntryPointVersion, GasFees, UserOperation,
     UserOperationId, UserOperationVariant,
 };
 use rundler_utils::emit::WithEntryPoint;
@@ -55,6 +62,7 @@ pub(crate) struct UoPool<UO: UserOperation, P: Prechecker, S: Simulator, E: Entr
     event_sender: broadcast::Sender<WithEntryPoint<OpPoolEvent>>,
     prechecker: P,
     simulator: S,
+    revalidating: AtomicBool,
     _uo_type: PhantomData<UO>,
 }
 
@@ -95,6 +103,7 @@ where
             prechecker,
             simulator,
             config,
+            revalidating: AtomicBool::new(false),
             _uo_type: PhantomData,
         }
     }
@@ -137,6 +146,63 @@ where
         UoPoolMetrics::increment_removed_operations(count, self.config.entry_point);
         UoPoolMetrics::increment_removed_entities(self.config.entry_point);
     }
+
+    // Re-simulate the next batch of operations in the pool and drop those that are no longer valid.
+    // Operations are only dropped on simulation violations, other errors are logged and ignored.
+    async fn revalidate_batch(&self, block_number: u64)
+    where
+        UO: From<UserOperationVariant>,
+    {
+        let ops = self
+            .state
+            .write()
+            .pool
+            .next_revalidation_batch(self.config.revalidation_max_ops);
+
+        let mut to_remove = vec![];
+        for op in ops {
+            match self
+                .simulator
+                .simulate_validation(op.uo.clone().into(), None, None)
+                .await
+            {
+                Ok(_) => {}
+                Err(SimulationError {
+                    violation_error: ViolationError::Violations(violations),
+                    ..
+                }) => {
+                    let hash = op.uo.hash(self.config.entry_point, self.config.chain_id);
+                    info!("Dropping op {hash:?} after failed re-validation: {violations:?}");
+                    to_remove.push(hash);
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to re-validate op: {:?}", e.violation_error);
+                }
+            }
+        }
+
+        let mut removed_hashes = vec![];
+        {
+            let mut state = self.state.write();
+            for hash in to_remove {
+                if let Some(op) = state.pool.remove_operation_by_hash(hash) {
+                    self.paymaster.remove_operation(&op.uo.id());
+                    state.throttled_ops.remove(&hash);
+                    removed_hashes.push(hash);
+                }
+            }
+        }
+
+        let count = removed_hashes.len();
+        for op_hash in removed_hashes {
+            self.emit(OpPoolEvent::RemovedOp {
+                op_hash,
+                reason: OpRemovalReason::FailedRevalidation { block_number },
+            })
+        }
+        UoPoolMetrics::increment_removed_operations(count, self.config.entry_point);
+        UoPoolMetrics::increment_revalidation_dropped(count, self.config.entry_point);
+    }
 }
 
 #[async_trait]
@@ -181,6 +247,20 @@ where
             let mut mined_op_count = 0;
             let mut unmined_op_count = 0;
 
+            // Apply deposit events to the cached paymaster balances right away. Events and
+            // mined ops aren't applied in log order, so the balances of paymasters with events
+            // are reloaded from the entry point below.
+            if !update.reorg_larger_than_history {
+                for balance_update in update
+                    .entity_balance_updates
+                    .iter()
+                    .filter(|u| u.entrypoint == self.config.entry_point)
+                {
+                    self.paymaster
+                        .update_paymaster_balance_from_event(balance_update);
+                }
+            }
+
             for op in mined_ops {
                 if op.entry_point != self.config.entry_point {
                     continue;
@@ -359,6 +439,21 @@ where
         }
     }
 
+    async fn revalidate_operations(&self, block_number: u64) {
+        let Some(interval) = self.config.revalidation_interval_blocks else {
+            return;
+        };
+        if interval == 0 || block_number % interval != 0 {
+            return;
+        }
+        // Skip this pass if the previous one is still running
+        if self.revalidating.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.revalidate_batch(block_number).await;
+        self.revalidating.store(false, Ordering::Release);
+    }
+
     fn entry_point(&self) -> Address {
         self.config.entry_point
     }
@@ -376,8 +471,20 @@ where
         self.paymaster.reset_confirmed_balances().await
     }
 
-    async fn get_stake_status(&self, address: Address) -> MempoolResult<StakeStatus> {
-        self.paymaster.get_stake_status(address).await
+    async fn get_stake_status(
+        &self,
+        address: Address,
+        entity_type: Option<EntityType>,
+    ) -> MempoolResult<StakeStatus> {
+        let sim_settings = &self.config.sim_settings;
+        let requirement = match entity_type {
+            Some(kind) => sim_settings.stake_requirement(kind),
+            None => StakeRequirement {
+                min_stake_value: sim_settings.min_stake_value,
+                min_unstake_delay: sim_settings.min_unstake_delay,
+            },
+        };
+        self.paymaster.get_stake_status(address, requirement).await
     }
 
     async fn add_operation(
@@ -388,6 +495,10 @@ where
         // TODO(danc) aggregator reputation is not implemented
         // TODO(danc) catch ops with aggregators prior to simulation and reject
 
+        // Assign the arrival order on admission, before simulation, so that FIFO ordering
+        // follows the order operations were received in
+        let submission_id = self.state.read().pool.next_submission_id();
+
         // Check reputation of entities in involved in the operation
         // If throttled, entity can have THROTTLED_ENTITY_MEMPOOL_COUNT inflight operation at a time, else reject
         // If banned, reject
@@ -424,7 +535,24 @@ where
 
         // Check if op is already known or replacing another, and if so, ensure its fees are high enough
         // do this before simulation to save resources
-        let replacement = self.state.read().pool.check_replacement(&op)?;
+        let from_operator = matches!(origin, OperationOrigin::Operator);
+        let replacement = self
+            .state
+            .read()
+            .pool
+            .check_replacement(&op, from_operator)?;
+        // Replacements don't grow the pool, only apply backpressure to new operations
+        if replacement.is_none() {
+            let state = self.state.read();
+            state.pool.check_capacity(&op)?;
+
+            // Tighter limit on variants of the same operation
+            if let Some(max_ops) = self.config.max_ops_per_fingerprint {
+                if state.pool.fingerprint_count(&op) >= max_ops {
+                    return Err(MempoolError::MaxFingerprintOperationsReached(max_ops));
+                }
+            }
+        }
         // Check if op violates the STO-040 spec rule
         self.state.read().pool.check_multiple_roles_violation(&op)?;
 
@@ -466,6 +594,7 @@ where
             sim_block_number: sim_result.block_number.unwrap(), // simulation always returns a block number when called without a specified block_hash
             account_is_staked: sim_result.account_is_staked,
             entity_infos: sim_result.entity_infos,
+            no_replacement: from_operator,
         };
 
         // Check sender count in mempool. If sender has too many operations, must be staked
@@ -499,7 +628,9 @@ where
         // Add op to pool
         let hash = {
             let mut state = self.state.write();
-            let hash = state.pool.add_operation(pool_op.clone())?;
+            let hash = state
+                .pool
+                .add_operation_with_submission_id(pool_op.clone(), submission_id)?;
 
             if throttled {
                 state.throttled_ops.insert(hash);
@@ -627,13 +758,15 @@ where
             Err(anyhow::anyhow!("Invalid shard ID"))?;
         }
 
-        // get the best operations from the pool
-        let ordered_ops = self.state.read().pool.best_operations();
         // keep track of senders to avoid sending multiple ops from the same sender
         let mut senders = HashSet::<Address>::new();
 
-        Ok(ordered_ops
-            .into_iter()
+        // walk the best operations under the lock, only scanning the top candidates if limited
+        let state = self.state.read();
+        let ops = state
+            .pool
+            .best_operations()
+            .take(self.config.max_ops_scanned_per_bundle.unwrap_or(usize::MAX))
             .filter(|op| {
                 // short-circuit the mod if there is only 1 shard
                 ((self.config.num_shards == 1) ||
@@ -649,8 +782,8 @@ where
                 }
             })
             .take(max)
-            .map(Into::into)
-            .collect())
+            .collect();
+        Ok(ops)
     }
 
     fn all_operations(&self, max: usize) -> Vec<Arc<PoolOperation>> {
@@ -661,6 +794,10 @@ where
         self.state.read().pool.get_operation_by_hash(hash)
     }
 
+    fn get_user_operations_by_sender(&self, sender: Address) -> Vec<Arc<PoolOperation>> {
+        self.state.read().pool.get_operations_by_sender(sender)
+    }
+
     fn clear_state(&self, clear_mempool: bool, clear_paymaster: bool, clear_reputation: bool) {
         if clear_mempool {
             self.state.write().pool.clear();
@@ -711,6 +848,11 @@ impl UoPoolMetrics {
             .increment(num_ops as u64);
     }
 
+    fn increment_revalidation_dropped(num_ops: usize, entry_point: Address) {
+        metrics::counter!("op_pool_revalidation_dropped", "entry_point" => entry_point.to_string())
+            .increment(num_ops as u64);
+    }
+
     fn increment_removed_entities(entry_point: Address) {
         metrics::counter!("op_pool_removed_entities", "entry_point" => entry_point.to_string())
             .increment(1);
@@ -741,6 +883,7 @@ mod tests {
         SimulationResult, SimulationSettings, ViolationError,
     };
     use rundler_types::{
+        chain::ChainSpec,
         pool::{PrecheckViolation, SimulationViolation},
         v0_6::UserOperation,
         EntityInfo, EntityInfos, EntityType, EntryPointVersion, GasFees,
@@ -750,7 +893,7 @@ mod tests {
     use super::*;
     use crate::{
         chain::{BalanceUpdate, MinedOp},
-        mempool::{PaymasterConfig, ReputationParams},
+        mempool::{PaymasterConfig, PoolOrderingMode, ReputationParams},
     };
 
     const THROTTLE_SLACK: u64 = 5;
@@ -816,6 +959,25 @@ mod tests {
         assert_eq!(pool.best_operations(3, 0).unwrap(), vec![]);
     }
 
+    #[tokio::test]
+    async fn clear_resets_dump_and_reputation() {
+        let op = create_op(Address::random(), 0, 1, None);
+        let uo = op.op.clone();
+        let pool = create_pool(vec![op]);
+
+        let _ = pool
+            .add_operation(OperationOrigin::Local, uo.clone())
+            .await
+            .unwrap();
+        pool.set_reputation(uo.sender(), 100, 10);
+        assert_eq!(pool.all_operations(16).len(), 1);
+        assert!(!pool.dump_reputation().is_empty());
+
+        pool.clear_state(true, true, true);
+        assert!(pool.all_operations(16).is_empty());
+        assert!(pool.dump_reputation().is_empty());
+    }
+
     #[tokio::test]
     async fn chain_update_mine() {
         let paymaster = Address::random();
@@ -878,6 +1040,117 @@ mod tests {
         assert_eq!(paymaster_balance.confirmed_balance, 1110.into());
     }
 
+    #[tokio::test]
+    async fn chain_update_deposit_events() {
+        let paymaster = Address::random();
+
+        let mut entrypoint = MockEntryPointV0_6::new();
+        // initial balance
+        entrypoint
+            .expect_balance_of()
+            .returning(|_, _| Ok(U256::from(1000)));
+        // the reload fails, so the balances are those applied from the events
+        entrypoint
+            .expect_get_balances()
+            .returning(|_| Err(anyhow::anyhow!("reload failed")));
+
+        let (pool, _) = create_pool_with_entrypoint_insert_ops(
+            vec![create_op(Address::random(), 0, 1, Some(paymaster))],
+            entrypoint,
+        )
+        .await;
+
+        let balance_update = |amount: u64, is_addition: bool| BalanceUpdate {
+            address: paymaster,
+            amount: amount.into(),
+            entrypoint: pool.config.entry_point,
+            is_addition,
+        };
+        let chain_update = |entity_balance_updates| ChainUpdate {
+            latest_block_number: 1,
+            latest_block_hash: H256::random(),
+            latest_block_timestamp: 0.into(),
+            earliest_remembered_block_number: 0,
+            reorg_depth: 0,
+            mined_ops: vec![],
+            unmined_ops: vec![],
+            entity_balance_updates,
+            unmined_entity_balance_updates: vec![],
+            reorg_larger_than_history: false,
+        };
+
+        // Deposited carries the new total deposit
+        pool.on_chain_update(&chain_update(vec![balance_update(1500, true)]))
+            .await;
+        let metadata = pool.paymaster.paymaster_balance(paymaster).await.unwrap();
+        assert_eq!(metadata.confirmed_balance, 1500.into());
+
+        // Withdrawn carries the amount withdrawn
+        pool.on_chain_update(&chain_update(vec![balance_update(200, false)]))
+            .await;
+        let metadata = pool.paymaster.paymaster_balance(paymaster).await.unwrap();
+        assert_eq!(metadata.confirmed_balance, 1300.into());
+
+        // events for other entry points are ignored
+        let mut other = balance_update(5000, true);
+        other.entrypoint = Address::random();
+        pool.on_chain_update(&chain_update(vec![other])).await;
+        let metadata = pool.paymaster.paymaster_balance(paymaster).await.unwrap();
+        assert_eq!(metadata.confirmed_balance, 1300.into());
+    }
+
+    #[tokio::test]
+    async fn chain_update_deposit_event_reloads_balance() {
+        let paymaster = Address::random();
+
+        let mut entrypoint = MockEntryPointV0_6::new();
+        // initial balance
+        entrypoint
+            .expect_balance_of()
+            .returning(|_, _| Ok(U256::from(1000)));
+        // the op was mined before the deposit, so the deposit total already includes its cost
+        entrypoint
+            .expect_get_balances()
+            .withf(move |addresses| addresses == &vec![paymaster])
+            .times(1)
+            .returning(|_| Ok(vec![1500.into()]));
+
+        let (pool, uos) = create_pool_with_entrypoint_insert_ops(
+            vec![create_op(Address::random(), 0, 1, Some(paymaster))],
+            entrypoint,
+        )
+        .await;
+
+        pool.on_chain_update(&ChainUpdate {
+            latest_block_number: 1,
+            latest_block_hash: H256::random(),
+            latest_block_timestamp: 0.into(),
+            earliest_remembered_block_number: 0,
+            reorg_depth: 0,
+            mined_ops: vec![MinedOp {
+                entry_point: pool.config.entry_point,
+                hash: uos[0].hash(pool.config.entry_point, 1),
+                sender: uos[0].sender(),
+                nonce: uos[0].nonce(),
+                actual_gas_cost: 100.into(),
+                paymaster: Some(paymaster),
+            }],
+            unmined_ops: vec![],
+            entity_balance_updates: vec![BalanceUpdate {
+                address: paymaster,
+                amount: 1500.into(),
+                entrypoint: pool.config.entry_point,
+                is_addition: true,
+            }],
+            unmined_entity_balance_updates: vec![],
+            reorg_larger_than_history: false,
+        })
+        .await;
+
+        let metadata = pool.paymaster.paymaster_balance(paymaster).await.unwrap();
+        assert_eq!(metadata.confirmed_balance, 1500.into());
+    }
+
     #[tokio::test]
     async fn chain_update_mine_unmine() {
         let paymaster = Address::random();
@@ -1298,6 +1571,31 @@ mod tests {
         check_ops(pool.best_operations(1, 0).unwrap(), vec![op.op]);
     }
 
+    #[tokio::test]
+    async fn test_operator_op_replacement_not_allowed() {
+        let op = create_op(Address::random(), 0, 100, None);
+        let pool = create_pool(vec![op.clone()]);
+
+        let _ = pool
+            .add_operation(OperationOrigin::Operator, op.op.clone())
+            .await
+            .unwrap();
+
+        let mut replacement = op.op.clone();
+        let r: &mut UserOperation = replacement.as_mut();
+        r.max_fee_per_gas = r.max_fee_per_gas * 2;
+        r.max_priority_fee_per_gas = r.max_priority_fee_per_gas * 2;
+
+        let err = pool
+            .add_operation(OperationOrigin::Local, replacement)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, MempoolError::ReplacementNotAllowed));
+
+        check_ops(pool.best_operations(1, 0).unwrap(), vec![op.op]);
+    }
+
     #[tokio::test]
     async fn test_stake_status_not_staked() {
         let mut entrypoint = MockEntryPointV0_6::new();
@@ -1315,11 +1613,52 @@ mod tests {
         pool.config.sim_settings.min_stake_value = 10001;
         pool.config.sim_settings.min_unstake_delay = 101;
 
-        let status = pool.get_stake_status(Address::random()).await.unwrap();
+        let status = pool
+            .get_stake_status(Address::random(), None)
+            .await
+            .unwrap();
 
         assert!(!status.is_staked);
     }
 
+    #[tokio::test]
+    async fn test_stake_status_per_entity_type() {
+        let mut entrypoint = MockEntryPointV0_6::new();
+        entrypoint.expect_get_deposit_info().returning(|_| {
+            Ok(DepositInfo {
+                deposit: 1000.into(),
+                staked: true,
+                stake: 10000,
+                unstake_delay_sec: 100,
+                withdraw_time: 10,
+            })
+        });
+        let mut pool = create_pool_with_entry_point(vec![], entrypoint);
+
+        pool.config.sim_settings.min_stake_value = 10001;
+        pool.config.sim_settings.min_unstake_delay = 101;
+        pool.config.sim_settings.entity_stake_requirements.insert(
+            EntityType::Factory,
+            StakeRequirement {
+                min_stake_value: 10000,
+                min_unstake_delay: 100,
+            },
+        );
+
+        let address = Address::random();
+        let factory = pool
+            .get_stake_status(address, Some(EntityType::Factory))
+            .await
+            .unwrap();
+        let paymaster = pool
+            .get_stake_status(address, Some(EntityType::Paymaster))
+            .await
+            .unwrap();
+
+        assert!(factory.is_staked);
+        assert!(!paymaster.is_staked);
+    }
+
     #[tokio::test]
     async fn test_replacement() {
         let paymaster = Address::random();
@@ -1506,6 +1845,131 @@ mod tests {
         check_ops(pool.best_operations(3, 0).unwrap(), uos);
     }
 
+    #[tokio::test]
+    async fn test_best_max_ops_scanned() {
+        let ops = (1..=4)
+            .map(|fee| create_op(Address::random(), 0, fee, None))
+            .collect::<Vec<_>>();
+        let mut config = create_pool_config();
+        config.max_ops_scanned_per_bundle = Some(2);
+        let pool = create_pool_with_config(ops.clone(), MockEntryPointV0_6::new(), config);
+        for op in &ops {
+            pool.add_operation(OperationOrigin::Local, op.op.clone())
+                .await
+                .unwrap();
+        }
+
+        // only the 2 highest fee ops are considered, even though more are requested
+        check_ops(
+            pool.best_operations(4, 0).unwrap(),
+            vec![ops[3].op.clone(), ops[2].op.clone()],
+        );
+    }
+
+    #[tokio::test]
+    async fn test_max_ops_per_fingerprint() {
+        let sender = Address::random();
+        let same = (0..4)
+            .map(|nonce| create_op(sender, nonce, 1, None))
+            .collect::<Vec<_>>();
+        let diverse = (0..4)
+            .map(|nonce| {
+                let mut op = create_op(sender, nonce, 1, None);
+                op.op = UserOperation {
+                    sender,
+                    nonce: nonce.into(),
+                    max_fee_per_gas: 1.into(),
+                    call_data: vec![nonce as u8].into(),
+                    ..UserOperation::default()
+                }
+                .into();
+                op
+            })
+            .collect::<Vec<_>>();
+
+        let mut config = create_pool_config();
+        config.max_ops_per_fingerprint = Some(2);
+
+        // ops sharing a fingerprint are throttled before the sender limit
+        let pool = create_pool_with_config(same.clone(), MockEntryPointV0_6::new(), config.clone());
+        for op in &same[..2] {
+            pool.add_operation(OperationOrigin::Local, op.op.clone())
+                .await
+                .unwrap();
+        }
+        match pool
+            .add_operation(OperationOrigin::Local, same[2].op.clone())
+            .await
+        {
+            Err(MempoolError::MaxFingerprintOperationsReached(2)) => {}
+            _ => panic!("Expected MaxFingerprintOperationsReached error"),
+        }
+
+        // diverse ops are only subject to the sender limit
+        let pool = create_pool_with_config(diverse.clone(), MockEntryPointV0_6::new(), config);
+        for op in &diverse {
+            pool.add_operation(OperationOrigin::Local, op.op.clone())
+                .await
+                .unwrap();
+        }
+        assert_eq!(pool.best_operations(4, 0).unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_revalidation_drops_invalid_op() {
+        let op = create_op(Address::random(), 0, 1, None);
+        let sender = op.op.sender();
+
+        let mut prechecker = MockPrechecker::new();
+        prechecker.expect_check().returning(|_| Ok(()));
+
+        // valid when added, invalid on re-validation
+        let mut seq = Sequence::new();
+        let mut simulator = MockSimulator::new();
+        simulator
+            .expect_simulate_validation()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(move |_, _, _| {
+                Ok(SimulationResult {
+                    block_number: Some(0),
+                    entity_infos: EntityInfos {
+                        sender: EntityInfo {
+                            entity: Entity::account(sender),
+                            is_staked: false,
+                        },
+                        ..EntityInfos::default()
+                    },
+                    ..SimulationResult::default()
+                })
+            });
+        simulator
+            .expect_simulate_validation()
+            .once()
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| {
+                Err(SimulationError {
+                    violation_error: ViolationError::Violations(vec![
+                        SimulationViolation::InvalidSignature,
+                    ]),
+                    entity_infos: None,
+                })
+            });
+
+        let mut config = create_pool_config();
+        config.revalidation_interval_blocks = Some(1);
+        let pool = create_pool_with_mocks(config, MockEntryPointV0_6::new(), prechecker, simulator);
+
+        pool.add_operation(OperationOrigin::Local, op.op.clone())
+            .await
+            .unwrap();
+        check_ops(pool.best_operations(1, 0).unwrap(), vec![op.op]);
+
+        pool.revalidate_operations(1).await;
+
+        assert!(pool.best_operations(1, 0).unwrap().is_empty());
+    }
+
     #[derive(Clone, Debug)]
     struct OpWithErrors {
         op: UserOperationVariant,
@@ -1536,12 +2000,67 @@ mod tests {
         impl Simulator<UO = UserOperation>,
         impl EntryPoint,
     > {
-        let args = PoolConfig {
+        create_pool_with_config(ops, entrypoint, create_pool_config())
+    }
+
+    fn create_pool_with_config(
+        ops: Vec<OpWithErrors>,
+        entrypoint: MockEntryPointV0_6,
+        config: PoolConfig,
+    ) -> UoPool<
+        UserOperation,
+        impl Prechecker<UO = UserOperation>,
+        impl Simulator<UO = UserOperation>,
+        impl EntryPoint,
+    > {
+        let mut simulator = MockSimulator::new();
+        let mut prechecker = MockPrechecker::new();
+
+        for op in ops {
+            prechecker.expect_check().returning(move |_| {
+                if let Some(error) = &op.precheck_error {
+                    Err(PrecheckError::Violations(vec![error.clone()]))
+                } else {
+                    Ok(())
+                }
+            });
+            simulator
+                .expect_simulate_validation()
+                .returning(move |_, _, _| {
+                    if let Some(error) = &op.simulation_error {
+                        Err(SimulationError {
+                            violation_error: ViolationError::Violations(vec![error.clone()]),
+                            entity_infos: None,
+                        })
+                    } else {
+                        Ok(SimulationResult {
+                            account_is_staked: op.staked,
+                            block_number: Some(0),
+                            valid_time_range: op.valid_time_range,
+                            entity_infos: EntityInfos {
+                                sender: EntityInfo {
+                                    entity: Entity::account(op.op.sender()),
+                                    is_staked: false,
+                                },
+                                ..EntityInfos::default()
+                            },
+                            ..SimulationResult::default()
+                        })
+                    }
+                });
+        }
+
+        create_pool_with_mocks(config, entrypoint, prechecker, simulator)
+    }
+
+    fn create_pool_config() -> PoolConfig {
+        PoolConfig {
             entry_point: Address::random(),
             entry_point_version: EntryPointVersion::V0_6,
             chain_id: 1,
             min_replacement_fee_increase_percentage: 10,
             max_size_of_pool_bytes: 10000,
+            size_headroom_percent: 5,
             blocklist: None,
             allowlist: None,
             precheck_settings: PrecheckSettings::default(),
@@ -1554,20 +2073,31 @@ mod tests {
             paymaster_tracking_enabled: true,
             paymaster_cache_length: 100,
             reputation_tracking_enabled: true,
+            reputation_decay_interval_secs: 3600,
+            reputation_decay_factor: 24,
+            reputation_min_inclusion_rate_denominator: 10,
+            reputation_throttling_slack: 10,
+            reputation_ban_slack: 50,
+            reputation_persist_path: None,
             drop_min_num_blocks: 10,
-        };
-
-        let mut simulator = MockSimulator::new();
-        let mut prechecker = MockPrechecker::new();
+            revalidation_interval_blocks: None,
+            revalidation_max_ops: 100,
+            ordering_mode: PoolOrderingMode::default(),
+            max_ops_scanned_per_bundle: None,
+            max_ops_per_fingerprint: None,
+        }
+    }
 
+    fn create_pool_with_mocks(
+        args: PoolConfig,
+        entrypoint: MockEntryPointV0_6,
+        mut prechecker: MockPrechecker,
+        simulator: MockSimulator,
+    ) -> UoPool<UserOperation, MockPrechecker, MockSimulator, MockEntryPointV0_6> {
         let paymaster = PaymasterTracker::new(
+            ChainSpec::default(),
             entrypoint,
-            PaymasterConfig::new(
-                args.sim_settings.min_stake_value,
-                args.sim_settings.min_unstake_delay,
-                args.paymaster_tracking_enabled,
-                args.paymaster_cache_length,
-            ),
+            PaymasterConfig::new(args.paymaster_tracking_enabled, args.paymaster_cache_length),
         );
 
         let reputation = Arc::new(AddressReputation::new(
@@ -1586,40 +2116,6 @@ mod tests {
             ))
         });
 
-        for op in ops {
-            prechecker.expect_check().returning(move |_| {
-                if let Some(error) = &op.precheck_error {
-                    Err(PrecheckError::Violations(vec![error.clone()]))
-                } else {
-                    Ok(())
-                }
-            });
-            simulator
-                .expect_simulate_validation()
-                .returning(move |_, _, _| {
-                    if let Some(error) = &op.simulation_error {
-                        Err(SimulationError {
-                            violation_error: ViolationError::Violations(vec![error.clone()]),
-                            entity_infos: None,
-                        })
-                    } else {
-                        Ok(SimulationResult {
-                            account_is_staked: op.staked,
-                            block_number: Some(0),
-                            valid_time_range: op.valid_time_range,
-                            entity_infos: EntityInfos {
-                                sender: EntityInfo {
-                                    entity: Entity::account(op.op.sender()),
-                                    is_staked: false,
-                                },
-                                ..EntityInfos::default()
-                            },
-                            ..SimulationResult::default()
-                        })
-                    }
-                });
-        }
-
         let (event_sender, _) = broadcast::channel(4);
 
         UoPool::new(
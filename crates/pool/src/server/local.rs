@@ -24,7 +24,7 @@ use rundler_types::{
         MempoolError, NewHead, PaymasterMetadata, Pool, PoolError, PoolOperation, PoolResult,
         Reputation, ReputationStatus, StakeStatus,
     },
-    EntityUpdate, EntryPointVersion, UserOperationId, UserOperationVariant,
+    EntityType, EntityUpdate, EntryPointVersion, UserOperationId, UserOperationVariant,
 };
 use tokio::{
     sync::{broadcast, mpsc, oneshot},
@@ -168,6 +168,15 @@ impl Pool for LocalPoolHandle {
         }
     }
 
+    async fn get_ops_by_sender(&self, sender: Address) -> PoolResult<Vec<PoolOperation>> {
+        let req = ServerRequestKind::GetOpsBySender { sender };
+        let resp = self.send(req).await?;
+        match resp {
+            ServerResponse::GetOpsBySender { ops } => Ok(ops),
+            _ => Err(PoolError::UnexpectedResponse),
+        }
+    }
+
     async fn remove_ops(&self, entry_point: Address, ops: Vec<H256>) -> PoolResult<()> {
         let req = ServerRequestKind::RemoveOps { entry_point, ops };
         let resp = self.send(req).await?;
@@ -242,6 +251,23 @@ impl Pool for LocalPoolHandle {
         }
     }
 
+    async fn admin_add_operator_op(
+        &self,
+        entry_point: Address,
+        op: UserOperationVariant,
+    ) -> PoolResult<H256> {
+        let req = ServerRequestKind::AddOp {
+            entry_point,
+            op,
+            origin: OperationOrigin::Operator,
+        };
+        let resp = self.send(req).await?;
+        match resp {
+            ServerResponse::AddOp { hash } => Ok(hash),
+            _ => Err(PoolError::UnexpectedResponse),
+        }
+    }
+
     async fn debug_dump_mempool(&self, entry_point: Address) -> PoolResult<Vec<PoolOperation>> {
         let req = ServerRequestKind::DebugDumpMempool { entry_point };
         let resp = self.send(req).await?;
@@ -292,10 +318,12 @@ impl Pool for LocalPoolHandle {
         &self,
         entry_point: Address,
         address: Address,
+        entity_type: Option<EntityType>,
     ) -> PoolResult<StakeStatus> {
         let req = ServerRequestKind::GetStakeStatus {
             entry_point,
             address,
+            entity_type,
         };
         let resp = self.send(req).await?;
         match resp {
@@ -402,6 +430,18 @@ impl LocalPoolServerRunner {
         Ok(None)
     }
 
+    fn get_ops_by_sender(&self, sender: Address) -> PoolResult<Vec<PoolOperation>> {
+        let mut ops = self
+            .mempools
+            .values()
+            .flat_map(|mempool| mempool.get_user_operations_by_sender(sender))
+            .map(|op| (*op).clone())
+            .collect::<Vec<_>>();
+        // the mempools map is unordered, sort so the result is stable across entry points
+        ops.sort_by_key(|op| (op.uo.nonce(), op.entry_point));
+        Ok(ops)
+    }
+
     fn remove_ops(&self, entry_point: Address, ops: &[H256]) -> PoolResult<()> {
         let mempool = self.get_pool(entry_point)?;
         mempool.remove_operations(ops);
@@ -537,12 +577,19 @@ impl LocalPoolServerRunner {
                             let cu = Arc::clone(&chain_update);
                             async move { m.on_chain_update(&cu).await }
                         }).collect();
+                        let mempools: Vec<_> = self.mempools.values().cloned().collect();
                         tokio::spawn(async move {
                             future::join_all(update_futures).await;
                             let _ = block_sender.send(NewHead {
                                 block_hash: chain_update.latest_block_hash,
                                 block_number: chain_update.latest_block_number,
                             });
+                            // Re-validation can run many simulations, so it runs after listeners
+                            // are notified and isn't awaited by the next chain update.
+                            future::join_all(mempools.iter().map(|m| {
+                                m.revalidate_operations(chain_update.latest_block_number)
+                            }))
+                            .await;
                         });
                     }
                 }
@@ -556,12 +603,12 @@ impl LocalPoolServerRunner {
                                     match mempool.entry_point_version() {
                                         EntryPointVersion::V0_6 => {
                                             if !matches!(&op, UserOperationVariant::V0_6(_)){
-                                                 break 'resp Err(anyhow::anyhow!("Invalid user operation version for mempool v0.6 {:?}", op.uo_type()).into());
+                                                 break 'resp Err(anyhow::anyhow!("Invalid user operation version for mempool v0.6 {:?}", op.version()).into());
                                             }
                                         }
                                         EntryPointVersion::V0_7 => {
                                             if !matches!(&op, UserOperationVariant::V0_7(_)){
-                                                break 'resp Err(anyhow::anyhow!("Invalid user operation version for mempool v0.7 {:?}", op.uo_type()).into());
+                                                break 'resp Err(anyhow::anyhow!("Invalid user operation version for mempool v0.7 {:?}", op.version()).into());
                                             }
                                         }
                                         EntryPointVersion::Unspecified => {
@@ -583,9 +630,9 @@ impl LocalPoolServerRunner {
                             self.get_pool_and_spawn(entry_point, req.response, fut);
                             continue;
                         },
-                        ServerRequestKind::GetStakeStatus { entry_point, address }=> {
+                        ServerRequestKind::GetStakeStatus { entry_point, address, entity_type }=> {
                             let fut = |mempool: Arc<dyn Mempool>, response: oneshot::Sender<Result<ServerResponse, PoolError>>| async move {
-                                let resp = match mempool.get_stake_status(address).await {
+                                let resp = match mempool.get_stake_status(address, entity_type).await {
                                     Ok(status) => Ok(ServerResponse::GetStakeStatus { status }),
                                     Err(e) => Err(e.into()),
                                 };
@@ -616,6 +663,12 @@ impl LocalPoolServerRunner {
                                 Err(e) => Err(e),
                             }
                         }
+                        ServerRequestKind::GetOpsBySender { sender } => {
+                            match self.get_ops_by_sender(sender) {
+                                Ok(ops) => Ok(ServerResponse::GetOpsBySender { ops }),
+                                Err(e) => Err(e),
+                            }
+                        }
                         ServerRequestKind::RemoveOps { entry_point, ops } => {
                             match self.remove_ops(entry_point, &ops) {
                                 Ok(_) => Ok(ServerResponse::RemoveOps),
@@ -713,6 +766,9 @@ enum ServerRequestKind {
     GetOpByHash {
         hash: H256,
     },
+    GetOpsBySender {
+        sender: Address,
+    },
     RemoveOps {
         entry_point: Address,
         ops: Vec<H256>,
@@ -755,6 +811,7 @@ enum ServerRequestKind {
     GetStakeStatus {
         entry_point: Address,
         address: Address,
+        entity_type: Option<EntityType>,
     },
     SubscribeNewHeads,
 }
@@ -773,6 +830,9 @@ enum ServerResponse {
     GetOpByHash {
         op: Option<PoolOperation>,
     },
+    GetOpsBySender {
+        ops: Vec<PoolOperation>,
+    },
     RemoveOps,
     RemoveOpById {
         hash: Option<H256>,
@@ -834,6 +894,7 @@ mod tests {
     async fn test_chain_update() {
         let mut mock_pool = MockMempool::new();
         mock_pool.expect_on_chain_update().returning(|_| ());
+        mock_pool.expect_revalidate_operations().returning(|_| ());
 
         let ep = Address::random();
         let pool: Arc<dyn Mempool> = Arc::new(mock_pool);
@@ -918,6 +979,53 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_get_ops_by_sender_sorted() {
+        let sender = Address::random();
+        let mut eps = [Address::random(), Address::random()];
+        eps.sort();
+
+        // the entry points hold interleaved nonces, each in reverse order, and both hold nonce 6
+        let mut pools = [MockMempool::new(), MockMempool::new()];
+        let nonces = [[6, 4, 2, 0], [6, 5, 3, 1]];
+        for ((ep, pool), nonces) in zip(eps.iter(), pools.iter_mut()).zip(nonces) {
+            let ops = nonces
+                .into_iter()
+                .map(|nonce| Arc::new(pool_op(sender, nonce, *ep)))
+                .collect::<Vec<_>>();
+            pool.expect_get_user_operations_by_sender()
+                .returning(move |_| ops.clone());
+        }
+
+        let state = setup(
+            zip(eps.iter(), pools.into_iter())
+                .map(|(ep, pool)| {
+                    let pool: Arc<dyn Mempool> = Arc::new(pool);
+                    (*ep, pool)
+                })
+                .collect(),
+        );
+
+        let ops = state.handle.get_ops_by_sender(sender).await.unwrap();
+        let order = ops
+            .iter()
+            .map(|op| (op.uo.nonce().as_u64(), op.entry_point))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![
+                (0, eps[0]),
+                (1, eps[1]),
+                (2, eps[0]),
+                (3, eps[1]),
+                (4, eps[0]),
+                (5, eps[1]),
+                (6, eps[0]),
+                (6, eps[1]),
+            ]
+        );
+    }
+
     struct State {
         handle: LocalPoolHandle,
         chain_update_tx: broadcast::Sender<Arc<ChainUpdate>>,
@@ -939,4 +1047,24 @@ mod tests {
     fn mock_op() -> UserOperationVariant {
         UserOperationVariant::V0_6(UserOperation::default())
     }
+
+    fn pool_op(sender: Address, nonce: u64, entry_point: Address) -> PoolOperation {
+        PoolOperation {
+            uo: UserOperation {
+                sender,
+                nonce: nonce.into(),
+                ..Default::default()
+            }
+            .into(),
+            entry_point,
+            aggregator: None,
+            valid_time_range: Default::default(),
+            expected_code_hash: None,
+            sim_block_hash: H256::zero(),
+            sim_block_number: 0,
+            account_is_staked: false,
+            entity_infos: Default::default(),
+            no_replacement: false,
+        }
+    }
 }
@@ -26,7 +26,7 @@ use rundler_types::{
         NewHead, PaymasterMetadata, Pool, PoolError, PoolOperation, PoolResult, Reputation,
         ReputationStatus, StakeStatus,
     },
-    EntityUpdate, UserOperationId, UserOperationVariant,
+    EntityType, EntityUpdate, UserOperationId, UserOperationVariant,
 };
 use rundler_utils::retry::{self, UnlimitedRetryOpts};
 use tokio::sync::mpsc;
@@ -44,13 +44,14 @@ use super::protos::{
     self, add_op_response, admin_set_tracking_response, debug_clear_state_response,
     debug_dump_mempool_response, debug_dump_paymaster_balances_response,
     debug_dump_reputation_response, debug_set_reputation_response, get_op_by_hash_response,
-    get_ops_response, get_reputation_status_response, get_stake_status_response,
-    op_pool_client::OpPoolClient, remove_op_by_id_response, remove_ops_response,
-    update_entities_response, AddOpRequest, AdminSetTrackingRequest, DebugClearStateRequest,
-    DebugDumpMempoolRequest, DebugDumpPaymasterBalancesRequest, DebugDumpReputationRequest,
-    DebugSetReputationRequest, GetOpsRequest, GetReputationStatusRequest, GetStakeStatusRequest,
-    RemoveOpsRequest, ReputationStatus as ProtoReputationStatus, SubscribeNewHeadsRequest,
-    SubscribeNewHeadsResponse, TryUoFromProto, UpdateEntitiesRequest,
+    get_ops_by_sender_response, get_ops_response, get_reputation_status_response,
+    get_stake_status_response, op_pool_client::OpPoolClient, remove_op_by_id_response,
+    remove_ops_response, update_entities_response, AddOpRequest, AdminSetTrackingRequest,
+    DebugClearStateRequest, DebugDumpMempoolRequest, DebugDumpPaymasterBalancesRequest,
+    DebugDumpReputationRequest, DebugSetReputationRequest, GetOpsRequest,
+    GetReputationStatusRequest, GetStakeStatusRequest, RemoveOpsRequest,
+    ReputationStatus as ProtoReputationStatus, SubscribeNewHeadsRequest, SubscribeNewHeadsResponse,
+    TryUoFromProto, UpdateEntitiesRequest,
 };
 
 /// Remote pool client
@@ -152,6 +153,7 @@ impl Pool for RemotePoolClient {
             .add_op(AddOpRequest {
                 entry_point: entry_point.as_bytes().to_vec(),
                 op: Some(protos::UserOperation::from(&op)),
+                operator: false,
             })
             .await
             .map_err(anyhow::Error::from)?
@@ -235,6 +237,35 @@ impl Pool for RemotePoolClient {
         }
     }
 
+    async fn get_ops_by_sender(&self, sender: Address) -> PoolResult<Vec<PoolOperation>> {
+        let res = self
+            .op_pool_client
+            .clone()
+            .get_ops_by_sender(protos::GetOpsBySenderRequest {
+                sender: sender.as_bytes().to_vec(),
+            })
+            .await
+            .map_err(anyhow::Error::from)?
+            .into_inner()
+            .result;
+
+        match res {
+            Some(get_ops_by_sender_response::Result::Success(s)) => s
+                .ops
+                .into_iter()
+                .map(|proto_uo| {
+                    PoolOperation::try_uo_from_proto(proto_uo, &self.chain_spec)
+                        .context("should convert proto uo to pool operation")
+                })
+                .map(|res| res.map_err(PoolError::from))
+                .collect(),
+            Some(get_ops_by_sender_response::Result::Failure(f)) => Err(f.try_into()?),
+            None => Err(PoolError::Other(anyhow::anyhow!(
+                "should have received result from op pool"
+            )))?,
+        }
+    }
+
     async fn remove_ops(&self, entry_point: Address, ops: Vec<H256>) -> PoolResult<()> {
         let res = self
             .op_pool_client
@@ -375,6 +406,33 @@ impl Pool for RemotePoolClient {
         }
     }
 
+    async fn admin_add_operator_op(
+        &self,
+        entry_point: Address,
+        op: UserOperationVariant,
+    ) -> PoolResult<H256> {
+        let res = self
+            .op_pool_client
+            .clone()
+            .add_op(AddOpRequest {
+                entry_point: entry_point.as_bytes().to_vec(),
+                op: Some(protos::UserOperation::from(&op)),
+                operator: true,
+            })
+            .await
+            .map_err(anyhow::Error::from)?
+            .into_inner()
+            .result;
+
+        match res {
+            Some(add_op_response::Result::Success(s)) => Ok(H256::from_slice(&s.hash)),
+            Some(add_op_response::Result::Failure(f)) => Err(f.try_into()?),
+            None => Err(PoolError::Other(anyhow::anyhow!(
+                "should have received result from op pool"
+            )))?,
+        }
+    }
+
     async fn debug_dump_mempool(&self, entry_point: Address) -> PoolResult<Vec<PoolOperation>> {
         let res = self
             .op_pool_client
@@ -523,6 +581,7 @@ impl Pool for RemotePoolClient {
         &self,
         entry_point: Address,
         address: Address,
+        entity_type: Option<EntityType>,
     ) -> PoolResult<StakeStatus> {
         let res = self
             .op_pool_client
@@ -530,6 +589,7 @@ impl Pool for RemotePoolClient {
             .get_stake_status(GetStakeStatusRequest {
                 entry_point: entry_point.as_bytes().to_vec(),
                 address: address.as_bytes().to_vec(),
+                entity_type: protos::EntityType::from(entity_type).into(),
             })
             .await
             .map_err(anyhow::Error::from)?
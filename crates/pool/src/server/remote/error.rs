@@ -25,20 +25,23 @@ use super::protos::{
     AccessedUndeployedContract, AccessedUnsupportedContractType, AggregatorValidationFailed,
     AssociatedStorageDuringDeploy, AssociatedStorageIsAlternateSender, CallGasLimitTooLow,
     CallHadValue, CalledBannedEntryPointMethod, CodeHashChanged, DidNotRevert,
-    DiscardedOnInsertError, Entity, EntityThrottledError, EntityType, EntryPointRevert,
-    ExistingSenderWithInitCode, FactoryCalledCreate2Twice, FactoryIsNotContract,
+    DuplicateEntityAddress, Entity, EntityThrottledError, EntityType, EntryPointRevert,
+    ExistingSenderWithInitCode, FactoryCalledCreate2Twice, FactoryIsNotContract, InitCodeTooLarge,
     InvalidAccountSignature, InvalidPaymasterSignature, InvalidSignature, InvalidStorageAccess,
-    InvalidTimeRange, MaxFeePerGasTooLow, MaxOperationsReachedError, MaxPriorityFeePerGasTooLow,
-    MempoolError as ProtoMempoolError, MultipleRolesViolation, NotStaked,
-    OperationAlreadyKnownError, OperationDropTooSoon, OperationRevert, OutOfGas,
-    PaymasterBalanceTooLow, PaymasterDepositTooLow, PaymasterIsNotContract,
-    PreVerificationGasTooLow, PrecheckViolationError as ProtoPrecheckViolationError,
+    InvalidTimeRange, MaxFeePerGasTooLow, MaxFingerprintOperationsReachedError,
+    MaxOperationsReachedError, MaxPriorityFeePerGasTooLow, MempoolError as ProtoMempoolError,
+    MultipleRolesViolation, NonceTooHigh, NonceTooLow, NotStaked, OperationAlreadyKnownError,
+    OperationDropTooSoon, OperationRevert, OutOfGas, PaymasterBalanceTooLow,
+    PaymasterDepositTooLow, PaymasterIsNotContract, PoolBusyError, PoolFullError,
+    PreVerificationGasTooHigh, PreVerificationGasTooLow,
+    PrecheckViolationError as ProtoPrecheckViolationError, ReplacementNotAllowedError,
     ReplacementUnderpricedError, SenderAddressUsedAsAlternateEntity, SenderFundsTooLow,
     SenderIsNotContractAndNoInitCode, SimulationViolationError as ProtoSimulationViolationError,
-    TotalGasLimitTooHigh, UnintendedRevert, UnintendedRevertWithMessage, UnknownEntryPointError,
-    UnknownRevert, UnstakedAggregator, UnstakedPaymasterContext, UnsupportedAggregatorError,
-    UsedForbiddenOpcode, UsedForbiddenPrecompile, ValidationRevert as ProtoValidationRevert,
-    VerificationGasLimitBufferTooLow, VerificationGasLimitTooHigh, WrongNumberOfPhases,
+    TotalGasLimitTooHigh, TraceTooLarge, UnintendedRevert, UnintendedRevertWithMessage,
+    UnknownEntryPointError, UnknownRevert, UnstakedAggregator, UnstakedPaymasterContext,
+    UnsupportedAggregatorError, UsedForbiddenOpcode, UsedForbiddenPrecompile,
+    ValidationRevert as ProtoValidationRevert, VerificationGasLimitBufferTooLow,
+    VerificationGasLimitTooHigh, VerificationGasToCallGasRatioTooHigh, WrongNumberOfPhases,
 };
 
 impl TryFrom<ProtoMempoolError> for PoolError {
@@ -89,7 +92,7 @@ impl TryFrom<ProtoMempoolError> for MempoolError {
             Some(mempool_error::Error::EntityThrottled(e)) => MempoolError::EntityThrottled(
                 (&e.entity.context("should have entity in error")?).try_into()?,
             ),
-            Some(mempool_error::Error::DiscardedOnInsert(_)) => MempoolError::DiscardedOnInsert,
+            Some(mempool_error::Error::PoolFull(_)) => MempoolError::PoolFull,
             Some(mempool_error::Error::PrecheckViolation(e)) => {
                 MempoolError::PrecheckViolation(e.try_into()?)
             }
@@ -125,6 +128,13 @@ impl TryFrom<ProtoMempoolError> for MempoolError {
             Some(mempool_error::Error::OperationDropTooSoon(e)) => {
                 MempoolError::OperationDropTooSoon(e.added_at, e.attempted_at, e.must_wait)
             }
+            Some(mempool_error::Error::PoolBusy(_)) => MempoolError::PoolBusy,
+            Some(mempool_error::Error::ReplacementNotAllowed(_)) => {
+                MempoolError::ReplacementNotAllowed
+            }
+            Some(mempool_error::Error::MaxFingerprintOperationsReached(e)) => {
+                MempoolError::MaxFingerprintOperationsReached(e.num_ops as usize)
+            }
             None => bail!("unknown proto mempool error"),
         })
     }
@@ -183,9 +193,22 @@ impl From<MempoolError> for ProtoMempoolError {
                     },
                 )),
             },
-            MempoolError::DiscardedOnInsert => ProtoMempoolError {
-                error: Some(mempool_error::Error::DiscardedOnInsert(
-                    DiscardedOnInsertError {},
+            MempoolError::PoolFull => ProtoMempoolError {
+                error: Some(mempool_error::Error::PoolFull(PoolFullError {})),
+            },
+            MempoolError::PoolBusy => ProtoMempoolError {
+                error: Some(mempool_error::Error::PoolBusy(PoolBusyError {})),
+            },
+            MempoolError::ReplacementNotAllowed => ProtoMempoolError {
+                error: Some(mempool_error::Error::ReplacementNotAllowed(
+                    ReplacementNotAllowedError {},
+                )),
+            },
+            MempoolError::MaxFingerprintOperationsReached(ops) => ProtoMempoolError {
+                error: Some(mempool_error::Error::MaxFingerprintOperationsReached(
+                    MaxFingerprintOperationsReachedError {
+                        num_ops: ops as u64,
+                    },
                 )),
             },
             MempoolError::PaymasterBalanceTooLow(current_balance, required_balance) => {
@@ -295,6 +318,18 @@ impl From<PrecheckViolation> for ProtoPrecheckViolationError {
                     ),
                 }
             }
+            PrecheckViolation::PreVerificationGasTooHigh(actual, max) => {
+                ProtoPrecheckViolationError {
+                    violation: Some(
+                        precheck_violation_error::Violation::PreVerificationGasTooHigh(
+                            PreVerificationGasTooHigh {
+                                actual_gas: actual.to_proto_bytes(),
+                                max_gas: max.to_proto_bytes(),
+                            },
+                        ),
+                    ),
+                }
+            }
             PrecheckViolation::PaymasterIsNotContract(addr) => ProtoPrecheckViolationError {
                 violation: Some(precheck_violation_error::Violation::PaymasterIsNotContract(
                     PaymasterIsNotContract {
@@ -346,6 +381,55 @@ impl From<PrecheckViolation> for ProtoPrecheckViolationError {
                     },
                 )),
             },
+            PrecheckViolation::NonceTooLow(actual, min) => ProtoPrecheckViolationError {
+                violation: Some(precheck_violation_error::Violation::NonceTooLow(
+                    NonceTooLow {
+                        actual_nonce: actual.to_proto_bytes(),
+                        min_nonce: min.to_proto_bytes(),
+                    },
+                )),
+            },
+            PrecheckViolation::NonceTooHigh(actual, max) => ProtoPrecheckViolationError {
+                violation: Some(precheck_violation_error::Violation::NonceTooHigh(
+                    NonceTooHigh {
+                        actual_nonce: actual.to_proto_bytes(),
+                        max_nonce: max.to_proto_bytes(),
+                    },
+                )),
+            },
+            PrecheckViolation::InitCodeTooLarge(size, max_size) => ProtoPrecheckViolationError {
+                violation: Some(precheck_violation_error::Violation::InitCodeTooLarge(
+                    InitCodeTooLarge {
+                        size: size as u64,
+                        max_size: max_size as u64,
+                    },
+                )),
+            },
+            PrecheckViolation::VerificationGasToCallGasRatioTooHigh(
+                verification_gas_limit,
+                call_gas_limit,
+                max_ratio,
+            ) => ProtoPrecheckViolationError {
+                violation: Some(
+                    precheck_violation_error::Violation::VerificationGasToCallGasRatioTooHigh(
+                        VerificationGasToCallGasRatioTooHigh {
+                            verification_gas_limit: verification_gas_limit.to_proto_bytes(),
+                            call_gas_limit: call_gas_limit.to_proto_bytes(),
+                            max_ratio,
+                        },
+                    ),
+                ),
+            },
+            PrecheckViolation::DuplicateEntityAddress(entity, other_entity) => {
+                ProtoPrecheckViolationError {
+                    violation: Some(precheck_violation_error::Violation::DuplicateEntityAddress(
+                        DuplicateEntityAddress {
+                            entity: Some((&entity).into()),
+                            other_entity: Some((&other_entity).into()),
+                        },
+                    )),
+                }
+            }
         }
     }
 }
@@ -382,6 +466,12 @@ impl TryFrom<ProtoPrecheckViolationError> for PrecheckViolation {
                     from_bytes(&e.min_gas)?,
                 )
             }
+            Some(precheck_violation_error::Violation::PreVerificationGasTooHigh(e)) => {
+                PrecheckViolation::PreVerificationGasTooHigh(
+                    from_bytes(&e.actual_gas)?,
+                    from_bytes(&e.max_gas)?,
+                )
+            }
             Some(precheck_violation_error::Violation::PaymasterIsNotContract(e)) => {
                 PrecheckViolation::PaymasterIsNotContract(from_bytes(&e.paymaster_address)?)
             }
@@ -415,6 +505,36 @@ impl TryFrom<ProtoPrecheckViolationError> for PrecheckViolation {
                     from_bytes(&e.min_gas_limit)?,
                 )
             }
+            Some(precheck_violation_error::Violation::NonceTooLow(e)) => {
+                PrecheckViolation::NonceTooLow(
+                    from_bytes(&e.actual_nonce)?,
+                    from_bytes(&e.min_nonce)?,
+                )
+            }
+            Some(precheck_violation_error::Violation::NonceTooHigh(e)) => {
+                PrecheckViolation::NonceTooHigh(
+                    from_bytes(&e.actual_nonce)?,
+                    from_bytes(&e.max_nonce)?,
+                )
+            }
+            Some(precheck_violation_error::Violation::InitCodeTooLarge(e)) => {
+                PrecheckViolation::InitCodeTooLarge(e.size as usize, e.max_size as usize)
+            }
+            Some(precheck_violation_error::Violation::VerificationGasToCallGasRatioTooHigh(e)) => {
+                PrecheckViolation::VerificationGasToCallGasRatioTooHigh(
+                    from_bytes(&e.verification_gas_limit)?,
+                    from_bytes(&e.call_gas_limit)?,
+                    e.max_ratio,
+                )
+            }
+            Some(precheck_violation_error::Violation::DuplicateEntityAddress(e)) => {
+                PrecheckViolation::DuplicateEntityAddress(
+                    (&e.entity.context("should have entity in error")?).try_into()?,
+                    (&e.other_entity
+                        .context("should have other entity in error")?)
+                        .try_into()?,
+                )
+            }
             None => {
                 bail!("unknown proto mempool precheck violation")
             }
@@ -652,6 +772,14 @@ impl From<SimulationViolation> for ProtoSimulationViolationError {
                     ),
                 }
             }
+            SimulationViolation::TraceTooLarge(size, max_size) => ProtoSimulationViolationError {
+                violation: Some(simulation_violation_error::Violation::TraceTooLarge(
+                    TraceTooLarge {
+                        size: size as u64,
+                        max_size: max_size as u64,
+                    },
+                )),
+            },
         }
     }
 }
@@ -818,6 +946,9 @@ impl TryFrom<ProtoSimulationViolationError> for SimulationViolation {
                     from_bytes(&e.contract_address)?,
                 )
             }
+            Some(simulation_violation_error::Violation::TraceTooLarge(e)) => {
+                SimulationViolation::TraceTooLarge(e.size as usize, e.max_size as usize)
+            }
             None => {
                 bail!("unknown proto mempool simulation violation")
             }
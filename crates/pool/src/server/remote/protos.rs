@@ -352,9 +352,10 @@ impl From<&PoolOperation> for MempoolOp {
             aggregator: op.aggregator.map_or(vec![], |a| a.to_proto_bytes()),
             valid_after: op.valid_time_range.valid_after.seconds_since_epoch(),
             valid_until: op.valid_time_range.valid_until.seconds_since_epoch(),
-            expected_code_hash: op.expected_code_hash.to_proto_bytes(),
+            expected_code_hash: op.expected_code_hash.map_or(vec![], |h| h.to_proto_bytes()),
             sim_block_hash: op.sim_block_hash.to_proto_bytes(),
             account_is_staked: op.account_is_staked,
+            no_replacement: op.no_replacement,
         }
     }
 }
@@ -377,7 +378,11 @@ impl TryUoFromProto<MempoolOp> for PoolOperation {
 
         let valid_time_range = ValidTimeRange::new(op.valid_after.into(), op.valid_until.into());
 
-        let expected_code_hash = H256::from_slice(&op.expected_code_hash);
+        let expected_code_hash = if op.expected_code_hash.is_empty() {
+            None
+        } else {
+            Some(H256::from_slice(&op.expected_code_hash))
+        };
         let sim_block_hash = H256::from_slice(&op.sim_block_hash);
 
         Ok(PoolOperation {
@@ -390,6 +395,7 @@ impl TryUoFromProto<MempoolOp> for PoolOperation {
             sim_block_number: 0,
             account_is_staked: op.account_is_staked,
             entity_infos: EntityInfos::default(),
+            no_replacement: op.no_replacement,
         })
     }
 }
@@ -26,7 +26,7 @@ use rundler_task::grpc::{metrics::GrpcMetricsLayer, protos::from_bytes};
 use rundler_types::{
     chain::ChainSpec,
     pool::{Pool, Reputation},
-    EntityUpdate, UserOperationId, UserOperationVariant,
+    EntityType as RundlerEntityType, EntityUpdate, UserOperationId, UserOperationVariant,
 };
 use tokio::{sync::mpsc, task::JoinHandle};
 use tokio_stream::wrappers::UnboundedReceiverStream;
@@ -37,7 +37,8 @@ use super::protos::{
     add_op_response, admin_set_tracking_response, debug_clear_state_response,
     debug_dump_mempool_response, debug_dump_paymaster_balances_response,
     debug_dump_reputation_response, debug_set_reputation_response, get_op_by_hash_response,
-    get_ops_response, get_reputation_status_response, get_stake_status_response,
+    get_ops_by_sender_response, get_ops_response, get_reputation_status_response,
+    get_stake_status_response,
     op_pool_server::{OpPool, OpPoolServer},
     remove_op_by_id_response, remove_ops_response, update_entities_response, AddOpRequest,
     AddOpResponse, AddOpSuccess, AdminSetTrackingRequest, AdminSetTrackingResponse,
@@ -46,7 +47,8 @@ use super::protos::{
     DebugDumpMempoolSuccess, DebugDumpPaymasterBalancesRequest, DebugDumpPaymasterBalancesResponse,
     DebugDumpPaymasterBalancesSuccess, DebugDumpReputationRequest, DebugDumpReputationResponse,
     DebugDumpReputationSuccess, DebugSetReputationRequest, DebugSetReputationResponse,
-    DebugSetReputationSuccess, GetOpByHashRequest, GetOpByHashResponse, GetOpByHashSuccess,
+    DebugSetReputationSuccess, EntityType, GetOpByHashRequest, GetOpByHashResponse,
+    GetOpByHashSuccess, GetOpsBySenderRequest, GetOpsBySenderResponse, GetOpsBySenderSuccess,
     GetOpsRequest, GetOpsResponse, GetOpsSuccess, GetReputationStatusRequest,
     GetReputationStatusResponse, GetReputationStatusSuccess, GetStakeStatusRequest,
     GetStakeStatusResponse, GetStakeStatusSuccess, GetSupportedEntryPointsRequest,
@@ -116,6 +118,18 @@ impl OpPoolImpl {
     fn get_address(&self, address: &[u8]) -> Result<Address> {
         from_bytes(address).map_err(|e| Status::invalid_argument(format!("Invalid address: {e}")))
     }
+
+    fn get_entity_type(&self, entity_type: i32) -> Result<Option<RundlerEntityType>> {
+        match EntityType::try_from(entity_type) {
+            Ok(EntityType::Unspecified) => Ok(None),
+            Ok(kind) => RundlerEntityType::try_from(kind)
+                .map(Some)
+                .map_err(|e| Status::invalid_argument(format!("Invalid entity type: {e}"))),
+            Err(_) => Err(Status::invalid_argument(format!(
+                "Invalid entity type: {entity_type}"
+            ))),
+        }
+    }
 }
 
 #[async_trait]
@@ -152,7 +166,12 @@ impl OpPool for OpPoolImpl {
                 Status::invalid_argument(format!("Failed to convert to UserOperation: {e}"))
             })?;
 
-        let resp = match self.local_pool.add_op(ep, uo).await {
+        let res = if req.operator {
+            self.local_pool.admin_add_operator_op(ep, uo).await
+        } else {
+            self.local_pool.add_op(ep, uo).await
+        };
+        let resp = match res {
             Ok(hash) => AddOpResponse {
                 result: Some(add_op_response::Result::Success(AddOpSuccess {
                     hash: hash.as_bytes().to_vec(),
@@ -214,6 +233,32 @@ impl OpPool for OpPoolImpl {
         Ok(Response::new(resp))
     }
 
+    async fn get_ops_by_sender(
+        &self,
+        request: Request<GetOpsBySenderRequest>,
+    ) -> Result<Response<GetOpsBySenderResponse>> {
+        let req = request.into_inner();
+
+        let sender = from_bytes(&req.sender).map_err(|e| {
+            Status::invalid_argument(format!("Invalid sender in GetOpsBySenderRequest: {e}"))
+        })?;
+
+        let resp = match self.local_pool.get_ops_by_sender(sender).await {
+            Ok(ops) => GetOpsBySenderResponse {
+                result: Some(get_ops_by_sender_response::Result::Success(
+                    GetOpsBySenderSuccess {
+                        ops: ops.iter().map(MempoolOp::from).collect(),
+                    },
+                )),
+            },
+            Err(error) => GetOpsBySenderResponse {
+                result: Some(get_ops_by_sender_response::Result::Failure(error.into())),
+            },
+        };
+
+        Ok(Response::new(resp))
+    }
+
     async fn remove_ops(
         &self,
         request: Request<RemoveOpsRequest>,
@@ -454,8 +499,13 @@ impl OpPool for OpPoolImpl {
 
         let address = self.get_address(&req.address)?;
         let entry_point = self.get_entry_point(&req.entry_point)?;
+        let entity_type = self.get_entity_type(req.entity_type)?;
 
-        let resp = match self.local_pool.get_stake_status(entry_point, address).await {
+        let resp = match self
+            .local_pool
+            .get_stake_status(entry_point, address, entity_type)
+            .await
+        {
             Ok(status) => GetStakeStatusResponse {
                 result: Some(get_stake_status_response::Result::Success(
                     GetStakeStatusSuccess {
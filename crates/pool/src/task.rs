@@ -44,6 +44,14 @@ pub struct Args {
     pub chain_spec: ChainSpec,
     /// True if using unsafe mode.
     pub unsafe_mode: bool,
+    /// True if unsafe mode should be used when the node does not support custom tracers.
+    pub allow_trace_fallback: bool,
+    /// True if operations are admitted to the pool using `simulateValidation` only, without
+    /// tracing. Operations are still traced by the builder unless running in unsafe mode.
+    ///
+    /// WARNING: this removes the pool's ERC-7562 DoS protections. Only use on test or
+    /// permissioned chains.
+    pub untraced_admission: bool,
     /// HTTP URL for the full node.
     pub http_url: String,
     /// Interval to poll the chain for updates.
@@ -59,6 +67,14 @@ pub struct Args {
     pub chain_update_channel_capacity: usize,
 }
 
+impl Args {
+    /// True if operations are admitted to the pool without tracing, either because of unsafe
+    /// mode or untraced admission.
+    fn admission_unsafe_mode(&self) -> bool {
+        self.unsafe_mode || self.untraced_admission
+    }
+}
+
 /// Mempool task.
 #[derive(Debug)]
 pub struct PoolTask {
@@ -90,6 +106,18 @@ impl Task for PoolTask {
             &self.args.http_url,
             Some(self.args.chain_poll_interval),
         )?;
+        self.args.unsafe_mode = simulation::resolve_unsafe_mode(
+            &*provider,
+            self.args.unsafe_mode,
+            self.args.allow_trace_fallback,
+        )
+        .await?;
+        let admission_unsafe_mode = self.args.admission_unsafe_mode();
+        if admission_unsafe_mode && !self.args.unsafe_mode {
+            tracing::warn!(
+                "Untraced admission enabled: operations enter the mempool without ERC-7562 checks"
+            );
+        }
         let chain = Chain::new(provider.clone(), chain_settings);
         let (update_sender, _) = broadcast::channel(self.args.chain_update_channel_capacity);
         let chain_handle = chain.spawn_watcher(update_sender.clone(), shutdown_token.clone());
@@ -102,9 +130,10 @@ impl Task for PoolTask {
                     let pool = PoolTask::create_mempool_v0_6(
                         self.args.chain_spec.clone(),
                         pool_config,
-                        self.args.unsafe_mode,
+                        admission_unsafe_mode,
                         self.event_sender.clone(),
                         provider.clone(),
+                        shutdown_token.clone(),
                     )
                     .context("should have created mempool")?;
 
@@ -114,9 +143,10 @@ impl Task for PoolTask {
                     let pool = PoolTask::create_mempool_v0_7(
                         self.args.chain_spec.clone(),
                         pool_config,
-                        self.args.unsafe_mode,
+                        admission_unsafe_mode,
                         self.event_sender.clone(),
                         provider.clone(),
+                        shutdown_token.clone(),
                     )
                     .context("should have created mempool")?;
 
@@ -190,6 +220,7 @@ impl PoolTask {
         unsafe_mode: bool,
         event_sender: broadcast::Sender<WithEntryPoint<OpPoolEvent>>,
         provider: Arc<P>,
+        shutdown_token: CancellationToken,
     ) -> anyhow::Result<Arc<dyn Mempool>> {
         let ep = EthersEntryPointV0_6::new(
             pool_config.entry_point,
@@ -211,9 +242,11 @@ impl PoolTask {
                 provider,
                 ep,
                 simulator,
+                shutdown_token,
             )
         } else {
             let simulator = simulation::new_v0_6_simulator(
+                chain_spec.clone(),
                 Arc::clone(&provider),
                 ep.clone(),
                 pool_config.sim_settings.clone(),
@@ -226,6 +259,7 @@ impl PoolTask {
                 provider,
                 ep,
                 simulator,
+                shutdown_token,
             )
         }
     }
@@ -236,6 +270,7 @@ impl PoolTask {
         unsafe_mode: bool,
         event_sender: broadcast::Sender<WithEntryPoint<OpPoolEvent>>,
         provider: Arc<P>,
+        shutdown_token: CancellationToken,
     ) -> anyhow::Result<Arc<dyn Mempool>> {
         let ep = EthersEntryPointV0_7::new(
             pool_config.entry_point,
@@ -257,6 +292,7 @@ impl PoolTask {
                 provider,
                 ep,
                 simulator,
+                shutdown_token,
             )
         } else {
             let simulator = simulation::new_v0_7_simulator(
@@ -272,6 +308,7 @@ impl PoolTask {
                 provider,
                 ep,
                 simulator,
+                shutdown_token,
             )
         }
     }
@@ -283,6 +320,7 @@ impl PoolTask {
         provider: Arc<P>,
         ep: E,
         simulator: S,
+        shutdown_token: CancellationToken,
     ) -> anyhow::Result<Arc<dyn Mempool>>
     where
         UO: UserOperation + From<UserOperationVariant> + Into<UserOperationVariant>,
@@ -292,27 +330,39 @@ impl PoolTask {
         S: Simulator<UO = UO>,
     {
         let prechecker = PrecheckerImpl::new(
-            chain_spec,
+            chain_spec.clone(),
             Arc::clone(&provider),
             ep.clone(),
             pool_config.precheck_settings,
         );
 
-        let reputation = Arc::new(AddressReputation::new(
-            ReputationParams::new(pool_config.reputation_tracking_enabled),
+        let mut reputation = AddressReputation::new(
+            ReputationParams::new(
+                pool_config.reputation_tracking_enabled,
+                pool_config.reputation_decay_interval_secs,
+                pool_config.reputation_decay_factor,
+            )
+            .with_thresholds(
+                pool_config.reputation_min_inclusion_rate_denominator,
+                pool_config.reputation_throttling_slack,
+                pool_config.reputation_ban_slack,
+            ),
             pool_config.blocklist.clone().unwrap_or_default(),
             pool_config.allowlist.clone().unwrap_or_default(),
-        ));
+        );
+        if let Some(path) = &pool_config.reputation_persist_path {
+            reputation = reputation.with_persistence(path.clone());
+        }
+        let reputation = Arc::new(reputation);
 
         // Start reputation manager
         let reputation_runner = Arc::clone(&reputation);
-        tokio::spawn(async move { reputation_runner.run().await });
+        tokio::spawn(async move { reputation_runner.run(shutdown_token).await });
 
         let paymaster = PaymasterTracker::new(
+            chain_spec,
             ep.clone(),
             PaymasterConfig::new(
-                pool_config.sim_settings.min_stake_value,
-                pool_config.sim_settings.min_unstake_delay,
                 pool_config.paymaster_tracking_enabled,
                 pool_config.paymaster_cache_length,
             ),
@@ -330,3 +380,32 @@ impl PoolTask {
         Ok(Arc::new(uo_pool))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(unsafe_mode: bool, untraced_admission: bool) -> Args {
+        Args {
+            chain_spec: ChainSpec::default(),
+            unsafe_mode,
+            allow_trace_fallback: false,
+            untraced_admission,
+            http_url: String::new(),
+            chain_poll_interval: Duration::from_millis(100),
+            chain_max_sync_retries: 5,
+            pool_configs: vec![],
+            remote_address: None,
+            chain_update_channel_capacity: 1024,
+        }
+    }
+
+    #[test]
+    fn test_admission_unsafe_mode() {
+        assert!(!args(false, false).admission_unsafe_mode());
+        // untraced admission only skips tracing in the pool
+        assert!(args(false, true).admission_unsafe_mode());
+        assert!(args(true, false).admission_unsafe_mode());
+        assert!(args(true, true).admission_unsafe_mode());
+    }
+}
@@ -35,7 +35,8 @@ use rundler_types::{
         },
     },
     v0_6::UserOperation,
-    GasFees, UserOpsPerAggregator, ValidationError, ValidationOutput, ValidationRevert,
+    GasFees, UserOperation as UserOperationTrait, UserOpsPerAggregator, ValidationError,
+    ValidationOutput, ValidationRevert,
 };
 use rundler_utils::eth::{self, ContractRevertError};
 
@@ -134,6 +135,14 @@ where
             .context("should compute balances")?;
         Ok(out.balances)
     }
+
+    async fn get_nonce(&self, address: Address, key: U256) -> anyhow::Result<U256> {
+        self.i_entry_point
+            .get_nonce(address, key)
+            .call()
+            .await
+            .context("entry point should return nonce")
+    }
 }
 
 #[async_trait::async_trait]
@@ -184,6 +193,22 @@ where
             Err(error) => Err(error).context("should call aggregator to validate signature")?,
         }
     }
+
+    async fn estimate_validate_user_op_signature_gas(
+        &self,
+        aggregator_address: Address,
+        user_op: UserOperation,
+        gas_cap: u64,
+    ) -> anyhow::Result<U256> {
+        let aggregator = IAggregator::new(aggregator_address, Arc::clone(&self.provider));
+
+        aggregator
+            .validate_user_op_signature(user_op)
+            .gas(gas_cap)
+            .estimate_gas()
+            .await
+            .context("should estimate aggregator signature validation gas")
+    }
 }
 
 #[async_trait::async_trait]
@@ -257,11 +282,7 @@ where
         user_op: UserOperation,
         gas_price: U256,
     ) -> anyhow::Result<U256> {
-        let data = self
-            .i_entry_point
-            .handle_ops(vec![user_op], Address::random())
-            .calldata()
-            .context("should get calldata for entry point handle ops")?;
+        let data = user_op.single_op_bundle_calldata(Address::random());
 
         self.l1_gas_oracle
             .estimate_l1_gas(entry_point_address, data, gas_price)
@@ -327,15 +348,7 @@ where
         &self,
         revert_data: Bytes,
     ) -> Result<ExecutionResult, ValidationRevert> {
-        if let Ok(result) = ExecutionResultV0_6::decode(&revert_data) {
-            Ok(result.into())
-        } else if let Ok(failed_op) = FailedOp::decode(&revert_data) {
-            Err(ValidationRevert::EntryPoint(failed_op.reason))
-        } else if let Ok(err) = ContractRevertError::decode(&revert_data) {
-            Err(ValidationRevert::EntryPoint(err.reason))
-        } else {
-            Err(ValidationRevert::Unknown(revert_data))
-        }
+        decode_simulate_handle_ops_revert(revert_data)
     }
 
     fn get_simulate_op_call_data(
@@ -387,6 +400,22 @@ impl<P> EntryPointProvider<UserOperation> for EntryPoint<P> where
 {
 }
 
+// The v0.6 entry point always reverts from `simulateHandleOp`, a successful
+// simulation reverts with `ExecutionResult`.
+fn decode_simulate_handle_ops_revert(
+    revert_data: Bytes,
+) -> Result<ExecutionResult, ValidationRevert> {
+    if let Ok(result) = ExecutionResultV0_6::decode(&revert_data) {
+        Ok(result.into())
+    } else if let Ok(failed_op) = FailedOp::decode(&revert_data) {
+        Err(ValidationRevert::EntryPoint(failed_op.reason))
+    } else if let Ok(err) = ContractRevertError::decode(&revert_data) {
+        Err(ValidationRevert::EntryPoint(err.reason))
+    } else {
+        Err(ValidationRevert::Unknown(revert_data))
+    }
+}
+
 fn get_handle_ops_call<M: Middleware>(
     entry_point: &IEntryPoint<M>,
     ops_per_aggregator: Vec<UserOpsPerAggregator<UserOperation>>,
@@ -419,6 +448,7 @@ impl From<ExecutionResultV0_6> for ExecutionResult {
             valid_until: result.valid_until.into(),
             target_success: result.target_success,
             target_result: result.target_result,
+            aggregator: None,
         }
     }
 }
@@ -434,3 +464,47 @@ impl From<DepositInfoV0_6> for DepositInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ethers::abi::AbiEncode;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_execution_result() {
+        let revert_data: Bytes = ExecutionResultV0_6 {
+            pre_op_gas: 50_000.into(),
+            paid: 1_000_000.into(),
+            valid_after: 10,
+            valid_until: 20,
+            target_success: true,
+            target_result: vec![0xab, 0xcd].into(),
+        }
+        .encode()
+        .into();
+
+        let result = decode_simulate_handle_ops_revert(revert_data).unwrap();
+        assert_eq!(result.pre_op_gas, 50_000.into());
+        assert_eq!(result.paid, 1_000_000.into());
+        assert_eq!(result.valid_after, 10.into());
+        assert_eq!(result.valid_until, 20.into());
+        assert!(result.target_success);
+        assert_eq!(result.target_result, Bytes::from(vec![0xab, 0xcd]));
+    }
+
+    #[test]
+    fn test_decode_failed_op() {
+        let revert_data: Bytes = FailedOp {
+            op_index: 0.into(),
+            reason: "AA21 didn't pay prefund".to_string(),
+        }
+        .encode()
+        .into();
+
+        let err = decode_simulate_handle_ops_revert(revert_data).unwrap_err();
+        assert!(
+            matches!(err, ValidationRevert::EntryPoint(reason) if reason == "AA21 didn't pay prefund")
+        );
+    }
+}
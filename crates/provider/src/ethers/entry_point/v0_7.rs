@@ -38,7 +38,8 @@ use rundler_types::{
         },
     },
     v0_7::UserOperation,
-    GasFees, UserOpsPerAggregator, ValidationError, ValidationOutput, ValidationRevert,
+    GasFees, UserOperation as UserOperationTrait, UserOpsPerAggregator, ValidationError,
+    ValidationOutput, ValidationRevert,
 };
 use rundler_utils::eth::{self, ContractRevertError};
 
@@ -137,6 +138,14 @@ where
             .context("should compute balances")?;
         Ok(out.balances)
     }
+
+    async fn get_nonce(&self, address: Address, key: U256) -> anyhow::Result<U256> {
+        self.i_entry_point
+            .get_nonce(address, key)
+            .call()
+            .await
+            .context("entry point should return nonce")
+    }
 }
 
 #[async_trait::async_trait]
@@ -191,6 +200,22 @@ where
             Err(error) => Err(error).context("should call aggregator to validate signature")?,
         }
     }
+
+    async fn estimate_validate_user_op_signature_gas(
+        &self,
+        aggregator_address: Address,
+        user_op: UserOperation,
+        gas_cap: u64,
+    ) -> anyhow::Result<U256> {
+        let aggregator = IAggregator::new(aggregator_address, Arc::clone(&self.provider));
+
+        aggregator
+            .validate_user_op_signature(user_op.pack())
+            .gas(gas_cap)
+            .estimate_gas()
+            .await
+            .context("should estimate aggregator signature validation gas")
+    }
 }
 
 #[async_trait::async_trait]
@@ -258,11 +283,7 @@ where
         user_op: UserOperation,
         gas_price: U256,
     ) -> anyhow::Result<U256> {
-        let data = self
-            .i_entry_point
-            .handle_ops(vec![user_op.pack()], Address::random())
-            .calldata()
-            .context("should get calldata for entry point handle ops")?;
+        let data = user_op.single_op_bundle_calldata(Address::random());
 
         self.l1_gas_oracle
             .estimate_l1_gas(entry_point_address, data, gas_price)
@@ -466,6 +487,7 @@ impl From<ExecutionResultV0_7> for ExecutionResult {
             valid_until: intersect_range.valid_until,
             target_success: result.target_success,
             target_result: result.target_result,
+            aggregator: account.aggregator(),
         }
     }
 }
@@ -0,0 +1,140 @@
+// This file is part of Rundler.
+//
+// Rundler is free software: you can redistribute it and/or modify it under the
+// terms of the GNU Lesser General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// Rundler is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Rundler.
+// If not, see https://www.gnu.org/licenses/.
+
+use core::fmt::Debug;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, RpcError};
+use metrics::{counter, gauge};
+use serde::{de::DeserializeOwned, Serialize};
+
+#[derive(Debug)]
+/// JSON-RPC client that fails over across a list of endpoints.
+///
+/// Requests are sent to the current endpoint. On a transport error the request is
+/// retried against the next endpoint, which becomes the current endpoint if it
+/// succeeds. JSON-RPC error responses are returned to the caller without failing over.
+pub struct FailoverClient<C> {
+    endpoints: Vec<C>,
+    current: AtomicUsize,
+}
+
+impl<C> FailoverClient<C>
+where
+    C: JsonRpcClient,
+{
+    /// Constructor for the failover client, panics if `endpoints` is empty
+    pub fn new(endpoints: Vec<C>) -> Self {
+        assert!(
+            !endpoints.is_empty(),
+            "failover client requires an endpoint"
+        );
+        for index in 0..endpoints.len() {
+            set_endpoint_health(index, true);
+        }
+        Self {
+            endpoints,
+            current: AtomicUsize::new(0),
+        }
+    }
+}
+
+#[async_trait]
+impl<C: JsonRpcClient> JsonRpcClient for FailoverClient<C> {
+    type Error = C::Error;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let start = self.current.load(Ordering::Relaxed);
+        let mut attempt = 0;
+        loop {
+            let index = (start + attempt) % self.endpoints.len();
+            match self.endpoints[index].request(method, &params).await {
+                Err(error)
+                    if error.as_error_response().is_none()
+                        && attempt + 1 < self.endpoints.len() =>
+                {
+                    tracing::warn!("provider endpoint {index} failed, failing over: {error:?}");
+                    set_endpoint_health(index, false);
+                    counter!("provider_endpoint_failovers", "endpoint" => index.to_string())
+                        .increment(1);
+                    attempt += 1;
+                }
+                result => {
+                    let healthy = match &result {
+                        Ok(_) => true,
+                        Err(error) => error.as_error_response().is_some(),
+                    };
+                    set_endpoint_health(index, healthy);
+                    if healthy {
+                        self.current.store(index, Ordering::Relaxed);
+                    }
+                    return result;
+                }
+            }
+        }
+    }
+}
+
+// Endpoints are labeled by index, URLs may contain API keys
+fn set_endpoint_health(index: usize, healthy: bool) {
+    let value = if healthy { 1.0 } else { 0.0 };
+    gauge!("provider_endpoint_healthy", "endpoint" => index.to_string()).set(value);
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        providers::{JsonRpcError, MockProvider, MockResponse},
+        types::U64,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failover_to_second_endpoint() {
+        // the first endpoint has no responses queued and fails
+        let first = MockProvider::new();
+        let second = MockProvider::new();
+        second.push(U64::from(1)).unwrap();
+
+        let client = FailoverClient::new(vec![first, second]);
+        let block_number: U64 = client.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(block_number, U64::from(1));
+
+        // the second endpoint stays current for the next request
+        client.endpoints[1].push(U64::from(2)).unwrap();
+        let block_number: U64 = client.request("eth_blockNumber", ()).await.unwrap();
+        assert_eq!(block_number, U64::from(2));
+    }
+
+    #[tokio::test]
+    async fn test_no_failover_on_rpc_error() {
+        let first = MockProvider::new();
+        first.push_response(MockResponse::Error(JsonRpcError {
+            code: -32000,
+            message: "execution reverted".to_string(),
+            data: None,
+        }));
+        let second = MockProvider::new();
+        second.push(U64::from(1)).unwrap();
+
+        let client = FailoverClient::new(vec![first, second]);
+        let result: Result<U64, _> = client.request("eth_blockNumber", ()).await;
+        assert!(result.is_err());
+    }
+}
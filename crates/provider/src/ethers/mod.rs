@@ -15,5 +15,6 @@
 
 mod entry_point;
 pub use entry_point::{v0_6::EntryPoint as EntryPointV0_6, v0_7::EntryPoint as EntryPointV0_7};
+mod failover;
 mod metrics_middleware;
 pub(crate) mod provider;
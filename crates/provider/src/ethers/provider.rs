@@ -34,8 +34,9 @@ use rundler_types::contracts::utils::{
     storage_loader::STORAGELOADER_DEPLOYED_BYTECODE,
 };
 use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
 
-use super::metrics_middleware::MetricsMiddleware;
+use super::{failover::FailoverClient, metrics_middleware::MetricsMiddleware};
 use crate::{Provider, ProviderError, ProviderResult};
 
 #[async_trait::async_trait]
@@ -143,6 +144,25 @@ impl<C: JsonRpcClient + 'static> Provider for EthersProvider<C> {
         Ok(Middleware::debug_trace_call(self, tx, block_id, trace_options).await?)
     }
 
+    async fn debug_trace_call_with_size_check(
+        &self,
+        tx: TypedTransaction,
+        block_id: Option<BlockId>,
+        trace_options: GethDebugTracingCallOptions,
+        max_size_bytes: usize,
+    ) -> ProviderResult<GethTrace> {
+        let block_id = block_id.unwrap_or_else(|| BlockNumber::Latest.into());
+        // Load the raw JSON so the size is checked before building the trace, the response
+        // body itself has already been read in full by the transport
+        let raw: Box<RawValue> =
+            EthersProvider::request(self, "debug_traceCall", (tx, block_id, trace_options)).await?;
+        let size = raw.get().len();
+        if size > max_size_bytes {
+            return Err(ProviderError::ResponseTooLarge(size, max_size_bytes));
+        }
+        Ok(serde_json::from_str(raw.get()).context("should deserialize debug_traceCall trace")?)
+    }
+
     async fn get_balance(&self, address: Address, block: Option<BlockId>) -> ProviderResult<U256> {
         Ok(Middleware::get_balance(self, address, block).await?)
     }
@@ -288,18 +308,28 @@ fn get_revert_data<D: AbiDecode>(error: ProviderError) -> Result<D, ProviderErro
 
 /// Construct a new Ethers provider from a URL and a poll interval.
 ///
+/// The URL may be a comma-separated list of URLs, in which case requests fail over to the
+/// next URL on transport errors.
+///
 /// Creates a provider with a retry client that retries 10 times, with an initial backoff of 500ms.
 pub fn new_provider(
     url: &str,
     poll_interval: Option<Duration>,
-) -> anyhow::Result<Arc<EthersProvider<RetryClient<MetricsMiddleware<Http>>>>> {
-    let parsed_url = Url::parse(url).context("provider url should be valid")?;
-
+) -> anyhow::Result<Arc<EthersProvider<RetryClient<FailoverClient<MetricsMiddleware<Http>>>>>> {
     let http_client = reqwest::Client::builder()
         .connect_timeout(Duration::from_secs(1))
         .build()
         .context("failed to build reqwest client")?;
-    let http = MetricsMiddleware::new(Http::new_with_client(parsed_url, http_client));
+    let endpoints = url
+        .split(',')
+        .map(|url| {
+            let parsed_url = Url::parse(url.trim()).context("provider url should be valid")?;
+            Ok(MetricsMiddleware::new(Http::new_with_client(
+                parsed_url,
+                http_client.clone(),
+            )))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
 
     let client = RetryClientBuilder::default()
         // these retries are if the server returns a 429
@@ -307,7 +337,10 @@ pub fn new_provider(
         // these retries are if the connection is dubious
         .timeout_retries(3)
         .initial_backoff(Duration::from_millis(500))
-        .build(http, Box::<HttpRateLimitRetryPolicy>::default());
+        .build(
+            FailoverClient::new(endpoints),
+            Box::<HttpRateLimitRetryPolicy>::default(),
+        );
 
     let mut provider = EthersProvider::new(client);
 
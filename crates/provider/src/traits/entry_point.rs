@@ -83,6 +83,10 @@ pub struct ExecutionResult {
     pub target_success: bool,
     /// Result of the operation execution
     pub target_result: Bytes,
+    /// Signature aggregator returned by the account's validation, if any.
+    ///
+    /// Only reported by entry point v0.7, always `None` for v0.6.
+    pub aggregator: Option<Address>,
 }
 
 /// Trait for interacting with an entry point contract.
@@ -101,6 +105,9 @@ pub trait EntryPoint: Send + Sync + 'static {
 
     /// Get the balances of a list of addresses in order
     async fn get_balances(&self, addresses: Vec<Address>) -> anyhow::Result<Vec<U256>>;
+
+    /// Get the next valid nonce for a sender and nonce key from the entry point's nonce manager
+    async fn get_nonce(&self, address: Address, key: U256) -> anyhow::Result<U256>;
 }
 
 /// Trait for handling signature aggregators
@@ -124,6 +131,14 @@ pub trait SignatureAggregator: Send + Sync + 'static {
         user_op: Self::UO,
         gas_cap: u64,
     ) -> anyhow::Result<AggregatorOut>;
+
+    /// Estimate the gas used by an aggregator to validate a user operation signature
+    async fn estimate_validate_user_op_signature_gas(
+        &self,
+        aggregator_address: Address,
+        user_op: Self::UO,
+        gas_cap: u64,
+    ) -> anyhow::Result<U256>;
 }
 
 /// Trait for submitting bundles of operations to an entry point contract
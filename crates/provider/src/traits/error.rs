@@ -22,6 +22,9 @@ pub enum ProviderError {
     /// Contract Error
     #[error("Contract Error: {0}")]
     ContractError(String),
+    /// Response larger than the allowed maximum, with its size and the maximum in bytes
+    #[error("response of {0} bytes exceeds the maximum of {1} bytes")]
+    ResponseTooLarge(usize, usize),
     /// Internal errors
     #[error(transparent)]
     Other(#[from] anyhow::Error),
@@ -111,6 +111,17 @@ pub trait Provider: Send + Sync + Debug + 'static {
         trace_options: GethDebugTracingCallOptions,
     ) -> ProviderResult<GethTrace>;
 
+    /// Debug trace a call, failing with `ProviderError::ResponseTooLarge` if the trace's JSON
+    /// is larger than `max_size_bytes`. The check runs after the full response is received,
+    /// so it rejects large traces but does not bound the memory used to read them.
+    async fn debug_trace_call_with_size_check(
+        &self,
+        tx: TypedTransaction,
+        block_id: Option<BlockId>,
+        trace_options: GethDebugTracingCallOptions,
+        max_size_bytes: usize,
+    ) -> ProviderResult<GethTrace>;
+
     /// Get the latest block hash and number
     async fn get_latest_block_hash_and_number(&self) -> ProviderResult<(H256, U64)>;
 
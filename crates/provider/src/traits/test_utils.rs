@@ -33,6 +33,7 @@ mockall::mock! {
             -> anyhow::Result<U256>;
         async fn get_deposit_info(&self, address: Address) -> anyhow::Result<DepositInfo>;
         async fn get_balances(&self, addresses: Vec<Address>) -> anyhow::Result<Vec<U256>>;
+        async fn get_nonce(&self, address: Address, key: U256) -> anyhow::Result<U256>;
     }
 
     #[async_trait::async_trait]
@@ -49,6 +50,12 @@ mockall::mock! {
             user_op: v0_6::UserOperation,
             gas_cap: u64,
         ) -> anyhow::Result<AggregatorOut>;
+        async fn estimate_validate_user_op_signature_gas(
+            &self,
+            aggregator_address: Address,
+            user_op: v0_6::UserOperation,
+            gas_cap: u64,
+        ) -> anyhow::Result<U256>;
     }
 
     #[async_trait::async_trait]
@@ -126,6 +133,7 @@ mockall::mock! {
             -> anyhow::Result<U256>;
         async fn get_deposit_info(&self, address: Address) -> anyhow::Result<DepositInfo>;
         async fn get_balances(&self, addresses: Vec<Address>) -> anyhow::Result<Vec<U256>>;
+        async fn get_nonce(&self, address: Address, key: U256) -> anyhow::Result<U256>;
     }
 
     #[async_trait::async_trait]
@@ -142,6 +150,12 @@ mockall::mock! {
             user_op: v0_7::UserOperation,
             gas_cap: u64,
         ) -> anyhow::Result<AggregatorOut>;
+        async fn estimate_validate_user_op_signature_gas(
+            &self,
+            aggregator_address: Address,
+            user_op: v0_7::UserOperation,
+            gas_cap: u64,
+        ) -> anyhow::Result<U256>;
     }
 
     #[async_trait::async_trait]
@@ -13,13 +13,14 @@
 
 use anyhow::Context;
 use async_trait::async_trait;
-use ethers::types::Address;
+use ethers::types::{Address, H256};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use rundler_types::pool::Pool;
+use rundler_types::{chain::ChainSpec, pool::Pool, UserOperationVariant};
 
 use crate::{
-    types::{RpcAdminClearState, RpcAdminSetTracking},
-    utils::{self, InternalRpcResult},
+    eth::{EthResult, EthRpcError},
+    types::{FromRpc, RpcAdminClearState, RpcAdminSetTracking, RpcUserOperation},
+    utils::{InternalRpcResult, MethodTimeouts},
 };
 
 /// Admin API
@@ -36,15 +37,30 @@ pub trait AdminApi {
         entry_point: Address,
         tracking_info: RpcAdminSetTracking,
     ) -> RpcResult<String>;
+
+    /// Adds an operator user operation to the mempool. Unlike operations sent via
+    /// `eth_sendUserOperation`, it can only be replaced by another operator operation.
+    #[method(name = "addOperatorUserOperation")]
+    async fn add_operator_user_operation(
+        &self,
+        op: RpcUserOperation,
+        entry_point: Address,
+    ) -> RpcResult<H256>;
 }
 
 pub(crate) struct AdminApi<P> {
     pool: P,
+    chain_spec: ChainSpec,
+    method_timeouts: MethodTimeouts,
 }
 
 impl<P> AdminApi<P> {
-    pub(crate) fn new(pool: P) -> Self {
-        Self { pool }
+    pub(crate) fn new(pool: P, chain_spec: ChainSpec, method_timeouts: MethodTimeouts) -> Self {
+        Self {
+            pool,
+            chain_spec,
+            method_timeouts,
+        }
     }
 }
 
@@ -54,11 +70,12 @@ where
     P: Pool,
 {
     async fn clear_state(&self, clear_params: RpcAdminClearState) -> RpcResult<String> {
-        utils::safe_call_rpc_handler(
-            "admin_clearState",
-            AdminApi::clear_state(self, clear_params),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "admin_clearState",
+                AdminApi::clear_state(self, clear_params),
+            )
+            .await
     }
 
     async fn set_tracking(
@@ -66,11 +83,29 @@ where
         entry_point: Address,
         tracking_params: RpcAdminSetTracking,
     ) -> RpcResult<String> {
-        utils::safe_call_rpc_handler(
-            "admin_setTracking",
-            AdminApi::set_tracking(self, entry_point, tracking_params),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "admin_setTracking",
+                AdminApi::set_tracking(self, entry_point, tracking_params),
+            )
+            .await
+    }
+
+    async fn add_operator_user_operation(
+        &self,
+        op: RpcUserOperation,
+        entry_point: Address,
+    ) -> RpcResult<H256> {
+        self.method_timeouts
+            .call(
+                "admin_addOperatorUserOperation",
+                AdminApi::add_operator_user_operation(
+                    self,
+                    UserOperationVariant::from_rpc(op, &self.chain_spec),
+                    entry_point,
+                ),
+            )
+            .await
     }
 }
 
@@ -107,4 +142,46 @@ where
 
         Ok("ok".to_string())
     }
+
+    async fn add_operator_user_operation(
+        &self,
+        op: UserOperationVariant,
+        entry_point: Address,
+    ) -> EthResult<H256> {
+        self.pool
+            .admin_add_operator_op(entry_point, op)
+            .await
+            .map_err(EthRpcError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rundler_types::{pool::MockPool, v0_6::UserOperation};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_add_operator_user_operation() {
+        let entry_point = Address::random();
+        let hash = H256::random();
+
+        let mut pool = MockPool::default();
+        pool.expect_admin_add_operator_op()
+            .withf(move |ep, _| *ep == entry_point)
+            .times(1)
+            .returning(move |_, _| Ok(hash));
+        pool.expect_add_op().never();
+
+        let api = AdminApi::new(pool, ChainSpec::default(), MethodTimeouts::default());
+        let res = api
+            .add_operator_user_operation(
+                UserOperationVariant::V0_6(UserOperation::default()),
+                entry_point,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res, hash);
+    }
 }
@@ -0,0 +1,161 @@
+// This file is part of Rundler.
+//
+// Rundler is free software: you can redistribute it and/or modify it under the
+// terms of the GNU Lesser General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// Rundler is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Rundler.
+// If not, see https://www.gnu.org/licenses/.
+
+use std::{fmt::Debug, sync::Arc};
+
+use async_trait::async_trait;
+use rundler_types::{UserOperation, UserOperationVariant};
+
+use crate::{eth::EthRpcError, utils::SubmittedOps};
+
+/// Custom admission logic applied to user operations before they are sent to the pool.
+///
+/// Hooks run after the built-in admission checks, so they only see operations
+/// that would otherwise be accepted.
+#[async_trait]
+pub trait AdmissionHook: Send + Sync + 'static {
+    /// Check a user operation, returning an error to reject it.
+    async fn check(&self, op: &UserOperationVariant) -> Result<(), EthRpcError>;
+}
+
+/// Admission state shared by the namespaces that add user operations to the pool.
+#[derive(Clone)]
+pub(crate) struct AdmissionContext {
+    /// Custom hooks run after the built-in admission checks
+    pub(crate) hooks: AdmissionHooks,
+    /// Log the full user operation at debug level when it is rejected
+    pub(crate) log_rejected_ops: bool,
+    /// Hashes of the user operations accepted by this server
+    pub(crate) submitted_ops: SubmittedOps,
+}
+
+/// An ordered list of admission hooks.
+///
+/// Hooks are run in the order they were added and the first rejection is returned.
+#[derive(Clone, Default)]
+pub struct AdmissionHooks(Arc<Vec<Arc<dyn AdmissionHook>>>);
+
+impl AdmissionHooks {
+    /// Create a new list of admission hooks, run in the given order
+    pub fn new(hooks: Vec<Arc<dyn AdmissionHook>>) -> Self {
+        Self(Arc::new(hooks))
+    }
+
+    /// Run every hook against the user operation, stopping at the first rejection
+    pub(crate) async fn check(&self, op: &UserOperationVariant) -> Result<(), EthRpcError> {
+        for hook in self.0.iter() {
+            hook.check(op).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Rejects user operations without a signature.
+///
+/// An empty signature is only valid during gas estimation, an operation sent for inclusion
+/// must carry a real signature.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RejectEmptySignature;
+
+#[async_trait]
+impl AdmissionHook for RejectEmptySignature {
+    async fn check(&self, op: &UserOperationVariant) -> Result<(), EthRpcError> {
+        if op.signature().is_empty() {
+            return Err(EthRpcError::SignatureCheckFailed);
+        }
+        Ok(())
+    }
+}
+
+impl Debug for AdmissionHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdmissionHooks")
+            .field("count", &self.0.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use ethers::types::Address;
+    use rundler_types::v0_6;
+
+    use super::*;
+
+    struct RejectSender(Address);
+
+    #[async_trait]
+    impl AdmissionHook for RejectSender {
+        async fn check(&self, op: &UserOperationVariant) -> Result<(), EthRpcError> {
+            if op.sender() == self.0 {
+                return Err(EthRpcError::InvalidParams(format!(
+                    "sender {:?} is not allowed",
+                    self.0
+                )));
+            }
+            Ok(())
+        }
+    }
+
+    struct CountCalls(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl AdmissionHook for CountCalls {
+        async fn check(&self, _op: &UserOperationVariant) -> Result<(), EthRpcError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn op(sender: Address) -> UserOperationVariant {
+        v0_6::UserOperation {
+            sender,
+            ..Default::default()
+        }
+        .into()
+    }
+
+    #[tokio::test]
+    async fn test_hooks_run_in_order() {
+        let rejected = Address::random();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let hooks = AdmissionHooks::new(vec![
+            Arc::new(RejectSender(rejected)),
+            Arc::new(CountCalls(calls.clone())),
+        ]);
+
+        assert!(hooks.check(&op(Address::random())).await.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // a rejection stops later hooks from running
+        let res = hooks.check(&op(rejected)).await;
+        assert!(matches!(res, Err(EthRpcError::InvalidParams(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_reject_empty_signature() {
+        let res = RejectEmptySignature.check(&op(Address::random())).await;
+        assert!(matches!(res, Err(EthRpcError::SignatureCheckFailed)));
+
+        let signed: UserOperationVariant = v0_6::UserOperation {
+            sender: Address::random(),
+            signature: vec![1; 65].into(),
+            ..Default::default()
+        }
+        .into();
+        assert!(RejectEmptySignature.check(&signed).await.is_ok());
+    }
+}
@@ -19,14 +19,15 @@ use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 use rundler_types::{
     builder::{Builder, BundlingMode},
     pool::Pool,
+    EntityType,
 };
 
 use crate::{
     types::{
-        RpcDebugPaymasterBalance, RpcReputationInput, RpcReputationOutput, RpcStakeInfo,
-        RpcStakeStatus, RpcUserOperation,
+        RpcBundlePreview, RpcDebugPaymasterBalance, RpcReputationInput, RpcReputationOutput,
+        RpcStakeInfo, RpcStakeStatus, RpcUserOperation,
     },
-    utils::{self, InternalRpcResult},
+    utils::{InternalRpcResult, MethodTimeouts},
 };
 
 /// Debug API
@@ -69,12 +70,14 @@ pub trait DebugApi {
         entry_point: Address,
     ) -> RpcResult<Vec<RpcReputationOutput>>;
 
-    /// Returns stake status given an address and entrypoint
+    /// Returns stake status given an address and entrypoint, checked against the stake
+    /// requirement of the given entity type if provided
     #[method(name = "bundler_getStakeStatus")]
     async fn bundler_get_stake_status(
         &self,
         address: Address,
         entry_point: Address,
+        entity_type: Option<EntityType>,
     ) -> RpcResult<RpcStakeStatus>;
 
     /// Dumps the paymaster balance cache
@@ -87,16 +90,26 @@ pub trait DebugApi {
     /// Clear the reputations of pool.
     #[method(name = "bundler_clearReputation")]
     async fn bundler_clear_reputation(&self) -> RpcResult<String>;
+
+    /// Returns the bundle the builder would send right now for the given entry
+    /// point, without sending anything.
+    #[method(name = "previewBundle")]
+    async fn preview_bundle(&self, entry_point: Address) -> RpcResult<RpcBundlePreview>;
 }
 
 pub(crate) struct DebugApi<P, B> {
     pool: P,
     builder: B,
+    method_timeouts: MethodTimeouts,
 }
 
 impl<P, B> DebugApi<P, B> {
-    pub(crate) fn new(pool: P, builder: B) -> Self {
-        Self { pool, builder }
+    pub(crate) fn new(pool: P, builder: B, method_timeouts: MethodTimeouts) -> Self {
+        Self {
+            pool,
+            builder,
+            method_timeouts,
+        }
     }
 }
 
@@ -107,40 +120,48 @@ where
     B: Builder,
 {
     async fn bundler_clear_state(&self) -> RpcResult<String> {
-        utils::safe_call_rpc_handler("bundler_clearState", DebugApi::bundler_clear_state(self))
+        self.method_timeouts
+            .call(
+                "debug_bundler_clearState",
+                DebugApi::bundler_clear_state(self),
+            )
             .await
     }
 
     async fn bundler_clear_mempool(&self) -> RpcResult<String> {
-        utils::safe_call_rpc_handler(
-            "bundler_clearMempool",
-            DebugApi::bundler_clear_mempool(self),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "debug_bundler_clearMempool",
+                DebugApi::bundler_clear_mempool(self),
+            )
+            .await
     }
 
     async fn bundler_dump_mempool(&self, entry_point: Address) -> RpcResult<Vec<RpcUserOperation>> {
-        utils::safe_call_rpc_handler(
-            "bundler_dumpMempool",
-            DebugApi::bundler_dump_mempool(self, entry_point),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "debug_bundler_dumpMempool",
+                DebugApi::bundler_dump_mempool(self, entry_point),
+            )
+            .await
     }
 
     async fn bundler_send_bundle_now(&self) -> RpcResult<H256> {
-        utils::safe_call_rpc_handler(
-            "bundler_sendBundleNow",
-            DebugApi::bundler_send_bundle_now(self),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "debug_bundler_sendBundleNow",
+                DebugApi::bundler_send_bundle_now(self),
+            )
+            .await
     }
 
     async fn bundler_set_bundling_mode(&self, mode: BundlingMode) -> RpcResult<String> {
-        utils::safe_call_rpc_handler(
-            "bundler_setBundlingMode",
-            DebugApi::bundler_set_bundling_mode(self, mode),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "debug_bundler_setBundlingMode",
+                DebugApi::bundler_set_bundling_mode(self, mode),
+            )
+            .await
     }
 
     async fn bundler_set_reputation(
@@ -148,53 +169,68 @@ where
         reputations: Vec<RpcReputationInput>,
         entry_point: Address,
     ) -> RpcResult<String> {
-        utils::safe_call_rpc_handler(
-            "bundler_setReputation",
-            DebugApi::bundler_set_reputation(self, reputations, entry_point),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "debug_bundler_setReputation",
+                DebugApi::bundler_set_reputation(self, reputations, entry_point),
+            )
+            .await
     }
 
     async fn bundler_dump_reputation(
         &self,
         entry_point: Address,
     ) -> RpcResult<Vec<RpcReputationOutput>> {
-        utils::safe_call_rpc_handler(
-            "bundler_dumpReputation",
-            DebugApi::bundler_dump_reputation(self, entry_point),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "debug_bundler_dumpReputation",
+                DebugApi::bundler_dump_reputation(self, entry_point),
+            )
+            .await
     }
 
     async fn bundler_get_stake_status(
         &self,
         address: Address,
         entry_point: Address,
+        entity_type: Option<EntityType>,
     ) -> RpcResult<RpcStakeStatus> {
-        utils::safe_call_rpc_handler(
-            "bundler_getStakeStatus",
-            DebugApi::bundler_get_stake_status(self, address, entry_point),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "debug_bundler_getStakeStatus",
+                DebugApi::bundler_get_stake_status(self, address, entry_point, entity_type),
+            )
+            .await
     }
 
     async fn bundler_dump_paymaster_balances(
         &self,
         entry_point: Address,
     ) -> RpcResult<Vec<RpcDebugPaymasterBalance>> {
-        utils::safe_call_rpc_handler(
-            "bundler_dumpPaymasterBalances",
-            DebugApi::bundler_dump_paymaster_balances(self, entry_point),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "debug_bundler_dumpPaymasterBalances",
+                DebugApi::bundler_dump_paymaster_balances(self, entry_point),
+            )
+            .await
     }
 
     async fn bundler_clear_reputation(&self) -> RpcResult<String> {
-        utils::safe_call_rpc_handler(
-            "bundler_clearReputation",
-            DebugApi::bundler_clear_reputation(self),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "debug_bundler_clearReputation",
+                DebugApi::bundler_clear_reputation(self),
+            )
+            .await
+    }
+
+    async fn preview_bundle(&self, entry_point: Address) -> RpcResult<RpcBundlePreview> {
+        self.method_timeouts
+            .call(
+                "debug_previewBundle",
+                DebugApi::preview_bundle(self, entry_point),
+            )
+            .await
     }
 }
 
@@ -333,10 +369,11 @@ where
         &self,
         address: Address,
         entry_point: Address,
+        entity_type: Option<EntityType>,
     ) -> InternalRpcResult<RpcStakeStatus> {
         let result = self
             .pool
-            .get_stake_status(entry_point, address)
+            .get_stake_status(entry_point, address, entity_type)
             .await
             .context("should get stake status")?;
 
@@ -382,4 +419,14 @@ where
 
         Ok("ok".to_string())
     }
+
+    async fn preview_bundle(&self, entry_point: Address) -> InternalRpcResult<RpcBundlePreview> {
+        let preview = self
+            .builder
+            .debug_preview_bundle(entry_point)
+            .await
+            .context("should preview bundle")?;
+
+        Ok(preview.into())
+    }
 }
@@ -19,48 +19,71 @@ use ethers::{
 };
 use futures_util::future;
 use rundler_types::{
-    chain::ChainSpec, pool::Pool, UserOperation, UserOperationOptionalGas, UserOperationVariant,
+    chain::ChainSpec,
+    pool::{MempoolError, Pool, PoolError, PrecheckViolation},
+    UserOperation, UserOperationOptionalGas, UserOperationVariant,
 };
 use rundler_utils::log::LogOnError;
 use tracing::Level;
 
 use super::{
-    error::{EthResult, EthRpcError},
+    error::{ErrorCompatMode, EthResult, EthRpcError, OperationAlreadyIncludedData},
     router::EntryPointRouter,
 };
-use crate::types::{RpcGasEstimate, RpcUserOperationByHash, RpcUserOperationReceipt};
+use crate::{
+    admission::{AdmissionContext, AdmissionHooks},
+    metrics::RpcMetrics,
+    types::{RpcGasEstimate, RpcUserOperation, RpcUserOperationByHash, RpcUserOperationReceipt},
+    utils::MethodTimeouts,
+};
 
 /// Settings for the `eth_` API
 #[derive(Copy, Clone, Debug)]
 pub struct Settings {
     /// The number of blocks to look back for user operation events
     pub user_operation_event_block_distance: Option<u64>,
+    /// The error response convention to use
+    pub error_compat_mode: ErrorCompatMode,
 }
 
 impl Settings {
     /// Create new settings for the `eth_` API
-    pub fn new(block_distance: Option<u64>) -> Self {
+    pub fn new(block_distance: Option<u64>, error_compat_mode: ErrorCompatMode) -> Self {
         Self {
             user_operation_event_block_distance: block_distance,
+            error_compat_mode,
         }
     }
 }
 
 pub(crate) struct EthApi<P> {
     pub(crate) chain_spec: ChainSpec,
+    pub(crate) error_compat_mode: ErrorCompatMode,
+    pub(crate) method_timeouts: MethodTimeouts,
     pool: P,
     router: EntryPointRouter,
+    admission: AdmissionContext,
 }
 
 impl<P> EthApi<P>
 where
     P: Pool,
 {
-    pub(crate) fn new(chain_spec: ChainSpec, router: EntryPointRouter, pool: P) -> Self {
+    pub(crate) fn new(
+        chain_spec: ChainSpec,
+        router: EntryPointRouter,
+        pool: P,
+        error_compat_mode: ErrorCompatMode,
+        method_timeouts: MethodTimeouts,
+        admission: AdmissionContext,
+    ) -> Self {
         Self {
             router,
             pool,
             chain_spec,
+            error_compat_mode,
+            method_timeouts,
+            admission,
         }
     }
 
@@ -69,21 +92,15 @@ where
         op: UserOperationVariant,
         entry_point: Address,
     ) -> EthResult<H256> {
-        let bundle_size = op.single_uo_bundle_size_bytes();
-        if bundle_size > self.chain_spec.max_transaction_size_bytes {
-            return Err(EthRpcError::InvalidParams(format!(
-                "User operation in bundle size {} exceeds max transaction size {}",
-                bundle_size, self.chain_spec.max_transaction_size_bytes
-            )));
-        }
-
-        self.router.check_and_get_route(&entry_point, &op)?;
-
-        self.pool
-            .add_op(entry_point, op)
-            .await
-            .map_err(EthRpcError::from)
-            .log_on_error_level(Level::DEBUG, "failed to add op to the mempool")
+        add_user_operation(
+            &self.chain_spec,
+            &self.router,
+            &self.pool,
+            &self.admission,
+            op,
+            entry_point,
+        )
+        .await
     }
 
     pub(crate) async fn estimate_user_operation_gas(
@@ -92,17 +109,14 @@ where
         entry_point: Address,
         state_override: Option<spoof::State>,
     ) -> EthResult<RpcGasEstimate> {
-        let bundle_size = op.single_uo_bundle_size_bytes();
-        if bundle_size > self.chain_spec.max_transaction_size_bytes {
-            return Err(EthRpcError::InvalidParams(format!(
-                "User operation in bundle size {} exceeds max transaction size {}",
-                bundle_size, self.chain_spec.max_transaction_size_bytes
-            )));
-        }
-
-        self.router
-            .estimate_gas(&entry_point, op, state_override)
-            .await
+        estimate_user_operation_gas(
+            &self.chain_spec,
+            &self.router,
+            op,
+            entry_point,
+            state_override,
+        )
+        .await
     }
 
     pub(crate) async fn get_user_operation_by_hash(
@@ -134,19 +148,7 @@ where
         &self,
         hash: H256,
     ) -> EthResult<Option<RpcUserOperationReceipt>> {
-        if hash == H256::zero() {
-            return Err(EthRpcError::InvalidParams(
-                "Missing/invalid userOpHash".to_string(),
-            ));
-        }
-
-        let futs = self
-            .router
-            .entry_points()
-            .map(|ep| self.router.get_receipt(ep, hash));
-
-        let results = future::try_join_all(futs).await?;
-        Ok(results.into_iter().find_map(|x| x))
+        get_user_operation_receipt(&self.router, hash).await
     }
 
     pub(crate) async fn supported_entry_points(&self) -> EthResult<Vec<String>> {
@@ -181,27 +183,178 @@ where
     }
 }
 
+/// Looks up the receipt of a user operation across all entry points
+pub(crate) async fn get_user_operation_receipt(
+    router: &EntryPointRouter,
+    hash: H256,
+) -> EthResult<Option<RpcUserOperationReceipt>> {
+    if hash == H256::zero() {
+        return Err(EthRpcError::InvalidParams(
+            "Missing/invalid userOpHash".to_string(),
+        ));
+    }
+
+    let futs = router.entry_points().map(|ep| router.get_receipt(ep, hash));
+
+    let results = future::try_join_all(futs).await?;
+    Ok(results.into_iter().find_map(|x| x))
+}
+
+/// Estimates the gas of a user operation, rejecting operations too large to fit in a bundle
+pub(crate) async fn estimate_user_operation_gas(
+    chain_spec: &ChainSpec,
+    router: &EntryPointRouter,
+    op: UserOperationOptionalGas,
+    entry_point: Address,
+    state_override: Option<spoof::State>,
+) -> EthResult<RpcGasEstimate> {
+    let bundle_size = op.single_uo_bundle_size_bytes();
+    if bundle_size > chain_spec.max_transaction_size_bytes {
+        return Err(EthRpcError::InvalidParams(format!(
+            "User operation in bundle size {} exceeds max transaction size {}",
+            bundle_size, chain_spec.max_transaction_size_bytes
+        )));
+    }
+
+    router.estimate_gas(&entry_point, op, state_override).await
+}
+
+// Checks a user operation against the chain and entry point limits and adds it to the pool.
+//
+// Shared by all endpoints that submit user operations so they apply the same admission checks.
+// Custom admission hooks run after the built-in checks. If `log_rejected_ops` is set, the full
+// user operation is logged at debug level on rejection. Accepted operations are recorded in
+// `submitted_ops`.
+pub(crate) async fn add_user_operation<P: Pool>(
+    chain_spec: &ChainSpec,
+    router: &EntryPointRouter,
+    pool: &P,
+    admission: &AdmissionContext,
+    op: UserOperationVariant,
+    entry_point: Address,
+) -> EthResult<H256> {
+    // only copy the op when it may be logged, so that adds don't pay for a clone otherwise
+    let logged_op = admission.log_rejected_ops.then(|| op.clone());
+    let res =
+        check_and_add_user_operation(chain_spec, router, pool, &admission.hooks, op, entry_point)
+            .await;
+
+    match &res {
+        Ok(hash) => admission.submitted_ops.insert(*hash),
+        Err(error) => {
+            RpcMetrics::increment_rejected_ops(error.into());
+            if let Some(json) = logged_op.and_then(rejected_op_json) {
+                tracing::debug!(
+                    "rejected user operation {json} for entry point {entry_point:?}: {error}"
+                );
+            }
+        }
+    }
+    res
+}
+
+// Serializes a rejected user operation for logging
+fn rejected_op_json(op: UserOperationVariant) -> Option<String> {
+    serde_json::to_string(&RpcUserOperation::from(op)).ok()
+}
+
+async fn check_and_add_user_operation<P: Pool>(
+    chain_spec: &ChainSpec,
+    router: &EntryPointRouter,
+    pool: &P,
+    admission_hooks: &AdmissionHooks,
+    op: UserOperationVariant,
+    entry_point: Address,
+) -> EthResult<H256> {
+    let bundle_size = op.single_uo_bundle_size_bytes();
+    if bundle_size > chain_spec.max_transaction_size_bytes {
+        return Err(EthRpcError::InvalidParams(format!(
+            "User operation in bundle size {} exceeds max transaction size {}",
+            bundle_size, chain_spec.max_transaction_size_bytes
+        )));
+    }
+
+    if op.max_priority_fee_per_gas() > op.max_fee_per_gas() {
+        return Err(EthRpcError::InvalidParams(format!(
+            "maxPriorityFeePerGas {} exceeds maxFeePerGas {}",
+            op.max_priority_fee_per_gas(),
+            op.max_fee_per_gas()
+        )));
+    }
+
+    // A zero verification gas limit always fails validation, estimation never returns one
+    if op.verification_gas_limit().is_zero() {
+        return Err(EthRpcError::InvalidParams(
+            "verificationGasLimit must be greater than zero".to_string(),
+        ));
+    }
+
+    if is_reserved_sender(op.sender()) {
+        return Err(EthRpcError::InvalidParams(format!(
+            "sender {:?} is the zero address or a precompile",
+            op.sender()
+        )));
+    }
+
+    router.check_and_get_route(&entry_point, &op)?;
+
+    admission_hooks.check(&op).await?;
+
+    let hash = op.hash(entry_point, chain_spec.id);
+    let res = pool.add_op(entry_point, op).await;
+
+    // The pool rejects a nonce already used on chain before simulation, if the op itself
+    // was mined report its inclusion instead
+    if let Err(PoolError::MempoolError(MempoolError::PrecheckViolation(
+        PrecheckViolation::NonceTooLow(..),
+    ))) = &res
+    {
+        if let Some(mined) = router.get_mined_by_hash(&entry_point, hash).await? {
+            return Err(EthRpcError::OperationAlreadyIncluded(
+                OperationAlreadyIncludedData {
+                    transaction_hash: mined.transaction_hash,
+                },
+            ));
+        }
+    }
+
+    res.map_err(EthRpcError::from)
+        .log_on_error_level(Level::DEBUG, "failed to add op to the mempool")
+}
+
+/// Highest address in the precompile range, no user operation can have a sender at or below it
+const MAX_PRECOMPILE_ADDRESS: u64 = 0x09;
+
+fn is_reserved_sender(sender: Address) -> bool {
+    sender <= Address::from_low_u64_be(MAX_PRECOMPILE_ADDRESS)
+}
+
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{collections::HashMap, sync::Arc, time::Duration};
 
     use ethers::{
-        abi::AbiEncode,
-        types::{Bytes, Log, Transaction},
+        abi::{self, AbiEncode, Token},
+        prelude::EthEvent,
+        types::{Bytes, Log, Transaction, TransactionReceipt, U256},
     };
     use mockall::predicate::eq;
     use rundler_provider::{MockEntryPointV0_6, MockProvider};
-    use rundler_sim::MockGasEstimator;
+    use rundler_sim::{GasEstimationError, GasEstimator, MockGasEstimator};
     use rundler_types::{
-        contracts::v0_6::i_entry_point::{HandleOpsCall, IEntryPointCalls},
+        contracts::v0_6::i_entry_point::{
+            HandleOpsCall, IEntryPointCalls, UserOperationEventFilter,
+        },
         pool::{MockPool, PoolOperation},
-        v0_6::UserOperation,
-        EntityInfos, UserOperation as UserOperationTrait, ValidTimeRange,
+        v0_6::{self, UserOperation},
+        EntityInfos, GasEstimate, UserOperation as UserOperationTrait, ValidTimeRange,
     };
 
     use super::*;
-    use crate::eth::{
-        EntryPointRouteImpl, EntryPointRouterBuilder, UserOperationEventProviderV0_6,
+    use crate::{
+        admission::{AdmissionHook, RejectEmptySignature},
+        eth::{EntryPointRouteImpl, EntryPointRouterBuilder, UserOperationEventProviderV0_6},
+        utils::SubmittedOps,
     };
 
     #[tokio::test]
@@ -215,10 +368,11 @@ mod tests {
             entry_point: ep,
             aggregator: None,
             valid_time_range: ValidTimeRange::default(),
-            expected_code_hash: H256::random(),
+            expected_code_hash: Some(H256::random()),
             sim_block_hash: H256::random(),
             sim_block_number: 1000,
             account_is_staked: false,
+            no_replacement: false,
             entity_infos: EntityInfos::default(),
         };
 
@@ -334,6 +488,533 @@ mod tests {
         assert_eq!(res, None);
     }
 
+    #[tokio::test]
+    async fn test_send_user_op_priority_fee_exceeds_max_fee() {
+        let ep = Address::random();
+        let uo = UserOperation {
+            max_fee_per_gas: 100.into(),
+            max_priority_fee_per_gas: 101.into(),
+            ..Default::default()
+        };
+
+        let mut pool = MockPool::default();
+        pool.expect_add_op().never();
+
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let api = create_api(
+            MockProvider::default(),
+            entry_point,
+            pool,
+            MockGasEstimator::default(),
+        );
+        let res = api.send_user_operation(uo.into(), ep).await;
+        assert!(matches!(res, Err(EthRpcError::InvalidParams(_))));
+    }
+
+    #[tokio::test]
+    async fn test_send_user_op_priority_fee_equals_max_fee() {
+        let ep = Address::random();
+        let uo = UserOperation {
+            sender: Address::random(),
+            max_fee_per_gas: 100.into(),
+            max_priority_fee_per_gas: 100.into(),
+            verification_gas_limit: 100_000.into(),
+            ..Default::default()
+        };
+        let hash = uo.hash(ep, 1);
+
+        let mut pool = MockPool::default();
+        pool.expect_add_op()
+            .times(1)
+            .returning(move |_, _| Ok(hash));
+
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let api = create_api(
+            MockProvider::default(),
+            entry_point,
+            pool,
+            MockGasEstimator::default(),
+        );
+        let res = api.send_user_operation(uo.into(), ep).await.unwrap();
+        assert_eq!(res, hash);
+    }
+
+    #[tokio::test]
+    async fn test_send_user_op_zero_verification_gas_limit() {
+        let ep = Address::random();
+        let uo = UserOperation {
+            sender: Address::random(),
+            verification_gas_limit: 0.into(),
+            ..Default::default()
+        };
+        let valid = UserOperation {
+            verification_gas_limit: 1.into(),
+            ..uo.clone()
+        };
+        let hash = valid.hash(ep, 1);
+
+        let mut pool = MockPool::default();
+        pool.expect_add_op()
+            .times(1)
+            .returning(move |_, _| Ok(hash));
+
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let api = create_api(
+            MockProvider::default(),
+            entry_point,
+            pool,
+            MockGasEstimator::default(),
+        );
+        let res = api.send_user_operation(uo.into(), ep).await;
+        assert!(matches!(res, Err(EthRpcError::InvalidParams(_))));
+
+        let res = api.send_user_operation(valid.into(), ep).await.unwrap();
+        assert_eq!(res, hash);
+    }
+
+    #[tokio::test]
+    async fn test_send_user_op_already_included() {
+        let cs = ChainSpec {
+            id: 1,
+            ..Default::default()
+        };
+        let ep = cs.entry_point_address_v0_6;
+        let uo = UserOperation {
+            sender: Address::random(),
+            nonce: 5.into(),
+            verification_gas_limit: 100_000.into(),
+            ..Default::default()
+        };
+
+        // the op's nonce has been consumed, the next valid nonce is 6
+        let mut pool = MockPool::default();
+        pool.expect_add_op().returning(|_, _| {
+            Err(
+                MempoolError::PrecheckViolation(PrecheckViolation::NonceTooLow(5.into(), 6.into()))
+                    .into(),
+            )
+        });
+
+        let tx_data: Bytes = IEntryPointCalls::HandleOps(HandleOpsCall {
+            beneficiary: Address::zero(),
+            ops: vec![uo.clone()],
+        })
+        .encode()
+        .into();
+        let tx = Transaction {
+            to: Some(ep),
+            input: tx_data,
+            block_number: Some(1000.into()),
+            block_hash: Some(H256::random()),
+            ..Default::default()
+        };
+        let tx_hash = tx.hash();
+        let log = Log {
+            address: ep,
+            transaction_hash: Some(tx_hash),
+            ..Default::default()
+        };
+
+        let mut provider = MockProvider::default();
+        provider.expect_get_block_number().returning(|| Ok(1000));
+        provider
+            .expect_get_logs()
+            .returning(move |_| Ok(vec![log.clone()]));
+        provider
+            .expect_get_transaction()
+            .with(eq(tx_hash))
+            .returning(move |_| Ok(Some(tx.clone())));
+
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let api = create_api(provider, entry_point, pool, MockGasEstimator::default());
+        let res = api.send_user_operation(uo.into(), ep).await;
+        match res {
+            Err(EthRpcError::OperationAlreadyIncluded(data)) => {
+                assert_eq!(data.transaction_hash, Some(tx_hash));
+            }
+            _ => panic!("expected already included error, got {res:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_user_op_nonce_too_low_not_included() {
+        let cs = ChainSpec {
+            id: 1,
+            ..Default::default()
+        };
+        let ep = cs.entry_point_address_v0_6;
+        let uo = UserOperation {
+            sender: Address::random(),
+            nonce: 5.into(),
+            verification_gas_limit: 100_000.into(),
+            ..Default::default()
+        };
+
+        // the nonce was consumed by a different op
+        let mut pool = MockPool::default();
+        pool.expect_add_op().returning(|_, _| {
+            Err(
+                MempoolError::PrecheckViolation(PrecheckViolation::NonceTooLow(5.into(), 6.into()))
+                    .into(),
+            )
+        });
+
+        let mut provider = MockProvider::default();
+        provider.expect_get_block_number().returning(|| Ok(1000));
+        provider.expect_get_logs().returning(|_| Ok(vec![]));
+
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let api = create_api(provider, entry_point, pool, MockGasEstimator::default());
+        let res = api.send_user_operation(uo.into(), ep).await;
+        match res {
+            Err(EthRpcError::PrecheckFailed(PrecheckViolation::NonceTooLow(nonce, min))) => {
+                assert_eq!(nonce, 5.into());
+                assert_eq!(min, 6.into());
+            }
+            _ => panic!("expected nonce too low error, got {res:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_user_op_receipt_effective_gas_price() {
+        let cs = ChainSpec {
+            id: 1,
+            ..Default::default()
+        };
+        let ep = cs.entry_point_address_v0_6;
+        let hash = H256::random();
+        let tx_hash = H256::random();
+
+        // charged 6_000_000 wei for 100_000 gas
+        let log = Log {
+            address: ep,
+            topics: vec![
+                UserOperationEventFilter::signature(),
+                hash,
+                H256::from(Address::random()),
+                H256::zero(),
+            ],
+            data: abi::encode(&[
+                Token::Uint(U256::zero()),
+                Token::Bool(true),
+                Token::Uint(U256::from(6_000_000)),
+                Token::Uint(U256::from(100_000)),
+            ])
+            .into(),
+            transaction_hash: Some(tx_hash),
+            ..Default::default()
+        };
+        let receipt = TransactionReceipt {
+            transaction_hash: tx_hash,
+            logs: vec![log.clone()],
+            ..Default::default()
+        };
+
+        // only the event and the tx receipt are needed
+        let mut provider = MockProvider::default();
+        provider.expect_get_block_number().returning(|| Ok(1000));
+        provider
+            .expect_get_logs()
+            .returning(move |_| Ok(vec![log.clone()]));
+        provider
+            .expect_get_transaction_receipt()
+            .with(eq(tx_hash))
+            .returning(move |_| Ok(Some(receipt.clone())));
+
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let api = create_api(
+            provider,
+            entry_point,
+            MockPool::default(),
+            MockGasEstimator::default(),
+        );
+        let receipt = api.get_user_operation_receipt(hash).await.unwrap().unwrap();
+        assert_eq!(receipt.effective_gas_price, U256::from(60));
+    }
+
+    struct RejectSender(Address);
+
+    #[async_trait::async_trait]
+    impl AdmissionHook for RejectSender {
+        async fn check(&self, op: &UserOperationVariant) -> Result<(), EthRpcError> {
+            if op.sender() == self.0 {
+                return Err(EthRpcError::InvalidParams("sender not allowed".to_string()));
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_user_op_admission_hook() {
+        let ep = Address::random();
+        let rejected = Address::random();
+        let uo = UserOperation {
+            sender: Address::random(),
+            verification_gas_limit: 100_000.into(),
+            ..Default::default()
+        };
+        let hash = uo.hash(ep, 1);
+
+        let mut pool = MockPool::default();
+        pool.expect_add_op()
+            .times(1)
+            .returning(move |_, _| Ok(hash));
+
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let mut api = create_api(
+            MockProvider::default(),
+            entry_point,
+            pool,
+            MockGasEstimator::default(),
+        );
+        api.admission.hooks = AdmissionHooks::new(vec![Arc::new(RejectSender(rejected))]);
+
+        let res = api
+            .send_user_operation(
+                UserOperation {
+                    sender: rejected,
+                    ..uo.clone()
+                }
+                .into(),
+                ep,
+            )
+            .await;
+        assert!(matches!(res, Err(EthRpcError::InvalidParams(_))));
+
+        let res = api.send_user_operation(uo.into(), ep).await.unwrap();
+        assert_eq!(res, hash);
+    }
+
+    #[tokio::test]
+    async fn test_empty_signature_rejected_on_send_only() {
+        let ep = Address::random();
+        let uo = UserOperation {
+            sender: Address::random(),
+            verification_gas_limit: 100_000.into(),
+            ..Default::default()
+        };
+
+        let mut pool = MockPool::default();
+        pool.expect_add_op().never();
+
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let mut gas_estimator = MockGasEstimator::default();
+        gas_estimator.expect_estimate_op_gas().returning(|_, _| {
+            Ok(GasEstimate {
+                pre_verification_gas: 1.into(),
+                call_gas_limit: 2.into(),
+                verification_gas_limit: 3.into(),
+                paymaster_verification_gas_limit: None,
+                warnings: vec![],
+            })
+        });
+
+        let mut api = create_api(MockProvider::default(), entry_point, pool, gas_estimator);
+        api.admission.hooks = AdmissionHooks::new(vec![Arc::new(RejectEmptySignature)]);
+
+        let res = api.send_user_operation(uo.clone().into(), ep).await;
+        assert!(matches!(res, Err(EthRpcError::SignatureCheckFailed)));
+
+        // estimation runs before the op is signed, so an empty signature is allowed
+        let op = UserOperationOptionalGas::V0_6(v0_6::UserOperationOptionalGas {
+            sender: uo.sender,
+            nonce: U256::zero(),
+            init_code: Bytes::new(),
+            call_data: Bytes::new(),
+            call_gas_limit: None,
+            verification_gas_limit: None,
+            pre_verification_gas: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            paymaster_and_data: Bytes::new(),
+            signature: Bytes::new(),
+        });
+        assert!(api.estimate_user_operation_gas(op, ep, None).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_send_user_op_precompile_sender() {
+        let ep = Address::random();
+
+        let mut pool = MockPool::default();
+        pool.expect_add_op().never();
+
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let api = create_api(
+            MockProvider::default(),
+            entry_point,
+            pool,
+            MockGasEstimator::default(),
+        );
+
+        for sender in [
+            Address::zero(),
+            Address::from_low_u64_be(1),
+            Address::from_low_u64_be(9),
+        ] {
+            let uo = UserOperation {
+                sender,
+                ..Default::default()
+            };
+            let res = api.send_user_operation(uo.into(), ep).await;
+            assert!(matches!(res, Err(EthRpcError::InvalidParams(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_user_op_normal_sender() {
+        let ep = Address::random();
+        let uo = UserOperation {
+            sender: Address::from_low_u64_be(0x0a),
+            verification_gas_limit: 100_000.into(),
+            ..Default::default()
+        };
+        let hash = uo.hash(ep, 1);
+
+        let mut pool = MockPool::default();
+        pool.expect_add_op()
+            .times(1)
+            .returning(move |_, _| Ok(hash));
+
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let api = create_api(
+            MockProvider::default(),
+            entry_point,
+            pool,
+            MockGasEstimator::default(),
+        );
+        let res = api.send_user_operation(uo.into(), ep).await.unwrap();
+        assert_eq!(res, hash);
+    }
+
+    #[test]
+    fn test_rejected_op_json() {
+        let uo: UserOperationVariant = UserOperation {
+            sender: Address::random(),
+            call_data: vec![1, 2, 3].into(),
+            ..Default::default()
+        }
+        .into();
+
+        let json = rejected_op_json(uo.clone()).unwrap();
+        let logged: RpcUserOperation = serde_json::from_str(&json).unwrap();
+        assert_eq!(logged, RpcUserOperation::from(uo));
+    }
+
+    struct SlowGasEstimator(Duration);
+
+    #[async_trait::async_trait]
+    impl GasEstimator for SlowGasEstimator {
+        type UserOperationOptionalGas = v0_6::UserOperationOptionalGas;
+
+        async fn estimate_op_gas(
+            &self,
+            _op: v0_6::UserOperationOptionalGas,
+            _state_override: spoof::State,
+        ) -> Result<GasEstimate, GasEstimationError> {
+            tokio::time::sleep(self.0).await;
+            Ok(GasEstimate {
+                pre_verification_gas: U256::zero(),
+                call_gas_limit: U256::zero(),
+                verification_gas_limit: U256::zero(),
+                paymaster_verification_gas_limit: None,
+                warnings: vec![],
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas_method_timeout() {
+        let ep = Address::random();
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+        let chain_spec = ChainSpec {
+            id: 1,
+            ..Default::default()
+        };
+        let router = EntryPointRouterBuilder::default()
+            .v0_6(EntryPointRouteImpl::new(
+                Arc::new(entry_point),
+                SlowGasEstimator(Duration::from_secs(10)),
+                UserOperationEventProviderV0_6::new(
+                    chain_spec.clone(),
+                    Arc::new(MockProvider::default()),
+                    None,
+                ),
+            ))
+            .build();
+
+        let timeout = Duration::from_millis(100);
+        let api = EthApi::new(
+            chain_spec,
+            router,
+            MockPool::default(),
+            ErrorCompatMode::Standard,
+            MethodTimeouts::new(HashMap::from([(
+                "eth_estimateUserOperationGas".to_string(),
+                timeout,
+            )])),
+            AdmissionContext {
+                hooks: AdmissionHooks::default(),
+                log_rejected_ops: false,
+                submitted_ops: SubmittedOps::new(10),
+            },
+        );
+
+        let op = UserOperationOptionalGas::V0_6(v0_6::UserOperationOptionalGas {
+            sender: Address::zero(),
+            nonce: U256::zero(),
+            init_code: Bytes::new(),
+            call_data: Bytes::new(),
+            call_gas_limit: None,
+            verification_gas_limit: None,
+            pre_verification_gas: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            paymaster_and_data: Bytes::new(),
+            signature: Bytes::new(),
+        });
+
+        let start = std::time::Instant::now();
+        let err = api
+            .method_timeouts
+            .call(
+                "eth_estimateUserOperationGas",
+                api.estimate_user_operation_gas(op, ep, None),
+            )
+            .await
+            .unwrap_err();
+        let elapsed = start.elapsed();
+
+        assert_eq!(err.code(), -32002);
+        assert_eq!(
+            err.message(),
+            "eth_estimateUserOperationGas timed out after 100ms"
+        );
+        assert!(elapsed >= timeout);
+        assert!(elapsed < Duration::from_secs(10));
+    }
+
     fn create_api(
         provider: MockProvider,
         ep: MockEntryPointV0_6,
@@ -358,7 +1039,14 @@ mod tests {
         EthApi {
             router,
             chain_spec,
+            error_compat_mode: ErrorCompatMode::Standard,
+            method_timeouts: MethodTimeouts::default(),
             pool,
+            admission: AdmissionContext {
+                hooks: AdmissionHooks::default(),
+                log_rejected_ops: false,
+                submitted_ops: SubmittedOps::new(10),
+            },
         }
     }
 }
@@ -11,11 +11,13 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
-use ethers::types::{Address, Bytes, U256};
+use ethers::types::{Address, Bytes, H256, U256};
 use jsonrpsee::types::{
-    error::{CALL_EXECUTION_FAILED_CODE, INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE},
+    error::{
+        CALL_EXECUTION_FAILED_CODE, INTERNAL_ERROR_CODE, INVALID_PARAMS_CODE, SERVER_IS_BUSY_CODE,
+    },
     ErrorObjectOwned,
 };
 use rundler_provider::ProviderError;
@@ -44,11 +46,27 @@ const UNSUPORTED_AGGREGATOR_CODE: i32 = -32506;
 const SIGNATURE_CHECK_FAILED_CODE: i32 = -32507;
 const PAYMASTER_DEPOSIT_TOO_LOW: i32 = -32508;
 const EXECUTION_REVERTED: i32 = -32521;
+const REQUEST_TIMEOUT_CODE: i32 = -32002;
 
 pub(crate) type EthResult<T> = Result<T, EthRpcError>;
 
+/// Error response convention used when converting `eth_` errors to RPC errors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, strum::EnumString)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum ErrorCompatMode {
+    /// Standard ERC-4337 error codes and data
+    #[default]
+    Standard,
+    /// Alchemy/Infura compatible errors.
+    ///
+    /// Revert reasons are always surfaced under `data.revertData` and paymaster
+    /// rejections are reported with the entry point validation code.
+    Vendor,
+}
+
 /// Error returned by the RPC server eth namespace
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, thiserror::Error, strum::IntoStaticStr)]
+#[strum(serialize_all = "snake_case")]
 pub enum EthRpcError {
     #[error(transparent)]
     Internal(#[from] anyhow::Error),
@@ -116,6 +134,9 @@ pub enum EthRpcError {
     /// Operation already known
     #[error("already known")]
     OperationAlreadyKnown,
+    /// Operation nonce has already been used on chain
+    #[error("operation already included on chain")]
+    OperationAlreadyIncluded(OperationAlreadyIncludedData),
     /// Other internal errors
     #[error("Invalid UserOp signature or paymaster signature")]
     SignatureCheckFailed,
@@ -130,11 +151,20 @@ pub enum EthRpcError {
     #[error("validation reverted: {0}")]
     ValidationRevert(ValidationRevertData),
     #[error("{0}")]
-    ExecutionReverted(String),
+    ExecutionReverted(String, Bytes),
     #[error("execution reverted")]
     ExecutionRevertedWithBytes(ExecutionRevertedWithBytesData),
     #[error("operation rejected by mempool: {0}")]
     OperationRejected(String),
+    /// Mempool is near capacity and not accepting new operations
+    #[error("mempool is busy, try again later")]
+    PoolBusy,
+    /// Mempool is full and the operation pays less than every operation in it
+    #[error("mempool is full, try again later or increase fees")]
+    PoolFull,
+    /// The request did not complete within the configured timeout for its method
+    #[error("{0} timed out after {1:?}")]
+    RequestTimeout(&'static str, Duration),
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -248,6 +278,12 @@ impl ReplacementUnderpricedData {
     }
 }
 
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationAlreadyIncludedData {
+    pub transaction_hash: Option<H256>,
+}
+
 #[derive(Debug, Clone, Copy, Serialize)]
 pub struct UnsupportedAggregatorData {
     pub aggregator: Address,
@@ -259,6 +295,43 @@ pub struct ExecutionRevertedWithBytesData {
     pub revert_data: Bytes,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VendorRevertData {
+    revert_data: Bytes,
+}
+
+impl EthRpcError {
+    /// Convert into an RPC error object following the given compatibility convention
+    pub(crate) fn into_rpc_error(self, mode: ErrorCompatMode) -> ErrorObjectOwned {
+        match mode {
+            ErrorCompatMode::Standard => self.into(),
+            ErrorCompatMode::Vendor => self.into_vendor_rpc_error(),
+        }
+    }
+
+    fn into_vendor_rpc_error(self) -> ErrorObjectOwned {
+        let msg = self.to_string();
+
+        match self {
+            EthRpcError::ValidationRevert(data) => rpc_err_with_data(
+                ENTRYPOINT_VALIDATION_REJECTED_CODE,
+                msg,
+                VendorRevertData {
+                    revert_data: data.revert_data.unwrap_or_default(),
+                },
+            ),
+            EthRpcError::ExecutionReverted(_, revert_data) => {
+                rpc_err_with_data(EXECUTION_REVERTED, msg, VendorRevertData { revert_data })
+            }
+            EthRpcError::PaymasterValidationRejected(_) => {
+                rpc_err(ENTRYPOINT_VALIDATION_REJECTED_CODE, msg)
+            }
+            _ => self.into(),
+        }
+    }
+}
+
 impl From<PoolError> for EthRpcError {
     fn from(value: PoolError) -> Self {
         match value {
@@ -296,9 +369,6 @@ impl From<MempoolError> for EthRpcError {
             MempoolError::SenderAddressUsedAsAlternateEntity(address) => {
                 Self::SenderAddressUsedAsAlternateEntity(address)
             }
-            MempoolError::DiscardedOnInsert => {
-                Self::OperationRejected("discarded on insert".to_owned())
-            }
             MempoolError::PrecheckViolation(violation) => violation.into(),
             MempoolError::SimulationViolation(violation) => violation.into(),
             MempoolError::UnsupportedAggregator(a) => {
@@ -307,14 +377,29 @@ impl From<MempoolError> for EthRpcError {
             MempoolError::UnknownEntryPoint(a) => {
                 Self::EntryPointValidationRejected(format!("unknown entry point: {}", a))
             }
-            MempoolError::OperationDropTooSoon(_, _, _) => Self::InvalidParams(value.to_string()),
+            MempoolError::OperationDropTooSoon(_, _, _)
+            | MempoolError::ReplacementNotAllowed
+            | MempoolError::MaxFingerprintOperationsReached(_) => {
+                Self::InvalidParams(value.to_string())
+            }
+            MempoolError::PoolBusy => Self::PoolBusy,
+            MempoolError::PoolFull => Self::PoolFull,
         }
     }
 }
 
 impl From<PrecheckViolation> for EthRpcError {
     fn from(value: PrecheckViolation) -> Self {
-        Self::PrecheckFailed(value)
+        match value {
+            // an undeployed paymaster would revert during validation, report it the same way
+            PrecheckViolation::PaymasterIsNotContract(paymaster) => {
+                Self::PaymasterValidationRejected(PaymasterValidationRejectedData {
+                    paymaster,
+                    reason: value.to_string(),
+                })
+            }
+            _ => Self::PrecheckFailed(value),
+        }
     }
 }
 
@@ -341,8 +426,10 @@ impl From<SimulationViolation> for EthRpcError {
             SimulationViolation::UsedForbiddenPrecompile(_, _, _)
             | SimulationViolation::AccessedUndeployedContract(_, _)
             | SimulationViolation::AccessedUnsupportedContractType(_, _)
-            | SimulationViolation::CalledBannedEntryPointMethod(_)
             | SimulationViolation::CallHadValue(_) => Self::OpcodeViolationMap(value),
+            SimulationViolation::CalledBannedEntryPointMethod(entity) => {
+                Self::OpcodeViolation(entity.kind, Opcode::CALL)
+            }
             SimulationViolation::FactoryCalledCreate2Twice(_) => {
                 Self::OpcodeViolation(EntityType::Factory, Opcode::CREATE2)
             }
@@ -365,6 +452,13 @@ impl From<SimulationViolation> for EthRpcError {
                 )))
             }
             SimulationViolation::AggregatorValidationFailed => Self::SignatureCheckFailed,
+            SimulationViolation::InvalidTimeRange(valid_until, valid_after) => {
+                Self::OutOfTimeRange(OutOfTimeRangeData {
+                    valid_until,
+                    valid_after,
+                    paymaster: None,
+                })
+            }
             SimulationViolation::OutOfGas(entity) => Self::OutOfGas(entity),
             SimulationViolation::ValidationRevert(revert) => Self::ValidationRevert(revert.into()),
             _ => Self::SimulationFailed(value),
@@ -410,6 +504,9 @@ impl From<EthRpcError> for ErrorObjectOwned {
                 rpc_err_with_data(INVALID_PARAMS_CODE, msg, data)
             }
             EthRpcError::OperationAlreadyKnown => rpc_err(INVALID_PARAMS_CODE, msg),
+            EthRpcError::OperationAlreadyIncluded(data) => {
+                rpc_err_with_data(INVALID_PARAMS_CODE, msg, data)
+            }
             EthRpcError::MaxOperationsReached(_, _) => rpc_err(STAKE_TOO_LOW_CODE, msg),
             EthRpcError::SignatureCheckFailed
             | EthRpcError::AccountSignatureCheckFailed
@@ -417,7 +514,7 @@ impl From<EthRpcError> for ErrorObjectOwned {
                 rpc_err(SIGNATURE_CHECK_FAILED_CODE, msg)
             }
             EthRpcError::PrecheckFailed(_) => rpc_err(CALL_EXECUTION_FAILED_CODE, msg),
-            EthRpcError::ExecutionReverted(_) => rpc_err(EXECUTION_REVERTED, msg),
+            EthRpcError::ExecutionReverted(..) => rpc_err(EXECUTION_REVERTED, msg),
             EthRpcError::ExecutionRevertedWithBytes(data) => {
                 rpc_err_with_data(EXECUTION_REVERTED, msg, data)
             }
@@ -425,6 +522,8 @@ impl From<EthRpcError> for ErrorObjectOwned {
                 rpc_err_with_data(ENTRYPOINT_VALIDATION_REJECTED_CODE, msg, data)
             }
             EthRpcError::OperationRejected(_) => rpc_err(INVALID_PARAMS_CODE, msg),
+            EthRpcError::PoolBusy | EthRpcError::PoolFull => rpc_err(SERVER_IS_BUSY_CODE, msg),
+            EthRpcError::RequestTimeout(_, _) => rpc_err(REQUEST_TIMEOUT_CODE, msg),
         }
     }
 }
@@ -449,8 +548,8 @@ impl From<GasEstimationError> for EthRpcError {
     fn from(e: GasEstimationError) -> Self {
         match e {
             GasEstimationError::RevertInValidation(revert) => Self::ValidationRevert(revert.into()),
-            GasEstimationError::RevertInCallWithMessage(message) => {
-                Self::ExecutionReverted(message)
+            GasEstimationError::RevertInCallWithMessage(message, revert_data) => {
+                Self::ExecutionReverted(message, revert_data)
             }
             GasEstimationError::RevertInCallWithBytes(b) => {
                 Self::ExecutionRevertedWithBytes(ExecutionRevertedWithBytesData { revert_data: b })
@@ -468,3 +567,93 @@ impl From<GasEstimationError> for EthRpcError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_point_reentrancy_is_opcode_violation() {
+        let violation = SimulationViolation::CalledBannedEntryPointMethod(Entity {
+            kind: EntityType::Account,
+            address: Address::zero(),
+        });
+
+        let err: EthRpcError = violation.into();
+        assert!(matches!(
+            err,
+            EthRpcError::OpcodeViolation(EntityType::Account, Opcode::CALL)
+        ));
+
+        let obj: ErrorObjectOwned = err.into();
+        assert_eq!(obj.code(), OPCODE_VIOLATION_CODE);
+    }
+
+    #[test]
+    fn test_pool_full_is_server_busy() {
+        let err: EthRpcError = MempoolError::PoolFull.into();
+        assert!(matches!(err, EthRpcError::PoolFull));
+
+        let obj: ErrorObjectOwned = err.into();
+        assert_eq!(obj.code(), SERVER_IS_BUSY_CODE);
+    }
+
+    #[test]
+    fn test_vendor_validation_revert() {
+        let revert_data: Bytes = vec![0xde, 0xad, 0xbe, 0xef].into();
+        let err = EthRpcError::ValidationRevert(ValidationRevert::Unknown(revert_data).into());
+
+        let obj = err.into_rpc_error(ErrorCompatMode::Vendor);
+        assert_eq!(obj.code(), ENTRYPOINT_VALIDATION_REJECTED_CODE);
+        assert_eq!(obj.data().unwrap().get(), r#"{"revertData":"0xdeadbeef"}"#);
+    }
+
+    #[test]
+    fn test_vendor_execution_reverted() {
+        let revert_data: Bytes = vec![0xde, 0xad, 0xbe, 0xef].into();
+        let err = EthRpcError::ExecutionReverted("reverted".to_string(), revert_data);
+
+        let obj = err.into_rpc_error(ErrorCompatMode::Vendor);
+        assert_eq!(obj.code(), EXECUTION_REVERTED);
+        assert_eq!(obj.message(), "reverted");
+        assert_eq!(obj.data().unwrap().get(), r#"{"revertData":"0xdeadbeef"}"#);
+    }
+
+    #[test]
+    fn test_vendor_paymaster_rejected() {
+        let err = EthRpcError::PaymasterValidationRejected(PaymasterValidationRejectedData {
+            paymaster: Address::zero(),
+            reason: "rejected".to_string(),
+        });
+
+        let obj = err.into_rpc_error(ErrorCompatMode::Vendor);
+        assert_eq!(obj.code(), ENTRYPOINT_VALIDATION_REJECTED_CODE);
+        assert_eq!(obj.message(), "rejected");
+        assert!(obj.data().is_none());
+    }
+
+    #[test]
+    fn test_paymaster_not_deployed_rejected() {
+        let paymaster = Address::random();
+        let err: EthRpcError = PrecheckViolation::PaymasterIsNotContract(paymaster).into();
+
+        let EthRpcError::PaymasterValidationRejected(data) = &err else {
+            panic!("expected paymaster validation rejected, got {err:?}");
+        };
+        assert_eq!(data.paymaster, paymaster);
+
+        let obj = err.into_rpc_error(ErrorCompatMode::Standard);
+        assert_eq!(obj.code(), PAYMASTER_VALIDATION_REJECTED_CODE);
+    }
+
+    #[test]
+    fn test_standard_paymaster_rejected() {
+        let err = EthRpcError::PaymasterValidationRejected(PaymasterValidationRejectedData {
+            paymaster: Address::zero(),
+            reason: "rejected".to_string(),
+        });
+
+        let obj = err.into_rpc_error(ErrorCompatMode::Standard);
+        assert_eq!(obj.code(), PAYMASTER_VALIDATION_REJECTED_CODE);
+    }
+}
@@ -12,7 +12,12 @@
 // If not, see https://www.gnu.org/licenses/.
 
 use anyhow::bail;
-use ethers::types::{Log, TransactionReceipt, H256};
+use ethers::{
+    types::{Address, Bytes, Log, TransactionReceipt, H256, U256},
+    utils::keccak256,
+};
+use rundler_types::split_nonce;
+use rundler_utils::eth;
 
 use crate::types::{RpcUserOperationByHash, RpcUserOperationReceipt};
 
@@ -78,11 +83,76 @@ fn filter_receipt_logs_matching_user_op(
     Ok(logs[start_idx..=end_idx].to_vec())
 }
 
+// Decodes the revert reason of a `UserOperationRevertReason` event into a readable string.
+//
+// Falls back to the hex encoded bytes if the reason is not a `revert("message")` style error
+fn decode_revert_reason(revert_reason: &Bytes) -> String {
+    eth::parse_revert_message(revert_reason).unwrap_or_else(|| revert_reason.to_string())
+}
+
+// Splits the nonce of a `UserOperationEvent` into its 2D nonce key and sequence for the receipt.
+//
+// Both are `None` for the default key, as there is only a single nonce stream to track.
+fn receipt_nonce_parts(nonce: U256) -> (Option<U256>, Option<U256>) {
+    let (key, sequence) = split_nonce(nonce);
+    if key.is_zero() {
+        (None, None)
+    } else {
+        (Some(key), Some(sequence.into()))
+    }
+}
+
+// Finds the ERC-20 payment a token paymaster collected from the sender of a user operation.
+//
+// Token paymasters charge the sender by transferring tokens to themselves, so the first `Transfer`
+// from the sender to the paymaster identifies the token. The cost is net of any refund the
+// paymaster transferred back to the sender in the same token.
+fn find_paymaster_token_payment(
+    sender: Address,
+    paymaster: Address,
+    logs: &[Log],
+) -> Option<(Address, U256)> {
+    if paymaster.is_zero() {
+        return None;
+    }
+
+    let transfer_topic = H256(keccak256("Transfer(address,address,uint256)"));
+    let transfers = logs
+        .iter()
+        .filter(|l| l.topics.len() == 3 && l.topics[0] == transfer_topic && l.data.len() == 32)
+        .map(|l| {
+            (
+                l.address,
+                Address::from(l.topics[1]),
+                Address::from(l.topics[2]),
+                U256::from_big_endian(&l.data),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let token = transfers
+        .iter()
+        .find(|(_, from, to, _)| *from == sender && *to == paymaster)?
+        .0;
+
+    let (paid, refunded) = transfers.iter().filter(|(t, ..)| *t == token).fold(
+        (U256::zero(), U256::zero()),
+        |(paid, refunded), &(_, from, to, amount)| {
+            if from == sender && to == paymaster {
+                (paid.saturating_add(amount), refunded)
+            } else if from == paymaster && to == sender {
+                (paid, refunded.saturating_add(amount))
+            } else {
+                (paid, refunded)
+            }
+        },
+    );
+    Some((token, paid.saturating_sub(refunded)))
+}
+
 #[cfg(test)]
 mod tests {
 
-    use ethers::{types::Address, utils::keccak256};
-
     use super::*;
 
     const UO_OP_TOPIC: &str = "user-op-event-topic";
@@ -203,6 +273,35 @@ mod tests {
         assert!(result.is_err(), "{:?}", result.unwrap());
     }
 
+    #[test]
+    fn test_find_paymaster_token_payment() {
+        let sender = Address::random();
+        let paymaster = Address::random();
+        let token = Address::random();
+        let other_token = Address::random();
+
+        let logs = vec![
+            given_log("other-topic", "some-hash"),
+            given_transfer(other_token, Address::random(), sender, 5.into()),
+            given_transfer(token, sender, paymaster, 100.into()),
+            given_transfer(token, paymaster, sender, 30.into()),
+        ];
+        assert_eq!(
+            find_paymaster_token_payment(sender, paymaster, &logs),
+            Some((token, 70.into()))
+        );
+
+        // no transfer to the paymaster, not a token paymaster
+        assert_eq!(
+            find_paymaster_token_payment(sender, Address::random(), &logs),
+            None
+        );
+        assert_eq!(
+            find_paymaster_token_payment(sender, Address::zero(), &logs),
+            None
+        );
+    }
+
     fn given_log(topic_0: &str, topic_1: &str) -> Log {
         Log {
             topics: vec![
@@ -213,6 +312,21 @@ mod tests {
         }
     }
 
+    fn given_transfer(token: Address, from: Address, to: Address, amount: U256) -> Log {
+        let mut data = [0; 32];
+        amount.to_big_endian(&mut data);
+        Log {
+            address: token,
+            topics: vec![
+                H256(keccak256("Transfer(address,address,uint256)")),
+                from.into(),
+                to.into(),
+            ],
+            data: data.to_vec().into(),
+            ..Default::default()
+        }
+    }
+
     fn given_receipt(logs: Vec<Log>) -> TransactionReceipt {
         TransactionReceipt {
             logs,
@@ -24,7 +24,10 @@ use rundler_types::{
     v0_6::UserOperation,
 };
 
-use super::common::{EntryPointFilters, UserOperationEventProviderImpl};
+use super::{
+    common::{EntryPointFilters, UserOperationEventProviderImpl},
+    decode_revert_reason, find_paymaster_token_payment, receipt_nonce_parts,
+};
 use crate::types::RpcUserOperationReceipt;
 
 pub(crate) type UserOperationEventProviderV0_6<P> =
@@ -51,7 +54,7 @@ impl EntryPointFilters for EntryPointFiltersV0_6 {
             let revert_reason_evt: Option<Self::UserOperationRevertReasonFilter> = logs
                 .iter()
                 .filter(|l| l.topics.len() > 1 && l.topics[1] == hash)
-                .map_while(|l| {
+                .filter_map(|l| {
                     Self::UserOperationRevertReasonFilter::decode_log(&RawLog {
                         topics: l.topics.clone(),
                         data: l.data.to_vec(),
@@ -61,18 +64,31 @@ impl EntryPointFilters for EntryPointFiltersV0_6 {
                 .next();
 
             revert_reason_evt
-                .map(|r| r.revert_reason.to_string())
+                .map(|r| decode_revert_reason(&r.revert_reason))
                 .unwrap_or_default()
         };
 
+        let (nonce_key, nonce_sequence) = receipt_nonce_parts(event.nonce);
+        let (paymaster_token, paymaster_token_cost) =
+            find_paymaster_token_payment(event.sender, event.paymaster, &logs).unzip();
+
         RpcUserOperationReceipt {
             user_op_hash: hash,
             entry_point: entry_point.into(),
             sender: event.sender.into(),
             nonce: event.nonce,
+            nonce_key,
+            nonce_sequence,
             paymaster: event.paymaster.into(),
+            paymaster_token,
+            paymaster_token_cost,
             actual_gas_cost: event.actual_gas_cost,
             actual_gas_used: event.actual_gas_used,
+            // the entry point charges the op its gas price for all of the gas it used
+            effective_gas_price: event
+                .actual_gas_cost
+                .checked_div(event.actual_gas_used)
+                .unwrap_or_default(),
             success: event.success,
             logs,
             receipt: tx_receipt,
@@ -103,3 +119,94 @@ impl EntryPointFilters for EntryPointFiltersV0_6 {
         chain_spec.entry_point_address_v0_6
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        abi::{self, AbiEncode, Token},
+        types::U256,
+    };
+    use rundler_utils::eth::ContractRevertError;
+
+    use super::*;
+
+    #[test]
+    fn test_construct_receipt_decodes_revert_reason() {
+        let hash = H256::random();
+        let sender = Address::random();
+        let revert_reason: Bytes = ContractRevertError {
+            reason: "account reverted".to_string(),
+        }
+        .encode()
+        .into();
+
+        let revert_log = Log {
+            topics: vec![
+                UserOperationRevertReasonFilter::signature(),
+                hash,
+                H256::from(sender),
+            ],
+            data: abi::encode(&[
+                Token::Uint(U256::zero()),
+                Token::Bytes(revert_reason.to_vec()),
+            ])
+            .into(),
+            ..Default::default()
+        };
+        let event = UserOperationEventFilter {
+            user_op_hash: hash.into(),
+            sender,
+            success: false,
+            ..Default::default()
+        };
+
+        let receipt = EntryPointFiltersV0_6::construct_receipt(
+            event,
+            hash,
+            Address::random(),
+            vec![revert_log],
+            TransactionReceipt::default(),
+        );
+
+        assert!(!receipt.success);
+        assert_eq!(receipt.reason, "account reverted");
+    }
+
+    #[test]
+    fn test_construct_receipt_splits_2d_nonce() {
+        let hash = H256::random();
+        let event = UserOperationEventFilter {
+            user_op_hash: hash.into(),
+            nonce: (U256::from(5) << 64) + U256::from(9),
+            success: true,
+            ..Default::default()
+        };
+
+        let receipt = EntryPointFiltersV0_6::construct_receipt(
+            event,
+            hash,
+            Address::random(),
+            vec![],
+            TransactionReceipt::default(),
+        );
+
+        assert_eq!(receipt.nonce, (U256::from(5) << 64) + U256::from(9));
+        assert_eq!(receipt.nonce_key, Some(U256::from(5)));
+        assert_eq!(receipt.nonce_sequence, Some(U256::from(9)));
+
+        // the default nonce key omits the split
+        let receipt = EntryPointFiltersV0_6::construct_receipt(
+            UserOperationEventFilter {
+                nonce: U256::from(9),
+                success: true,
+                ..Default::default()
+            },
+            hash,
+            Address::random(),
+            vec![],
+            TransactionReceipt::default(),
+        );
+        assert_eq!(receipt.nonce_key, None);
+        assert_eq!(receipt.nonce_sequence, None);
+    }
+}
@@ -24,7 +24,10 @@ use rundler_types::{
     v0_7::UserOperation,
 };
 
-use super::common::{EntryPointFilters, UserOperationEventProviderImpl};
+use super::{
+    common::{EntryPointFilters, UserOperationEventProviderImpl},
+    decode_revert_reason, find_paymaster_token_payment, receipt_nonce_parts,
+};
 use crate::types::RpcUserOperationReceipt;
 
 pub(crate) type UserOperationEventProviderV0_7<P> =
@@ -51,7 +54,7 @@ impl EntryPointFilters for EntryPointFiltersV0_7 {
             let revert_reason_evt: Option<Self::UserOperationRevertReasonFilter> = logs
                 .iter()
                 .filter(|l| l.topics.len() > 1 && l.topics[1] == hash)
-                .map_while(|l| {
+                .filter_map(|l| {
                     Self::UserOperationRevertReasonFilter::decode_log(&RawLog {
                         topics: l.topics.clone(),
                         data: l.data.to_vec(),
@@ -61,18 +64,31 @@ impl EntryPointFilters for EntryPointFiltersV0_7 {
                 .next();
 
             revert_reason_evt
-                .map(|r| r.revert_reason.to_string())
+                .map(|r| decode_revert_reason(&r.revert_reason))
                 .unwrap_or_default()
         };
 
+        let (nonce_key, nonce_sequence) = receipt_nonce_parts(event.nonce);
+        let (paymaster_token, paymaster_token_cost) =
+            find_paymaster_token_payment(event.sender, event.paymaster, &logs).unzip();
+
         RpcUserOperationReceipt {
             user_op_hash: hash,
             entry_point: entry_point.into(),
             sender: event.sender.into(),
             nonce: event.nonce,
+            nonce_key,
+            nonce_sequence,
             paymaster: event.paymaster.into(),
+            paymaster_token,
+            paymaster_token_cost,
             actual_gas_cost: event.actual_gas_cost,
             actual_gas_used: event.actual_gas_used,
+            // the entry point charges the op its gas price for all of the gas it used
+            effective_gas_price: event
+                .actual_gas_cost
+                .checked_div(event.actual_gas_used)
+                .unwrap_or_default(),
             success: event.success,
             logs,
             receipt: tx_receipt,
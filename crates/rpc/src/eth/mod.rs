@@ -12,14 +12,17 @@
 // If not, see https://www.gnu.org/licenses/.
 
 mod api;
-pub(crate) use api::EthApi;
 pub use api::Settings as EthApiSettings;
+pub(crate) use api::{
+    add_user_operation, estimate_user_operation_gas, get_user_operation_receipt, EthApi,
+};
 
 mod router;
 pub(crate) use router::*;
 
 mod error;
-pub(crate) use error::{EthResult, EthRpcError};
+pub use error::{ErrorCompatMode, EthRpcError};
+pub(crate) use error::{EthResult, ReplacementUnderpricedData};
 mod events;
 pub(crate) use events::{UserOperationEventProviderV0_6, UserOperationEventProviderV0_7};
 mod server;
@@ -29,11 +29,16 @@ use crate::{
     },
 };
 
+// Creates a route for an entry point address that is not in the configured set
+type CustomRouteFactory = Arc<dyn Fn(Address) -> Arc<dyn EntryPointRoute> + Send + Sync>;
+
 #[derive(Default)]
 pub(crate) struct EntryPointRouterBuilder {
     entry_points: Vec<Address>,
     v0_6: Option<(Address, Arc<dyn EntryPointRoute>)>,
     v0_7: Option<(Address, Arc<dyn EntryPointRoute>)>,
+    custom_v0_6: Option<CustomRouteFactory>,
+    custom_v0_7: Option<CustomRouteFactory>,
 }
 
 impl EntryPointRouterBuilder {
@@ -69,11 +74,39 @@ impl EntryPointRouterBuilder {
         self
     }
 
+    /// Allow gas estimation of v0.6 user operations against entry points that are not
+    /// configured, using routes created by `factory` for the requested address.
+    pub(crate) fn custom_v0_6<F, R>(mut self, factory: F) -> Self
+    where
+        F: Fn(Address) -> R + Send + Sync + 'static,
+        R: EntryPointRoute,
+    {
+        self.custom_v0_6 = Some(Arc::new(move |address| -> Arc<dyn EntryPointRoute> {
+            Arc::new(factory(address))
+        }));
+        self
+    }
+
+    /// Allow gas estimation of v0.7 user operations against entry points that are not
+    /// configured, using routes created by `factory` for the requested address.
+    pub(crate) fn custom_v0_7<F, R>(mut self, factory: F) -> Self
+    where
+        F: Fn(Address) -> R + Send + Sync + 'static,
+        R: EntryPointRoute,
+    {
+        self.custom_v0_7 = Some(Arc::new(move |address| -> Arc<dyn EntryPointRoute> {
+            Arc::new(factory(address))
+        }));
+        self
+    }
+
     pub(crate) fn build(self) -> EntryPointRouter {
         EntryPointRouter {
             entry_points: self.entry_points,
             v0_6: self.v0_6,
             v0_7: self.v0_7,
+            custom_v0_6: self.custom_v0_6,
+            custom_v0_7: self.custom_v0_7,
         }
     }
 }
@@ -83,6 +116,8 @@ pub(crate) struct EntryPointRouter {
     entry_points: Vec<Address>,
     v0_6: Option<(Address, Arc<dyn EntryPointRoute>)>,
     v0_7: Option<(Address, Arc<dyn EntryPointRoute>)>,
+    custom_v0_6: Option<CustomRouteFactory>,
+    custom_v0_7: Option<CustomRouteFactory>,
 }
 
 impl EntryPointRouter {
@@ -95,27 +130,14 @@ impl EntryPointRouter {
         entry_point: &Address,
         uo: &UserOperationVariant,
     ) -> EthResult<&Arc<dyn EntryPointRoute>> {
-        match self.get_ep_version(entry_point)? {
-            EntryPointVersion::V0_6 => {
-                if !matches!(uo, UserOperationVariant::V0_6(_)) {
-                    return Err(EthRpcError::InvalidParams(format!(
-                        "Invalid user operation for entry point: {:?}",
-                        entry_point
-                    )));
-                }
-                Ok(&self.v0_6.as_ref().unwrap().1)
-            }
-            EntryPointVersion::V0_7 => {
-                if !matches!(uo, UserOperationVariant::V0_7(_)) {
-                    return Err(EthRpcError::InvalidParams(format!(
-                        "Invalid user operation for entry point: {:?}",
-                        entry_point
-                    )));
-                }
-                Ok(&self.v0_7.as_ref().unwrap().1)
-            }
-            EntryPointVersion::Unspecified => unreachable!("unspecified entry point version"),
+        let route = self.get_route(entry_point)?;
+        if route.version() != uo.version() {
+            return Err(EthRpcError::InvalidParams(format!(
+                "Invalid user operation for entry point: {:?}",
+                entry_point
+            )));
         }
+        Ok(route)
     }
 
     pub(crate) async fn get_mined_by_hash(
@@ -146,6 +168,12 @@ impl EntryPointRouter {
         uo: UserOperationOptionalGas,
         state_override: Option<spoof::State>,
     ) -> EthResult<RpcGasEstimate> {
+        if !self.entry_points.contains(entry_point) {
+            return self
+                .estimate_gas_custom(entry_point, uo, state_override)
+                .await;
+        }
+
         match self.get_ep_version(entry_point)? {
             EntryPointVersion::V0_6 => {
                 if !matches!(uo, UserOperationOptionalGas::V0_6(_)) {
@@ -187,6 +215,34 @@ impl EntryPointRouter {
         }
     }
 
+    // Estimates gas against an entry point that is not configured. Only allowed if
+    // custom entry points are enabled for the user operation's version.
+    async fn estimate_gas_custom(
+        &self,
+        entry_point: &Address,
+        uo: UserOperationOptionalGas,
+        state_override: Option<spoof::State>,
+    ) -> EthResult<RpcGasEstimate> {
+        let factory = match uo {
+            UserOperationOptionalGas::V0_6(_) => self.custom_v0_6.as_ref(),
+            UserOperationOptionalGas::V0_7(_) => self.custom_v0_7.as_ref(),
+        };
+        let Some(factory) = factory else {
+            return Err(EthRpcError::InvalidParams(format!(
+                "No entry point found for address: {:?}",
+                entry_point
+            )));
+        };
+
+        let route = factory(*entry_point);
+        let e = route.estimate_gas(uo, state_override).await?;
+        match route.version() {
+            EntryPointVersion::V0_6 => Ok(RpcGasEstimateV0_6::from(e).into()),
+            EntryPointVersion::V0_7 => Ok(RpcGasEstimateV0_7::from(e).into()),
+            EntryPointVersion::Unspecified => unreachable!("unspecified entry point version"),
+        }
+    }
+
     pub(crate) async fn check_signature(
         &self,
         entry_point: &Address,
@@ -199,7 +255,7 @@ impl EntryPointRouter {
             .map_err(Into::into)
     }
 
-    fn get_ep_version(&self, entry_point: &Address) -> EthResult<EntryPointVersion> {
+    pub(crate) fn get_ep_version(&self, entry_point: &Address) -> EthResult<EntryPointVersion> {
         if let Some((addr, _)) = self.v0_6 {
             if addr == *entry_point {
                 return Ok(EntryPointVersion::V0_6);
@@ -322,3 +378,91 @@ impl<UO, E, G, EP> EntryPointRouteImpl<UO, E, G, EP> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use ethers::types::{Bytes, U256};
+    use rundler_provider::{MockEntryPointV0_6, MockProvider};
+    use rundler_sim::MockGasEstimator;
+    use rundler_types::{chain::ChainSpec, v0_6};
+
+    use super::*;
+    use crate::eth::UserOperationEventProviderV0_6;
+
+    #[tokio::test]
+    async fn test_estimate_gas_custom_entry_point() {
+        let custom = Address::random();
+        let used = Arc::new(Mutex::new(None));
+
+        let used_clone = Arc::clone(&used);
+        let router = EntryPointRouterBuilder::default()
+            .v0_6(v0_6_route(Address::random()))
+            .custom_v0_6(move |address| {
+                *used_clone.lock().unwrap() = Some(address);
+                v0_6_route(address)
+            })
+            .build();
+
+        router
+            .estimate_gas(&custom, optional_gas_op(), None)
+            .await
+            .unwrap();
+        // the route must be created for exactly the requested entry point
+        assert_eq!(*used.lock().unwrap(), Some(custom));
+    }
+
+    #[tokio::test]
+    async fn test_estimate_gas_custom_entry_point_not_allowed() {
+        let router = EntryPointRouterBuilder::default()
+            .v0_6(v0_6_route(Address::random()))
+            .build();
+
+        let res = router
+            .estimate_gas(&Address::random(), optional_gas_op(), None)
+            .await;
+        assert!(matches!(res, Err(EthRpcError::InvalidParams(_))));
+    }
+
+    fn v0_6_route(address: Address) -> impl EntryPointRoute {
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || address);
+        let mut gas_estimator = MockGasEstimator::default();
+        gas_estimator.expect_estimate_op_gas().returning(|_, _| {
+            Ok(GasEstimate {
+                pre_verification_gas: U256::zero(),
+                call_gas_limit: U256::zero(),
+                verification_gas_limit: U256::zero(),
+                paymaster_verification_gas_limit: None,
+                warnings: vec![],
+            })
+        });
+
+        EntryPointRouteImpl::new(
+            Arc::new(entry_point),
+            gas_estimator,
+            UserOperationEventProviderV0_6::new(
+                ChainSpec::default(),
+                Arc::new(MockProvider::default()),
+                None,
+            ),
+        )
+    }
+
+    fn optional_gas_op() -> UserOperationOptionalGas {
+        UserOperationOptionalGas::V0_6(v0_6::UserOperationOptionalGas {
+            sender: Address::zero(),
+            nonce: U256::zero(),
+            init_code: Bytes::new(),
+            call_data: Bytes::new(),
+            call_gas_limit: None,
+            verification_gas_limit: None,
+            pre_verification_gas: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            paymaster_and_data: Bytes::new(),
+            signature: Bytes::new(),
+        })
+    }
+}
@@ -12,16 +12,14 @@
 // If not, see https://www.gnu.org/licenses/.
 
 use ethers::types::{spoof, Address, H256, U64};
+use futures_util::TryFutureExt;
 use jsonrpsee::core::RpcResult;
 use rundler_types::{pool::Pool, UserOperationVariant};
 
 use super::{api::EthApi, EthApiServer};
-use crate::{
-    types::{
-        FromRpc, RpcGasEstimate, RpcUserOperation, RpcUserOperationByHash,
-        RpcUserOperationOptionalGas, RpcUserOperationReceipt,
-    },
-    utils,
+use crate::types::{
+    FromRpc, RpcGasEstimate, RpcUserOperation, RpcUserOperationByHash, RpcUserOperationOptionalGas,
+    RpcUserOperationReceipt,
 };
 
 #[async_trait::async_trait]
@@ -34,15 +32,17 @@ where
         op: RpcUserOperation,
         entry_point: Address,
     ) -> RpcResult<H256> {
-        utils::safe_call_rpc_handler(
-            "eth_sendUserOperation",
-            EthApi::send_user_operation(
-                self,
-                UserOperationVariant::from_rpc(op, &self.chain_spec),
-                entry_point,
-            ),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "eth_sendUserOperation",
+                EthApi::send_user_operation(
+                    self,
+                    UserOperationVariant::from_rpc(op, &self.chain_spec),
+                    entry_point,
+                )
+                .map_err(|e| e.into_rpc_error(self.error_compat_mode)),
+            )
+            .await
     }
 
     async fn estimate_user_operation_gas(
@@ -51,44 +51,51 @@ where
         entry_point: Address,
         state_override: Option<spoof::State>,
     ) -> RpcResult<RpcGasEstimate> {
-        utils::safe_call_rpc_handler(
-            "eth_estimateUserOperationGas",
-            EthApi::estimate_user_operation_gas(self, op.into(), entry_point, state_override),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "eth_estimateUserOperationGas",
+                EthApi::estimate_user_operation_gas(self, op.into(), entry_point, state_override)
+                    .map_err(|e| e.into_rpc_error(self.error_compat_mode)),
+            )
+            .await
     }
 
     async fn get_user_operation_by_hash(
         &self,
         hash: H256,
     ) -> RpcResult<Option<RpcUserOperationByHash>> {
-        utils::safe_call_rpc_handler(
-            "eth_getUserOperationByHash",
-            EthApi::get_user_operation_by_hash(self, hash),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "eth_getUserOperationByHash",
+                EthApi::get_user_operation_by_hash(self, hash),
+            )
+            .await
     }
 
     async fn get_user_operation_receipt(
         &self,
         hash: H256,
     ) -> RpcResult<Option<RpcUserOperationReceipt>> {
-        utils::safe_call_rpc_handler(
-            "eth_getUserOperationReceipt",
-            EthApi::get_user_operation_receipt(self, hash),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "eth_getUserOperationReceipt",
+                EthApi::get_user_operation_receipt(self, hash),
+            )
+            .await
     }
 
     async fn supported_entry_points(&self) -> RpcResult<Vec<String>> {
-        utils::safe_call_rpc_handler(
-            "eth_supportedEntryPoints",
-            EthApi::supported_entry_points(self),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "eth_supportedEntryPoints",
+                EthApi::supported_entry_points(self),
+            )
+            .await
     }
 
     async fn chain_id(&self) -> RpcResult<U64> {
-        utils::safe_call_rpc_handler("eth_chainId", EthApi::chain_id(self)).await
+        self.method_timeouts
+            .call("eth_chainId", EthApi::chain_id(self))
+            .await
     }
 }
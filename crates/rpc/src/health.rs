@@ -12,10 +12,14 @@
 // If not, see https://www.gnu.org/licenses/.
 
 use async_trait::async_trait;
-use jsonrpsee::{core::RpcResult, proc_macros::rpc, types::error::INTERNAL_ERROR_CODE};
+use jsonrpsee::{
+    core::RpcResult,
+    proc_macros::rpc,
+    types::{error::INTERNAL_ERROR_CODE, ErrorObjectOwned},
+};
 use rundler_task::server::{HealthCheck, ServerStatus};
 
-use crate::error::rpc_err;
+use crate::{error::rpc_err, utils::MethodTimeouts};
 
 #[rpc(server, namespace = "system")]
 pub trait SystemApi {
@@ -25,17 +29,18 @@ pub trait SystemApi {
 
 pub(crate) struct HealthChecker {
     servers: Vec<Box<dyn HealthCheck>>,
+    method_timeouts: MethodTimeouts,
 }
 
 impl HealthChecker {
-    pub(crate) fn new(servers: Vec<Box<dyn HealthCheck>>) -> Self {
-        Self { servers }
+    pub(crate) fn new(servers: Vec<Box<dyn HealthCheck>>, method_timeouts: MethodTimeouts) -> Self {
+        Self {
+            servers,
+            method_timeouts,
+        }
     }
-}
 
-#[async_trait]
-impl SystemApiServer for HealthChecker {
-    async fn get_health(&self) -> RpcResult<String> {
+    async fn check_health(&self) -> Result<String, ErrorObjectOwned> {
         let mut errors = Vec::new();
         for server in &self.servers {
             match server.status().await {
@@ -53,3 +58,12 @@ impl SystemApiServer for HealthChecker {
         }
     }
 }
+
+#[async_trait]
+impl SystemApiServer for HealthChecker {
+    async fn get_health(&self) -> RpcResult<String> {
+        self.method_timeouts
+            .call("system_health", self.check_health())
+            .await
+    }
+}
@@ -19,6 +19,9 @@
 ))]
 //! JSON-RPC server for the Rundler.
 
+mod admission;
+pub use admission::{AdmissionHook, AdmissionHooks, RejectEmptySignature};
+
 mod debug;
 pub use debug::DebugApiClient;
 
@@ -28,10 +31,11 @@ pub use admin::AdminApiClient;
 mod error;
 
 mod eth;
-pub use eth::{EthApiClient, EthApiSettings};
+pub use eth::{ErrorCompatMode, EthApiClient, EthApiSettings, EthRpcError};
 
 mod health;
 mod metrics;
+pub use metrics::MetricsSnapshot;
 
 mod rundler;
 pub use rundler::{RundlerApiClient, Settings as RundlerApiSettings};
@@ -11,9 +11,109 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::time::{Duration, Instant};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 
 use jsonrpsee::{helpers::MethodResponseResult, server::logger::Logger};
+use metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+
+/// Names of the metrics included in the `rundler_metrics` snapshot
+const SNAPSHOT_METRICS: &[&str] = &[
+    "op_pool_num_ops_in_pool",
+    "op_pool_size_bytes",
+    "builder_bundle_txns_sent",
+    "builder_bundle_txns_success",
+    "bundle_builder_account_balance",
+    "rpc_rejected_ops",
+];
+
+#[derive(Debug, Clone, Copy)]
+enum SnapshotKind {
+    Counter,
+    Gauge,
+}
+
+/// Metrics recorder keeping the latest values of key counters and gauges, so that they
+/// can be served as a JSON snapshot without a Prometheus scraper.
+///
+/// Only sees metrics recorded in this process, install it alongside the Prometheus
+/// recorder with a fanout. Histograms are not tracked.
+#[derive(Debug, Clone, Default)]
+pub struct MetricsSnapshot {
+    values: Arc<Mutex<HashMap<Key, (SnapshotKind, Arc<AtomicU64>)>>>,
+}
+
+impl MetricsSnapshot {
+    /// Returns the current values of the tracked metrics, keyed by the metric name
+    /// followed by its labels, i.e. `op_pool_num_ops_in_pool{entry_point=0x...}`
+    pub fn values(&self) -> BTreeMap<String, f64> {
+        let values = self.values.lock().unwrap();
+        values
+            .iter()
+            .map(|(key, (kind, value))| {
+                let value = value.load(Ordering::Acquire);
+                let value = match kind {
+                    SnapshotKind::Counter => value as f64,
+                    SnapshotKind::Gauge => f64::from_bits(value),
+                };
+                (snapshot_key(key), value)
+            })
+            .collect()
+    }
+
+    fn register(&self, key: &Key, kind: SnapshotKind) -> Option<Arc<AtomicU64>> {
+        if !SNAPSHOT_METRICS.contains(&key.name()) {
+            return None;
+        }
+        let mut values = self.values.lock().unwrap();
+        let (_, value) = values
+            .entry(key.clone())
+            .or_insert_with(|| (kind, Arc::new(AtomicU64::new(0))));
+        Some(Arc::clone(value))
+    }
+}
+
+impl Recorder for MetricsSnapshot {
+    fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+        self.register(key, SnapshotKind::Counter)
+            .map(Counter::from_arc)
+            .unwrap_or_else(Counter::noop)
+    }
+
+    fn register_gauge(&self, key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+        self.register(key, SnapshotKind::Gauge)
+            .map(Gauge::from_arc)
+            .unwrap_or_else(Gauge::noop)
+    }
+
+    fn register_histogram(&self, _key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+        Histogram::noop()
+    }
+}
+
+fn snapshot_key(key: &Key) -> String {
+    let labels = key
+        .labels()
+        .map(|l| format!("{}={}", l.key(), l.value()))
+        .collect::<Vec<_>>();
+    if labels.is_empty() {
+        key.name().to_string()
+    } else {
+        format!("{}{{{}}}", key.name(), labels.join(","))
+    }
+}
 
 #[derive(Clone)]
 pub(crate) struct RpcMetricsLogger;
@@ -93,6 +193,10 @@ impl RpcMetrics {
         metrics::gauge!("rpc_open_requests", "method_name" => method_name).decrement(1_f64);
     }
 
+    pub(crate) fn increment_rejected_ops(reason: &'static str) {
+        metrics::counter!("rpc_rejected_ops", "reason" => reason).increment(1);
+    }
+
     fn increment_rpc_error_count(method_name: String) {
         metrics::counter!("rpc_error_count", "method_name" => method_name).increment(1);
     }
@@ -102,3 +206,32 @@ impl RpcMetrics {
             .record(latency.as_millis() as f64);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_includes_key_metrics() {
+        let snapshot = MetricsSnapshot::default();
+        metrics::with_local_recorder(&snapshot, || {
+            metrics::gauge!("op_pool_num_ops_in_pool", "entry_point" => "0x01").set(3.0);
+            metrics::counter!("builder_bundle_txns_sent", "entry_point" => "0x01").increment(2);
+            RpcMetrics::increment_rejected_ops("signature_check_failed");
+            RpcMetrics::increment_rejected_ops("signature_check_failed");
+            metrics::gauge!("bundle_builder_account_balance", "addr" => "0x02").set(1.5);
+            // not a key metric
+            metrics::counter!("rpc_num_requests").increment(1);
+        });
+
+        let values = snapshot.values();
+        assert_eq!(values.len(), 4);
+        assert_eq!(values["op_pool_num_ops_in_pool{entry_point=0x01}"], 3.0);
+        assert_eq!(values["builder_bundle_txns_sent{entry_point=0x01}"], 2.0);
+        assert_eq!(
+            values["rpc_rejected_ops{reason=signature_check_failed}"],
+            2.0
+        );
+        assert_eq!(values["bundle_builder_account_balance{addr=0x02}"], 1.5);
+    }
+}
@@ -11,20 +11,37 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
 
 use anyhow::Context;
 use async_trait::async_trait;
-use ethers::types::{Address, H256, U256};
-use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use ethers::{
+    abi::AbiDecode,
+    types::{Address, Bytes, H256, U256},
+};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc, RpcModule};
 use rundler_provider::Provider;
 use rundler_sim::{gas, FeeEstimator};
-use rundler_types::{chain::ChainSpec, pool::Pool, UserOperation, UserOperationVariant};
+use rundler_types::{
+    chain::ChainSpec, contracts::v0_7::shared_types::PackedUserOperation, pool::Pool, v0_6,
+    EntryPointVersion, UserOperation, UserOperationOptionalGas, UserOperationVariant,
+};
 
 use crate::{
-    eth::{EntryPointRouter, EthResult, EthRpcError},
-    types::{FromRpc, RpcUserOperation},
-    utils,
+    admission::AdmissionContext,
+    eth::{
+        add_user_operation, estimate_user_operation_gas, get_user_operation_receipt,
+        EntryPointRouter, EthResult, EthRpcError, ReplacementUnderpricedData,
+    },
+    metrics::MetricsSnapshot,
+    types::{
+        FromRpc, RpcConfig, RpcGasEstimate, RpcPendingUserOperation, RpcUserOperation,
+        RpcUserOperationOptionalSignature, RpcUserOperationReceipt, RpcUserOperationStatus,
+    },
+    utils::{self, MethodTimeouts},
 };
 
 /// Settings for the `rundler_` API
@@ -39,6 +56,21 @@ pub struct Settings {
     pub max_verification_gas: u64,
 }
 
+/// Method listing all methods served by the RPC server
+const SUPPORTED_METHODS_METHOD: &str = "rundler_getSupportedMethods";
+
+/// Registers `rundler_getSupportedMethods` on a module with all enabled namespaces attached.
+///
+/// This is registered separately from `RundlerApi` as the method list is only known once
+/// the enabled namespaces have been merged into the module.
+pub(crate) fn register_supported_methods(module: &mut RpcModule<()>) -> anyhow::Result<()> {
+    let mut methods: Vec<&'static str> = module.method_names().collect();
+    methods.push(SUPPORTED_METHODS_METHOD);
+    methods.sort_unstable();
+    module.register_method(SUPPORTED_METHODS_METHOD, move |_, _| methods.clone())?;
+    Ok(())
+}
+
 #[rpc(client, server, namespace = "rundler")]
 pub trait RundlerApi {
     /// Returns the maximum priority fee per gas required by Rundler
@@ -59,6 +91,69 @@ pub trait RundlerApi {
         uo: RpcUserOperation,
         entry_point: Address,
     ) -> RpcResult<Option<H256>>;
+
+    /// Returns a sanitized view of the effective configuration of the server.
+    #[method(name = "getConfig")]
+    async fn get_config(&self) -> RpcResult<RpcConfig>;
+
+    /// Sends an ABI encoded user operation to the pool.
+    ///
+    /// The user operation must be encoded as the entry point's user operation struct:
+    /// `UserOperation` for v0.6 and `PackedUserOperation` for v0.7.
+    #[method(name = "sendRawUserOperation")]
+    async fn send_raw_user_operation(&self, entry_point: Address, op: Bytes) -> RpcResult<H256>;
+
+    /// Returns all user operations from a sender that are pending in the mempool, sorted by nonce
+    /// and then by entry point.
+    #[method(name = "getPendingUserOperations")]
+    async fn get_pending_user_operations(
+        &self,
+        sender: Address,
+    ) -> RpcResult<Vec<RpcPendingUserOperation>>;
+
+    /// Estimates the gas fields of a user operation, like `eth_estimateUserOperationGas`.
+    ///
+    /// If the signature is omitted, it is filled with the dummy signature configured for
+    /// the account's factory, or a default ECDSA shaped dummy signature if none is configured.
+    /// Paymaster data may also be omitted, and is then left empty.
+    #[method(name = "estimateUserOperationGas")]
+    async fn estimate_user_operation_gas(
+        &self,
+        op: RpcUserOperationOptionalSignature,
+        entry_point: Address,
+    ) -> RpcResult<RpcGasEstimate>;
+
+    /// Returns the receipts of multiple user operations, like `eth_getUserOperationReceipt`.
+    ///
+    /// Receipts are returned in the order of the given hashes, null for operations not yet mined.
+    /// The number of hashes per call is limited by the server configuration.
+    #[method(name = "getUserOperationReceipts")]
+    async fn get_user_operation_receipts(
+        &self,
+        hashes: Vec<H256>,
+    ) -> RpcResult<Vec<Option<RpcUserOperationReceipt>>>;
+
+    /// Replaces a pending user operation with a re-signed copy that pays higher fees.
+    ///
+    /// The new operation must have the same sender and nonce as the pending operation with the
+    /// given hash, and is sent to the pending operation's entry point. The mempool enforces the
+    /// minimum fee increase when replacing the operation.
+    ///
+    /// Returns the hash of the new user operation.
+    #[method(name = "replaceUserOperationFee")]
+    async fn replace_user_operation_fee(&self, hash: H256, op: RpcUserOperation)
+        -> RpcResult<H256>;
+
+    /// Returns the status of a user operation: `unknown`, `pending`, `included` or `dropped`.
+    ///
+    /// Unlike `eth_getUserOperationReceipt`, this distinguishes operations never seen by this
+    /// bundler from those that were accepted but removed from the mempool without being included.
+    #[method(name = "getUserOperationStatus")]
+    async fn get_user_operation_status(&self, hash: H256) -> RpcResult<RpcUserOperationStatus>;
+
+    /// Returns a snapshot of key metrics recorded by this process, keyed by metric name and labels.
+    #[method(name = "metrics")]
+    async fn metrics(&self) -> RpcResult<BTreeMap<String, f64>>;
 }
 
 pub(crate) struct RundlerApi<P, PL> {
@@ -67,6 +162,14 @@ pub(crate) struct RundlerApi<P, PL> {
     fee_estimator: FeeEstimator<P>,
     pool_server: PL,
     entry_point_router: EntryPointRouter,
+    config: RpcConfig,
+    dummy_signatures: HashMap<Address, Bytes>,
+    token_paymasters: HashSet<Address>,
+    method_timeouts: MethodTimeouts,
+    receipt_lookup_concurrency: usize,
+    max_receipt_lookup_hashes: usize,
+    admission: AdmissionContext,
+    metrics_snapshot: MetricsSnapshot,
 }
 
 #[async_trait]
@@ -76,11 +179,12 @@ where
     PL: Pool,
 {
     async fn max_priority_fee_per_gas(&self) -> RpcResult<U256> {
-        utils::safe_call_rpc_handler(
-            "rundler_maxPriorityFeePerGas",
-            RundlerApi::max_priority_fee_per_gas(self),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "rundler_maxPriorityFeePerGas",
+                RundlerApi::max_priority_fee_per_gas(self),
+            )
+            .await
     }
 
     async fn drop_local_user_operation(
@@ -88,11 +192,88 @@ where
         user_op: RpcUserOperation,
         entry_point: Address,
     ) -> RpcResult<Option<H256>> {
-        utils::safe_call_rpc_handler(
-            "rundler_dropLocalUserOperation",
-            RundlerApi::drop_local_user_operation(self, user_op, entry_point),
-        )
-        .await
+        self.method_timeouts
+            .call(
+                "rundler_dropLocalUserOperation",
+                RundlerApi::drop_local_user_operation(self, user_op, entry_point),
+            )
+            .await
+    }
+
+    async fn get_config(&self) -> RpcResult<RpcConfig> {
+        Ok(self.config.clone())
+    }
+
+    async fn send_raw_user_operation(&self, entry_point: Address, op: Bytes) -> RpcResult<H256> {
+        self.method_timeouts
+            .call(
+                "rundler_sendRawUserOperation",
+                RundlerApi::send_raw_user_operation(self, entry_point, op),
+            )
+            .await
+    }
+
+    async fn get_pending_user_operations(
+        &self,
+        sender: Address,
+    ) -> RpcResult<Vec<RpcPendingUserOperation>> {
+        self.method_timeouts
+            .call(
+                "rundler_getPendingUserOperations",
+                RundlerApi::get_pending_user_operations(self, sender),
+            )
+            .await
+    }
+
+    async fn estimate_user_operation_gas(
+        &self,
+        op: RpcUserOperationOptionalSignature,
+        entry_point: Address,
+    ) -> RpcResult<RpcGasEstimate> {
+        self.method_timeouts
+            .call(
+                "rundler_estimateUserOperationGas",
+                RundlerApi::estimate_user_operation_gas(self, op.into(), entry_point),
+            )
+            .await
+    }
+
+    async fn get_user_operation_receipts(
+        &self,
+        hashes: Vec<H256>,
+    ) -> RpcResult<Vec<Option<RpcUserOperationReceipt>>> {
+        self.method_timeouts
+            .call(
+                "rundler_getUserOperationReceipts",
+                RundlerApi::get_user_operation_receipts(self, hashes),
+            )
+            .await
+    }
+
+    async fn replace_user_operation_fee(
+        &self,
+        hash: H256,
+        op: RpcUserOperation,
+    ) -> RpcResult<H256> {
+        self.method_timeouts
+            .call(
+                "rundler_replaceUserOperationFee",
+                RundlerApi::replace_user_operation_fee(self, hash, op),
+            )
+            .await
+    }
+
+    async fn get_user_operation_status(&self, hash: H256) -> RpcResult<RpcUserOperationStatus> {
+        self.method_timeouts
+            .call(
+                "rundler_getUserOperationStatus",
+                RundlerApi::get_user_operation_status(self, hash),
+            )
+            .await
+    }
+
+    async fn metrics(&self) -> RpcResult<BTreeMap<String, f64>> {
+        Ok(self.metrics_snapshot.values())
     }
 }
 
@@ -101,12 +282,21 @@ where
     P: Provider,
     PL: Pool,
 {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         chain_spec: &ChainSpec,
         provider: Arc<P>,
         entry_point_router: EntryPointRouter,
         pool_server: PL,
         settings: Settings,
+        config: RpcConfig,
+        dummy_signatures: HashMap<Address, Bytes>,
+        token_paymasters: HashSet<Address>,
+        method_timeouts: MethodTimeouts,
+        receipt_lookup_concurrency: usize,
+        max_receipt_lookup_hashes: usize,
+        admission: AdmissionContext,
+        metrics_snapshot: MetricsSnapshot,
     ) -> Self {
         Self {
             chain_spec: chain_spec.clone(),
@@ -119,6 +309,14 @@ where
             ),
             entry_point_router,
             pool_server,
+            config,
+            dummy_signatures,
+            token_paymasters,
+            method_timeouts,
+            receipt_lookup_concurrency,
+            max_receipt_lookup_hashes,
+            admission,
+            metrics_snapshot,
         }
     }
 
@@ -172,4 +370,907 @@ where
 
         Ok(ret)
     }
+
+    async fn send_raw_user_operation(&self, entry_point: Address, op: Bytes) -> EthResult<H256> {
+        let uo: UserOperationVariant =
+            match self.entry_point_router.get_ep_version(&entry_point)? {
+                EntryPointVersion::V0_6 => v0_6::UserOperation::decode(&op).map(Into::into),
+                EntryPointVersion::V0_7 => PackedUserOperation::decode(&op)
+                    .map(|packed| packed.unpack(&self.chain_spec).into()),
+                EntryPointVersion::Unspecified => unreachable!("unspecified entry point version"),
+            }
+            .map_err(|e| {
+                EthRpcError::InvalidParams(format!("Invalid user operation encoding: {e}"))
+            })?;
+
+        add_user_operation(
+            &self.chain_spec,
+            &self.entry_point_router,
+            &self.pool_server,
+            &self.admission,
+            uo,
+            entry_point,
+        )
+        .await
+    }
+
+    async fn get_pending_user_operations(
+        &self,
+        sender: Address,
+    ) -> EthResult<Vec<RpcPendingUserOperation>> {
+        let mut ops = self
+            .pool_server
+            .get_ops_by_sender(sender)
+            .await
+            .map_err(EthRpcError::from)?;
+        ops.sort_by_key(|op| (op.uo.nonce(), op.entry_point));
+
+        Ok(ops
+            .into_iter()
+            .map(|op| RpcPendingUserOperation {
+                user_op_hash: op.uo.hash(op.entry_point, self.chain_spec.id),
+                entry_point: op.entry_point.into(),
+                nonce: op.uo.nonce(),
+                max_fee_per_gas: op.uo.max_fee_per_gas(),
+                max_priority_fee_per_gas: op.uo.max_priority_fee_per_gas(),
+                paymaster_token: op
+                    .uo
+                    .paymaster()
+                    .filter(|p| self.token_paymasters.contains(p))
+                    .and_then(|_| op.uo.paymaster_token()),
+                user_operation: op.uo.into(),
+            })
+            .collect())
+    }
+
+    async fn estimate_user_operation_gas(
+        &self,
+        mut op: UserOperationOptionalGas,
+        entry_point: Address,
+    ) -> EthResult<RpcGasEstimate> {
+        self.fill_dummy_signature(&mut op);
+
+        estimate_user_operation_gas(
+            &self.chain_spec,
+            &self.entry_point_router,
+            op,
+            entry_point,
+            None,
+        )
+        .await
+    }
+
+    async fn get_user_operation_receipts(
+        &self,
+        hashes: Vec<H256>,
+    ) -> EthResult<Vec<Option<RpcUserOperationReceipt>>> {
+        if hashes.len() > self.max_receipt_lookup_hashes {
+            return Err(EthRpcError::InvalidParams(format!(
+                "Too many user operation hashes: {} exceeds the maximum of {}",
+                hashes.len(),
+                self.max_receipt_lookup_hashes
+            )));
+        }
+
+        let futs = hashes
+            .into_iter()
+            .map(|hash| get_user_operation_receipt(&self.entry_point_router, hash));
+        utils::try_join_bounded(futs, self.receipt_lookup_concurrency).await
+    }
+
+    async fn replace_user_operation_fee(
+        &self,
+        hash: H256,
+        op: RpcUserOperation,
+    ) -> EthResult<H256> {
+        let Some(pending) = self
+            .pool_server
+            .get_op_by_hash(hash)
+            .await
+            .map_err(EthRpcError::from)?
+        else {
+            return Err(EthRpcError::InvalidParams(format!(
+                "No pending user operation with hash {hash:?}"
+            )));
+        };
+
+        let uo = UserOperationVariant::from_rpc(op, &self.chain_spec);
+        if uo.id() != pending.uo.id() {
+            return Err(EthRpcError::InvalidParams(
+                "Replacement user operation must have the same sender and nonce as the pending operation"
+                    .to_string(),
+            ));
+        }
+
+        // reject obvious non-bumps before touching the pool, the pool enforces the minimum increase
+        if uo.max_fee_per_gas() <= pending.uo.max_fee_per_gas()
+            || uo.max_priority_fee_per_gas() <= pending.uo.max_priority_fee_per_gas()
+        {
+            return Err(EthRpcError::ReplacementUnderpriced(
+                ReplacementUnderpricedData::new(
+                    pending.uo.max_priority_fee_per_gas(),
+                    pending.uo.max_fee_per_gas(),
+                ),
+            ));
+        }
+
+        add_user_operation(
+            &self.chain_spec,
+            &self.entry_point_router,
+            &self.pool_server,
+            &self.admission,
+            uo,
+            pending.entry_point,
+        )
+        .await
+    }
+
+    async fn get_user_operation_status(&self, hash: H256) -> EthResult<RpcUserOperationStatus> {
+        if self
+            .pool_server
+            .get_op_by_hash(hash)
+            .await
+            .map_err(EthRpcError::from)?
+            .is_some()
+        {
+            return Ok(RpcUserOperationStatus::Pending);
+        }
+
+        if get_user_operation_receipt(&self.entry_point_router, hash)
+            .await?
+            .is_some()
+        {
+            return Ok(RpcUserOperationStatus::Included);
+        }
+
+        if self.admission.submitted_ops.contains(hash) {
+            Ok(RpcUserOperationStatus::Dropped)
+        } else {
+            Ok(RpcUserOperationStatus::Unknown)
+        }
+    }
+
+    fn fill_dummy_signature(&self, op: &mut UserOperationOptionalGas) {
+        let (factory, signature) = match op {
+            UserOperationOptionalGas::V0_6(op) => (
+                (op.init_code.len() >= 20).then(|| Address::from_slice(&op.init_code[..20])),
+                &mut op.signature,
+            ),
+            UserOperationOptionalGas::V0_7(op) => (op.factory, &mut op.signature),
+        };
+        if !signature.is_empty() {
+            return;
+        }
+
+        *signature = factory
+            .and_then(|f| self.dummy_signatures.get(&f))
+            .cloned()
+            .unwrap_or_else(default_dummy_signature);
+    }
+}
+
+// A 65 byte ECDSA shaped signature, used when no dummy signature is configured for the account's
+// factory. Its `s` value is in the lower half of the curve order and its `v` is valid, so the
+// usual ECDSA checks of an account reach signature recovery.
+fn default_dummy_signature() -> Bytes {
+    "0xfffffffffffffffffffffffffffffff0000000000000000000000000000000007aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa1c"
+        .parse()
+        .expect("should parse the default dummy signature")
+}
+
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        abi::{self, AbiEncode, Token},
+        prelude::EthEvent,
+        types::{Log, TransactionReceipt, ValueOrArray},
+    };
+    use jsonrpsee::core::params::ArrayParams;
+    use rundler_provider::{MockEntryPointV0_6, MockProvider};
+    use rundler_sim::{MockGasEstimator, PriorityFeeMode};
+    use rundler_types::{
+        builder::MockBuilder,
+        contracts::v0_6::i_entry_point::UserOperationEventFilter,
+        pool::{MempoolError, MockPool, PoolOperation},
+        v0_7, EntityInfos, GasEstimate, ValidTimeRange,
+    };
+    use serde_json::json;
+
+    use super::*;
+    use crate::{
+        admin::{AdminApi, AdminApiServer},
+        admission::AdmissionHooks,
+        debug::{DebugApi, DebugApiServer},
+        eth::{EntryPointRouteImpl, EntryPointRouterBuilder, UserOperationEventProviderV0_6},
+        types::RpcUserOperationOptionalGas,
+        utils::SubmittedOps,
+    };
+
+    #[tokio::test]
+    async fn test_supported_methods() {
+        // debug disabled
+        let mut module = RpcModule::new(());
+        module
+            .merge(
+                AdminApi::new(
+                    MockPool::default(),
+                    ChainSpec::default(),
+                    MethodTimeouts::default(),
+                )
+                .into_rpc(),
+            )
+            .unwrap();
+        register_supported_methods(&mut module).unwrap();
+
+        let methods: Vec<String> = module
+            .call(SUPPORTED_METHODS_METHOD, ArrayParams::new())
+            .await
+            .unwrap();
+        assert!(methods.iter().any(|m| m.starts_with("admin_")));
+        assert!(methods.iter().any(|m| m == SUPPORTED_METHODS_METHOD));
+        assert!(!methods.iter().any(|m| m.starts_with("debug_")));
+
+        // debug enabled
+        let mut module = RpcModule::new(());
+        module
+            .merge(
+                DebugApi::new(
+                    MockPool::default(),
+                    MockBuilder::default(),
+                    MethodTimeouts::default(),
+                )
+                .into_rpc(),
+            )
+            .unwrap();
+        register_supported_methods(&mut module).unwrap();
+
+        let methods: Vec<String> = module
+            .call(SUPPORTED_METHODS_METHOD, ArrayParams::new())
+            .await
+            .unwrap();
+        assert!(methods.iter().any(|m| m.starts_with("debug_")));
+    }
+
+    #[tokio::test]
+    async fn test_send_raw_user_operation() {
+        let ep = Address::random();
+        let uo = v0_6::UserOperation {
+            sender: Address::random(),
+            max_fee_per_gas: 100.into(),
+            max_priority_fee_per_gas: 10.into(),
+            verification_gas_limit: 100_000.into(),
+            ..Default::default()
+        };
+        let hash = uo.hash(ep, 1);
+
+        let mut pool = MockPool::default();
+        let expected: UserOperationVariant = uo.clone().into();
+        pool.expect_add_op()
+            .withf(move |&entry_point, op| entry_point == ep && *op == expected)
+            .times(1)
+            .returning(move |_, _| Ok(hash));
+
+        let api = create_api(ep, pool);
+        let res = api
+            .send_raw_user_operation(ep, uo.encode().into())
+            .await
+            .unwrap();
+        assert_eq!(res, hash);
+    }
+
+    #[tokio::test]
+    async fn test_send_raw_user_operation_invalid_encoding() {
+        let ep = Address::random();
+        let mut pool = MockPool::default();
+        pool.expect_add_op().never();
+
+        let api = create_api(ep, pool);
+        let res = api
+            .send_raw_user_operation(ep, Bytes::from(vec![1, 2, 3]))
+            .await;
+        assert!(matches!(res, Err(EthRpcError::InvalidParams(_))));
+    }
+
+    #[tokio::test]
+    async fn test_get_pending_user_operations() {
+        let ep = Address::random();
+        let sender = Address::random();
+        let ops = [0, 1]
+            .into_iter()
+            .map(|nonce| PoolOperation {
+                uo: v0_6::UserOperation {
+                    sender,
+                    nonce: nonce.into(),
+                    max_fee_per_gas: 100.into(),
+                    max_priority_fee_per_gas: 10.into(),
+                    ..Default::default()
+                }
+                .into(),
+                entry_point: ep,
+                aggregator: None,
+                valid_time_range: ValidTimeRange::default(),
+                expected_code_hash: None,
+                sim_block_hash: H256::zero(),
+                sim_block_number: 0,
+                account_is_staked: false,
+                no_replacement: false,
+                entity_infos: EntityInfos::default(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut pool = MockPool::default();
+        let returned = ops.clone();
+        pool.expect_get_ops_by_sender()
+            .withf(move |&s| s == sender)
+            .returning(move |_| Ok(returned.clone()));
+        pool.expect_get_ops_by_sender().returning(|_| Ok(vec![]));
+
+        let api = create_api(ep, pool);
+        let res = api.get_pending_user_operations(sender).await.unwrap();
+        assert_eq!(res.len(), 2);
+        for (pending, op) in res.iter().zip(ops.iter()) {
+            assert_eq!(pending.user_op_hash, op.uo.hash(ep, 1));
+            assert_eq!(pending.nonce, op.uo.nonce());
+            assert_eq!(pending.max_fee_per_gas, 100.into());
+            assert_eq!(pending.max_priority_fee_per_gas, 10.into());
+        }
+
+        let res = api
+            .get_pending_user_operations(Address::random())
+            .await
+            .unwrap();
+        assert!(res.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_pending_user_operations_sorted() {
+        let sender = Address::random();
+        let mut eps = [Address::random(), Address::random()];
+        eps.sort();
+        // interleaved nonces across the entry points, in no particular order
+        let ops = [
+            (3, eps[1]),
+            (0, eps[0]),
+            (2, eps[1]),
+            (2, eps[0]),
+            (1, eps[1]),
+        ]
+        .into_iter()
+        .map(|(nonce, entry_point)| PoolOperation {
+            uo: v0_6::UserOperation {
+                sender,
+                nonce: nonce.into(),
+                ..Default::default()
+            }
+            .into(),
+            entry_point,
+            aggregator: None,
+            valid_time_range: ValidTimeRange::default(),
+            expected_code_hash: None,
+            sim_block_hash: H256::zero(),
+            sim_block_number: 0,
+            account_is_staked: false,
+            no_replacement: false,
+            entity_infos: EntityInfos::default(),
+        })
+        .collect::<Vec<_>>();
+
+        let mut pool = MockPool::default();
+        pool.expect_get_ops_by_sender()
+            .returning(move |_| Ok(ops.clone()));
+
+        let api = create_api(eps[0], pool);
+        let res = api.get_pending_user_operations(sender).await.unwrap();
+        let order = res
+            .iter()
+            .map(|op| (op.nonce.as_u64(), Address::from(op.entry_point)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            order,
+            vec![
+                (0, eps[0]),
+                (1, eps[1]),
+                (2, eps[0]),
+                (2, eps[1]),
+                (3, eps[1])
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_pending_user_operations_paymaster_token() {
+        let ep = Address::random();
+        let sender = Address::random();
+        let token_paymaster = Address::random();
+        let token = Address::random();
+        let ops = [token_paymaster, Address::random()]
+            .into_iter()
+            .map(|paymaster| PoolOperation {
+                uo: v0_6::UserOperation {
+                    sender,
+                    paymaster_and_data: [paymaster.as_bytes(), token.as_bytes()].concat().into(),
+                    ..Default::default()
+                }
+                .into(),
+                entry_point: ep,
+                aggregator: None,
+                valid_time_range: ValidTimeRange::default(),
+                expected_code_hash: None,
+                sim_block_hash: H256::zero(),
+                sim_block_number: 0,
+                account_is_staked: false,
+                no_replacement: false,
+                entity_infos: EntityInfos::default(),
+            })
+            .collect::<Vec<_>>();
+
+        let mut pool = MockPool::default();
+        pool.expect_get_ops_by_sender()
+            .returning(move |_| Ok(ops.clone()));
+
+        let mut api = create_api(ep, pool);
+        api.token_paymasters = HashSet::from([token_paymaster]);
+        let res = api.get_pending_user_operations(sender).await.unwrap();
+        assert_eq!(res.len(), 2);
+        // only the configured token paymaster's data is parsed for a token
+        assert_eq!(res[0].paymaster_token, Some(token));
+        assert_eq!(res[1].paymaster_token, None);
+    }
+
+    #[tokio::test]
+    async fn test_replace_user_operation_fee() {
+        let ep = Address::random();
+        let pending = pending_op(ep);
+        let pending_hash = pending.uo.hash(ep, 1);
+        let replacement = v0_6::UserOperation {
+            max_fee_per_gas: 110.into(),
+            max_priority_fee_per_gas: 11.into(),
+            signature: vec![1; 65].into(),
+            ..pending.uo.clone().into()
+        };
+        let replacement_hash = replacement.hash(ep, 1);
+
+        let mut pool = MockPool::default();
+        pool.expect_get_op_by_hash()
+            .withf(move |&h| h == pending_hash)
+            .returning(move |_| Ok(Some(pending.clone())));
+        let expected: UserOperationVariant = replacement.clone().into();
+        pool.expect_add_op()
+            .withf(move |&entry_point, op| entry_point == ep && *op == expected)
+            .times(1)
+            .returning(move |_, _| Ok(replacement_hash));
+
+        let api = create_api(ep, pool);
+        let res = api
+            .replace_user_operation_fee(
+                pending_hash,
+                UserOperationVariant::from(replacement).into(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res, replacement_hash);
+    }
+
+    #[tokio::test]
+    async fn test_replace_user_operation_fee_under_bumped() {
+        let ep = Address::random();
+        let pending = pending_op(ep);
+        let pending_hash = pending.uo.hash(ep, 1);
+        let pending_uo: v0_6::UserOperation = pending.uo.clone().into();
+
+        let mut pool = MockPool::default();
+        pool.expect_get_op_by_hash()
+            .returning(move |_| Ok(Some(pending.clone())));
+        pool.expect_add_op().times(1).returning(|_, _| {
+            Err(MempoolError::ReplacementUnderpriced(10.into(), 100.into()).into())
+        });
+        let api = create_api(ep, pool);
+
+        // fees not increased are rejected before reaching the pool
+        let not_bumped = v0_6::UserOperation {
+            max_fee_per_gas: 200.into(),
+            ..pending_uo.clone()
+        };
+        let res = api
+            .replace_user_operation_fee(pending_hash, UserOperationVariant::from(not_bumped).into())
+            .await;
+        assert!(matches!(res, Err(EthRpcError::ReplacementUnderpriced(_))));
+
+        // fees increased by less than the pool's minimum are rejected by the pool
+        let under_bumped = v0_6::UserOperation {
+            max_fee_per_gas: 101.into(),
+            max_priority_fee_per_gas: 11.into(),
+            ..pending_uo.clone()
+        };
+        let res = api
+            .replace_user_operation_fee(
+                pending_hash,
+                UserOperationVariant::from(under_bumped).into(),
+            )
+            .await;
+        assert!(matches!(res, Err(EthRpcError::ReplacementUnderpriced(_))));
+
+        // a different nonce is not a replacement
+        let other_nonce = v0_6::UserOperation {
+            nonce: 1.into(),
+            max_fee_per_gas: 200.into(),
+            max_priority_fee_per_gas: 20.into(),
+            ..pending_uo
+        };
+        let res = api
+            .replace_user_operation_fee(
+                pending_hash,
+                UserOperationVariant::from(other_nonce).into(),
+            )
+            .await;
+        assert!(matches!(res, Err(EthRpcError::InvalidParams(_))));
+    }
+
+    fn pending_op(ep: Address) -> PoolOperation {
+        PoolOperation {
+            uo: v0_6::UserOperation {
+                sender: Address::random(),
+                max_fee_per_gas: 100.into(),
+                max_priority_fee_per_gas: 10.into(),
+                verification_gas_limit: 100_000.into(),
+                ..Default::default()
+            }
+            .into(),
+            entry_point: ep,
+            aggregator: None,
+            valid_time_range: ValidTimeRange::default(),
+            expected_code_hash: None,
+            sim_block_hash: H256::zero(),
+            sim_block_number: 0,
+            account_is_staked: false,
+            no_replacement: false,
+            entity_infos: EntityInfos::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_estimate_user_operation_gas_dummy_signature() {
+        let ep = Address::random();
+        let factory = Address::random();
+        let dummy_signature = Bytes::from(vec![0xff; 65]);
+
+        let mut gas_estimator = MockGasEstimator::default();
+        let expected = dummy_signature.clone();
+        gas_estimator
+            .expect_estimate_op_gas()
+            .withf(move |op, _| op.signature == expected)
+            .times(1)
+            .returning(|_, _| {
+                Ok(GasEstimate {
+                    pre_verification_gas: 1.into(),
+                    call_gas_limit: 2.into(),
+                    verification_gas_limit: 3.into(),
+                    paymaster_verification_gas_limit: None,
+                    warnings: vec![],
+                })
+            });
+
+        let api = create_api_with_estimator(
+            ep,
+            MockPool::default(),
+            gas_estimator,
+            HashMap::from([(factory, dummy_signature)]),
+        );
+
+        let mut init_code = factory.as_bytes().to_vec();
+        init_code.extend_from_slice(&[1, 2, 3]);
+        api.estimate_user_operation_gas(optional_gas_op(init_code.into()), ep)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_estimate_user_operation_gas_default_dummy_signature() {
+        let ep = Address::random();
+        let mut gas_estimator = MockGasEstimator::default();
+        gas_estimator
+            .expect_estimate_op_gas()
+            .withf(|op, _| op.signature == default_dummy_signature())
+            .times(2)
+            .returning(|_, _| {
+                Ok(GasEstimate {
+                    pre_verification_gas: 1.into(),
+                    call_gas_limit: 2.into(),
+                    verification_gas_limit: 3.into(),
+                    paymaster_verification_gas_limit: None,
+                    warnings: vec![],
+                })
+            });
+
+        let api = create_api_with_estimator(
+            ep,
+            MockPool::default(),
+            gas_estimator,
+            HashMap::from([(Address::random(), Bytes::from(vec![0xff; 65]))]),
+        );
+
+        // an op with an unknown factory, and an op without one, both get the default
+        api.estimate_user_operation_gas(
+            optional_gas_op(Address::random().as_bytes().to_vec().into()),
+            ep,
+        )
+        .await
+        .unwrap();
+        api.estimate_user_operation_gas(optional_gas_op(Bytes::new()), ep)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_estimate_user_operation_gas_too_large() {
+        let ep = Address::random();
+        let api = create_api_with_estimator(
+            ep,
+            MockPool::default(),
+            MockGasEstimator::default(),
+            HashMap::new(),
+        );
+
+        let mut op = optional_gas_op(Bytes::new());
+        let UserOperationOptionalGas::V0_6(inner) = &mut op else {
+            unreachable!()
+        };
+        inner.call_data = vec![1; ChainSpec::default().max_transaction_size_bytes].into();
+        let res = api.estimate_user_operation_gas(op, ep).await;
+        assert!(matches!(res, Err(EthRpcError::InvalidParams(_))));
+    }
+
+    #[test]
+    fn test_fill_dummy_signature_v0_7_factory() {
+        let factory = Address::random();
+        let dummy_signature = Bytes::from(vec![0xff; 65]);
+        let api = create_api_with_estimator(
+            Address::random(),
+            MockPool::default(),
+            MockGasEstimator::default(),
+            HashMap::from([(factory, dummy_signature.clone())]),
+        );
+
+        let mut op = optional_gas_op_v0_7(Some(factory));
+        api.fill_dummy_signature(&mut op);
+        let UserOperationOptionalGas::V0_7(op) = op else {
+            panic!("expected a v0.7 op");
+        };
+        assert_eq!(op.signature, dummy_signature);
+
+        // no factory uses the default dummy signature
+        let mut op = optional_gas_op_v0_7(None);
+        api.fill_dummy_signature(&mut op);
+        let UserOperationOptionalGas::V0_7(op) = op else {
+            panic!("expected a v0.7 op");
+        };
+        assert_eq!(op.signature, default_dummy_signature());
+    }
+
+    #[test]
+    fn test_optional_signature_only_on_rundler_estimate() {
+        let op = json!({
+            "sender": Address::random(),
+            "nonce": "0x0",
+            "initCode": "0x",
+            "callData": "0x",
+        });
+
+        let filled: RpcUserOperationOptionalSignature = serde_json::from_value(op.clone()).unwrap();
+        let UserOperationOptionalGas::V0_6(filled) = filled.into() else {
+            panic!("expected a v0.6 op");
+        };
+        assert!(filled.signature.is_empty());
+        assert!(filled.paymaster_and_data.is_empty());
+
+        // eth_estimateUserOperationGas still requires both
+        assert!(serde_json::from_value::<RpcUserOperationOptionalGas>(op).is_err());
+    }
+
+    fn optional_gas_op_v0_7(factory: Option<Address>) -> UserOperationOptionalGas {
+        UserOperationOptionalGas::V0_7(v0_7::UserOperationOptionalGas {
+            sender: Address::random(),
+            nonce: U256::zero(),
+            call_data: Bytes::new(),
+            call_gas_limit: None,
+            verification_gas_limit: None,
+            pre_verification_gas: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            signature: Bytes::new(),
+            paymaster: None,
+            paymaster_data: Bytes::new(),
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            factory,
+            factory_data: Bytes::new(),
+        })
+    }
+
+    fn optional_gas_op(init_code: Bytes) -> UserOperationOptionalGas {
+        UserOperationOptionalGas::V0_6(v0_6::UserOperationOptionalGas {
+            sender: Address::random(),
+            nonce: U256::zero(),
+            init_code,
+            call_data: Bytes::new(),
+            call_gas_limit: None,
+            verification_gas_limit: None,
+            pre_verification_gas: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            paymaster_and_data: Bytes::new(),
+            signature: Bytes::new(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_get_user_operation_status() {
+        let ep = Address::random();
+        let pending = pending_op(ep);
+        let pending_hash = pending.uo.hash(ep, 1);
+        let chain_spec = ChainSpec {
+            id: 1,
+            ..Default::default()
+        };
+        let included_hash = H256::random();
+        let dropped_op = v0_6::UserOperation {
+            sender: Address::random(),
+            max_fee_per_gas: 100.into(),
+            max_priority_fee_per_gas: 10.into(),
+            verification_gas_limit: 100_000.into(),
+            ..Default::default()
+        };
+        let dropped_hash = dropped_op.hash(ep, 1);
+
+        let mut pool = MockPool::default();
+        pool.expect_get_op_by_hash()
+            .withf(move |&h| h == pending_hash)
+            .returning(move |_| Ok(Some(pending.clone())));
+        pool.expect_get_op_by_hash().returning(|_| Ok(None));
+        pool.expect_add_op().returning(move |_, _| Ok(dropped_hash));
+
+        let event_log = Log {
+            address: chain_spec.entry_point_address_v0_6,
+            topics: vec![
+                UserOperationEventFilter::signature(),
+                included_hash,
+                H256::from(Address::random()),
+                H256::zero(),
+            ],
+            data: abi::encode(&[
+                Token::Uint(U256::zero()),
+                Token::Bool(true),
+                Token::Uint(U256::zero()),
+                Token::Uint(U256::zero()),
+            ])
+            .into(),
+            transaction_hash: Some(H256::random()),
+            ..Default::default()
+        };
+        let mut provider = MockProvider::default();
+        provider.expect_get_block_number().returning(|| Ok(1));
+        let logs = vec![event_log.clone()];
+        provider
+            .expect_get_logs()
+            .returning(move |filter| match filter.topics[1] {
+                Some(ValueOrArray::Value(Some(h))) if h == included_hash => Ok(logs.clone()),
+                _ => Ok(vec![]),
+            });
+        provider
+            .expect_get_transaction_receipt()
+            .returning(move |_: H256| {
+                Ok(Some(TransactionReceipt {
+                    logs: vec![event_log.clone()],
+                    ..Default::default()
+                }))
+            });
+
+        let api = build_api(
+            ep,
+            pool,
+            provider,
+            MockGasEstimator::default(),
+            HashMap::new(),
+        );
+        // the dropped op is accepted, then removed from the pool without being mined
+        api.send_raw_user_operation(ep, dropped_op.encode().into())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            api.get_user_operation_status(pending_hash).await.unwrap(),
+            RpcUserOperationStatus::Pending
+        );
+        assert_eq!(
+            api.get_user_operation_status(included_hash).await.unwrap(),
+            RpcUserOperationStatus::Included
+        );
+        assert_eq!(
+            api.get_user_operation_status(dropped_hash).await.unwrap(),
+            RpcUserOperationStatus::Dropped
+        );
+        assert_eq!(
+            api.get_user_operation_status(H256::random()).await.unwrap(),
+            RpcUserOperationStatus::Unknown
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_user_operation_receipts_too_many_hashes() {
+        let api = create_api(Address::random(), MockPool::default());
+        let hashes = (0..9).map(|_| H256::random()).collect();
+
+        // rejected before any receipt is looked up
+        assert!(matches!(
+            RundlerApi::get_user_operation_receipts(&api, hashes).await,
+            Err(EthRpcError::InvalidParams(_))
+        ));
+    }
+
+    fn create_api(ep: Address, pool: MockPool) -> RundlerApi<MockProvider, MockPool> {
+        create_api_with_estimator(ep, pool, MockGasEstimator::default(), HashMap::new())
+    }
+
+    fn create_api_with_estimator(
+        ep: Address,
+        pool: MockPool,
+        gas_estimator: MockGasEstimator,
+        dummy_signatures: HashMap<Address, Bytes>,
+    ) -> RundlerApi<MockProvider, MockPool> {
+        build_api(
+            ep,
+            pool,
+            MockProvider::default(),
+            gas_estimator,
+            dummy_signatures,
+        )
+    }
+
+    fn build_api(
+        ep: Address,
+        pool: MockPool,
+        provider: MockProvider,
+        gas_estimator: MockGasEstimator,
+        dummy_signatures: HashMap<Address, Bytes>,
+    ) -> RundlerApi<MockProvider, MockPool> {
+        let chain_spec = ChainSpec {
+            id: 1,
+            ..Default::default()
+        };
+        let provider = Arc::new(provider);
+        let mut entry_point = MockEntryPointV0_6::default();
+        entry_point.expect_address().returning(move || ep);
+
+        let router = EntryPointRouterBuilder::default()
+            .v0_6(EntryPointRouteImpl::new(
+                Arc::new(entry_point),
+                gas_estimator,
+                UserOperationEventProviderV0_6::new(chain_spec.clone(), provider.clone(), None),
+            ))
+            .build();
+
+        RundlerApi::new(
+            &chain_spec,
+            provider,
+            router,
+            pool,
+            Settings {
+                priority_fee_mode: PriorityFeeMode::BaseFeePercent(0),
+                bundle_priority_fee_overhead_percent: 0,
+                max_verification_gas: 0,
+            },
+            RpcConfig::default(),
+            dummy_signatures,
+            HashSet::new(),
+            MethodTimeouts::default(),
+            4,
+            8,
+            AdmissionContext {
+                hooks: AdmissionHooks::default(),
+                log_rejected_ops: false,
+                submitted_ops: SubmittedOps::new(10),
+            },
+            MetricsSnapshot::default(),
+        )
+    }
 }
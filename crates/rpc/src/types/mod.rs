@@ -16,6 +16,7 @@ use ethers::{
     utils::to_checksum,
 };
 use rundler_types::{
+    builder::BundlePreview,
     chain::ChainSpec,
     pool::{Reputation, ReputationStatus},
     v0_6::UserOperation as UserOperationV0_6,
@@ -145,6 +146,41 @@ pub(crate) struct RpcUserOperationByHash {
     pub(crate) transaction_hash: Option<H256>,
 }
 
+/// User operation pending in the mempool
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RpcPendingUserOperation {
+    /// The hash of the user operation
+    pub(crate) user_op_hash: H256,
+    /// The entry point address this operation was sent to
+    pub(crate) entry_point: RpcAddress,
+    /// The nonce of the user operation
+    pub(crate) nonce: U256,
+    /// The max fee per gas of the user operation
+    pub(crate) max_fee_per_gas: U256,
+    /// The max priority fee per gas of the user operation
+    pub(crate) max_priority_fee_per_gas: U256,
+    /// The ERC-20 token charged by the operation's token paymaster, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) paymaster_token: Option<Address>,
+    /// The full user operation
+    pub(crate) user_operation: RpcUserOperation,
+}
+
+/// Status of a user operation, as known to this bundler
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum RpcUserOperationStatus {
+    /// Never accepted by this bundler, or accepted too long ago to be remembered
+    Unknown,
+    /// Pending in the mempool
+    Pending,
+    /// Included on chain
+    Included,
+    /// Accepted by this bundler, but removed from the mempool without being included
+    Dropped,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(untagged)]
 pub(crate) enum RpcUserOperationOptionalGas {
@@ -161,6 +197,35 @@ impl From<RpcUserOperationOptionalGas> for UserOperationOptionalGas {
     }
 }
 
+/// A user operation with optional gas fields that may also omit its signature and
+/// paymaster data, which are filled with presets by `rundler_estimateUserOperationGas`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub(crate) struct RpcUserOperationOptionalSignature(RpcUserOperationOptionalGas);
+
+impl<'de> Deserialize<'de> for RpcUserOperationOptionalSignature {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut fields = serde_json::Map::deserialize(deserializer)?;
+        for field in ["signature", "paymasterAndData"] {
+            fields
+                .entry(field)
+                .or_insert_with(|| serde_json::Value::String("0x".to_string()));
+        }
+        RpcUserOperationOptionalGas::deserialize(serde_json::Value::Object(fields))
+            .map(Self)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<RpcUserOperationOptionalSignature> for UserOperationOptionalGas {
+    fn from(op: RpcUserOperationOptionalSignature) -> Self {
+        op.0.into()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
 pub(crate) enum RpcGasEstimate {
@@ -192,12 +257,26 @@ pub struct RpcUserOperationReceipt {
     pub sender: RpcAddress,
     /// The nonce of this user operation
     pub nonce: U256,
+    /// The key portion of the 2D nonce, only present if the key is nonzero
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce_key: Option<U256>,
+    /// The sequence portion of the 2D nonce, only present if the key is nonzero
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce_sequence: Option<U256>,
     /// The paymaster used by this operation, empty if none used
     pub paymaster: RpcAddress,
+    /// The ERC-20 token the sender paid the paymaster in, only present for token paymasters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_token: Option<Address>,
+    /// The net amount of `paymaster_token` the sender paid the paymaster
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_token_cost: Option<U256>,
     /// The gas cost of this operation
     pub actual_gas_cost: U256,
     /// The gas used by this operation
     pub actual_gas_used: U256,
+    /// The gas price paid by this operation, `actual_gas_cost / actual_gas_used`
+    pub effective_gas_price: U256,
     /// Whether this operation's execution was successful
     pub success: bool,
     /// If not successful, the revert reason string
@@ -291,3 +370,64 @@ pub struct RpcDebugPaymasterBalance {
     /// Paymaster confirmed balance onchain
     pub confirmed_balance: U256,
 }
+
+/// Preview of the bundle the builder would send right now
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcBundlePreview {
+    /// Hashes of the user operations in the bundle, in bundle order
+    pub user_op_hashes: Vec<H256>,
+    /// Estimated total gas of the bundle transaction
+    pub gas_estimate: U256,
+}
+
+impl From<BundlePreview> for RpcBundlePreview {
+    fn from(preview: BundlePreview) -> Self {
+        Self {
+            user_op_hashes: preview.user_op_hashes,
+            gas_estimate: preview.gas_estimate,
+        }
+    }
+}
+
+/// Sanitized view of the effective configuration of the RPC server
+///
+/// Secrets, such as credentials embedded in the node URL, are never included.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcConfig {
+    /// Chain ID
+    pub chain_id: u64,
+    /// Enabled entry points
+    pub entry_points: Vec<Address>,
+    /// Full node URL with credentials, path and query removed
+    pub node_url: String,
+    /// True if running in unsafe mode
+    pub unsafe_mode: bool,
+    /// Maximum size of a bundle transaction in bytes
+    pub max_transaction_size_bytes: usize,
+    /// Maximum verification gas allowed for a user operation
+    pub max_verification_gas: U256,
+    /// Maximum total execution gas allowed for a user operation
+    pub max_total_execution_gas: U256,
+    /// Maximum call gas used during estimation, also the default call gas limit
+    /// for user operations that don't provide one
+    pub max_call_gas: u64,
+    /// Maximum verification gas used during estimation, also the default verification
+    /// gas limit for user operations that don't provide one
+    pub max_estimation_verification_gas: u64,
+    /// Maximum paymaster verification gas used during estimation
+    pub max_paymaster_verification_gas: u64,
+    /// Maximum paymaster post op gas used during estimation
+    pub max_paymaster_post_op_gas: u64,
+    /// Maximum gas used in a call to `simulateHandleOp`
+    pub max_simulate_handle_ops_gas: u64,
+    /// Priority fee mode used to calculate the required priority fee
+    pub priority_fee_mode: String,
+    /// Percentage added to the network priority fee for bundles
+    pub bundle_priority_fee_overhead_percent: u64,
+    /// Percentage of the network base fee that a user operation must pay
+    pub base_fee_accept_percent: u64,
+    /// Percentage of the required preVerificationGas that a user operation must provide
+    pub pre_verification_gas_accept_percent: u64,
+}
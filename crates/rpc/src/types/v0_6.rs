@@ -114,6 +114,8 @@ pub(crate) struct RpcGasEstimate {
     pre_verification_gas: U256,
     call_gas_limit: U256,
     verification_gas_limit: U256,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
 }
 
 impl From<GasEstimate> for RpcGasEstimate {
@@ -122,6 +124,7 @@ impl From<GasEstimate> for RpcGasEstimate {
             pre_verification_gas: estimate.pre_verification_gas,
             call_gas_limit: estimate.call_gas_limit,
             verification_gas_limit: estimate.verification_gas_limit,
+            warnings: estimate.warnings,
         }
     }
 }
@@ -180,6 +180,8 @@ pub(crate) struct RpcGasEstimate {
     call_gas_limit: U256,
     verification_gas_limit: U256,
     paymaster_verification_gas_limit: Option<U256>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
 }
 
 impl From<GasEstimate> for RpcGasEstimate {
@@ -189,6 +191,7 @@ impl From<GasEstimate> for RpcGasEstimate {
             call_gas_limit: estimate.call_gas_limit,
             verification_gas_limit: estimate.verification_gas_limit,
             paymaster_verification_gas_limit: estimate.paymaster_verification_gas_limit,
+            warnings: estimate.warnings,
         }
     }
 }
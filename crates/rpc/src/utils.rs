@@ -11,13 +11,21 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::panic::AssertUnwindSafe;
+use std::{
+    collections::{HashMap, HashSet},
+    panic::AssertUnwindSafe,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
-use futures_util::{Future, FutureExt};
+use ethers::types::H256;
+use futures_util::{stream, Future, FutureExt, StreamExt, TryStreamExt};
 use jsonrpsee::{
     core::RpcResult,
     types::{error::INTERNAL_ERROR_CODE, ErrorObjectOwned},
 };
+use rundler_utils::cache::LruMap;
+use url::Url;
 
 use crate::{error::rpc_err, eth::EthRpcError};
 
@@ -37,6 +45,113 @@ where
     }
 }
 
+/// Per-method timeouts for RPC handlers.
+///
+/// Methods without a configured timeout are only bound by the server wide timeout.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct MethodTimeouts(Arc<HashMap<String, Duration>>);
+
+impl MethodTimeouts {
+    /// Create a new set of timeouts keyed by full method name, i.e. `eth_estimateUserOperationGas`
+    pub(crate) fn new(timeouts: HashMap<String, Duration>) -> Self {
+        Self(Arc::new(timeouts))
+    }
+
+    /// Returns an error if a timeout is configured for a method that isn't in `methods`,
+    /// so that a misspelled method name fails startup instead of being silently ignored.
+    pub(crate) fn check_methods<'a>(
+        &self,
+        methods: impl IntoIterator<Item = &'a str>,
+    ) -> anyhow::Result<()> {
+        let methods = methods.into_iter().collect::<HashSet<_>>();
+        let mut unknown = self
+            .0
+            .keys()
+            .filter(|m| !methods.contains(m.as_str()))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        unknown.sort_unstable();
+        anyhow::bail!(
+            "method timeouts configured for unknown methods: {}",
+            unknown.join(", ")
+        )
+    }
+
+    /// Call an RPC handler, returning a timeout error if it does not complete within
+    /// the timeout configured for its method.
+    pub(crate) async fn call<F, R, E>(&self, rpc_name: &'static str, f: F) -> RpcResult<R>
+    where
+        F: Future<Output = Result<R, E>> + Send,
+        E: Into<ErrorObjectOwned>,
+    {
+        let Some(timeout) = self.0.get(rpc_name).copied() else {
+            return safe_call_rpc_handler(rpc_name, f).await;
+        };
+
+        match tokio::time::timeout(timeout, safe_call_rpc_handler(rpc_name, f)).await {
+            Ok(r) => r,
+            Err(_) => {
+                metrics::counter!("rpc_timeout_count", "rpc_name" => rpc_name).increment(1);
+                Err(EthRpcError::RequestTimeout(rpc_name, timeout).into())
+            }
+        }
+    }
+}
+
+/// Hashes of the user operations most recently accepted into the mempool by this server.
+///
+/// Used to tell operations that were dropped from the mempool apart from operations this
+/// server has never seen. Only the most recent hashes are kept, and none survive a restart.
+#[derive(Clone)]
+pub(crate) struct SubmittedOps(Arc<Mutex<LruMap<H256, ()>>>);
+
+impl SubmittedOps {
+    /// Create a new tracker remembering up to `capacity` hashes
+    pub(crate) fn new(capacity: u32) -> Self {
+        Self(Arc::new(Mutex::new(LruMap::new(capacity))))
+    }
+
+    /// Record that a user operation was accepted into the mempool
+    pub(crate) fn insert(&self, hash: H256) {
+        self.0.lock().unwrap().insert(hash, ());
+    }
+
+    /// Returns true if the user operation was recently accepted into the mempool
+    pub(crate) fn contains(&self, hash: H256) -> bool {
+        self.0.lock().unwrap().peek(&hash).is_some()
+    }
+}
+
+/// Strip credentials, path and query from a URL, as these commonly carry API keys.
+pub(crate) fn redact_url(url: &str) -> String {
+    match Url::parse(url) {
+        Ok(url) => match url.host_str() {
+            Some(host) => match url.port() {
+                Some(port) => format!("{}://{host}:{port}", url.scheme()),
+                None => format!("{}://{host}", url.scheme()),
+            },
+            None => "<redacted>".to_string(),
+        },
+        Err(_) => "<redacted>".to_string(),
+    }
+}
+
+/// Runs fallible futures with at most `limit` in flight at once, returning their outputs
+/// in the original order, or the first error encountered.
+pub(crate) async fn try_join_bounded<I, F, T, E>(futs: I, limit: usize) -> Result<Vec<T>, E>
+where
+    I: IntoIterator<Item = F>,
+    F: Future<Output = Result<T, E>>,
+{
+    stream::iter(futs)
+        .buffered(limit.max(1))
+        .try_collect()
+        .await
+}
+
 /// Internal RPC result type.
 pub(crate) type InternalRpcResult<T> = std::result::Result<T, InternalRpcError>;
 
@@ -56,3 +171,75 @@ impl From<InternalRpcError> for ErrorObjectOwned {
         rpc_err(INTERNAL_ERROR_CODE, e.0.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use tokio::sync::Barrier;
+
+    use super::*;
+
+    #[test]
+    fn test_method_timeouts_check_methods() {
+        let timeouts = MethodTimeouts::new(HashMap::from([
+            (
+                "eth_estimateUserOperationGas".to_string(),
+                Duration::from_secs(1),
+            ),
+            (
+                "debug_bundler_dumpMempool".to_string(),
+                Duration::from_secs(1),
+            ),
+        ]));
+        let methods = [
+            "eth_estimateUserOperationGas",
+            "debug_bundler_dumpMempool",
+            "admin_clearState",
+        ];
+        assert!(timeouts.check_methods(methods).is_ok());
+
+        let err = timeouts
+            .check_methods(["eth_estimateUserOperationGas"])
+            .unwrap_err();
+        assert!(err.to_string().contains("debug_bundler_dumpMempool"));
+    }
+
+    #[tokio::test]
+    async fn test_try_join_bounded_runs_concurrently() {
+        // every future waits for all the others, so this only completes if they run concurrently
+        let barrier = Barrier::new(4);
+        let futs = (0..4).map(|i| {
+            let barrier = &barrier;
+            async move {
+                barrier.wait().await;
+                Ok::<_, ()>(i)
+            }
+        });
+
+        let res = tokio::time::timeout(Duration::from_secs(5), try_join_bounded(futs, 4))
+            .await
+            .expect("lookups should resolve concurrently");
+        assert_eq!(res, Ok(vec![0, 1, 2, 3]));
+    }
+
+    #[tokio::test]
+    async fn test_try_join_bounded_respects_limit() {
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let futs = (0..10).map(|i| {
+            let (in_flight, max_in_flight) = (&in_flight, &max_in_flight);
+            async move {
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok::<_, ()>(i)
+            }
+        });
+
+        let res = try_join_bounded(futs, 3).await;
+        assert_eq!(res, Ok((0..10).collect::<Vec<_>>()));
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 3);
+    }
+}
@@ -153,7 +153,7 @@ where
                     .context("gas estimate should fit in a 128-bit int")?);
             } else if let Ok(revert) = EstimateCallGasRevertAtMax::decode(&target_revert_data) {
                 let error = if let Some(message) = eth::parse_revert_message(&revert.revert_data) {
-                    GasEstimationError::RevertInCallWithMessage(message)
+                    GasEstimationError::RevertInCallWithMessage(message, revert.revert_data)
                 } else {
                     GasEstimationError::RevertInCallWithBytes(revert.revert_data)
                 };
@@ -216,7 +216,7 @@ where
                 Ok(())
             } else {
                 let error = if let Some(message) = eth::parse_revert_message(&result.revert_data) {
-                    GasEstimationError::RevertInCallWithMessage(message)
+                    GasEstimationError::RevertInCallWithMessage(message, result.revert_data)
                 } else {
                     GasEstimationError::RevertInCallWithBytes(result.revert_data)
                 };
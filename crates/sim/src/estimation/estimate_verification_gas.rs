@@ -41,7 +41,17 @@ pub trait VerificationGasEstimator: Send + Sync + 'static {
         state_override: &spoof::State,
         max_guess: U128,
         get_op_with_limit: F,
-    ) -> Result<U128, GasEstimationError>;
+    ) -> Result<VerificationGasEstimate, GasEstimationError>;
+}
+
+/// Result of a verification gas estimate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VerificationGasEstimate {
+    /// The estimated verification gas limit
+    pub gas: U128,
+    /// The signature aggregator returned by the account's validation at the
+    /// estimated limit, if any
+    pub aggregator: Option<Address>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -75,7 +85,7 @@ where
         state_override: &spoof::State,
         max_guess: U128,
         get_op_with_limit: F,
-    ) -> Result<U128, GasEstimationError> {
+    ) -> Result<VerificationGasEstimate, GasEstimationError> {
         let timer = std::time::Instant::now();
         let paymaster_gas_fee = U128::from(self.settings.verification_estimation_gas_fee);
 
@@ -138,7 +148,7 @@ where
 
         let run_attempt_returning_error = |gas: u64| async move {
             let op = get_op(gas.into());
-            let revert = self
+            let result = self
                 .entry_point
                 .call_spoofed_simulate_op(
                     op,
@@ -148,21 +158,21 @@ where
                     self.settings.max_simulate_handle_ops_gas.into(),
                     state_override,
                 )
-                .await?
-                .err();
-
-            if let Some(revert) = revert {
-                if let Some(error_code) = revert.entry_point_error_code() {
-                    if OUT_OF_GAS_ERROR_CODES.contains(&error_code) {
-                        // This error occurs when out of gas, return false.
-                        return Ok(false);
+                .await?;
+
+            match result {
+                // This succeeded, return the execution result
+                Ok(execution_result) => Ok(Some(execution_result)),
+                Err(revert) => {
+                    if let Some(error_code) = revert.entry_point_error_code() {
+                        if OUT_OF_GAS_ERROR_CODES.contains(&error_code) {
+                            // This error occurs when out of gas, return none.
+                            return Ok(None);
+                        }
                     }
+                    // This is a different error, return it
+                    Err(GasEstimationError::RevertInValidation(revert))
                 }
-                // This is a different error, return it
-                Err(GasEstimationError::RevertInValidation(revert))
-            } else {
-                // This succeeded, return true
-                Ok(true)
             }
         };
 
@@ -174,12 +184,14 @@ where
         }
         let mut guess = gas_used.gas_used.as_u64().saturating_mul(2);
         let mut num_rounds = 0;
+        let mut aggregator = None;
         while (min_success_gas as f64) / (max_failure_gas as f64)
             > (1.0 + GAS_ESTIMATION_ERROR_MARGIN)
         {
             num_rounds += 1;
-            if run_attempt_returning_error(guess).await? {
+            if let Some(execution_result) = run_attempt_returning_error(guess).await? {
                 min_success_gas = guess;
+                aggregator = execution_result.aggregator;
             } else {
                 max_failure_gas = guess;
             }
@@ -200,9 +212,12 @@ where
             min_success_gas += self.chain_spec.deposit_transfer_overhead;
         }
 
-        Ok(U128::try_from(min_success_gas)
-            .ok()
-            .context("min success gas should fit in 128-bit int")?)
+        Ok(VerificationGasEstimate {
+            gas: U128::try_from(min_success_gas)
+                .ok()
+                .context("min success gas should fit in 128-bit int")?,
+            aggregator,
+        })
     }
 }
 
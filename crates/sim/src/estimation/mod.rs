@@ -11,7 +11,7 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use ethers::types::{Bytes, U128};
+use ethers::types::{Bytes, U128, U256};
 #[cfg(feature = "test-utils")]
 use mockall::automock;
 use rundler_types::{GasEstimate, ValidationRevert};
@@ -19,7 +19,9 @@ use rundler_types::{GasEstimate, ValidationRevert};
 use crate::precheck::MIN_CALL_GAS_LIMIT;
 
 mod estimate_verification_gas;
-pub use estimate_verification_gas::{VerificationGasEstimator, VerificationGasEstimatorImpl};
+pub use estimate_verification_gas::{
+    VerificationGasEstimate, VerificationGasEstimator, VerificationGasEstimatorImpl,
+};
 mod estimate_call_gas;
 pub use estimate_call_gas::{
     CallGasEstimator, CallGasEstimatorImpl, CallGasEstimatorSpecialization,
@@ -42,9 +44,9 @@ pub enum GasEstimationError {
     /// Validation reverted
     #[error("{0}")]
     RevertInValidation(ValidationRevert),
-    /// Call reverted with a string message
+    /// Call reverted with a string message, along with the raw revert data
     #[error("user operation's call reverted: {0}")]
-    RevertInCallWithMessage(String),
+    RevertInCallWithMessage(String, Bytes),
     /// Call reverted with bytes
     #[error("user operation's call reverted: {0:#x}")]
     RevertInCallWithBytes(Bytes),
@@ -89,6 +91,9 @@ pub struct Settings {
     pub max_paymaster_verification_gas: u64,
     /// The maximum amount of gas that can be used for the paymaster post op step of a user operation
     pub max_paymaster_post_op_gas: u64,
+    /// Verification gas reserved for a paymaster's `postOp` call on top of the simulated
+    /// verification gas, for entry point v0.6 where `postOp` runs with the verification gas limit
+    pub paymaster_post_op_gas: u64,
     /// The maximum amount of total execution gas to check after estimation
     pub max_total_execution_gas: u64,
     /// The maximum amount of gas that can be used in a call to `simulateHandleOps`
@@ -99,6 +104,10 @@ pub struct Settings {
     /// gas price.
     /// Clients can use state overrides to set the balance of the fee-payer to at least this value.
     pub verification_estimation_gas_fee: u64,
+    /// After estimating, validate once more at the fees set on the user operation, to catch
+    /// validation that depends on the fees. Only applies to operations with a non-zero
+    /// `maxFeePerGas`, the fee-payer must be able to cover the estimated gas cost at those fees.
+    pub simulate_with_op_fees: bool,
 }
 
 impl Settings {
@@ -113,3 +122,20 @@ impl Settings {
         None
     }
 }
+
+/// Returns a warning if an estimated gas limit was clamped to its configured max.
+///
+/// Limits provided by the client are not estimated, so they never produce a warning.
+fn clamp_warning(field: &str, provided: bool, estimate: U256, max: u64) -> Option<String> {
+    (!provided && estimate >= U256::from(max)).then(|| format!("{field} clamped to max {max}"))
+}
+
+/// Returns true if simulations should keep the operation's fees instead of zeroing them
+fn use_op_fees(settings: &Settings, max_fee_per_gas: U256) -> bool {
+    settings.simulate_with_op_fees && !max_fee_per_gas.is_zero()
+}
+
+/// Returns true if the client set a gas limit, unset and zero limits are estimated
+fn is_provided<T: Default + PartialEq>(limit: Option<T>) -> bool {
+    limit.is_some_and(|l| l != T::default())
+}
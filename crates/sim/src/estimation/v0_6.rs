@@ -30,14 +30,14 @@ use rundler_types::{
         ENTRY_POINT_V0_6_DEPLOYED_BYTECODE,
     },
     v0_6::{UserOperation, UserOperationOptionalGas},
-    GasEstimate,
+    GasEstimate, UserOperation as _,
 };
 use rundler_utils::{eth, math};
 use tokio::join;
 
 use super::{
-    CallGasEstimator, CallGasEstimatorImpl, CallGasEstimatorSpecialization, GasEstimationError,
-    Settings, VerificationGasEstimator,
+    clamp_warning, is_provided, use_op_fees, CallGasEstimator, CallGasEstimatorImpl,
+    CallGasEstimatorSpecialization, GasEstimationError, Settings, VerificationGasEstimator,
 };
 use crate::{
     estimation::estimate_verification_gas::GetOpWithLimitArgs, gas, precheck::MIN_CALL_GAS_LIMIT,
@@ -107,6 +107,13 @@ where
         let mut op_with_gas = full_op;
         op_with_gas.verification_gas_limit = verification_gas_limit;
         op_with_gas.call_gas_limit = call_gas_limit;
+        // Credit any modeled gas refund against an estimated pre_verification_gas
+        if !is_provided(op.pre_verification_gas) {
+            let refund = gas::pre_verification_gas_refund(&self.chain_spec, &op_with_gas);
+            op_with_gas.pre_verification_gas =
+                op_with_gas.pre_verification_gas.saturating_sub(refund);
+        }
+        let pre_verification_gas = op_with_gas.pre_verification_gas;
         let gas_limit =
             gas::user_operation_execution_gas_limit(&self.chain_spec, &op_with_gas, true);
         if gas_limit > self.settings.max_total_execution_gas.into() {
@@ -116,11 +123,33 @@ where
             ));
         }
 
+        self.check_validation_with_op_fees(&op_with_gas, block_hash, &state_override)
+            .await?;
+
+        let warnings = [
+            clamp_warning(
+                "verificationGasLimit",
+                is_provided(op.verification_gas_limit),
+                verification_gas_limit,
+                self.settings.max_verification_gas,
+            ),
+            clamp_warning(
+                "callGasLimit",
+                is_provided(op.call_gas_limit),
+                call_gas_limit,
+                self.settings.max_call_gas,
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+
         Ok(GasEstimate {
             pre_verification_gas,
             verification_gas_limit,
             call_gas_limit,
             paymaster_verification_gas_limit: None,
+            warnings,
         })
     }
 }
@@ -213,6 +242,34 @@ where
         Ok(())
     }
 
+    // Some accounts and paymasters revert during validation depending on the op's fees,
+    // which estimating with zeroed fees hides. Validates once at the op's own fees with
+    // the estimated limits.
+    async fn check_validation_with_op_fees(
+        &self,
+        op: &UserOperation,
+        block_hash: H256,
+        state_override: &spoof::State,
+    ) -> Result<(), GasEstimationError> {
+        if !use_op_fees(&self.settings, op.max_fee_per_gas) {
+            return Ok(());
+        }
+
+        self.entry_point
+            .call_spoofed_simulate_op(
+                op.clone(),
+                Address::zero(),
+                Bytes::new(),
+                block_hash,
+                self.settings.max_simulate_handle_ops_gas.into(),
+                state_override,
+            )
+            .await?
+            .map_err(GasEstimationError::RevertInValidation)?;
+
+        Ok(())
+    }
+
     async fn estimate_verification_gas(
         &self,
         optional_op: &UserOperationOptionalGas,
@@ -250,18 +307,27 @@ where
                 get_op_with_limit,
             )
             .await
-            .map(|gas_u128| gas_u128.into())?;
+            .map(|estimate| estimate.gas.into())?;
 
         // Add a buffer to the verification gas limit. Add 10% or 2000 gas, whichever is larger
-        // to ensure we get at least a 2000 gas buffer. Cap at the max verification gas.
-        let verification_gas_limit = cmp::max(
+        // to ensure we get at least a 2000 gas buffer.
+        let mut verification_gas_limit = cmp::max(
             math::increase_by_percent(
                 verification_gas_limit,
                 super::VERIFICATION_GAS_BUFFER_PERCENT,
             ),
             verification_gas_limit + simulation::v0_6::REQUIRED_VERIFICATION_GAS_LIMIT_BUFFER,
-        )
-        .min(self.settings.max_verification_gas.into());
+        );
+
+        // The paymaster's postOp runs with the verification gas limit, and may use more gas
+        // onchain than it did in simulation. Reserve an allowance for it.
+        if full_op.paymaster().is_some() {
+            verification_gas_limit += self.settings.paymaster_post_op_gas.into();
+        }
+
+        // Cap at the max verification gas.
+        let verification_gas_limit =
+            verification_gas_limit.min(self.settings.max_verification_gas.into());
 
         Ok(verification_gas_limit)
     }
@@ -528,19 +594,25 @@ mod tests {
     const TEST_MAX_GAS_LIMITS: u64 = 10000000000;
     const TEST_FEE: U256 = U256([1000, 0, 0, 0]);
 
-    fn create_estimator(
-        entry: MockEntryPointV0_6,
-        provider: MockProvider,
-    ) -> (GasEstimatorWithMocks, Settings) {
-        let settings = Settings {
+    fn create_settings() -> Settings {
+        Settings {
             max_verification_gas: TEST_MAX_GAS_LIMITS,
             max_call_gas: TEST_MAX_GAS_LIMITS,
             max_paymaster_verification_gas: TEST_MAX_GAS_LIMITS,
             max_paymaster_post_op_gas: TEST_MAX_GAS_LIMITS,
+            paymaster_post_op_gas: 0,
             max_total_execution_gas: TEST_MAX_GAS_LIMITS,
             max_simulate_handle_ops_gas: TEST_MAX_GAS_LIMITS,
             verification_estimation_gas_fee: 1_000_000_000_000,
-        };
+            simulate_with_op_fees: false,
+        }
+    }
+
+    fn create_estimator(
+        entry: MockEntryPointV0_6,
+        provider: MockProvider,
+    ) -> (GasEstimatorWithMocks, Settings) {
+        let settings = create_settings();
         let estimator = create_custom_estimator(ChainSpec::default(), provider, entry, settings);
         (estimator, settings)
     }
@@ -631,9 +703,11 @@ mod tests {
             max_call_gas: 10000000000,
             max_paymaster_verification_gas: 10000000000,
             max_paymaster_post_op_gas: 10000000000,
+            paymaster_post_op_gas: 0,
             max_total_execution_gas: 10000000000,
             max_simulate_handle_ops_gas: 100000000,
             verification_estimation_gas_fee: 1_000_000_000_000,
+            simulate_with_op_fees: false,
         };
 
         // Chose arbitrum
@@ -699,9 +773,11 @@ mod tests {
             max_call_gas: 10000000000,
             max_paymaster_verification_gas: 10000000000,
             max_paymaster_post_op_gas: 10000000000,
+            paymaster_post_op_gas: 0,
             max_total_execution_gas: 10000000000,
             max_simulate_handle_ops_gas: 100000000,
             verification_estimation_gas_fee: 1_000_000_000_000,
+            simulate_with_op_fees: false,
         };
 
         // Chose OP
@@ -759,12 +835,13 @@ mod tests {
                     valid_until: 100000000001.into(),
                     target_success: true,
                     target_result: Bytes::new(),
+                    aggregator: None,
                 })
             });
         entry
             .expect_call_spoofed_simulate_op()
             .returning(move |op, _b, _c, _d, _e, _f| {
-                if op.total_verification_gas_limit() < gas_usage {
+                if op.total_verification_gas_limit(&ChainSpec::default()) < gas_usage {
                     return Ok(Err(ValidationRevert::EntryPoint("AA23".to_string())));
                 }
 
@@ -806,6 +883,97 @@ mod tests {
         assert_eq!(expected_with_buffer, estimation);
     }
 
+    #[tokio::test]
+    async fn test_verification_gas_paymaster_post_op_allowance() {
+        let (mut entry, mut provider) = create_base_config();
+
+        let gas_usage = 10_000.into();
+
+        entry
+            .expect_decode_simulate_handle_ops_revert()
+            .returning(|_a| {
+                Ok(ExecutionResult {
+                    pre_op_gas: U256::from(10000),
+                    paid: U256::from(100000),
+                    valid_after: 100000000000.into(),
+                    valid_until: 100000000001.into(),
+                    target_success: true,
+                    target_result: Bytes::new(),
+                    aggregator: None,
+                })
+            });
+        entry
+            .expect_call_spoofed_simulate_op()
+            .returning(move |op, _b, _c, _d, _e, _f| {
+                if op.total_verification_gas_limit(&ChainSpec::default()) < gas_usage {
+                    return Ok(Err(ValidationRevert::EntryPoint("AA23".to_string())));
+                }
+
+                Ok(Ok(ExecutionResult {
+                    target_result: EstimateCallGasResult {
+                        gas_estimate: gas_usage,
+                        num_rounds: 10.into(),
+                    }
+                    .encode()
+                    .into(),
+                    target_success: true,
+                    ..Default::default()
+                }))
+            });
+
+        provider
+            .expect_get_gas_used()
+            .returning(move |_a, _b, _c, _d| {
+                Ok(GasUsedResult {
+                    gas_used: gas_usage * 2,
+                    success: false,
+                    result: Bytes::new(),
+                })
+            });
+
+        let settings = Settings {
+            max_verification_gas: TEST_MAX_GAS_LIMITS,
+            max_call_gas: TEST_MAX_GAS_LIMITS,
+            max_paymaster_verification_gas: TEST_MAX_GAS_LIMITS,
+            max_paymaster_post_op_gas: TEST_MAX_GAS_LIMITS,
+            paymaster_post_op_gas: 15_000,
+            max_total_execution_gas: TEST_MAX_GAS_LIMITS,
+            max_simulate_handle_ops_gas: TEST_MAX_GAS_LIMITS,
+            verification_estimation_gas_fee: 1_000_000_000_000,
+            simulate_with_op_fees: false,
+        };
+        let estimator = create_custom_estimator(ChainSpec::default(), provider, entry, settings);
+
+        let optional_op = demo_user_op_optional_gas(Some(U256::from(10000)));
+        let user_op = UserOperation {
+            paymaster_and_data: Address::random().as_bytes().to_vec().into(),
+            ..demo_user_op()
+        };
+        let estimation = estimator
+            .estimate_verification_gas(&optional_op, &user_op, H256::zero(), &spoof::state())
+            .await
+            .unwrap();
+
+        // the estimation should include the postOp allowance on top of the buffered gas usage,
+        // paymaster ops don't pay the deposit transfer overhead
+        let expected_with_buffer = cmp::max(
+            math::increase_by_percent(gas_usage, VERIFICATION_GAS_BUFFER_PERCENT),
+            gas_usage + REQUIRED_VERIFICATION_GAS_LIMIT_BUFFER,
+        );
+
+        assert_eq!(expected_with_buffer + U256::from(15_000), estimation);
+
+        // ops without a paymaster don't get the allowance
+        let estimation = estimator
+            .estimate_verification_gas(&optional_op, &demo_user_op(), H256::zero(), &spoof::state())
+            .await
+            .unwrap();
+        let expected = gas_usage + ChainSpec::default().deposit_transfer_overhead;
+        let expected_with_buffer =
+            math::increase_by_percent(expected, VERIFICATION_GAS_BUFFER_PERCENT);
+        assert_eq!(expected_with_buffer, estimation);
+    }
+
     #[tokio::test]
     async fn test_binary_search_verification_gas_should_not_overflow() {
         let (mut entry, mut provider) = create_base_config();
@@ -820,6 +988,7 @@ mod tests {
                     valid_until: 100000000001.into(),
                     target_success: true,
                     target_result: Bytes::new(),
+                    aggregator: None,
                 })
             });
         entry
@@ -877,6 +1046,7 @@ mod tests {
                     valid_until: 100000000001.into(),
                     target_success: true,
                     target_result: Bytes::new(),
+                    aggregator: None,
                 })
             });
         entry
@@ -977,6 +1147,7 @@ mod tests {
                     valid_until: 100000000001.into(),
                     target_success: true,
                     target_result: Bytes::new(),
+                    aggregator: None,
                 })
             });
 
@@ -1019,6 +1190,7 @@ mod tests {
                     valid_until: 100000000001.into(),
                     target_success: true,
                     target_result: Bytes::new(),
+                    aggregator: None,
                 })
             });
 
@@ -1054,6 +1226,63 @@ mod tests {
         assert!(estimation.is_err());
     }
 
+    #[tokio::test]
+    async fn test_check_validation_with_op_fees() {
+        // the account reverts during validation when the fee is below this threshold
+        const MIN_FEE: u64 = 500;
+
+        let create_entry = || {
+            let (mut entry, provider) = create_base_config();
+            entry
+                .expect_call_spoofed_simulate_op()
+                .returning(|op, _b, _c, _d, _e, _f| {
+                    if op.max_fee_per_gas < U256::from(MIN_FEE) {
+                        return Ok(Err(ValidationRevert::Operation {
+                            entry_point_reason: "AA23 reverted".to_string(),
+                            inner_revert_data: Bytes::new(),
+                            inner_revert_reason: Some("fee too low".to_string()),
+                        }));
+                    }
+                    Ok(Ok(ExecutionResult::default()))
+                });
+            (entry, provider)
+        };
+        let settings = Settings {
+            simulate_with_op_fees: true,
+            ..create_settings()
+        };
+
+        // the op's fees pass validation
+        let (entry, provider) = create_entry();
+        let estimator = create_custom_estimator(ChainSpec::default(), provider, entry, settings);
+        let result = estimator
+            .check_validation_with_op_fees(&demo_user_op(), H256::zero(), &spoof::state())
+            .await;
+        assert!(result.is_ok());
+
+        // fees below the threshold revert
+        let user_op = UserOperation {
+            max_fee_per_gas: U256::from(100),
+            ..demo_user_op()
+        };
+        let result = estimator
+            .check_validation_with_op_fees(&user_op, H256::zero(), &spoof::state())
+            .await;
+        assert!(matches!(
+            result,
+            Err(GasEstimationError::RevertInValidation(_))
+        ));
+
+        // not checked unless enabled
+        let (mut entry, provider) = create_base_config();
+        entry.expect_call_spoofed_simulate_op().never();
+        let (estimator, _) = create_estimator(entry, provider);
+        let result = estimator
+            .check_validation_with_op_fees(&user_op, H256::zero(), &spoof::state())
+            .await;
+        assert!(result.is_ok());
+    }
+
     #[tokio::test]
     async fn test_estimate_call_gas() {
         let (mut entry, mut provider) = create_base_config();
@@ -1192,7 +1421,7 @@ mod tests {
         entry
             .expect_call_spoofed_simulate_op()
             .returning(move |op, _b, _c, _d, _e, _f| {
-                if op.total_verification_gas_limit() < gas_usage {
+                if op.total_verification_gas_limit(&ChainSpec::default()) < gas_usage {
                     return Ok(Err(ValidationRevert::EntryPoint("AA23".to_string())));
                 }
 
@@ -1217,6 +1446,7 @@ mod tests {
                     valid_until: 100000000001.into(),
                     target_success: true,
                     target_result: Bytes::new(),
+                    aggregator: None,
                 })
             });
 
@@ -1268,6 +1498,93 @@ mod tests {
             estimation.call_gas_limit,
             U256::from(10000) + CALL_GAS_BUFFER_VALUE
         );
+        assert!(estimation.warnings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_estimation_clamp_warning() {
+        let (mut entry, mut provider) = create_base_config();
+        let gas_usage = 10_000.into();
+
+        entry
+            .expect_call_spoofed_simulate_op()
+            .returning(move |op, _b, _c, _d, _e, _f| {
+                if op.total_verification_gas_limit(&ChainSpec::default()) < gas_usage {
+                    return Ok(Err(ValidationRevert::EntryPoint("AA23".to_string())));
+                }
+
+                Ok(Ok(ExecutionResult {
+                    target_result: EstimateCallGasResult {
+                        gas_estimate: U256::from(10000),
+                        num_rounds: U256::from(10),
+                    }
+                    .encode()
+                    .into(),
+                    target_success: true,
+                    ..Default::default()
+                }))
+            });
+        entry
+            .expect_decode_simulate_handle_ops_revert()
+            .returning(|_a| {
+                Ok(ExecutionResult {
+                    pre_op_gas: U256::from(10000),
+                    paid: U256::from(100000),
+                    valid_after: 100000000000.into(),
+                    valid_until: 100000000001.into(),
+                    target_success: true,
+                    target_result: Bytes::new(),
+                    aggregator: None,
+                })
+            });
+
+        provider
+            .expect_get_code()
+            .returning(|_a, _b| Ok(Bytes::new()));
+        provider
+            .expect_get_latest_block_hash_and_number()
+            .returning(|| Ok((H256::zero(), U64::zero())));
+        provider
+            .expect_get_gas_used()
+            .returning(move |_a, _b, _c, _d| {
+                Ok(GasUsedResult {
+                    gas_used: gas_usage,
+                    success: false,
+                    result: Bytes::new(),
+                })
+            });
+
+        provider.expect_get_base_fee().returning(|| Ok(TEST_FEE));
+        provider
+            .expect_get_max_priority_fee()
+            .returning(|| Ok(TEST_FEE));
+
+        let settings = Settings {
+            max_verification_gas: TEST_MAX_GAS_LIMITS,
+            max_call_gas: 12_000,
+            max_paymaster_verification_gas: TEST_MAX_GAS_LIMITS,
+            max_paymaster_post_op_gas: TEST_MAX_GAS_LIMITS,
+            paymaster_post_op_gas: 0,
+            max_total_execution_gas: TEST_MAX_GAS_LIMITS,
+            max_simulate_handle_ops_gas: TEST_MAX_GAS_LIMITS,
+            verification_estimation_gas_fee: 1_000_000_000_000,
+            simulate_with_op_fees: false,
+        };
+        let estimator = create_custom_estimator(ChainSpec::default(), provider, entry, settings);
+
+        let optional_op = demo_user_op_optional_gas(Some(U256::from(10000)));
+
+        let estimation = estimator
+            .estimate_op_gas(optional_op, spoof::state())
+            .await
+            .unwrap();
+
+        // call gas estimate plus buffer exceeds the max and is clamped
+        assert_eq!(estimation.call_gas_limit, U256::from(12_000));
+        assert_eq!(
+            estimation.warnings,
+            vec!["callGasLimit clamped to max 12000".to_string()]
+        );
     }
 
     #[test]
@@ -1281,10 +1598,12 @@ mod tests {
             max_verification_gas: 10,
             max_call_gas: 10,
             max_paymaster_post_op_gas: 10,
+            paymaster_post_op_gas: 0,
             max_paymaster_verification_gas: 10,
             max_total_execution_gas: 10,
             max_simulate_handle_ops_gas: 10,
             verification_estimation_gas_fee: 1_000_000_000_000,
+            simulate_with_op_fees: false,
         };
 
         create_custom_estimator(ChainSpec::default(), provider, entry, settings);
@@ -1441,7 +1760,7 @@ mod tests {
 
         assert!(matches!(
             estimation_error,
-            GasEstimationError::RevertInCallWithMessage(msg) if msg == revert_msg
+            GasEstimationError::RevertInCallWithMessage(msg, _) if msg == revert_msg
         ));
     }
 
@@ -13,12 +13,16 @@
 
 use std::{cmp, ops::Add, sync::Arc};
 
+use anyhow::Context;
 use ethers::{
+    abi::AbiEncode,
     contract::EthCall,
     types::{spoof, Address, Bytes, H256, U128, U256},
 };
 use rand::Rng;
-use rundler_provider::{EntryPoint, L1GasProvider, Provider, SimulationProvider};
+use rundler_provider::{
+    EntryPoint, L1GasProvider, Provider, SignatureAggregator, SimulationProvider,
+};
 use rundler_types::{
     chain::ChainSpec,
     contracts::v0_7::{
@@ -27,17 +31,22 @@ use rundler_types::{
             CALLGASESTIMATIONPROXY_DEPLOYED_BYTECODE,
         },
         entry_point_simulations::ENTRYPOINTSIMULATIONS_DEPLOYED_BYTECODE,
+        i_aggregator::ValidateUserOpSignatureCall,
     },
     v0_7::{UserOperation, UserOperationBuilder, UserOperationOptionalGas},
-    GasEstimate,
+    CalldataByteCounts, GasEstimate,
 };
 use rundler_utils::{eth, math};
 use tokio::join;
 
-use super::{estimate_verification_gas::GetOpWithLimitArgs, GasEstimationError, Settings};
+use super::{
+    clamp_warning, estimate_verification_gas::GetOpWithLimitArgs, is_provided, use_op_fees,
+    GasEstimationError, Settings,
+};
 use crate::{
     gas, CallGasEstimator, CallGasEstimatorImpl, CallGasEstimatorSpecialization, FeeEstimator,
-    VerificationGasEstimator, VerificationGasEstimatorImpl, MIN_CALL_GAS_LIMIT,
+    VerificationGasEstimate, VerificationGasEstimator, VerificationGasEstimatorImpl,
+    MIN_CALL_GAS_LIMIT,
 };
 
 /// Gas estimator for entry point v0.7
@@ -56,7 +65,10 @@ pub struct GasEstimator<P, E, VGE, CGE> {
 impl<P, E, VGE, CGE> super::GasEstimator for GasEstimator<P, E, VGE, CGE>
 where
     P: Provider,
-    E: EntryPoint + SimulationProvider<UO = UserOperation> + L1GasProvider<UO = UserOperation>,
+    E: EntryPoint
+        + SimulationProvider<UO = UserOperation>
+        + L1GasProvider<UO = UserOperation>
+        + SignatureAggregator<UO = UserOperation>,
     VGE: VerificationGasEstimator<UO = UserOperation>,
     CGE: CallGasEstimator<UO = UserOperation>,
 {
@@ -119,6 +131,13 @@ where
         op_with_gas.call_gas_limit = call_gas_limit;
         op_with_gas.verification_gas_limit = verification_gas_limit;
         op_with_gas.paymaster_verification_gas_limit = paymaster_verification_gas_limit;
+        // Credit any modeled gas refund against an estimated pre_verification_gas
+        if !is_provided(op.pre_verification_gas) {
+            let refund = gas::pre_verification_gas_refund(&self.chain_spec, &op_with_gas);
+            op_with_gas.pre_verification_gas =
+                op_with_gas.pre_verification_gas.saturating_sub(refund);
+        }
+        let pre_verification_gas = op_with_gas.pre_verification_gas;
         let gas_limit =
             gas::user_operation_execution_gas_limit(&self.chain_spec, &op_with_gas, true);
         if gas_limit > self.settings.max_total_execution_gas.into() {
@@ -128,6 +147,35 @@ where
             ));
         }
 
+        self.check_validation_with_op_fees(&op_with_gas, block_hash, &state_override)
+            .await?;
+
+        let mut warnings: Vec<String> = [
+            clamp_warning(
+                "verificationGasLimit",
+                is_provided(op.verification_gas_limit),
+                verification_gas_limit.into(),
+                self.settings.max_verification_gas,
+            ),
+            clamp_warning(
+                "callGasLimit",
+                is_provided(op.call_gas_limit),
+                call_gas_limit.into(),
+                self.settings.max_call_gas,
+            ),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if op.paymaster.is_some() {
+            warnings.extend(clamp_warning(
+                "paymasterVerificationGasLimit",
+                is_provided(op.paymaster_verification_gas_limit),
+                paymaster_verification_gas_limit.into(),
+                self.settings.max_paymaster_verification_gas,
+            ));
+        }
+
         Ok(GasEstimate {
             pre_verification_gas,
             call_gas_limit: call_gas_limit.into(),
@@ -135,6 +183,7 @@ where
             paymaster_verification_gas_limit: op
                 .paymaster
                 .map(|_| paymaster_verification_gas_limit.into()),
+            warnings,
         })
     }
 }
@@ -151,6 +200,7 @@ where
     E: EntryPoint
         + SimulationProvider<UO = UserOperation>
         + L1GasProvider<UO = UserOperation>
+        + SignatureAggregator<UO = UserOperation>
         + Clone,
 {
     /// Create a new gas estimator
@@ -193,7 +243,10 @@ where
 impl<P, E, VGE, CGE> GasEstimator<P, E, VGE, CGE>
 where
     P: Provider,
-    E: EntryPoint + SimulationProvider<UO = UserOperation> + L1GasProvider<UO = UserOperation>,
+    E: EntryPoint
+        + SimulationProvider<UO = UserOperation>
+        + L1GasProvider<UO = UserOperation>
+        + SignatureAggregator<UO = UserOperation>,
     VGE: VerificationGasEstimator<UO = UserOperation>,
     CGE: CallGasEstimator<UO = UserOperation>,
 {
@@ -245,6 +298,34 @@ where
         Ok(())
     }
 
+    // Some accounts and paymasters revert during validation depending on the op's fees,
+    // which estimating with zeroed fees hides. Validates once at the op's own fees with
+    // the estimated limits.
+    async fn check_validation_with_op_fees(
+        &self,
+        op: &UserOperation,
+        block_hash: H256,
+        state_override: &spoof::State,
+    ) -> Result<(), GasEstimationError> {
+        if !use_op_fees(&self.settings, op.max_fee_per_gas.into()) {
+            return Ok(());
+        }
+
+        self.entry_point
+            .call_spoofed_simulate_op(
+                op.clone(),
+                Address::zero(),
+                Bytes::new(),
+                block_hash,
+                self.settings.max_simulate_handle_ops_gas.into(),
+                state_override,
+            )
+            .await?
+            .map_err(GasEstimationError::RevertInValidation)?;
+
+        Ok(())
+    }
+
     async fn estimate_verification_gas(
         &self,
         optional_op: &UserOperationOptionalGas,
@@ -272,7 +353,10 @@ where
                 .build()
         };
 
-        let verification_gas_limit = self
+        let VerificationGasEstimate {
+            gas: verification_gas_limit,
+            aggregator,
+        } = self
             .verification_gas_estimator
             .estimate_verification_gas(
                 full_op,
@@ -283,15 +367,64 @@ where
             )
             .await?;
 
+        let aggregator_gas = match aggregator {
+            Some(aggregator) => self.estimate_aggregator_gas(aggregator, full_op).await?,
+            None => U128::zero(),
+        };
+
         let verification_gas_limit = math::increase_by_percent(
             verification_gas_limit,
             super::VERIFICATION_GAS_BUFFER_PERCENT,
         )
+        .saturating_add(aggregator_gas)
         .min(self.settings.max_verification_gas.into());
 
         Ok(verification_gas_limit)
     }
 
+    // Accounts that use a signature aggregator don't verify their signature during
+    // their own validation, the aggregator does. Returns the gas the aggregator uses
+    // to validate the op's signature, excluding the intrinsic and calldata gas of the
+    // estimation transaction.
+    async fn estimate_aggregator_gas(
+        &self,
+        aggregator: Address,
+        full_op: &UserOperation,
+    ) -> Result<U128, GasEstimationError> {
+        let op = UserOperationBuilder::from_uo(full_op.clone(), &self.chain_spec)
+            .paymaster_post_op_gas_limit(U128::zero())
+            .call_gas_limit(U128::zero())
+            .build();
+        let call_data = ValidateUserOpSignatureCall {
+            user_op: op.clone().pack(),
+        }
+        .encode();
+
+        let gas = self
+            .entry_point
+            .estimate_validate_user_op_signature_gas(
+                aggregator,
+                op,
+                self.settings.max_verification_gas,
+            )
+            .await?;
+        let gas = gas.saturating_sub(self.aggregator_call_overhead(&call_data));
+
+        Ok(U128::try_from(gas)
+            .ok()
+            .context("aggregator gas should fit in 128-bit int")?)
+    }
+
+    // Gas charged to the aggregator estimation transaction that isn't used by the
+    // aggregator itself: the transaction intrinsic gas plus its calldata gas.
+    fn aggregator_call_overhead(&self, call_data: &[u8]) -> U256 {
+        self.chain_spec.transaction_intrinsic_gas
+            + CalldataByteCounts::new(call_data).gas_cost(
+                self.chain_spec.calldata_zero_byte_gas,
+                self.chain_spec.calldata_non_zero_byte_gas,
+            )
+    }
+
     async fn estimate_paymaster_verification_gas(
         &self,
         optional_op: &UserOperationOptionalGas,
@@ -326,7 +459,8 @@ where
                 self.settings.max_paymaster_verification_gas.into(),
                 get_op_with_limit,
             )
-            .await?;
+            .await?
+            .gas;
 
         let paymaster_verification_gas_limit = math::increase_by_percent(
             paymaster_verification_gas_limit,
@@ -494,11 +628,15 @@ mod tests {
     };
     use rundler_provider::{ExecutionResult, MockEntryPointV0_7, MockProvider, SimulateOpCallData};
     use rundler_types::{
-        contracts::v0_7::{
-            call_gas_estimation_proxy::TestCallGasResult,
-            entry_point_simulations::SimulateHandleOpCall,
+        contracts::{
+            utils::get_gas_used::GasUsedResult,
+            v0_7::{
+                call_gas_estimation_proxy::TestCallGasResult,
+                entry_point_simulations::SimulateHandleOpCall,
+            },
         },
         v0_7::UserOperationOptionalGas,
+        UserOperation as _, ValidationRevert,
     };
     use rundler_utils::eth::{self, ContractRevertError};
 
@@ -581,9 +719,11 @@ mod tests {
             max_call_gas: TEST_MAX_GAS_LIMITS,
             max_paymaster_verification_gas: TEST_MAX_GAS_LIMITS,
             max_paymaster_post_op_gas: TEST_MAX_GAS_LIMITS,
+            paymaster_post_op_gas: 0,
             max_total_execution_gas: TEST_MAX_GAS_LIMITS,
             max_simulate_handle_ops_gas: TEST_MAX_GAS_LIMITS,
             verification_estimation_gas_fee: 1_000_000_000_000,
+            simulate_with_op_fees: false,
         };
         let estimator = create_custom_estimator(ChainSpec::default(), provider, entry, settings);
         (estimator, settings)
@@ -814,7 +954,7 @@ mod tests {
 
         assert!(matches!(
             estimation_error,
-            GasEstimationError::RevertInCallWithMessage(msg) if msg == revert_msg
+            GasEstimationError::RevertInCallWithMessage(msg, _) if msg == revert_msg
         ));
     }
 
@@ -875,6 +1015,96 @@ mod tests {
         ));
     }
 
+    fn expect_verification_simulation(
+        entry: &mut MockEntryPointV0_7,
+        provider: &mut MockProvider,
+        aggregator: Option<Address>,
+    ) {
+        let gas_usage = U256::from(10_000);
+        entry
+            .expect_decode_simulate_handle_ops_revert()
+            .returning(|_a| Ok(ExecutionResult::default()));
+        entry
+            .expect_call_spoofed_simulate_op()
+            .returning(move |op, _b, _c, _d, _e, _f| {
+                if op.total_verification_gas_limit(&ChainSpec::default()) < gas_usage {
+                    return Ok(Err(ValidationRevert::EntryPoint("AA23".to_string())));
+                }
+                Ok(Ok(ExecutionResult {
+                    aggregator,
+                    ..Default::default()
+                }))
+            });
+        provider
+            .expect_get_gas_used()
+            .returning(move |_a, _b, _c, _d| {
+                Ok(GasUsedResult {
+                    gas_used: gas_usage * 2,
+                    success: false,
+                    result: Bytes::new(),
+                })
+            });
+    }
+
+    async fn estimate_verification_gas_with(
+        aggregator: Option<Address>,
+        mut entry: MockEntryPointV0_7,
+        mut provider: MockProvider,
+    ) -> U128 {
+        expect_verification_simulation(&mut entry, &mut provider, aggregator);
+
+        let (estimator, settings) = create_estimator(entry, provider);
+        let optional_op = demo_user_op_optional_gas(None);
+        let full_op = optional_op
+            .clone()
+            .into_user_operation_builder(
+                &ChainSpec::default(),
+                settings.max_call_gas.into(),
+                settings.max_verification_gas.into(),
+                settings.max_paymaster_verification_gas.into(),
+            )
+            .build();
+
+        estimator
+            .estimate_verification_gas(&optional_op, &full_op, H256::zero(), &spoof::state())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_aggregator_gas() {
+        let aggregator = Address::random();
+        let (mut entry, provider) = create_base_config();
+        entry
+            .expect_estimate_validate_user_op_signature_gas()
+            .withf(move |&a, _, _| a == aggregator)
+            .times(1)
+            .returning(|_, op, _| {
+                // the estimate includes the intrinsic and calldata gas of the transaction
+                let call_data = ValidateUserOpSignatureCall { user_op: op.pack() }.encode();
+                let overhead = CalldataByteCounts::new(&call_data).gas_cost(4.into(), 16.into());
+                Ok(U256::from(21_000) + overhead + 50_000)
+            });
+        let with_aggregator =
+            estimate_verification_gas_with(Some(aggregator), entry, provider).await;
+
+        let (entry, provider) = create_base_config();
+        let without_aggregator = estimate_verification_gas_with(None, entry, provider).await;
+
+        assert_eq!(with_aggregator, without_aggregator + U128::from(50_000));
+    }
+
+    #[tokio::test]
+    async fn test_no_aggregator_gas() {
+        let (mut entry, provider) = create_base_config();
+        entry
+            .expect_estimate_validate_user_op_signature_gas()
+            .never();
+
+        let gas = estimate_verification_gas_with(None, entry, provider).await;
+        assert!(gas > U128::zero());
+    }
+
     #[test]
     fn test_proxy_target_offset() {
         let proxy_target_bytes = hex::decode(PROXY_IMPLEMENTATION_ADDRESS_MARKER).unwrap();
@@ -11,7 +11,7 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::{cmp, fmt::Debug, sync::Arc};
+use std::{cmp, fmt::Debug, sync::Arc, time::Duration};
 
 use anyhow::{bail, Context};
 use ethers::types::U256;
@@ -24,7 +24,8 @@ use rundler_utils::math;
 use tokio::try_join;
 
 use super::oracle::{
-    ConstantOracle, FeeOracle, ProviderOracle, UsageBasedFeeOracle, UsageBasedFeeOracleConfig,
+    CachedOracle, ConstantOracle, FeeOracle, ProviderOracle, UsageBasedFeeOracle,
+    UsageBasedFeeOracleConfig,
 };
 
 /// Returns the required pre_verification_gas for the given user operation
@@ -74,7 +75,10 @@ pub async fn calc_required_pre_verification_gas<
     op: &UO,
     base_fee: U256,
 ) -> anyhow::Result<U256> {
-    let static_gas = op.calc_static_pre_verification_gas(chain_spec, true);
+    let refund = pre_verification_gas_refund(chain_spec, op);
+    let static_gas = op
+        .calc_static_pre_verification_gas(chain_spec, true)
+        .saturating_sub(refund);
     if !chain_spec.calldata_pre_verification_gas {
         return Ok(static_gas);
     }
@@ -95,6 +99,33 @@ pub async fn calc_required_pre_verification_gas<
     Ok(static_gas + dynamic_gas)
 }
 
+/// EIP-3529 caps the gas refund of a transaction at gas used / 5
+const EIP3529_MAX_REFUND_QUOTIENT: u64 = 5;
+
+/// Maximum gas refund credited against pre_verification_gas, as a percent of its static portion
+const MAX_REFUND_STATIC_PVG_PERCENT: u64 = 10;
+
+/// Returns the gas refund expected for the given user operation, credited against its
+/// pre_verification_gas.
+///
+/// Modeled as `chain_spec.gas_refund_percent` of the operation's execution gas, capped at the
+/// EIP-3529 maximum. Execution gas limits are an upper bound on the gas actually used, so the
+/// refund is also capped at a small fraction of the static pre_verification_gas, bounding what
+/// the bundler can lose when an operation uses less gas than its limits. Only the operation's
+/// own fields are used, so estimation and prechecks arrive at the same refund for the same
+/// operation. Zero when refund modeling is disabled.
+pub fn pre_verification_gas_refund<UO: UserOperation>(chain_spec: &ChainSpec, uo: &UO) -> U256 {
+    if chain_spec.gas_refund_percent == 0 {
+        return U256::zero();
+    }
+
+    let execution_gas = uo.total_verification_gas_limit(chain_spec) + uo.call_gas_limit();
+    let static_gas = uo.calc_static_pre_verification_gas(chain_spec, true);
+    math::percent(execution_gas, chain_spec.gas_refund_percent)
+        .min(execution_gas / EIP3529_MAX_REFUND_QUOTIENT)
+        .min(math::percent(static_gas, MAX_REFUND_STATIC_PVG_PERCENT))
+}
+
 /// Gas limit functions
 ///
 /// Gas limit: Total as limit for the bundle transaction
@@ -123,7 +154,7 @@ pub fn user_operation_gas_limit<UO: UserOperation>(
     assume_single_op_bundle: bool,
 ) -> U256 {
     user_operation_pre_verification_gas_limit(chain_spec, uo, assume_single_op_bundle)
-        + uo.total_verification_gas_limit()
+        + uo.total_verification_gas_limit(chain_spec)
         + uo.required_pre_execution_buffer()
         + uo.call_gas_limit()
 }
@@ -139,7 +170,7 @@ pub fn user_operation_execution_gas_limit<UO: UserOperation>(
     assume_single_op_bundle: bool,
 ) -> U256 {
     user_operation_pre_verification_execution_gas_limit(chain_spec, uo, assume_single_op_bundle)
-        + uo.total_verification_gas_limit()
+        + uo.total_verification_gas_limit(chain_spec)
         + uo.required_pre_execution_buffer()
         + uo.call_gas_limit()
 }
@@ -244,6 +275,7 @@ pub struct FeeEstimator<P> {
     provider: Arc<P>,
     priority_fee_mode: PriorityFeeMode,
     bundle_priority_fee_overhead_percent: u64,
+    zero_priority_fee: bool,
     fee_oracle: Arc<dyn FeeOracle>,
 }
 
@@ -264,6 +296,7 @@ impl<P: Provider> FeeEstimator<P> {
             provider: provider.clone(),
             priority_fee_mode,
             bundle_priority_fee_overhead_percent,
+            zero_priority_fee: chain_spec.zero_priority_fee,
             fee_oracle: get_fee_oracle(chain_spec, provider),
         }
     }
@@ -304,7 +337,15 @@ impl<P: Provider> FeeEstimator<P> {
     }
 
     /// Returns the required operation fees for the given bundle fees.
+    ///
+    /// On chains without priority fees, operations are only required to cover the base fee.
     pub fn required_op_fees(&self, bundle_fees: GasFees) -> GasFees {
+        if self.zero_priority_fee {
+            return GasFees {
+                max_fee_per_gas: bundle_fees.max_fee_per_gas - bundle_fees.max_priority_fee_per_gas,
+                max_priority_fee_per_gas: U256::zero(),
+            };
+        }
         self.priority_fee_mode.required_fees(bundle_fees)
     }
 
@@ -324,15 +365,22 @@ fn get_fee_oracle<P>(chain_spec: &ChainSpec, provider: Arc<P>) -> Arc<dyn FeeOra
 where
     P: Provider + Debug,
 {
-    if !chain_spec.eip1559_enabled {
+    if !chain_spec.eip1559_enabled || chain_spec.zero_priority_fee {
         return Arc::new(ConstantOracle::new(U256::zero()));
     }
 
     match chain_spec.priority_fee_oracle_type {
-        chain::PriorityFeeOracleType::Provider => Arc::new(ProviderOracle::new(
-            provider,
-            chain_spec.min_max_priority_fee_per_gas,
-        )),
+        chain::PriorityFeeOracleType::Provider => {
+            let oracle = ProviderOracle::new(provider, chain_spec.min_max_priority_fee_per_gas);
+            if chain_spec.max_priority_fee_cache_ttl_millis == 0 {
+                Arc::new(oracle)
+            } else {
+                Arc::new(CachedOracle::new(
+                    oracle,
+                    Duration::from_millis(chain_spec.max_priority_fee_cache_ttl_millis),
+                ))
+            }
+        }
         chain::PriorityFeeOracleType::UsageBased => {
             let config = UsageBasedFeeOracleConfig {
                 minimum_fee: chain_spec.min_max_priority_fee_per_gas,
@@ -345,3 +393,75 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rundler_provider::MockEntryPointV0_6;
+    use rundler_types::v0_6::UserOperation;
+
+    use super::*;
+
+    fn refund_op() -> UserOperation {
+        UserOperation {
+            call_gas_limit: 12_000.into(),
+            verification_gas_limit: 8_000.into(),
+            pre_verification_gas: 50_000.into(),
+            max_fee_per_gas: 1_000.into(),
+            max_priority_fee_per_gas: 1_000.into(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_gas_refund_only_when_enabled() {
+        let entry_point = MockEntryPointV0_6::new();
+        let op = refund_op();
+
+        let chain_spec = ChainSpec::default();
+        let without_refund =
+            calc_required_pre_verification_gas(&chain_spec, &entry_point, &op, 0.into())
+                .await
+                .unwrap();
+        assert_eq!(
+            without_refund,
+            op.calc_static_pre_verification_gas(&chain_spec, true)
+        );
+        assert_eq!(pre_verification_gas_refund(&chain_spec, &op), U256::zero());
+
+        let chain_spec = ChainSpec {
+            gas_refund_percent: 10,
+            ..Default::default()
+        };
+        let with_refund =
+            calc_required_pre_verification_gas(&chain_spec, &entry_point, &op, 0.into())
+                .await
+                .unwrap();
+        // 10% of 20k execution gas
+        assert_eq!(with_refund, without_refund - U256::from(2_000));
+    }
+
+    #[test]
+    fn test_gas_refund_capped() {
+        let chain_spec = ChainSpec {
+            gas_refund_percent: 50,
+            ..Default::default()
+        };
+        // capped at 1/5 of 20k execution gas
+        assert_eq!(
+            pre_verification_gas_refund(&chain_spec, &refund_op()),
+            U256::from(4_000)
+        );
+
+        // large gas limits are capped at 10% of the static pre_verification_gas
+        let op = UserOperation {
+            call_gas_limit: 1_000_000.into(),
+            verification_gas_limit: 1_000_000.into(),
+            ..refund_op()
+        };
+        let static_gas = op.calc_static_pre_verification_gas(&chain_spec, true);
+        assert_eq!(
+            pre_verification_gas_refund(&chain_spec, &op),
+            static_gas / 10
+        );
+    }
+}
@@ -12,12 +12,13 @@
 // If not, see https://www.gnu.org/licenses/.
 #![allow(dead_code)]
 
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use ethers::types::{BlockNumber, U256};
 use futures_util::future::join_all;
 use rundler_provider::Provider;
+use tokio::{sync::Mutex, time::Instant};
 
 pub(crate) type Result<T, E = FeeOracleError> = std::result::Result<T, E>;
 
@@ -284,6 +285,46 @@ impl FeeOracle for MaxOracle {
     }
 }
 
+/// Oracle that caches the estimate of another oracle for a fixed TTL
+///
+/// Concurrent callers wait on the same refresh, so at most one request is
+/// made to the inner oracle per TTL.
+#[derive(Debug)]
+pub(crate) struct CachedOracle<O> {
+    oracle: O,
+    ttl: Duration,
+    cached: Mutex<Option<(Instant, U256)>>,
+}
+
+impl<O> CachedOracle<O> {
+    pub(crate) fn new(oracle: O, ttl: Duration) -> Self {
+        Self {
+            oracle,
+            ttl,
+            cached: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl<O> FeeOracle for CachedOracle<O>
+where
+    O: FeeOracle,
+{
+    async fn estimate_priority_fee(&self) -> Result<U256> {
+        let mut cached = self.cached.lock().await;
+        if let Some((fetched_at, fee)) = *cached {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(fee);
+            }
+        }
+
+        let fee = self.oracle.estimate_priority_fee().await?;
+        *cached = Some((Instant::now(), fee));
+        Ok(fee)
+    }
+}
+
 /// Oracle that returns a constant fee
 #[derive(Clone, Debug)]
 pub(crate) struct ConstantOracle {
@@ -435,6 +476,48 @@ mod tests {
         assert_eq!(fee, U256::from(401));
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_cached_oracle() {
+        let mut mock = MockProvider::default();
+        let mut seq = mockall::Sequence::new();
+        mock.expect_get_max_priority_fee()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|| Ok(U256::from(400)));
+        mock.expect_get_max_priority_fee()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|| Ok(U256::from(500)));
+        let oracle = CachedOracle::new(
+            ProviderOracle::new(Arc::new(mock), U256::zero()),
+            Duration::from_secs(1),
+        );
+
+        // repeated calls within the TTL hit the cache
+        for _ in 0..3 {
+            assert_eq!(
+                oracle.estimate_priority_fee().await.unwrap(),
+                U256::from(400)
+            );
+        }
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert_eq!(
+            oracle.estimate_priority_fee().await.unwrap(),
+            U256::from(400)
+        );
+
+        // a call after expiry refetches
+        tokio::time::advance(Duration::from_millis(600)).await;
+        assert_eq!(
+            oracle.estimate_priority_fee().await.unwrap(),
+            U256::from(500)
+        );
+        assert_eq!(
+            oracle.estimate_priority_fee().await.unwrap(),
+            U256::from(500)
+        );
+    }
+
     #[tokio::test]
     async fn test_max_oracle_choose_provider() {
         let mut mock = MockProvider::default();
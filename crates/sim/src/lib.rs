@@ -37,7 +37,7 @@ pub use estimation::MockGasEstimator;
 pub use estimation::{
     CallGasEstimator, CallGasEstimatorImpl, CallGasEstimatorSpecialization, GasEstimationError,
     GasEstimator, GasEstimatorV0_6, GasEstimatorV0_7, Settings as EstimationSettings,
-    VerificationGasEstimator, VerificationGasEstimatorImpl,
+    VerificationGasEstimate, VerificationGasEstimator, VerificationGasEstimatorImpl,
 };
 
 pub mod gas;
@@ -56,7 +56,7 @@ pub mod simulation;
 pub use simulation::MockSimulator;
 pub use simulation::{
     MempoolConfig, MempoolConfigs, Settings as SimulationSettings, SimulationError,
-    SimulationResult, Simulator,
+    SimulationResult, Simulator, StakeRequirement,
 };
 
 mod types;
@@ -25,7 +25,7 @@ use rundler_provider::{EntryPoint, L1GasProvider, Provider};
 use rundler_types::{
     chain::ChainSpec,
     pool::{MempoolError, PrecheckViolation},
-    GasFees, UserOperation,
+    Entity, EntityType, GasFees, UserOperation,
 };
 use rundler_utils::math;
 
@@ -97,6 +97,21 @@ pub struct Settings {
     pub base_fee_accept_percent: u64,
     /// Percentage of the preVerificationGas that a user operation must have to be accepted into the mempool.
     pub pre_verification_gas_accept_percent: u64,
+    /// Minimum call gas limit for a user operation with non-empty call data
+    pub min_call_gas_limit: U256,
+    /// Maximum size in bytes of a user operation's init code
+    pub max_init_code_bytes: usize,
+    /// Maximum ratio of verification gas limit to call gas limit for a user operation
+    /// without init code, if any
+    pub max_verification_gas_to_call_gas_ratio: Option<u64>,
+    /// Maximum pre-verification gas of a user operation, if any. Set to the bundle
+    /// pre-verification gas cap, as larger operations could never be bundled.
+    pub max_pre_verification_gas: Option<U256>,
+    /// Reject user operations where the sender is also another entity, e.g. the paymaster
+    pub reject_duplicate_entities: bool,
+    /// Maximum number of nonce sequences a user operation may be ahead of the entry point's
+    /// next nonce for its key, if any
+    pub max_nonce_gap: Option<u64>,
 }
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -109,6 +124,12 @@ impl Default for Settings {
             max_total_execution_gas: 10_000_000.into(),
             base_fee_accept_percent: 50,
             pre_verification_gas_accept_percent: 100,
+            min_call_gas_limit: MIN_CALL_GAS_LIMIT.into(),
+            max_init_code_bytes: 32_768,
+            max_verification_gas_to_call_gas_ratio: None,
+            max_pre_verification_gas: None,
+            reject_duplicate_entities: false,
+            max_nonce_gap: None,
         }
     }
 }
@@ -121,6 +142,8 @@ struct AsyncData {
     payer_funds: U256,
     base_fee: U256,
     min_pre_verification_gas: U256,
+    // Loaded once alongside the other async data and shared by all nonce checks
+    entry_point_nonce: U256,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -149,6 +172,8 @@ where
         violations.extend(self.check_init_code(op, async_data));
         violations.extend(self.check_gas(op, async_data));
         violations.extend(self.check_payer(op, async_data));
+        violations.extend(self.check_nonce(op, async_data));
+        violations.extend(self.check_entities(op));
         if !violations.is_empty() {
             Err(violations)?
         }
@@ -199,14 +224,20 @@ where
         }
     }
 
-    fn check_init_code(&self, op: &UO, async_data: AsyncData) -> ArrayVec<PrecheckViolation, 2> {
+    fn check_init_code(&self, op: &UO, async_data: AsyncData) -> ArrayVec<PrecheckViolation, 3> {
         let AsyncData {
             factory_exists,
             sender_exists,
             ..
         } = async_data;
         let mut violations = ArrayVec::new();
-        if op.factory().is_none() {
+        if op.init_code_len() > self.settings.max_init_code_bytes {
+            violations.push(PrecheckViolation::InitCodeTooLarge(
+                op.init_code_len(),
+                self.settings.max_init_code_bytes,
+            ));
+        }
+        if !op.is_deployment() {
             if !sender_exists {
                 violations.push(PrecheckViolation::SenderIsNotContractAndNoInitCode(
                     op.sender(),
@@ -225,10 +256,26 @@ where
         violations
     }
 
-    fn check_gas(&self, op: &UO, async_data: AsyncData) -> ArrayVec<PrecheckViolation, 6> {
+    fn check_entities(&self, op: &UO) -> Option<PrecheckViolation> {
+        if !self.settings.reject_duplicate_entities {
+            return None;
+        }
+
+        // other entities may share an address, e.g. a contract that is both factory and paymaster
+        let sender = Entity::account(op.sender());
+        op.entities()
+            .into_iter()
+            .find(|entity| entity.kind != EntityType::Account && entity.address == sender.address)
+            .map(|entity| PrecheckViolation::DuplicateEntityAddress(sender, entity))
+    }
+
+    fn check_gas(&self, op: &UO, async_data: AsyncData) -> ArrayVec<PrecheckViolation, 8> {
         let Settings {
             max_verification_gas,
             max_total_execution_gas,
+            min_call_gas_limit,
+            max_verification_gas_to_call_gas_ratio,
+            max_pre_verification_gas,
             ..
         } = self.settings;
         let AsyncData {
@@ -267,14 +314,26 @@ where
                 min_pre_verification_gas,
             ));
         }
+        if let Some(max_pre_verification_gas) = max_pre_verification_gas {
+            if op.pre_verification_gas() > max_pre_verification_gas {
+                violations.push(PrecheckViolation::PreVerificationGasTooHigh(
+                    op.pre_verification_gas(),
+                    max_pre_verification_gas,
+                ));
+            }
+        }
 
         // check that the max fee per gas and max priority fee per gas are at least the required fees
         let min_base_fee = math::percent(base_fee, self.settings.base_fee_accept_percent);
-        let min_priority_fee = self.settings.priority_fee_mode.minimum_priority_fee(
-            base_fee,
-            self.settings.base_fee_accept_percent,
-            self.chain_spec.min_max_priority_fee_per_gas,
-        );
+        let min_priority_fee = if self.chain_spec.zero_priority_fee {
+            U256::zero()
+        } else {
+            self.settings.priority_fee_mode.minimum_priority_fee(
+                base_fee,
+                self.settings.base_fee_accept_percent,
+                self.chain_spec.min_max_priority_fee_per_gas,
+            )
+        };
         let min_max_fee = min_base_fee + min_priority_fee;
 
         // check priority fee first, since once ruled out we can check max fee
@@ -291,12 +350,28 @@ where
             ));
         }
 
-        if op.call_gas_limit() < MIN_CALL_GAS_LIMIT.into() {
+        // ops with empty call data don't execute anything, so they don't need call gas
+        if !op.call_data().is_empty() && op.call_gas_limit() < min_call_gas_limit {
             violations.push(PrecheckViolation::CallGasLimitTooLow(
                 op.call_gas_limit(),
-                MIN_CALL_GAS_LIMIT.into(),
+                min_call_gas_limit,
             ));
         }
+
+        // ops with outsized verification gas relative to call gas are likely griefing attempts.
+        // Deployments are skipped as account creation legitimately uses a lot of verification gas.
+        if let Some(max_ratio) = max_verification_gas_to_call_gas_ratio {
+            if !op.is_deployment()
+                && op.verification_gas_limit()
+                    > op.call_gas_limit().saturating_mul(max_ratio.into())
+            {
+                violations.push(PrecheckViolation::VerificationGasToCallGasRatioTooHigh(
+                    op.verification_gas_limit(),
+                    op.call_gas_limit(),
+                    max_ratio,
+                ));
+            }
+        }
         violations
     }
 
@@ -311,7 +386,7 @@ where
                 return Some(PrecheckViolation::PaymasterIsNotContract(paymaster));
             }
         }
-        let max_gas_cost = op.max_gas_cost();
+        let max_gas_cost = op.max_gas_cost(&self.chain_spec);
         if payer_funds < max_gas_cost {
             if op.paymaster().is_none() {
                 return Some(PrecheckViolation::SenderFundsTooLow(
@@ -328,6 +403,28 @@ where
         None
     }
 
+    fn check_nonce(&self, op: &UO, async_data: AsyncData) -> Option<PrecheckViolation> {
+        // The entry point returns the next nonce for the op's key, so an op with a lower
+        // sequence for the same key can never be included.
+        if op.nonce() < async_data.entry_point_nonce {
+            return Some(PrecheckViolation::NonceTooLow(
+                op.nonce(),
+                async_data.entry_point_nonce,
+            ));
+        }
+        // A sequence far ahead of the next nonce, i.e. any but the first sequences of a key
+        // the nonce manager has never seen, can't be included until the gap is filled.
+        if let Some(max_nonce_gap) = self.settings.max_nonce_gap {
+            let max_nonce = async_data
+                .entry_point_nonce
+                .saturating_add(max_nonce_gap.into());
+            if op.nonce() > max_nonce {
+                return Some(PrecheckViolation::NonceTooHigh(op.nonce(), max_nonce));
+            }
+        }
+        None
+    }
+
     async fn load_async_data(&self, op: &UO) -> anyhow::Result<AsyncData> {
         let (_, base_fee) = self.get_fees().await?;
 
@@ -337,12 +434,14 @@ where
             paymaster_exists,
             payer_funds,
             min_pre_verification_gas,
+            entry_point_nonce,
         ) = tokio::try_join!(
             self.is_contract(op.factory()),
             self.is_contract(Some(op.sender())),
             self.is_contract(op.paymaster()),
             self.get_payer_funds(op),
-            self.get_required_pre_verification_gas(op.clone(), base_fee)
+            self.get_required_pre_verification_gas(op.clone(), base_fee),
+            self.get_entry_point_nonce(op),
         )?;
         Ok(AsyncData {
             factory_exists,
@@ -351,9 +450,17 @@ where
             payer_funds,
             base_fee,
             min_pre_verification_gas,
+            entry_point_nonce,
         })
     }
 
+    async fn get_entry_point_nonce(&self, op: &UO) -> anyhow::Result<U256> {
+        self.entry_point
+            .get_nonce(op.sender(), op.nonce_key())
+            .await
+            .context("precheck should get entry point nonce")
+    }
+
     async fn is_contract(&self, address: Option<Address>) -> anyhow::Result<bool> {
         let Some(address) = address else {
             return Ok(false);
@@ -437,6 +544,7 @@ mod tests {
             payer_funds: 5_000_000.into(),
             base_fee: 4_000.into(),
             min_pre_verification_gas: 1_000.into(),
+            entry_point_nonce: 0.into(),
         }
     }
 
@@ -467,6 +575,76 @@ mod tests {
         assert_eq!(res, expected);
     }
 
+    #[tokio::test]
+    async fn test_check_init_code_undeployed_sender() {
+        let (cs, provider, entry_point) = create_base_config();
+        let prechecker =
+            PrecheckerImpl::new(cs, Arc::new(provider), entry_point, Settings::default());
+        let sender = Address::random();
+        let op = UserOperation {
+            sender,
+            init_code: Bytes::default(),
+            ..Default::default()
+        };
+
+        let res = prechecker.check_init_code(
+            &op,
+            AsyncData {
+                sender_exists: false,
+                ..get_test_async_data()
+            },
+        );
+        let mut expected = ArrayVec::new();
+        expected.push(PrecheckViolation::SenderIsNotContractAndNoInitCode(sender));
+        assert_eq!(res, expected);
+
+        // an undeployed sender with init code is valid
+        let op = UserOperation {
+            sender,
+            init_code: Address::random().as_bytes().to_vec().into(),
+            ..Default::default()
+        };
+        let res = prechecker.check_init_code(
+            &op,
+            AsyncData {
+                sender_exists: false,
+                ..get_test_async_data()
+            },
+        );
+        assert!(res.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_check_init_code_size() {
+        let (cs, provider, entry_point) = create_base_config();
+        let settings = Settings {
+            max_init_code_bytes: 100,
+            ..Default::default()
+        };
+        let prechecker = PrecheckerImpl::new(cs, Arc::new(provider), entry_point, settings);
+        let async_data = AsyncData {
+            sender_exists: false,
+            ..get_test_async_data()
+        };
+
+        // at the limit
+        let op = UserOperation {
+            init_code: vec![1; 100].into(),
+            ..Default::default()
+        };
+        assert!(prechecker.check_init_code(&op, async_data).is_empty());
+
+        // over the limit
+        let op = UserOperation {
+            init_code: vec![1; 101].into(),
+            ..Default::default()
+        };
+        let res = prechecker.check_init_code(&op, async_data);
+        let mut expected = ArrayVec::new();
+        expected.push(PrecheckViolation::InitCodeTooLarge(101, 100));
+        assert_eq!(res, expected);
+    }
+
     #[tokio::test]
     async fn test_check_gas() {
         let (cs, provider, entry_point) = create_base_config();
@@ -477,13 +655,19 @@ mod tests {
             priority_fee_mode: gas::PriorityFeeMode::BaseFeePercent(100),
             base_fee_accept_percent: 100,
             pre_verification_gas_accept_percent: 100,
+            min_call_gas_limit: MIN_CALL_GAS_LIMIT.into(),
+            max_init_code_bytes: 32_768,
+            max_verification_gas_to_call_gas_ratio: None,
+            max_pre_verification_gas: None,
+            reject_duplicate_entities: true,
+            max_nonce_gap: None,
         };
         let prechecker = PrecheckerImpl::new(cs, Arc::new(provider), entry_point, test_settings);
         let op = UserOperation {
             sender: Address::from_str("0x3f8a2b6c4d5e1079286fa1b3c0d4e5f6902b7c8d").unwrap(),
             nonce: 100.into(),
             init_code: Bytes::from_str("0x1000000000000000000000000000000000000000").unwrap(),
-            call_data: Bytes::default(),
+            call_data: Bytes::from_str("0x01").unwrap(),
             call_gas_limit: 9_000.into(), // large call gas limit high to trigger TotalGasLimitTooHigh
             verification_gas_limit: 10_000_000.into(),
             pre_verification_gas: 0.into(),
@@ -497,7 +681,7 @@ mod tests {
 
         assert_eq!(
             res,
-            ArrayVec::<PrecheckViolation, 6>::from([
+            ArrayVec::<PrecheckViolation, 8>::from_iter([
                 PrecheckViolation::VerificationGasLimitTooHigh(10_000_000.into(), 5_000_000.into(),),
                 PrecheckViolation::TotalGasLimitTooHigh(20_014_000.into(), 10_000_000.into(),),
                 PrecheckViolation::PreVerificationGasTooLow(0.into(), 1_000.into(),),
@@ -508,6 +692,179 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_check_min_call_gas_limit() {
+        let (cs, provider, entry_point) = create_base_config();
+        let settings = Settings {
+            min_call_gas_limit: 20_000.into(),
+            ..Default::default()
+        };
+        let prechecker = PrecheckerImpl::new(cs, Arc::new(provider), entry_point, settings);
+        let op = UserOperation {
+            call_data: Bytes::from_str("0x01").unwrap(),
+            call_gas_limit: 19_999.into(),
+            pre_verification_gas: 1_000.into(),
+            max_fee_per_gas: 10_000.into(),
+            max_priority_fee_per_gas: 10_000.into(),
+            ..Default::default()
+        };
+
+        // below the floor
+        let res = prechecker.check_gas(&op, get_test_async_data());
+        assert_eq!(
+            res,
+            ArrayVec::<PrecheckViolation, 8>::from_iter([PrecheckViolation::CallGasLimitTooLow(
+                19_999.into(),
+                20_000.into()
+            )])
+        );
+
+        // at the floor
+        let op = UserOperation {
+            call_gas_limit: 20_000.into(),
+            ..op
+        };
+        assert!(prechecker.check_gas(&op, get_test_async_data()).is_empty());
+
+        // empty call data is not checked
+        let op = UserOperation {
+            call_data: Bytes::default(),
+            call_gas_limit: 0.into(),
+            ..op
+        };
+        assert!(prechecker.check_gas(&op, get_test_async_data()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_check_pre_verification_gas_too_high() {
+        let (cs, provider, entry_point) = create_base_config();
+        let settings = Settings {
+            max_pre_verification_gas: Some(100_000.into()),
+            ..Default::default()
+        };
+        let prechecker = PrecheckerImpl::new(cs, Arc::new(provider), entry_point, settings);
+
+        let op = UserOperation {
+            call_data: Bytes::from_str("0x01").unwrap(),
+            call_gas_limit: MIN_CALL_GAS_LIMIT.into(),
+            pre_verification_gas: 100_000.into(),
+            max_fee_per_gas: 10_000.into(),
+            max_priority_fee_per_gas: 10_000.into(),
+            ..Default::default()
+        };
+        assert!(prechecker.check_gas(&op, get_test_async_data()).is_empty());
+
+        // an op that could never fit in a bundle
+        let op = UserOperation {
+            pre_verification_gas: 100_001.into(),
+            ..op
+        };
+        assert_eq!(
+            prechecker.check_gas(&op, get_test_async_data()),
+            ArrayVec::<PrecheckViolation, 8>::from_iter([
+                PrecheckViolation::PreVerificationGasTooHigh(100_001.into(), 100_000.into())
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_verification_gas_to_call_gas_ratio() {
+        let (cs, provider, entry_point) = create_base_config();
+        let settings = Settings {
+            max_verification_gas_to_call_gas_ratio: Some(10),
+            ..Default::default()
+        };
+        let prechecker = PrecheckerImpl::new(cs, Arc::new(provider), entry_point, settings);
+
+        // normal ratio
+        let op = UserOperation {
+            call_data: Bytes::from_str("0x01").unwrap(),
+            call_gas_limit: 100_000.into(),
+            verification_gas_limit: 1_000_000.into(),
+            pre_verification_gas: 1_000.into(),
+            max_fee_per_gas: 10_000.into(),
+            max_priority_fee_per_gas: 10_000.into(),
+            ..Default::default()
+        };
+        assert!(prechecker.check_gas(&op, get_test_async_data()).is_empty());
+
+        // abusive ratio
+        let op = UserOperation {
+            call_gas_limit: 10_000.into(),
+            ..op
+        };
+        assert_eq!(
+            prechecker.check_gas(&op, get_test_async_data()),
+            ArrayVec::<PrecheckViolation, 8>::from_iter([
+                PrecheckViolation::VerificationGasToCallGasRatioTooHigh(
+                    1_000_000.into(),
+                    10_000.into(),
+                    10
+                )
+            ])
+        );
+
+        // deployments are not checked
+        let op = UserOperation {
+            init_code: Bytes::from_str("0x1000000000000000000000000000000000000000").unwrap(),
+            ..op
+        };
+        assert!(prechecker.check_gas(&op, get_test_async_data()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_check_duplicate_entities() {
+        let (cs, provider, entry_point) = create_base_config();
+        let settings = Settings {
+            reject_duplicate_entities: true,
+            ..Default::default()
+        };
+        let prechecker = PrecheckerImpl::new(cs, Arc::new(provider), entry_point, settings);
+        let sender = Address::random();
+        let paymaster = Address::random();
+
+        // normal op
+        let op = UserOperation {
+            sender,
+            paymaster_and_data: paymaster.as_bytes().to_vec().into(),
+            ..Default::default()
+        };
+        assert_eq!(prechecker.check_entities(&op), None);
+
+        // sender is also the paymaster
+        let op = UserOperation {
+            paymaster_and_data: sender.as_bytes().to_vec().into(),
+            ..op
+        };
+        assert_eq!(
+            prechecker.check_entities(&op),
+            Some(PrecheckViolation::DuplicateEntityAddress(
+                Entity::account(sender),
+                Entity::paymaster(sender),
+            ))
+        );
+
+        // the factory may also be the paymaster
+        let op = UserOperation {
+            sender,
+            init_code: paymaster.as_bytes().to_vec().into(),
+            paymaster_and_data: paymaster.as_bytes().to_vec().into(),
+            ..Default::default()
+        };
+        assert_eq!(prechecker.check_entities(&op), None);
+
+        // rejection is disabled by default
+        let (cs, provider, entry_point) = create_base_config();
+        let prechecker =
+            PrecheckerImpl::new(cs, Arc::new(provider), entry_point, Settings::default());
+        let op = UserOperation {
+            sender,
+            paymaster_and_data: sender.as_bytes().to_vec().into(),
+            ..Default::default()
+        };
+        assert_eq!(prechecker.check_entities(&op), None);
+    }
+
     #[tokio::test]
     async fn test_check_payer_paymaster_deposit_too_low() {
         let (cs, provider, entry_point) = create_base_config();
@@ -540,6 +897,37 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_check_payer_paymaster_not_deployed() {
+        let paymaster = Address::random();
+        let (cs, mut provider, entry_point) = create_base_config();
+        provider
+            .expect_get_code()
+            .withf(move |address, _| *address == paymaster)
+            .returning(|_, _| Ok(Bytes::new()));
+        let prechecker =
+            PrecheckerImpl::new(cs, Arc::new(provider), entry_point, Settings::default());
+        let op = UserOperation {
+            paymaster_and_data: paymaster.as_bytes().to_vec().into(),
+            ..Default::default()
+        };
+
+        let paymaster_exists = prechecker.is_contract(op.paymaster()).await.unwrap();
+        assert!(!paymaster_exists);
+
+        let res = prechecker.check_payer(
+            &op,
+            AsyncData {
+                paymaster_exists,
+                ..get_test_async_data()
+            },
+        );
+        assert_eq!(
+            res,
+            Some(PrecheckViolation::PaymasterIsNotContract(paymaster))
+        );
+    }
+
     #[tokio::test]
     async fn test_check_fees() {
         let settings = Settings {
@@ -596,7 +984,7 @@ mod tests {
         };
 
         let res = prechecker.check_gas(&op, async_data);
-        let mut expected = ArrayVec::<PrecheckViolation, 6>::new();
+        let mut expected = ArrayVec::<PrecheckViolation, 8>::new();
         expected.push(PrecheckViolation::MaxFeePerGasTooLow(
             math::percent(5_000, settings.base_fee_accept_percent - 10).into(),
             math::percent(5_000, settings.base_fee_accept_percent).into(),
@@ -633,7 +1021,7 @@ mod tests {
         };
 
         let res = prechecker.check_gas(&op, async_data);
-        let mut expected = ArrayVec::<PrecheckViolation, 6>::new();
+        let mut expected = ArrayVec::<PrecheckViolation, 8>::new();
         expected.push(PrecheckViolation::MaxPriorityFeePerGasTooLow(
             mintip - U256::from(1),
             mintip,
@@ -642,6 +1030,34 @@ mod tests {
         assert_eq!(res, expected);
     }
 
+    #[tokio::test]
+    async fn test_check_fees_zero_priority_fee() {
+        let settings = Settings {
+            base_fee_accept_percent: 100,
+            priority_fee_mode: gas::PriorityFeeMode::BaseFeePercent(10),
+            ..Default::default()
+        };
+        let (mut cs, provider, entry_point) = create_base_config();
+        cs.min_max_priority_fee_per_gas = 100_000.into();
+        cs.zero_priority_fee = true;
+        let prechecker = PrecheckerImpl::new(cs, Arc::new(provider), entry_point, settings);
+
+        let mut async_data = get_test_async_data();
+        async_data.base_fee = 5_000.into();
+        async_data.min_pre_verification_gas = 1_000.into();
+
+        let op = UserOperation {
+            max_fee_per_gas: 5_000.into(),
+            max_priority_fee_per_gas: U256::zero(),
+            pre_verification_gas: 1_000.into(),
+            call_gas_limit: MIN_CALL_GAS_LIMIT.into(),
+            ..Default::default()
+        };
+
+        let res = prechecker.check_gas(&op, async_data);
+        assert!(res.is_empty());
+    }
+
     #[tokio::test]
     async fn test_pvg_too_low() {
         let settings = Settings {
@@ -669,7 +1085,7 @@ mod tests {
         };
 
         let res = prechecker.check_gas(&op, async_data);
-        let mut expected = ArrayVec::<PrecheckViolation, 6>::new();
+        let mut expected = ArrayVec::<PrecheckViolation, 8>::new();
         expected.push(PrecheckViolation::PreVerificationGasTooLow(
             math::percent(1_000, settings.pre_verification_gas_accept_percent - 10).into(),
             math::percent(1_000, settings.pre_verification_gas_accept_percent).into(),
@@ -677,4 +1093,134 @@ mod tests {
 
         assert_eq!(res, expected);
     }
+
+    #[tokio::test]
+    async fn test_check_nonce() {
+        let sender = Address::random();
+        let key = U256::from(5);
+        let (cs, provider, mut entry_point) = create_base_config();
+        entry_point
+            .expect_get_nonce()
+            .withf(move |address, k| *address == sender && *k == key)
+            .returning(move |_, _| Ok((key << 64) + U256::from(10)));
+        let prechecker =
+            PrecheckerImpl::new(cs, Arc::new(provider), entry_point, Settings::default());
+
+        let matching = UserOperation {
+            sender,
+            nonce: (key << 64) + U256::from(10),
+            ..Default::default()
+        };
+        let mut async_data = get_test_async_data();
+        async_data.entry_point_nonce = prechecker.get_entry_point_nonce(&matching).await.unwrap();
+        assert_eq!(prechecker.check_nonce(&matching, async_data), None);
+
+        let stale = UserOperation {
+            sender,
+            nonce: (key << 64) + U256::from(9),
+            ..Default::default()
+        };
+        async_data.entry_point_nonce = prechecker.get_entry_point_nonce(&stale).await.unwrap();
+        assert_eq!(
+            prechecker.check_nonce(&stale, async_data),
+            Some(PrecheckViolation::NonceTooLow(
+                (key << 64) + U256::from(9),
+                (key << 64) + U256::from(10),
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_async_data_fetches_nonce_once() {
+        let sender = Address::random();
+        let (cs, mut provider, mut entry_point) = create_base_config();
+        provider
+            .expect_get_code()
+            .returning(|_, _| Ok(Bytes::default()));
+        provider
+            .expect_get_balance()
+            .returning(|_, _| Ok(1_000.into()));
+        entry_point
+            .expect_balance_of()
+            .returning(|_, _| Ok(2_000.into()));
+        entry_point
+            .expect_get_nonce()
+            .times(1)
+            .returning(|_, _| Ok(7.into()));
+        let prechecker =
+            PrecheckerImpl::new(cs, Arc::new(provider), entry_point, Settings::default());
+        prechecker.cache.write().unwrap().fees = Some(FeeCache {
+            bundle_fees: GasFees::default(),
+            base_fee: 1.into(),
+        });
+
+        let op = UserOperation {
+            sender,
+            nonce: 7.into(),
+            ..Default::default()
+        };
+        let async_data = prechecker.load_async_data(&op).await.unwrap();
+        assert_eq!(async_data.entry_point_nonce, 7.into());
+        assert_eq!(async_data.payer_funds, 3_000.into());
+        assert_eq!(prechecker.check_nonce(&op, async_data), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_nonce_gap() {
+        let sender = Address::random();
+        let used_key = U256::from(5);
+        let unused_key = U256::from(6);
+        let (cs, provider, mut entry_point) = create_base_config();
+        entry_point
+            .expect_get_nonce()
+            .withf(move |address, k| *address == sender && *k == used_key)
+            .returning(move |_, _| Ok((used_key << 64) + U256::from(10)));
+        // the nonce manager returns the first sequence for a key it has never seen
+        entry_point
+            .expect_get_nonce()
+            .withf(move |address, k| *address == sender && *k == unused_key)
+            .returning(move |_, _| Ok(unused_key << 64));
+        let settings = Settings {
+            max_nonce_gap: Some(2),
+            ..Default::default()
+        };
+        let prechecker = PrecheckerImpl::new(cs, Arc::new(provider), entry_point, settings);
+
+        let queued = UserOperation {
+            sender,
+            nonce: (used_key << 64) + U256::from(12),
+            ..Default::default()
+        };
+        let mut async_data = get_test_async_data();
+        async_data.entry_point_nonce = prechecker.get_entry_point_nonce(&queued).await.unwrap();
+        assert_eq!(prechecker.check_nonce(&queued, async_data), None);
+
+        let first = UserOperation {
+            sender,
+            nonce: unused_key << 64,
+            ..Default::default()
+        };
+        async_data.entry_point_nonce = prechecker.get_entry_point_nonce(&first).await.unwrap();
+        assert_eq!(prechecker.check_nonce(&first, async_data), None);
+
+        let ahead = UserOperation {
+            sender,
+            nonce: (unused_key << 64) + U256::from(3),
+            ..Default::default()
+        };
+        async_data.entry_point_nonce = prechecker.get_entry_point_nonce(&ahead).await.unwrap();
+        assert_eq!(
+            prechecker.check_nonce(&ahead, async_data),
+            Some(PrecheckViolation::NonceTooHigh(
+                (unused_key << 64) + U256::from(3),
+                (unused_key << 64) + U256::from(2),
+            ))
+        );
+
+        // the gap isn't checked by default
+        let (cs, provider, entry_point) = create_base_config();
+        let prechecker =
+            PrecheckerImpl::new(cs, Arc::new(provider), entry_point, Settings::default());
+        assert_eq!(prechecker.check_nonce(&ahead, async_data), None);
+    }
 }
@@ -15,6 +15,7 @@ use std::collections::{BTreeSet, HashMap, HashSet};
 
 use anyhow::Context;
 use ethers::types::{Address, BlockId, U256};
+use rundler_provider::ProviderError;
 use rundler_types::{
     pool::SimulationViolation, EntityInfos, EntityType, Opcode, StakeInfo, UserOperation,
     ValidationOutput,
@@ -51,7 +52,7 @@ pub(crate) struct TracerOutput {
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Phase {
     pub(crate) forbidden_opcodes_used: Vec<String>,
-    pub(crate) forbidden_precompiles_used: Vec<String>,
+    pub(crate) precompiles_used: Vec<String>,
     pub(crate) storage_accesses: HashMap<Address, AccessInfo>,
     pub(crate) called_banned_entry_point_method: bool,
     pub(crate) called_non_entry_point_with_value: bool,
@@ -118,6 +119,38 @@ pub trait ValidationContextProvider: Send + Sync + 'static {
     ) -> Vec<SimulationViolation>;
 }
 
+/// Error returned by a validation tracer when the trace exceeds the maximum allowed size
+#[derive(Debug, thiserror::Error)]
+#[error("simulation trace size {size} bytes exceeds the maximum of {max_size} bytes")]
+pub(crate) struct TraceTooLargeError {
+    pub(crate) size: usize,
+    pub(crate) max_size: usize,
+}
+
+/// Converts a provider error from a size limited trace into a tracer error, keeping the
+/// size details if the trace was too large
+pub(crate) fn trace_size_error(error: ProviderError) -> anyhow::Error {
+    match error {
+        ProviderError::ResponseTooLarge(size, max_size) => {
+            metrics::counter!("sim_trace_too_large").increment(1);
+            TraceTooLargeError { size, max_size }.into()
+        }
+        e => e.into(),
+    }
+}
+
+/// Converts a tracer error into a violation if the trace was too large
+pub(crate) fn tracer_error_to_violation(
+    error: anyhow::Error,
+) -> ViolationError<SimulationViolation> {
+    match error.downcast::<TraceTooLargeError>() {
+        Ok(e) => {
+            ViolationError::Violations(vec![SimulationViolation::TraceTooLarge(e.size, e.max_size)])
+        }
+        Err(e) => e.into(),
+    }
+}
+
 pub(crate) fn entity_type_from_simulation_phase(i: usize) -> Option<EntityType> {
     match i {
         0 => Some(EntityType::Factory),
@@ -137,33 +170,50 @@ pub(crate) fn infos_from_validation_output(
     let mut ei = EntityInfos::default();
     ei.set_sender(
         sender_address,
-        is_staked(entry_point_out.sender_info, sim_settings),
+        is_staked(
+            entry_point_out.sender_info,
+            EntityType::Account,
+            sim_settings,
+        ),
     );
     if let Some(factory_address) = factory_address {
         ei.set_factory(
             factory_address,
-            is_staked(entry_point_out.factory_info, sim_settings),
+            is_staked(
+                entry_point_out.factory_info,
+                EntityType::Factory,
+                sim_settings,
+            ),
         );
     }
     if let Some(paymaster_address) = paymaster_address {
         ei.set_paymaster(
             paymaster_address,
-            is_staked(entry_point_out.paymaster_info, sim_settings),
+            is_staked(
+                entry_point_out.paymaster_info,
+                EntityType::Paymaster,
+                sim_settings,
+            ),
         );
     }
     if let Some(aggregator_info) = entry_point_out.aggregator_info {
         ei.set_aggregator(
             aggregator_info.address,
-            is_staked(aggregator_info.stake_info, sim_settings),
+            is_staked(
+                aggregator_info.stake_info,
+                EntityType::Aggregator,
+                sim_settings,
+            ),
         );
     }
 
     ei
 }
 
-pub(crate) fn is_staked(info: StakeInfo, sim_settings: &Settings) -> bool {
-    info.stake >= sim_settings.min_stake_value.into()
-        && info.unstake_delay_sec >= sim_settings.min_unstake_delay.into()
+pub(crate) fn is_staked(info: StakeInfo, kind: EntityType, sim_settings: &Settings) -> bool {
+    let requirement = sim_settings.stake_requirement(kind);
+    info.stake >= requirement.min_stake_value.into()
+        && info.unstake_delay_sec >= requirement.min_unstake_delay.into()
 }
 
 pub(crate) fn parse_combined_context_str<A, B>(combined: &str) -> anyhow::Result<(A, B)>
@@ -178,3 +228,74 @@ where
         .context("tracer combined should contain two parts")?;
     Ok((a.parse()?, b.parse()?))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::StakeRequirement;
+
+    #[test]
+    fn test_trace_size_error() {
+        let err = trace_size_error(ProviderError::ResponseTooLarge(2048, 1024));
+        assert!(matches!(
+            tracer_error_to_violation(err),
+            ViolationError::Violations(violations)
+                if violations == vec![SimulationViolation::TraceTooLarge(2048, 1024)]
+        ));
+
+        let err = trace_size_error(ProviderError::Other(anyhow::anyhow!("transport")));
+        assert!(matches!(
+            tracer_error_to_violation(err),
+            ViolationError::Other(_)
+        ));
+    }
+
+    #[test]
+    fn test_is_staked_per_entity_type() {
+        let mut settings = Settings {
+            min_stake_value: 100,
+            min_unstake_delay: 10,
+            ..Default::default()
+        };
+        let requirements = [
+            (EntityType::Account, 200, 20),
+            (EntityType::Paymaster, 300, 30),
+            (EntityType::Aggregator, 400, 40),
+            (EntityType::Factory, 500, 50),
+        ];
+
+        // without overrides every entity type uses the default minimums
+        for (kind, _, _) in requirements {
+            assert!(is_staked((100.into(), 10.into()).into(), kind, &settings));
+            assert!(!is_staked((99.into(), 10.into()).into(), kind, &settings));
+            assert!(!is_staked((100.into(), 9.into()).into(), kind, &settings));
+        }
+
+        for (kind, stake, delay) in requirements {
+            settings.entity_stake_requirements.insert(
+                kind,
+                StakeRequirement {
+                    min_stake_value: stake,
+                    min_unstake_delay: delay,
+                },
+            );
+        }
+        for (kind, stake, delay) in requirements {
+            assert!(is_staked(
+                (stake.into(), delay.into()).into(),
+                kind,
+                &settings
+            ));
+            assert!(!is_staked(
+                ((stake - 1).into(), delay.into()).into(),
+                kind,
+                &settings
+            ));
+            assert!(!is_staked(
+                (stake.into(), (delay - 1).into()).into(),
+                kind,
+                &settings
+            ));
+        }
+    }
+}
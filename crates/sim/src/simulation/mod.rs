@@ -11,7 +11,7 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::Error;
 use ethers::types::{Address, H256, U256};
@@ -20,7 +20,7 @@ use mockall::automock;
 use rundler_provider::AggregatorSimOut;
 use rundler_types::{
     pool::{MempoolError, SimulationViolation},
-    EntityInfos, UserOperation, ValidTimeRange,
+    EntityInfos, EntityType, UserOperation, ValidTimeRange,
 };
 
 mod context;
@@ -32,6 +32,9 @@ pub use mempool::{MempoolConfig, MempoolConfigs};
 mod simulator;
 pub use simulator::{new_v0_6_simulator, new_v0_7_simulator, SimulatorImpl};
 
+mod trace_support;
+pub use trace_support::{resolve_unsafe_mode, supports_js_tracer};
+
 mod unsafe_sim;
 pub use unsafe_sim::UnsafeSimulator;
 
@@ -59,8 +62,8 @@ pub struct SimulationResult {
     /// If using an aggregator, the result of the aggregation
     /// simulation
     pub aggregator: Option<AggregatorSimOut>,
-    /// Code hash of all accessed contracts
-    pub code_hash: H256,
+    /// Code hash of all accessed contracts, `None` if simulation was not traced
+    pub code_hash: Option<H256>,
     /// Whether the sender account is staked
     pub account_is_staked: bool,
     /// List of all addresses accessed during validation
@@ -165,6 +168,32 @@ pub struct Settings {
     /// The max duration of the custom javascript tracer. Must be in a format parseable by the
     /// ParseDuration function on an ethereum node. See Docs: https://pkg.go.dev/time#ParseDuration
     pub tracer_timeout: String,
+    /// The maximum number of seconds the simulation block's timestamp may lag behind
+    /// wall-clock time. If unset, the block age is not checked.
+    pub max_sim_block_age_seconds: Option<u64>,
+    /// Precompile addresses that entities may call during validation.
+    pub allowed_precompiles: Vec<Address>,
+    /// The size in bytes above which a validation trace's JSON response is rejected, along
+    /// with its operation. The size is checked once the full response has been received, so
+    /// this does not bound the memory used to read a trace.
+    pub reject_trace_size_bytes: usize,
+    /// If set, operations with an unstaked paymaster are rejected.
+    pub require_staked_paymaster: bool,
+    /// The maximum number of seconds in the future an operation's `validAfter` may be.
+    /// Operations that only become valid later are rejected. If unset, not checked.
+    pub max_valid_after_horizon_seconds: Option<u64>,
+    /// Per entity type overrides of the minimum stake and unstake delay. Entity types
+    /// without an override use `min_stake_value` and `min_unstake_delay`.
+    pub entity_stake_requirements: HashMap<EntityType, StakeRequirement>,
+}
+
+/// The minimum stake an entity must have on the entry point to be considered staked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StakeRequirement {
+    /// The minimum amount of stake
+    pub min_stake_value: u128,
+    /// The minimum unstake delay in seconds
+    pub min_unstake_delay: u32,
 }
 
 impl Settings {
@@ -175,6 +204,12 @@ impl Settings {
         max_simulate_handle_ops_gas: u64,
         max_verification_gas: u64,
         tracer_timeout: String,
+        max_sim_block_age_seconds: Option<u64>,
+        allowed_precompiles: Vec<Address>,
+        reject_trace_size_bytes: usize,
+        require_staked_paymaster: bool,
+        max_valid_after_horizon_seconds: Option<u64>,
+        entity_stake_requirements: HashMap<EntityType, StakeRequirement>,
     ) -> Self {
         Self {
             min_unstake_delay,
@@ -182,8 +217,36 @@ impl Settings {
             max_simulate_handle_ops_gas,
             max_verification_gas,
             tracer_timeout,
+            max_sim_block_age_seconds,
+            allowed_precompiles,
+            reject_trace_size_bytes,
+            require_staked_paymaster,
+            max_valid_after_horizon_seconds,
+            entity_stake_requirements,
         }
     }
+
+    /// The stake requirement that applies to an entity of the given type
+    pub fn stake_requirement(&self, kind: EntityType) -> StakeRequirement {
+        self.entity_stake_requirements
+            .get(&kind)
+            .copied()
+            .unwrap_or(StakeRequirement {
+                min_stake_value: self.min_stake_value,
+                min_unstake_delay: self.min_unstake_delay,
+            })
+    }
+}
+
+/// The precompiles that ERC-4337 allows during validation.
+///
+/// Covers the stateless precompiles at `0x01` through `0x09` as well as the
+/// RIP-7212 secp256r1 verifier at `0x100`.
+pub fn default_allowed_precompiles() -> Vec<Address> {
+    (1..=9)
+        .chain(std::iter::once(0x100))
+        .map(Address::from_low_u64_be)
+        .collect()
 }
 
 #[cfg(any(test, feature = "test-utils"))]
@@ -198,6 +261,13 @@ impl Default for Settings {
             max_simulate_handle_ops_gas: 550_000_000,
             max_verification_gas: 5_000_000,
             tracer_timeout: "10s".to_string(),
+            max_sim_block_age_seconds: None,
+            allowed_precompiles: default_allowed_precompiles(),
+            // 10 MiB
+            reject_trace_size_bytes: 10 * 1024 * 1024,
+            require_staked_paymaster: false,
+            max_valid_after_horizon_seconds: None,
+            entity_stake_requirements: HashMap::new(),
         }
     }
 }
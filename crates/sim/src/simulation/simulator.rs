@@ -16,6 +16,7 @@ use std::{
     marker::PhantomData,
     ops::Deref,
     sync::Arc,
+    time::Duration,
 };
 
 use async_trait::async_trait;
@@ -24,10 +25,11 @@ use rundler_provider::{
     AggregatorOut, AggregatorSimOut, EntryPoint, Provider, SignatureAggregator, SimulationProvider,
 };
 use rundler_types::{
+    chain::ChainSpec,
     pool::{NeedsStakeInformation, SimulationViolation},
     v0_6::UserOperation as UserOperationV0_6,
     v0_7::UserOperation as UserOperationV0_7,
-    Entity, EntityInfo, EntityInfos, EntityType, Opcode, StorageSlot, UserOperation,
+    Entity, EntityInfo, EntityInfos, EntityType, Opcode, StorageSlot, Timestamp, UserOperation,
     ValidTimeRange, ValidationOutput, ValidationReturnInfo, ViolationOpCode,
 };
 
@@ -47,6 +49,7 @@ use crate::{
 
 /// Create a new simulator for v0.6 entry point contracts
 pub fn new_v0_6_simulator<P, E>(
+    chain_spec: ChainSpec,
     provider: Arc<P>,
     entry_point: E,
     sim_settings: Settings,
@@ -62,7 +65,7 @@ where
     SimulatorImpl::new(
         provider.clone(),
         entry_point.clone(),
-        ValidationContextProviderV0_6::new(provider, entry_point, sim_settings.clone()),
+        ValidationContextProviderV0_6::new(chain_spec, provider, entry_point, sim_settings.clone()),
         sim_settings,
         mempool_configs,
     )
@@ -223,8 +226,11 @@ where
                 }
             }
 
-            for precompile in &phase.forbidden_precompiles_used {
+            for precompile in &phase.precompiles_used {
                 let (contract, precompile) = context::parse_combined_context_str(precompile)?;
+                if self.sim_settings.allowed_precompiles.contains(&precompile) {
+                    continue;
+                }
                 // [OP-062]
                 violations.push(SimulationViolation::UsedForbiddenPrecompile(
                     ei.entity, contract, precompile,
@@ -259,6 +265,8 @@ where
                                 .expect("entity type not found in entity_infos");
 
                             if !needs_stake_entity.is_staked {
+                                let stake_requirement =
+                                    self.sim_settings.stake_requirement(needs_stake);
                                 // [STO-*]
                                 violations.push(SimulationViolation::NotStaked(Box::new(
                                     NeedsStakeInformation {
@@ -267,9 +275,8 @@ where
                                         accessed_entity,
                                         accessed_address,
                                         slot,
-                                        min_stake: self.sim_settings.min_stake_value.into(),
-                                        min_unstake_delay: self
-                                            .sim_settings
+                                        min_stake: stake_requirement.min_stake_value.into(),
+                                        min_unstake_delay: stake_requirement
                                             .min_unstake_delay
                                             .into(),
                                     },
@@ -346,15 +353,51 @@ where
                 entry_point_out.return_info.valid_until,
                 entry_point_out.return_info.valid_after,
             ));
+        } else if let Some(horizon) = self.sim_settings.max_valid_after_horizon_seconds {
+            // Operations that only become valid far in the future can't be bundled soon
+            // and would only take up space in the mempool
+            if entry_point_out.return_info.valid_after
+                > Timestamp::now() + Duration::from_secs(horizon)
+            {
+                violations.push(SimulationViolation::InvalidTimeRange(
+                    entry_point_out.return_info.valid_until,
+                    entry_point_out.return_info.valid_after,
+                ));
+            }
         }
 
         if let Some(aggregator_info) = entry_point_out.aggregator_info {
-            if !context::is_staked(aggregator_info.stake_info, &self.sim_settings) {
+            if !context::is_staked(
+                aggregator_info.stake_info,
+                EntityType::Aggregator,
+                &self.sim_settings,
+            ) {
                 // [EREP-040]
                 violations.push(SimulationViolation::UnstakedAggregator)
             }
         }
 
+        // Paymaster stake is read from the entry point's deposit info, regardless of reputation
+        if self.sim_settings.require_staked_paymaster {
+            if let Some(paymaster) = &entity_infos.paymaster {
+                if !paymaster.is_staked {
+                    let stake_requirement =
+                        self.sim_settings.stake_requirement(EntityType::Paymaster);
+                    violations.push(SimulationViolation::NotStaked(Box::new(
+                        NeedsStakeInformation {
+                            needs_stake: paymaster.entity,
+                            accessing_entity: EntityType::Paymaster,
+                            accessed_entity: Some(EntityType::Paymaster),
+                            accessed_address: paymaster.entity.address,
+                            slot: U256::zero(),
+                            min_stake: stake_requirement.min_stake_value.into(),
+                            min_unstake_delay: stake_requirement.min_unstake_delay.into(),
+                        },
+                    )));
+                }
+            }
+        }
+
         for (address, contract_info) in &tracer_out.accessed_contracts {
             if contract_info.header.as_str() == "0xEFF000" {
                 // All arbitrum stylus contracts start with 0xEFF000
@@ -452,6 +495,32 @@ where
 
         Ok((code_hash, aggregator))
     }
+
+    // Reject simulations run against a block that is too far behind wall-clock time,
+    // as decisions made on stale state are unreliable. This is a retryable error.
+    async fn check_block_age(&self, block_hash: H256) -> Result<(), SimulationError> {
+        let Some(max_age) = self.sim_settings.max_sim_block_age_seconds else {
+            return Ok(());
+        };
+
+        let block = self
+            .provider
+            .get_block(block_hash)
+            .await
+            .map_err(anyhow::Error::from)?
+            .ok_or_else(|| anyhow::anyhow!("simulation block {block_hash:?} not found"))?;
+        let block_timestamp = block.timestamp.as_u64();
+        let age = Timestamp::now()
+            .seconds_since_epoch()
+            .saturating_sub(block_timestamp);
+        if age > max_age {
+            Err(anyhow::anyhow!(
+                "simulation block {block_hash:?} is {age} seconds behind, max allowed is {max_age} seconds"
+            ))?;
+        }
+
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -482,6 +551,8 @@ where
                 (hash_and_num.0, Some(hash_and_num.1.as_u64()))
             }
         };
+        self.check_block_age(block_hash).await?;
+
         let block_id = block_hash.into();
         let mut context = match self
             .validation_context_provider
@@ -528,7 +599,8 @@ where
             sender_info,
             ..
         } = entry_point_out;
-        let account_is_staked = context::is_staked(sender_info, &self.sim_settings);
+        let account_is_staked =
+            context::is_staked(sender_info, EntityType::Account, &self.sim_settings);
         let ValidationReturnInfo {
             pre_op_gas,
             valid_after,
@@ -547,7 +619,7 @@ where
             pre_op_gas,
             valid_time_range: ValidTimeRange::new(valid_after, valid_until),
             aggregator,
-            code_hash,
+            code_hash: Some(code_hash),
             account_is_staked,
             accessed_addresses,
             associated_addresses,
@@ -683,7 +755,7 @@ mod tests {
     use std::str::FromStr;
 
     use context::ContractInfo;
-    use ethers::types::{Address, BlockId, BlockNumber, Bytes, U256, U64};
+    use ethers::types::{Address, Block, BlockId, BlockNumber, Bytes, U256, U64};
     use rundler_provider::{AggregatorOut, MockEntryPointV0_6, MockProvider};
     use rundler_types::{
         contracts::utils::get_code_hashes::CodeHashesResult, v0_6::UserOperation, Opcode, StakeInfo,
@@ -691,6 +763,7 @@ mod tests {
 
     use self::context::{Phase, TracerOutput};
     use super::*;
+    use crate::simulation::StakeRequirement;
 
     mockall::mock! {
         ValidationContextProviderV0_6 {}
@@ -777,7 +850,7 @@ mod tests {
                     called_banned_entry_point_method: false,
                     called_non_entry_point_with_value: false,
                     forbidden_opcodes_used: vec![],
-                    forbidden_precompiles_used: vec![],
+                    precompiles_used: vec![],
                     ran_out_of_gas: false,
                     storage_accesses: HashMap::new(),
                     undeployed_contract_accesses: vec![],
@@ -787,7 +860,7 @@ mod tests {
                     called_banned_entry_point_method: false,
                     called_non_entry_point_with_value: false,
                     forbidden_opcodes_used: vec![],
-                    forbidden_precompiles_used: vec![],
+                    precompiles_used: vec![],
                     ran_out_of_gas: false,
                     storage_accesses:  HashMap::new(),
                     undeployed_contract_accesses: vec![],
@@ -797,7 +870,7 @@ mod tests {
                     called_banned_entry_point_method: false,
                     called_non_entry_point_with_value: false,
                     forbidden_opcodes_used: vec![],
-                    forbidden_precompiles_used: vec![],
+                    precompiles_used: vec![],
                     ran_out_of_gas: false,
                     storage_accesses: HashMap::new(),
                     undeployed_contract_accesses: vec![],
@@ -942,6 +1015,46 @@ mod tests {
         assert!(res.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_simulate_validation_stale_block() {
+        let (mut provider, entry_point, context) = create_base_config();
+        let block_hash = H256::random();
+
+        provider
+            .expect_get_latest_block_hash_and_number()
+            .returning(move || Ok((block_hash, U64::zero())));
+        provider.expect_get_block::<H256>().returning(|_| {
+            Ok(Some(Block {
+                timestamp: (Timestamp::now().seconds_since_epoch() - 120).into(),
+                ..Default::default()
+            }))
+        });
+
+        let mut mempool_configs = HashMap::new();
+        mempool_configs.insert(H256::zero(), MempoolConfig::default());
+        let simulator = SimulatorImpl::new(
+            Arc::new(provider),
+            Arc::new(entry_point),
+            context,
+            Settings {
+                max_sim_block_age_seconds: Some(30),
+                ..Default::default()
+            },
+            mempool_configs,
+        );
+
+        let res = simulator
+            .simulate_validation(UserOperation::default(), None, None)
+            .await;
+        assert!(matches!(
+            res,
+            Err(SimulationError {
+                violation_error: ViolationError::Other(_),
+                ..
+            })
+        ));
+    }
+
     #[tokio::test]
     async fn test_gather_context_violations() {
         let (provider, mut entry_point, mut context_provider) = create_base_config();
@@ -959,7 +1072,7 @@ mod tests {
             String::from("0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4:GASPRICE"),
             String::from("0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4:COINBASE"),
         ];
-        context.tracer_out.phases[1].forbidden_precompiles_used = vec![String::from(
+        context.tracer_out.phases[1].precompiles_used = vec![String::from(
             "0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4:0x0000000000000000000000000000000000000019",
         )];
 
@@ -1083,6 +1196,66 @@ mod tests {
         assert!(res.unwrap().is_empty());
     }
 
+    #[tokio::test]
+    async fn test_allowed_precompiles() {
+        let (provider, ep, mut context_provider) = create_base_config();
+        context_provider
+            .expect_get_specific_violations()
+            .return_const(vec![]);
+
+        let mut context = get_test_context();
+
+        // ecRecover is allowed by default, the point evaluation precompile is not
+        context.tracer_out.phases[1].precompiles_used = vec![
+            String::from(
+                "0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4:0x0000000000000000000000000000000000000001",
+            ),
+            String::from(
+                "0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4:0x000000000000000000000000000000000000000a",
+            ),
+        ];
+
+        let simulator = create_simulator(provider, ep, context_provider);
+        let res = simulator.gather_context_violations(&mut context);
+
+        assert_eq!(
+            res.unwrap(),
+            vec![SimulationViolation::UsedForbiddenPrecompile(
+                Entity {
+                    kind: EntityType::Account,
+                    address: Address::from_str("0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4")
+                        .unwrap()
+                },
+                Address::from_str("0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4").unwrap(),
+                Address::from_low_u64_be(0x0a),
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_account_reenters_entry_point() {
+        let (provider, ep, mut context_provider) = create_base_config();
+        context_provider
+            .expect_get_specific_violations()
+            .return_const(vec![]);
+
+        let mut context = get_test_context();
+
+        // account phase calls back into the entry point during validation
+        context.tracer_out.phases[1].called_banned_entry_point_method = true;
+
+        let simulator = create_simulator(provider, ep, context_provider);
+        let res = simulator.gather_context_violations(&mut context);
+
+        assert_eq!(
+            res.unwrap(),
+            vec![SimulationViolation::CalledBannedEntryPointMethod(Entity {
+                kind: EntityType::Account,
+                address: Address::from_str("0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4").unwrap()
+            })]
+        );
+    }
+
     #[tokio::test]
     async fn test_factory_staking() {
         let (provider, mut ep, mut context_provider) = create_base_config();
@@ -1133,6 +1306,124 @@ mod tests {
         assert!(res.unwrap().is_empty());
     }
 
+    #[tokio::test]
+    async fn test_require_staked_paymaster() {
+        let (provider, ep, mut context_provider) = create_base_config();
+        context_provider
+            .expect_get_specific_violations()
+            .return_const(vec![]);
+
+        let mut context = get_test_context();
+        let paymaster = context.entity_infos.paymaster.unwrap().entity;
+
+        let mut simulator = create_simulator(provider, ep, context_provider);
+        // not required by default
+        let res = simulator.gather_context_violations(&mut context);
+        assert!(res.unwrap().is_empty());
+
+        simulator.sim_settings.require_staked_paymaster = true;
+        let res = simulator.gather_context_violations(&mut context);
+        assert_eq!(
+            res.unwrap(),
+            vec![SimulationViolation::NotStaked(Box::new(
+                NeedsStakeInformation {
+                    needs_stake: paymaster,
+                    accessing_entity: EntityType::Paymaster,
+                    accessed_entity: Some(EntityType::Paymaster),
+                    accessed_address: paymaster.address,
+                    slot: U256::zero(),
+                    min_stake: simulator.sim_settings.min_stake_value.into(),
+                    min_unstake_delay: simulator.sim_settings.min_unstake_delay.into(),
+                }
+            ))]
+        );
+
+        // staked paymaster is accepted
+        context.entity_infos.paymaster.as_mut().unwrap().is_staked = true;
+        let res = simulator.gather_context_violations(&mut context);
+        assert!(res.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_not_staked_uses_entity_stake_requirement() {
+        let (provider, ep, mut context_provider) = create_base_config();
+        context_provider
+            .expect_get_specific_violations()
+            .return_const(vec![]);
+
+        let mut context = get_test_context();
+        let paymaster = context.entity_infos.paymaster.unwrap().entity;
+
+        let mut simulator = create_simulator(provider, ep, context_provider);
+        simulator.sim_settings.require_staked_paymaster = true;
+        simulator.sim_settings.entity_stake_requirements.insert(
+            EntityType::Paymaster,
+            StakeRequirement {
+                min_stake_value: 5_000_000_000_000_000_000,
+                min_unstake_delay: 172_800,
+            },
+        );
+        simulator.sim_settings.entity_stake_requirements.insert(
+            EntityType::Factory,
+            StakeRequirement {
+                min_stake_value: 2_000_000_000_000_000_000,
+                min_unstake_delay: 100_000,
+            },
+        );
+
+        let res = simulator.gather_context_violations(&mut context);
+        assert_eq!(
+            res.unwrap(),
+            vec![SimulationViolation::NotStaked(Box::new(
+                NeedsStakeInformation {
+                    needs_stake: paymaster,
+                    accessing_entity: EntityType::Paymaster,
+                    accessed_entity: Some(EntityType::Paymaster),
+                    accessed_address: paymaster.address,
+                    slot: U256::zero(),
+                    min_stake: 5_000_000_000_000_000_000_u128.into(),
+                    min_unstake_delay: 172_800.into(),
+                }
+            ))]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_valid_after_horizon() {
+        let (provider, ep, mut context_provider) = create_base_config();
+        context_provider
+            .expect_get_specific_violations()
+            .return_const(vec![]);
+
+        let mut context = get_test_context();
+        let valid_after = Timestamp::now() + Duration::from_secs(2 * 24 * 60 * 60);
+        let valid_until = valid_after + Duration::from_secs(24 * 60 * 60);
+        context.entry_point_out.return_info.valid_after = valid_after;
+        context.entry_point_out.return_info.valid_until = valid_until;
+
+        let mut simulator = create_simulator(provider, ep, context_provider);
+        // no horizon by default
+        let res = simulator.gather_context_violations(&mut context);
+        assert!(res.unwrap().is_empty());
+
+        // valid only in the distant future
+        simulator.sim_settings.max_valid_after_horizon_seconds = Some(24 * 60 * 60);
+        let res = simulator.gather_context_violations(&mut context);
+        assert_eq!(
+            res.unwrap(),
+            vec![SimulationViolation::InvalidTimeRange(
+                valid_until,
+                valid_after
+            )]
+        );
+
+        // valid within the horizon
+        context.entry_point_out.return_info.valid_after =
+            Timestamp::now() + Duration::from_secs(60 * 60);
+        let res = simulator.gather_context_violations(&mut context);
+        assert!(res.unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn test_paymaster_access_during_deploy() {
         let (provider, mut ep, mut context_provider) = create_base_config();
@@ -0,0 +1,269 @@
+// This file is part of Rundler.
+//
+// Rundler is free software: you can redistribute it and/or modify it under the
+// terms of the GNU Lesser General Public License as published by the Free Software
+// Foundation, either version 3 of the License, or (at your option) any later version.
+//
+// Rundler is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY;
+// without even the implied warranty of MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.
+// See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with Rundler.
+// If not, see https://www.gnu.org/licenses/.
+
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use ethers::types::{
+    transaction::eip2718::TypedTransaction, GethDebugTracerType, GethDebugTracingCallOptions,
+    GethDebugTracingOptions,
+};
+use rundler_provider::{Provider, ProviderError};
+
+/// Minimal javascript tracer used to probe for custom tracer support
+const PROBE_TRACER_JS: &str =
+    "{step: function() {}, fault: function() {}, result: function() { return {}; }}";
+
+/// JSON-RPC error code returned when a method is not available on the node
+const METHOD_NOT_FOUND_CODE: i64 = -32601;
+
+/// Number of times the probe is retried on errors that don't indicate missing support
+const PROBE_RETRIES: u32 = 3;
+
+/// Delay between probe retries
+const PROBE_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Returns whether the provider supports `debug_traceCall` with a custom javascript
+/// tracer, which is required to check the simulation rules.
+///
+/// Only a "method not found" response, or an error response rejecting the tracer,
+/// means the tracer is unsupported. Other errors, such as transport failures, are
+/// retried and returned as an error if they persist.
+pub async fn supports_js_tracer<P: Provider>(provider: &P) -> anyhow::Result<bool> {
+    let mut attempt = 0;
+    loop {
+        let err = match provider
+            .debug_trace_call(
+                TypedTransaction::default(),
+                None,
+                GethDebugTracingCallOptions {
+                    tracing_options: GethDebugTracingOptions {
+                        tracer: Some(GethDebugTracerType::JsTracer(PROBE_TRACER_JS.to_string())),
+                        ..Default::default()
+                    },
+                    state_overrides: None,
+                },
+            )
+            .await
+        {
+            Ok(_) => return Ok(true),
+            Err(err) => err,
+        };
+
+        if is_unsupported_error(&err) {
+            return Ok(false);
+        }
+
+        attempt += 1;
+        if attempt > PROBE_RETRIES {
+            return Err(err).context("should probe node for custom tracer support");
+        }
+        tracing::warn!("Custom tracer support probe failed, retrying: {err:?}");
+        tokio::time::sleep(PROBE_RETRY_DELAY).await;
+    }
+}
+
+fn is_unsupported_error(err: &ProviderError) -> bool {
+    let ProviderError::JsonRpcError(err) = err else {
+        return false;
+    };
+    if err.code == METHOD_NOT_FOUND_CODE {
+        return true;
+    }
+    let message = err.message.to_lowercase();
+    message.contains("tracer")
+        && [
+            "not found",
+            "not supported",
+            "unsupported",
+            "not enabled",
+            "disabled",
+        ]
+        .iter()
+        .any(|reason| message.contains(reason))
+}
+
+/// Determines whether simulation should run in unsafe mode.
+///
+/// If unsafe mode is not already requested and the provider does not support custom
+/// tracers, this either falls back to unsafe mode when `allow_fallback` is set, or
+/// returns an error.
+pub async fn resolve_unsafe_mode<P: Provider>(
+    provider: &P,
+    unsafe_mode: bool,
+    allow_fallback: bool,
+) -> anyhow::Result<bool> {
+    if unsafe_mode || supports_js_tracer(provider).await? {
+        return Ok(unsafe_mode);
+    }
+
+    if !allow_fallback {
+        bail!("node does not support debug_traceCall with custom tracers, required for simulation. Use --unsafe or --allow_trace_fallback to skip simulation rule checks");
+    }
+
+    tracing::warn!("!!! Node does not support debug_traceCall with custom tracers. Falling back to UNSAFE simulation: opcode, storage and reputation rules are NOT checked !!!");
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ethers::{
+        providers::JsonRpcError,
+        types::{Address, Bytes, GethTrace, H256, U256},
+    };
+    use rundler_provider::{MockEntryPointV0_6, MockProvider};
+    use rundler_types::{v0_6, StakeInfo, ValidationOutput, ValidationReturnInfo};
+
+    use super::*;
+    use crate::simulation::{Settings as SimulationSettings, Simulator, UnsafeSimulator};
+
+    fn rpc_error(code: i64, message: &str) -> ProviderError {
+        ProviderError::JsonRpcError(JsonRpcError {
+            code,
+            message: message.to_string(),
+            data: None,
+        })
+    }
+
+    fn provider(supports_tracer: bool) -> MockProvider {
+        let mut provider = MockProvider::default();
+        provider
+            .expect_debug_trace_call()
+            .returning(move |_, _, _| {
+                if supports_tracer {
+                    Ok(GethTrace::Unknown(serde_json::json!({})))
+                } else {
+                    Err(rpc_error(
+                        METHOD_NOT_FOUND_CODE,
+                        "the method debug_traceCall does not exist/is not available",
+                    ))
+                }
+            });
+        provider
+    }
+
+    #[tokio::test]
+    async fn test_tracer_supported() {
+        assert!(!resolve_unsafe_mode(&provider(true), false, false)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tracer_unsupported_fallback() {
+        // with the permissive flag, simulation falls back to unsafe mode
+        assert!(resolve_unsafe_mode(&provider(false), false, true)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tracer_unsupported_no_fallback() {
+        assert!(resolve_unsafe_mode(&provider(false), false, false)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tracer_rejected() {
+        let mut provider = MockProvider::default();
+        provider
+            .expect_debug_trace_call()
+            .times(1)
+            .returning(|_, _, _| Err(rpc_error(-32000, "tracer not found")));
+        assert!(!supports_js_tracer(&provider).await.unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_transport_error_retried() {
+        let mut provider = MockProvider::default();
+        let mut seq = mockall::Sequence::new();
+        provider
+            .expect_debug_trace_call()
+            .times(2)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Err(ProviderError::Other(anyhow::anyhow!("connection reset"))));
+        provider
+            .expect_debug_trace_call()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_, _, _| Ok(GethTrace::Unknown(serde_json::json!({}))));
+        assert!(!resolve_unsafe_mode(&provider, false, false).await.unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_persistent_transport_error_fails() {
+        let mut provider = MockProvider::default();
+        provider
+            .expect_debug_trace_call()
+            .times(PROBE_RETRIES as usize + 1)
+            .returning(|_, _, _| Err(ProviderError::Other(anyhow::anyhow!("connection reset"))));
+        // a flaky node must not silently disable the simulation rules
+        assert!(resolve_unsafe_mode(&provider, false, true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unsafe_mode_skips_probe() {
+        let mut provider = MockProvider::default();
+        provider.expect_debug_trace_call().never();
+        assert!(resolve_unsafe_mode(&provider, true, false).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tracer_unsupported_fallback_admits_op() {
+        let block_hash = H256::random();
+        let mut provider = provider(false);
+        provider
+            .expect_get_latest_block_hash_and_number()
+            .returning(move || Ok((block_hash, 100.into())));
+        let provider = Arc::new(provider);
+
+        let unsafe_mode = resolve_unsafe_mode(&*provider, false, true).await.unwrap();
+        assert!(unsafe_mode);
+
+        // without a tracer, the op is admitted using simulateValidation alone
+        let mut entry_point = MockEntryPointV0_6::new();
+        entry_point
+            .expect_get_tracer_simulate_validation_call()
+            .never();
+        entry_point
+            .expect_call_simulate_validation()
+            .returning(|_, _, _| {
+                Ok(ValidationOutput {
+                    return_info: ValidationReturnInfo::from((
+                        U256::from(50_000),
+                        U256::default(),
+                        false,
+                        0,
+                        0,
+                        Bytes::default(),
+                    )),
+                    sender_info: StakeInfo::from((U256::default(), U256::default())),
+                    factory_info: StakeInfo::from((U256::default(), U256::default())),
+                    paymaster_info: StakeInfo::from((U256::default(), U256::default())),
+                    aggregator_info: None,
+                })
+            });
+        let simulator = UnsafeSimulator::new(provider, entry_point, SimulationSettings::default());
+        let op = v0_6::UserOperation {
+            sender: Address::random(),
+            ..Default::default()
+        };
+
+        let res = simulator.simulate_validation(op, None, None).await.unwrap();
+        assert_eq!(res.block_hash, block_hash);
+        assert_eq!(res.pre_op_gas, 50_000.into());
+    }
+}
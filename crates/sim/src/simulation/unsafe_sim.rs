@@ -55,7 +55,7 @@ impl<UO, P, E> Simulator for UnsafeSimulator<UO, P, E>
 where
     UO: UserOperation,
     P: Provider,
-    E: EntryPoint + SimulationProvider<UO = UO> + SignatureAggregator<UO = UO> + Clone,
+    E: EntryPoint + SimulationProvider<UO = UO> + SignatureAggregator<UO = UO>,
 {
     type UO = UO;
 
@@ -111,15 +111,11 @@ where
             },
         };
 
-        let valid_until = if validation_result.return_info.valid_until == 0.into() {
-            u64::MAX.into()
-        } else {
-            validation_result.return_info.valid_until
-        };
-
         let pre_op_gas = validation_result.return_info.pre_op_gas;
-        let valid_time_range =
-            ValidTimeRange::new(validation_result.return_info.valid_after, valid_until);
+        let valid_time_range = ValidTimeRange::new(
+            validation_result.return_info.valid_after,
+            validation_result.return_info.valid_until,
+        );
         let requires_post_op = !validation_result.return_info.paymaster_context.is_empty();
 
         let mut entity_infos = EntityInfos::default();
@@ -184,3 +180,56 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ethers::types::{Address, Bytes, U256};
+    use rundler_provider::{MockEntryPointV0_6, MockProvider};
+    use rundler_types::{v0_6, StakeInfo, ValidationOutput, ValidationReturnInfo};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_simulate_validation_without_tracing() {
+        let block_hash = H256::random();
+        let mut provider = MockProvider::new();
+        provider
+            .expect_get_latest_block_hash_and_number()
+            .returning(move || Ok((block_hash, 100.into())));
+
+        // only simulateValidation is called, the tracer call is never built
+        let mut entry_point = MockEntryPointV0_6::new();
+        entry_point
+            .expect_get_tracer_simulate_validation_call()
+            .never();
+        entry_point
+            .expect_call_simulate_validation()
+            .returning(|_, _, _| {
+                Ok(ValidationOutput {
+                    return_info: ValidationReturnInfo::from((
+                        U256::from(50_000),
+                        U256::default(),
+                        false,
+                        0,
+                        0,
+                        Bytes::default(),
+                    )),
+                    sender_info: StakeInfo::from((U256::default(), U256::default())),
+                    factory_info: StakeInfo::from((U256::default(), U256::default())),
+                    paymaster_info: StakeInfo::from((U256::default(), U256::default())),
+                    aggregator_info: None,
+                })
+            });
+
+        let simulator = UnsafeSimulator::new(Arc::new(provider), entry_point, Settings::default());
+        let op = v0_6::UserOperation {
+            sender: Address::random(),
+            ..Default::default()
+        };
+
+        let res = simulator.simulate_validation(op, None, None).await.unwrap();
+        assert_eq!(res.block_hash, block_hash);
+        assert_eq!(res.block_number, Some(100));
+        assert_eq!(res.pre_op_gas, 50_000.into());
+    }
+}
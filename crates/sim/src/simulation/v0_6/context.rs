@@ -16,8 +16,8 @@ use std::{collections::HashSet, sync::Arc};
 use ethers::{abi::AbiDecode, types::BlockId};
 use rundler_provider::{Provider, SimulationProvider};
 use rundler_types::{
-    contracts::v0_6::i_entry_point::FailedOp, pool::SimulationViolation, v0_6::UserOperation,
-    EntityType, UserOperation as UserOperationTrait, ValidationOutput,
+    chain::ChainSpec, contracts::v0_6::i_entry_point::FailedOp, pool::SimulationViolation,
+    v0_6::UserOperation, EntityType, UserOperation as UserOperationTrait, ValidationOutput,
 };
 
 use super::{
@@ -34,6 +34,7 @@ use crate::{
 
 /// A provider for creating `ValidationContext` for entry point v0.6.
 pub(crate) struct ValidationContextProvider<T> {
+    chain_spec: ChainSpec,
     simulate_validation_tracer: T,
     sim_settings: SimulationSettings,
 }
@@ -56,7 +57,8 @@ where
         let tracer_out = self
             .simulate_validation_tracer
             .trace_simulate_validation(op.clone(), block_id)
-            .await?;
+            .await
+            .map_err(sim_context::tracer_error_to_violation)?;
         let num_phases = tracer_out.phases.len() as u32;
         // Check if there are too many phases here, then check too few at the
         // end. We are detecting cases where the entry point is broken. Too many
@@ -111,7 +113,7 @@ where
         );
 
         let associated_addresses = tracer_out.associated_slots_by_address.addresses();
-        let has_factory = op.factory().is_some();
+        let has_factory = op.is_deployment();
         Ok(ValidationContext {
             op,
             block_id,
@@ -160,11 +162,11 @@ where
             .pre_op_gas
             .saturating_sub(op.pre_verification_gas());
         let verification_buffer = op
-            .total_verification_gas_limit()
+            .total_verification_gas_limit(&self.chain_spec)
             .saturating_sub(verification_gas_used);
         if verification_buffer < REQUIRED_VERIFICATION_GAS_LIMIT_BUFFER {
             violations.push(SimulationViolation::VerificationGasLimitBufferTooLow(
-                op.total_verification_gas_limit(),
+                op.total_verification_gas_limit(&self.chain_spec),
                 verification_gas_used + REQUIRED_VERIFICATION_GAS_LIMIT_BUFFER,
             ));
         }
@@ -179,13 +181,20 @@ where
     E: SimulationProvider<UO = UserOperation>,
 {
     /// Creates a new `ValidationContextProvider` for entry point v0.6 with the given provider and entry point.
-    pub(crate) fn new(provider: Arc<P>, entry_point: E, sim_settings: SimulationSettings) -> Self {
+    pub(crate) fn new(
+        chain_spec: ChainSpec,
+        provider: Arc<P>,
+        entry_point: E,
+        sim_settings: SimulationSettings,
+    ) -> Self {
         Self {
+            chain_spec,
             simulate_validation_tracer: SimulateValidationTracerImpl::new(
                 provider,
                 entry_point,
                 sim_settings.max_verification_gas,
                 sim_settings.tracer_timeout.clone(),
+                sim_settings.reject_trace_size_bytes,
             ),
             sim_settings,
         }
@@ -205,7 +214,7 @@ mod tests {
     use sim_context::ContractInfo;
 
     use super::*;
-    use crate::simulation::context::{Phase, TracerOutput};
+    use crate::simulation::context::{Phase, TraceTooLargeError, TracerOutput};
 
     fn get_test_tracer_output() -> TracerOutput {
         TracerOutput {
@@ -262,7 +271,7 @@ mod tests {
                     called_banned_entry_point_method: false,
                     called_non_entry_point_with_value: false,
                     forbidden_opcodes_used: vec![],
-                    forbidden_precompiles_used: vec![],
+                    precompiles_used: vec![],
                     ran_out_of_gas: false,
                     storage_accesses: HashMap::new(),
                     undeployed_contract_accesses: vec![],
@@ -272,7 +281,7 @@ mod tests {
                     called_banned_entry_point_method: false,
                     called_non_entry_point_with_value: true,
                     forbidden_opcodes_used: vec![],
-                    forbidden_precompiles_used: vec![],
+                    precompiles_used: vec![],
                     ran_out_of_gas: false,
                     storage_accesses:  HashMap::new(),
                     undeployed_contract_accesses: vec![],
@@ -282,7 +291,7 @@ mod tests {
                     called_banned_entry_point_method: false,
                     called_non_entry_point_with_value: false,
                     forbidden_opcodes_used: vec![],
-                    forbidden_precompiles_used: vec![],
+                    precompiles_used: vec![],
                     ran_out_of_gas: false,
                     storage_accesses: HashMap::new(),
                     undeployed_contract_accesses: vec![],
@@ -337,6 +346,7 @@ mod tests {
         };
 
         let context = ValidationContextProvider {
+            chain_spec: ChainSpec::default(),
             simulate_validation_tracer: tracer,
             sim_settings: Default::default(),
         };
@@ -357,4 +367,33 @@ mod tests {
             )
         ));
     }
+
+    #[tokio::test]
+    async fn test_create_context_trace_too_large() {
+        let mut tracer = MockTracer::new();
+
+        tracer.expect_trace_simulate_validation().returning(|_, _| {
+            Err(TraceTooLargeError {
+                size: 2048,
+                max_size: 1024,
+            }
+            .into())
+        });
+
+        let context = ValidationContextProvider {
+            chain_spec: ChainSpec::default(),
+            simulate_validation_tracer: tracer,
+            sim_settings: Default::default(),
+        };
+
+        let res = context
+            .get_context(UserOperation::default(), BlockId::Number(0.into()))
+            .await;
+
+        assert!(matches!(
+            res,
+            Err(ViolationError::Violations(violations))
+                if violations == vec![SimulationViolation::TraceTooLarge(2048, 1024)]
+        ));
+    }
 }
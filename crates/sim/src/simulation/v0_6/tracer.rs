@@ -21,7 +21,7 @@ use rundler_provider::{Provider, SimulationProvider};
 use rundler_types::v0_6::UserOperation;
 use serde::Deserialize;
 
-use crate::simulation::context::TracerOutput;
+use crate::simulation::context::{self, TracerOutput};
 
 impl TryFrom<GethTrace> for TracerOutput {
     type Error = anyhow::Error;
@@ -53,6 +53,7 @@ pub(crate) struct SimulateValidationTracerImpl<P, E> {
     entry_point: E,
     max_validation_gas: u64,
     tracer_timeout: String,
+    reject_trace_size_bytes: usize,
 }
 
 /// Runs the bundler's custom tracer on the entry point's `simulateValidation`
@@ -73,24 +74,27 @@ where
             .entry_point
             .get_tracer_simulate_validation_call(op, self.max_validation_gas);
 
-        TracerOutput::try_from(
-            self.provider
-                .debug_trace_call(
-                    tx,
-                    Some(block_id),
-                    GethDebugTracingCallOptions {
-                        tracing_options: GethDebugTracingOptions {
-                            tracer: Some(GethDebugTracerType::JsTracer(
-                                validation_tracer_js().to_string(),
-                            )),
-                            timeout: Some(self.tracer_timeout.clone()),
-                            ..Default::default()
-                        },
-                        state_overrides: Some(state_override),
+        let out = self
+            .provider
+            .debug_trace_call_with_size_check(
+                tx,
+                Some(block_id),
+                GethDebugTracingCallOptions {
+                    tracing_options: GethDebugTracingOptions {
+                        tracer: Some(GethDebugTracerType::JsTracer(
+                            validation_tracer_js().to_string(),
+                        )),
+                        timeout: Some(self.tracer_timeout.clone()),
+                        ..Default::default()
                     },
-                )
-                .await?,
-        )
+                    state_overrides: Some(state_override),
+                },
+                self.reject_trace_size_bytes,
+            )
+            .await
+            .map_err(context::trace_size_error)?;
+
+        TracerOutput::try_from(out)
     }
 }
 
@@ -101,12 +105,14 @@ impl<P, E> SimulateValidationTracerImpl<P, E> {
         entry_point: E,
         max_validation_gas: u64,
         tracer_timeout: String,
+        reject_trace_size_bytes: usize,
     ) -> Self {
         Self {
             provider,
             entry_point,
             max_validation_gas,
             tracer_timeout,
+            reject_trace_size_bytes,
         }
     }
 }
@@ -102,7 +102,8 @@ where
         let tracer_out = self
             .simulate_validation_tracer
             .trace_simulate_validation(op.clone(), block_id)
-            .await?;
+            .await
+            .map_err(sim_context::tracer_error_to_violation)?;
 
         let call_stack = self.parse_call_stack(tracer_out.calls.clone())?;
 
@@ -118,15 +119,12 @@ where
             ))?
         }
 
-        let mut entry_point_out = match self.parse_top_call(top)? {
+        let entry_point_out = match self.parse_top_call(top)? {
             Ok(validation_output) => validation_output,
             Err(revert) => Err(ViolationError::Violations(vec![
                 SimulationViolation::ValidationRevert(revert),
             ]))?,
         };
-        if entry_point_out.return_info.valid_until == 0.into() {
-            entry_point_out.return_info.valid_until = u64::MAX.into();
-        }
 
         let entity_infos = sim_context::infos_from_validation_output(
             op.factory(),
@@ -167,7 +165,7 @@ where
         }
 
         Ok(ValidationContext {
-            has_factory: op.factory().is_some(),
+            has_factory: op.is_deployment(),
             op,
             block_id,
             entity_infos,
@@ -415,14 +413,13 @@ impl<T> ValidationContextProvider<T> {
             })
             .collect();
 
-        let mut forbidden_precompiles_used = vec![];
+        let mut precompiles_used = vec![];
         let mut undeployed_contract_accesses = vec![];
         call.contract_info.iter().for_each(|(address, info)| {
             if info.length == 0 {
                 if *address < MAX_PRECOMPILE_ADDRESS {
-                    // [OP-062] - banned precompiles
-                    // The tracer catches any allowed precompiles and does not add them to this list
-                    forbidden_precompiles_used
+                    // [OP-062] - precompiles, allowed ones are filtered by the simulator
+                    precompiles_used
                         .push(format!("{}:{}", call.top_level_target_address, *address,));
                 } else {
                     // [OP-041]
@@ -433,7 +430,7 @@ impl<T> ValidationContextProvider<T> {
 
         Phase {
             forbidden_opcodes_used,
-            forbidden_precompiles_used,
+            precompiles_used,
             storage_accesses,
             called_banned_entry_point_method: false, // set during call stack parsing
             called_non_entry_point_with_value: false, // set during call stack parsing
@@ -494,8 +491,66 @@ where
                 entry_point,
                 sim_settings.max_verification_gas,
                 sim_settings.tracer_timeout.clone(),
+                sim_settings.reject_trace_size_bytes,
             ),
             sim_settings,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call_with_opcodes(opcodes: &[Opcode]) -> TopLevelCallInfo {
+        TopLevelCallInfo {
+            top_level_method_sig: VALIDATE_USER_OP_METHOD.to_string(),
+            top_level_target_address: "0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4".to_string(),
+            opcodes: opcodes.iter().map(|op| (*op, 1)).collect(),
+            access: HashMap::new(),
+            contract_info: HashMap::new(),
+            ext_code_access_info: HashMap::new(),
+            oog: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_call_to_phase_disallowed_create2() {
+        let call = call_with_opcodes(&[Opcode::CREATE2]);
+
+        let phase =
+            ValidationContextProvider::<()>::parse_call_to_phase(&call, EntityType::Account);
+        assert_eq!(
+            phase.forbidden_opcodes_used,
+            vec!["0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4:CREATE2".to_string()]
+        );
+
+        // [OP-031] - CREATE2 is allowed in the deployment phase
+        let phase =
+            ValidationContextProvider::<()>::parse_call_to_phase(&call, EntityType::Factory);
+        assert!(phase.forbidden_opcodes_used.is_empty());
+    }
+
+    #[test]
+    fn test_parse_call_to_phase_create_and_selfdestruct() {
+        let call = call_with_opcodes(&[Opcode::CREATE, Opcode::SELFDESTRUCT]);
+
+        for entity_type in [
+            EntityType::Factory,
+            EntityType::Account,
+            EntityType::Paymaster,
+        ] {
+            let mut forbidden =
+                ValidationContextProvider::<()>::parse_call_to_phase(&call, entity_type)
+                    .forbidden_opcodes_used;
+            forbidden.sort();
+            assert_eq!(
+                forbidden,
+                vec![
+                    "0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4:CREATE".to_string(),
+                    "0xb856dbd4fa1a79a46d426f537455e7d3e79ab7c4:SELFDESTRUCT".to_string(),
+                ]
+            );
+        }
+    }
+}
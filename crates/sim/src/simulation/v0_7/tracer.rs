@@ -22,7 +22,10 @@ use rundler_provider::{Provider, SimulationProvider};
 use rundler_types::{v0_7::UserOperation, Opcode};
 use serde::Deserialize;
 
-use crate::{simulation::context::ContractInfo, ExpectedStorage};
+use crate::{
+    simulation::context::{self, ContractInfo},
+    ExpectedStorage,
+};
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -127,6 +130,7 @@ pub(crate) struct SimulateValidationTracerImpl<P, E> {
     entry_point: E,
     max_validation_gas: u64,
     tracer_timeout: String,
+    reject_trace_size_bytes: usize,
 }
 
 /// Runs the bundler's custom tracer on the entry point's `simulateValidation`
@@ -149,7 +153,7 @@ where
 
         let out = self
             .provider
-            .debug_trace_call(
+            .debug_trace_call_with_size_check(
                 tx,
                 Some(block_id),
                 GethDebugTracingCallOptions {
@@ -162,8 +166,10 @@ where
                     },
                     state_overrides: Some(state_override),
                 },
+                self.reject_trace_size_bytes,
             )
-            .await?;
+            .await
+            .map_err(context::trace_size_error)?;
 
         TracerOutput::try_from(out)
     }
@@ -176,12 +182,14 @@ impl<P, E> SimulateValidationTracerImpl<P, E> {
         entry_point: E,
         max_validation_gas: u64,
         tracer_timeout: String,
+        reject_trace_size_bytes: usize,
     ) -> Self {
         Self {
             provider,
             entry_point,
             max_validation_gas,
             tracer_timeout,
+            reject_trace_size_bytes,
         }
     }
 }
@@ -14,7 +14,10 @@
 use std::collections::{btree_map, BTreeMap};
 
 use anyhow::bail;
-use ethers::types::{Address, H256, U256};
+use ethers::types::{
+    transaction::eip2930::{AccessList, AccessListItem},
+    Address, H256, U256,
+};
 use serde::{Deserialize, Serialize};
 
 /// The expected storage values for a user operation that must
@@ -57,6 +60,19 @@ impl ExpectedStorage {
 
         self.0.entry(address).or_default().insert(slot, value);
     }
+
+    /// Convert the accessed addresses and storage slots into an EIP-2930 access list.
+    pub fn access_list(&self) -> AccessList {
+        AccessList(
+            self.0
+                .iter()
+                .map(|(&address, values_by_slot)| AccessListItem {
+                    address,
+                    storage_keys: values_by_slot.keys().copied().collect(),
+                })
+                .collect(),
+        )
+    }
 }
 
 use std::fmt::{Display, Formatter};
@@ -29,7 +29,7 @@ interface Output {
 
 interface Phase {
   forbiddenOpcodesUsed: string[];
-  forbiddenPrecompilesUsed: string[];
+  precompilesUsed: string[];
   storageAccesses: Record<string, AccessInfo>;
   calledBannedEntryPointMethod: boolean;
   addressesCallingWithValue: string[];
@@ -60,13 +60,13 @@ interface ContractInfo {
 type InternalPhase = Omit<
   Phase,
   | "forbiddenOpcodesUsed"
-  | "forbiddenPrecompilesUsed"
+  | "precompilesUsed"
   | "storageAccesses"
   | "addressesCallingWithValue"
   | "undeployedContractAccesses"
 > & {
   forbiddenOpcodesUsed: StringSet;
-  forbiddenPrecompilesUsed: StringSet;
+  precompilesUsed: StringSet;
   storageAccesses: Record<string, AccessInfo>;
   addressesCallingWithValue: StringSet;
   undeployedContractAccesses: StringSet;
@@ -113,8 +113,10 @@ type StringSet = Record<string, boolean | undefined>;
   const EXT_OPCODES = stringSet(["EXTCODECOPY", "EXTCODEHASH", "EXTCODESIZE"]);
 
   const READ_WRITE_OPCODES = stringSet(["SSTORE", "SLOAD", "TSTORE", "TLOAD"]);
-  // Whitelisted precompile addresses.
-  const PRECOMPILE_WHITELIST = stringSet([
+  // Precompile addresses recognized in addition to those reported by the node.
+  // Whether a precompile may be called is decided by the simulator's allowed
+  // precompile settings.
+  const KNOWN_PRECOMPILES = stringSet([
     "0x0000000000000000000000000000000000000001", // ecRecover
     "0x0000000000000000000000000000000000000002", // SHA2-256
     "0x0000000000000000000000000000000000000003", // RIPEMD-160
@@ -142,7 +144,7 @@ type StringSet = Record<string, boolean | undefined>;
   function newInternalPhase(): InternalPhase {
     return {
       forbiddenOpcodesUsed: {},
-      forbiddenPrecompilesUsed: {},
+      precompilesUsed: {},
       storageAccesses: {},
       calledBannedEntryPointMethod: false,
       addressesCallingWithValue: {},
@@ -161,8 +163,8 @@ type StringSet = Record<string, boolean | undefined>;
       extCodeAccessInfo,
     } = currentPhase;
     const forbiddenOpcodesUsed = Object.keys(currentPhase.forbiddenOpcodesUsed);
-    const forbiddenPrecompilesUsed = Object.keys(
-      currentPhase.forbiddenPrecompilesUsed
+    const precompilesUsed = Object.keys(
+      currentPhase.precompilesUsed
     );
     const addressesCallingWithValue = Object.keys(
       currentPhase.addressesCallingWithValue
@@ -173,7 +175,7 @@ type StringSet = Record<string, boolean | undefined>;
 
     const phase: Phase = {
       forbiddenOpcodesUsed,
-      forbiddenPrecompilesUsed,
+      precompilesUsed,
       storageAccesses: currentPhase.storageAccesses,
       calledBannedEntryPointMethod,
       addressesCallingWithValue,
@@ -375,7 +377,7 @@ type StringSet = Record<string, boolean | undefined>;
         const index = EXT_OPCODES[opcode] ? 0 : 1;
         const address = toAddress(log.stack.peek(index).toString(16));
         const addressHex = toHex(address);
-        if (!isPrecompiled(address) && !PRECOMPILE_WHITELIST[addressHex]) {
+        if (!isPrecompiled(address) && !KNOWN_PRECOMPILES[addressHex]) {
           if (
             !accessedContracts[addressHex] ||
             currentPhase.undeployedContractAccesses[addressHex]
@@ -397,8 +399,8 @@ type StringSet = Record<string, boolean | undefined>;
             opcode,
             length: db.getCode(address).length,
           };
-        } else if (!PRECOMPILE_WHITELIST[addressHex]) {
-          currentPhase.forbiddenPrecompilesUsed[
+        } else {
+          currentPhase.precompilesUsed[
             getContractCombinedKey(log, addressHex)
           ] = true;
         }
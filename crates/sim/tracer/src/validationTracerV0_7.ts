@@ -336,23 +336,14 @@ interface BundlerCollectorTracer extends LogTracer<BundlerTracerResult>, Bundler
         }
       }
 
-      // not using 'isPrecompiled' to only allow the ones defined by the ERC-4337 as stateless precompiles
-      // [OP-062]
-      const isAllowedPrecompiled: (address: any) => boolean = (address) => {
-        const addrHex = toHex(address)
-        const addressInt = parseInt(addrHex)
-        // this.debug.push(`isPrecompiled address=${addrHex} addressInt=${addressInt}`)
-
-        // MODIFICATION: allow precompile RIP-7212 through - which is at 256
-        return (addressInt > 0 && addressInt < 10) || addressInt == 256
-      }
       // [OP-041]
+      // [OP-062] - precompiles are recorded as well, allowed ones are filtered by the simulator
       if (opcode.match(/^(EXT.*|CALL|CALLCODE|DELEGATECALL|STATICCALL)$/) != null) {
         const idx = opcode.startsWith('EXT') ? 0 : 1
         const addr = toAddress(log.stack.peek(idx).toString(16))
         const addrHex = toHex(addr)
         // this.debug.push('op=' + opcode + ' last=' + this.lastOp + ' stacksize=' + log.stack.length() + ' addr=' + addrHex)
-        if (this.currentLevel.contractInfo[addrHex] == null && !isAllowedPrecompiled(addr)) {
+        if (this.currentLevel.contractInfo[addrHex] == null) {
           this.currentLevel.contractInfo[addrHex] = {
             length: db.getCode(addr).length,
             opcode,
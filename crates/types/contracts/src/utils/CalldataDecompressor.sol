@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity ^0.8.19;
+
+// Decompresses bundle calldata compressed by Rundler and forwards it to its target.
+//
+// The decompressed payload is the 20 byte target address followed by the calldata to
+// forward. Runs of 0x00 and 0xff bytes are encoded as a 0x00 marker followed by a length
+// byte: bit 7 selects the fill byte (0xff if set) and the low 7 bits hold the run length
+// minus one. Any other byte is copied as is.
+//
+// The contract has no functions so that no compressed payload can collide with a selector.
+contract CalldataDecompressor {
+    fallback() external payable {
+        bytes memory payload = decompress(msg.data);
+        require(payload.length >= 20, "payload too short");
+
+        assembly {
+            let target := shr(96, mload(add(payload, 32)))
+            let success := call(gas(), target, callvalue(), add(payload, 52), sub(mload(payload), 20), 0, 0)
+            returndatacopy(0, 0, returndatasize())
+            if iszero(success) { revert(0, returndatasize()) }
+            return(0, returndatasize())
+        }
+    }
+
+    function decompress(bytes calldata data) internal pure returns (bytes memory out) {
+        unchecked {
+            // first pass sizes the output
+            uint256 len = 0;
+            for (uint256 i = 0; i < data.length;) {
+                if (data[i] != 0x00) {
+                    len++;
+                    i++;
+                    continue;
+                }
+                require(i + 1 < data.length, "run marker missing its length byte");
+                len += (uint8(data[i + 1]) & 0x7f) + 1;
+                i += 2;
+            }
+
+            // new memory is zeroed, so runs of 0x00 only advance the output
+            out = new bytes(len);
+            uint256 j = 0;
+            for (uint256 i = 0; i < data.length;) {
+                bytes1 b = data[i];
+                if (b != 0x00) {
+                    out[j++] = b;
+                    i++;
+                    continue;
+                }
+                uint8 marker = uint8(data[i + 1]);
+                uint256 run = (marker & 0x7f) + 1;
+                if (marker & 0x80 != 0) {
+                    for (uint256 k = 0; k < run; k++) {
+                        out[j++] = 0xff;
+                    }
+                } else {
+                    j += run;
+                }
+                i += 2;
+            }
+        }
+    }
+}
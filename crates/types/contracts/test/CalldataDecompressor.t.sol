@@ -0,0 +1,51 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity ^0.8.19;
+
+import "forge-std/Test.sol";
+
+import "../src/utils/CalldataDecompressor.sol";
+
+contract Recorder {
+    bytes public lastCall;
+
+    fallback() external payable {
+        lastCall = msg.data;
+    }
+}
+
+// The compressed vectors are the output of `compress` in
+// crates/builder/src/sender/compressed.rs, see `test_compress_vectors`.
+contract CalldataDecompressorTest is Test {
+    // contains no 0x00 or 0xff bytes, so it compresses to itself
+    address constant TARGET = 0x1111111111111111111111111111111111111111;
+
+    CalldataDecompressor decompressor;
+
+    function setUp() public {
+        decompressor = new CalldataDecompressor();
+        vm.etch(TARGET, address(new Recorder()).code);
+    }
+
+    function forwarded(bytes memory compressed) internal returns (bytes memory) {
+        (bool success,) = address(decompressor).call(abi.encodePacked(TARGET, compressed));
+        assertTrue(success);
+        return Recorder(payable(TARGET)).lastCall();
+    }
+
+    function testLiteralsAndRuns() public {
+        assertEq(forwarded(hex"120001008234"), hex"120000ffffff34");
+    }
+
+    function testLongZeroRun() public {
+        assertEq(forwarded(hex"007f007f002b"), new bytes(300));
+    }
+
+    function testSingleFf() public {
+        assertEq(forwarded(hex"ff"), hex"ff");
+    }
+
+    function testTruncatedMarkerReverts() public {
+        (bool success,) = address(decompressor).call(abi.encodePacked(TARGET, hex"00"));
+        assertFalse(success);
+    }
+}
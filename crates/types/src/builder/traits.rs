@@ -15,7 +15,10 @@ use ethers::types::{Address, H256};
 #[cfg(feature = "test-utils")]
 use mockall::automock;
 
-use super::{error::BuilderError, types::BundlingMode};
+use super::{
+    error::BuilderError,
+    types::{BundlePreview, BundlingMode},
+};
 
 /// Builder result
 pub type BuilderResult<T> = std::result::Result<T, BuilderError>;
@@ -34,4 +37,9 @@ pub trait Builder: Send + Sync + 'static {
 
     /// Set the bundling mode
     async fn debug_set_bundling_mode(&self, mode: BundlingMode) -> BuilderResult<()>;
+
+    /// Build the bundle that would be sent now for the given entry point, without sending it.
+    ///
+    /// Does not remove any rejected operations from the pool.
+    async fn debug_preview_bundle(&self, entry_point: Address) -> BuilderResult<BundlePreview>;
 }
@@ -11,6 +11,7 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
+use ethers::types::{H256, U256};
 use parse_display::Display;
 use serde::{Deserialize, Serialize};
 
@@ -28,3 +29,12 @@ pub enum BundlingMode {
     /// Bundles will be sent automatically.
     Auto,
 }
+
+/// A bundle that the builder would send now, without sending it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BundlePreview {
+    /// Hashes of the user operations in the bundle, in bundle order
+    pub user_op_hashes: Vec<H256>,
+    /// Estimated gas limit of the bundle transaction
+    pub gas_estimate: U256,
+}
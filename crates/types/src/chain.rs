@@ -72,18 +72,31 @@ pub struct ChainSpec {
     /// true if L1 calldata gas should be included in the gas limit
     /// only applies when calldata_pre_verification_gas is true
     pub include_l1_gas_in_gas_limit: bool,
+    /// Expected EIP-3529 gas refund, as a percent of a user operation's execution gas,
+    /// credited against its preVerificationGas. The refund is capped at the EIP-3529
+    /// maximum of 1/5 of gas used, and at 10% of the static preVerificationGas.
+    /// 0 disables refund modeling.
+    pub gas_refund_percent: u64,
+    /// Gas multipliers applied to v0.6 user operations that use a paymaster
+    pub paymaster_gas_policy_v0_6: PaymasterGasPolicy,
 
     /*
      * Fee estimation
      */
     /// true if eip1559 is enabled, and thus priority fees are used
     pub eip1559_enabled: bool,
+    /// true if the chain's sequencer ignores priority fees. Bundles are sent with a zero
+    /// priority fee and user operations are not required to pay one.
+    pub zero_priority_fee: bool,
     /// Type of oracle for estimating priority fees
     pub priority_fee_oracle_type: PriorityFeeOracleType,
     /// Minimum max priority fee per gas for the network
     pub min_max_priority_fee_per_gas: U256,
     /// Maximum max priority fee per gas for the network
     pub max_max_priority_fee_per_gas: U256,
+    /// How long to cache the result of `eth_maxPriorityFeePerGas` when using the
+    /// provider priority fee oracle. Zero disables caching.
+    pub max_priority_fee_cache_ttl_millis: u64,
     /// Usage ratio of the chain that determines "congestion"
     /// Some chains have artificially high block gas limits but
     /// actually cap block gas usage at a lower value.
@@ -110,6 +123,9 @@ pub struct ChainSpec {
     pub flashbots_status_url: Option<String>,
     /// True if the bloxroute sender is enabled on this chain
     pub bloxroute_enabled: bool,
+    /// Address of a calldata decompressor contract. If set, bundle transactions are
+    /// sent compressed through the decompressor to reduce L1 data costs.
+    pub calldata_decompressor_address: Option<Address>,
 
     /*
      * Pool
@@ -131,6 +147,28 @@ pub enum L1GasOracleContractType {
     OptimismBedrock,
 }
 
+/// Multipliers of the verification gas limit of a user operation that uses a paymaster.
+///
+/// Defaults to the values of the v0.6 entry point, custom entry points may differ.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct PaymasterGasPolicy {
+    /// Multiplier of the verification gas limit in the max gas cost, as the entry point
+    /// may run the paymaster's `postOp` twice with it
+    pub max_gas_cost_multiplier: u64,
+    /// Multiplier of the verification gas limit in the total verification gas limit
+    pub verification_gas_limit_multiplier: u64,
+}
+
+impl Default for PaymasterGasPolicy {
+    fn default() -> Self {
+        Self {
+            max_gas_cost_multiplier: 3,
+            verification_gas_limit_multiplier: 2,
+        }
+    }
+}
+
 /// Type of oracle for estimating priority fees
 #[derive(Clone, Debug, Deserialize, Default, Serialize)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -158,13 +196,17 @@ impl Default for ChainSpec {
             calldata_zero_byte_gas: U256::from(4),
             calldata_non_zero_byte_gas: U256::from(16),
             eip1559_enabled: true,
+            zero_priority_fee: false,
             calldata_pre_verification_gas: false,
             l1_gas_oracle_contract_type: L1GasOracleContractType::default(),
             l1_gas_oracle_contract_address: Address::zero(),
             include_l1_gas_in_gas_limit: true,
+            gas_refund_percent: 0,
+            paymaster_gas_policy_v0_6: PaymasterGasPolicy::default(),
             priority_fee_oracle_type: PriorityFeeOracleType::default(),
             min_max_priority_fee_per_gas: U256::zero(),
             max_max_priority_fee_per_gas: U256::MAX,
+            max_priority_fee_cache_ttl_millis: 0,
             congestion_trigger_usage_ratio_threshold: 0.75,
             max_transaction_size_bytes: 131072, // 128 KiB
             bundle_max_send_interval_millis: u64::MAX,
@@ -172,6 +214,7 @@ impl Default for ChainSpec {
             flashbots_relay_url: None,
             flashbots_status_url: None,
             bloxroute_enabled: false,
+            calldata_decompressor_address: None,
             chain_history_size: 64,
         }
     }
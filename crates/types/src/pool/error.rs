@@ -57,6 +57,10 @@ pub enum MempoolError {
     /// Max operations reached for unstaked sender [UREP-010] or unstaked non-sender entity [UREP-020]
     #[error("Max operations ({0}) reached for entity {1}")]
     MaxOperationsReached(usize, Entity),
+    /// Max operations reached for variants of the same operation, sharing a fingerprint
+    /// derived from the sender, call data and paymaster
+    #[error("Max operations ({0}) reached for variants of this operation")]
+    MaxFingerprintOperationsReached(usize),
     /// Multiple roles violation
     /// Spec rule: STO-040
     #[error("A {} at {} in this UserOperation is used as a sender entity in another UserOperation currently in mempool.", .0.kind, .0.address)]
@@ -72,9 +76,10 @@ pub enum MempoolError {
     /// An entity associated with the operation is throttled/banned.
     #[error("Entity {0} is throttled/banned")]
     EntityThrottled(Entity),
-    /// Operation was discarded on inserting due to size limit
-    #[error("Operation was discarded on inserting")]
-    DiscardedOnInsert,
+    /// The mempool is full and the operation was discarded on insert because it
+    /// pays less than every operation already in the pool
+    #[error("Mempool is full and the operation pays less than the cheapest operation in it")]
+    PoolFull,
     /// Paymaster balance too low
     /// Spec rule: EREP-010
     #[error("Paymaster balance too low. Required balance: {0}. Current balance {1}")]
@@ -94,6 +99,13 @@ pub enum MempoolError {
     /// The operation drop attempt too soon after being added to the pool
     #[error("Operation drop attempt too soon after being added to the pool. Added at {0}, attempted to drop at {1}, must wait {2} blocks.")]
     OperationDropTooSoon(u64, u64, u64),
+    /// The mempool is near its size limit and is not accepting new operations
+    #[error("Mempool is near capacity, try again later")]
+    PoolBusy,
+    /// Operation with same sender/nonce already in pool was added by the operator
+    /// and may not be replaced
+    #[error("Operation with the same sender and nonce may not be replaced")]
+    ReplacementNotAllowed,
 }
 
 /// Precheck violation enumeration
@@ -120,6 +132,9 @@ pub enum PrecheckViolation {
     /// The pre-verification gas of the user operation is too low.
     #[display("preVerificationGas is {0} but must be at least {1}")]
     PreVerificationGasTooLow(U256, U256),
+    /// The pre-verification gas of the user operation is higher than any bundle allows.
+    #[display("preVerificationGas is {0} but must be at most {1}")]
+    PreVerificationGasTooHigh(U256, U256),
     /// A paymaster is provided, but the address is not deployed.
     #[display("paymasterAndData indicates paymaster with no code: {0:?}")]
     PaymasterIsNotContract(Address),
@@ -139,6 +154,21 @@ pub enum PrecheckViolation {
     /// The call gas limit is too low to account for any possible call.
     #[display("callGasLimit is {0} but must be at least {1}")]
     CallGasLimitTooLow(U256, U256),
+    /// The nonce is behind the entry point's current nonce for its key.
+    #[display("nonce is {0} but must be at least {1}")]
+    NonceTooLow(U256, U256),
+    /// The nonce is too far ahead of the entry point's current nonce for its key.
+    #[display("nonce is {0} but must be at most {1}")]
+    NonceTooHigh(U256, U256),
+    /// The init code is larger than the allowed maximum.
+    #[display("initCode is {0} bytes but must be at most {1} bytes")]
+    InitCodeTooLarge(usize, usize),
+    /// The verification gas limit is too high relative to the call gas limit.
+    #[display("verificationGasLimit is {0} but must be at most {2} times the callGasLimit of {1}")]
+    VerificationGasToCallGasRatioTooHigh(U256, U256, u64),
+    /// The sender of the user operation is also another entity, e.g. the paymaster.
+    #[display("{0.kind} and {1.kind} must not share address {0.address:?}")]
+    DuplicateEntityAddress(Entity, Entity),
 }
 
 /// All possible simulation violations
@@ -228,6 +258,9 @@ pub enum SimulationViolation {
     /// Unsupported contract type
     #[display("accessed unsupported contract type: {0:?} at {1:?}. Address must be whitelisted")]
     AccessedUnsupportedContractType(String, Address),
+    /// The simulation trace exceeded the maximum allowed size
+    #[display("simulation trace size {0} bytes exceeds the maximum of {1} bytes")]
+    TraceTooLarge(usize, usize),
 }
 
 /// Information about a storage violation based on stake status
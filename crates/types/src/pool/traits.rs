@@ -22,7 +22,7 @@ use super::{
     error::PoolError,
     types::{NewHead, PaymasterMetadata, PoolOperation, Reputation, ReputationStatus, StakeStatus},
 };
-use crate::{EntityUpdate, UserOperationId, UserOperationVariant};
+use crate::{EntityType, EntityUpdate, UserOperationId, UserOperationVariant};
 
 /// Result type for pool server operations.
 pub type PoolResult<T> = std::result::Result<T, PoolError>;
@@ -50,6 +50,10 @@ pub trait Pool: Send + Sync + 'static {
     /// Returns None if the operation is not found
     async fn get_op_by_hash(&self, hash: H256) -> PoolResult<Option<PoolOperation>>;
 
+    /// Get all operations in the pool from a given sender across all entry points,
+    /// sorted by nonce
+    async fn get_ops_by_sender(&self, sender: Address) -> PoolResult<Vec<PoolOperation>>;
+
     /// Remove operations from the pool by hash
     async fn remove_ops(&self, entry_point: Address, ops: Vec<H256>) -> PoolResult<()>;
 
@@ -80,11 +84,13 @@ pub trait Pool: Send + Sync + 'static {
         address: Address,
     ) -> PoolResult<ReputationStatus>;
 
-    /// Get stake status given entrypoint and address
+    /// Get stake status given entrypoint and address. If an entity type is given the
+    /// stake is checked against that entity type's requirement, otherwise the default.
     async fn get_stake_status(
         &self,
         entry_point: Address,
         address: Address,
+        entity_type: Option<EntityType>,
     ) -> PoolResult<StakeStatus>;
 
     /// Clear the pool state, used for debug methods
@@ -121,4 +127,12 @@ pub trait Pool: Send + Sync + 'static {
         paymaster: bool,
         reputation: bool,
     ) -> PoolResult<()>;
+
+    /// Add an operation injected by the bundler operator to the pool. Operator
+    /// operations can only be replaced by other operator operations.
+    async fn admin_add_operator_op(
+        &self,
+        entry_point: Address,
+        op: UserOperationVariant,
+    ) -> PoolResult<H256>;
 }
@@ -110,7 +110,9 @@ pub struct PoolOperation {
     /// The valid time range for this operation.
     pub valid_time_range: ValidTimeRange,
     /// The expected code hash for all contracts accessed during validation for this operation.
-    pub expected_code_hash: H256,
+    ///
+    /// `None` if the operation was admitted without tracing.
+    pub expected_code_hash: Option<H256>,
     /// The block hash simulation was completed at
     pub sim_block_hash: H256,
     /// The block number simulation was completed at
@@ -119,6 +121,9 @@ pub struct PoolOperation {
     pub account_is_staked: bool,
     /// Staking information about all the entities.
     pub entity_infos: EntityInfos,
+    /// Whether the operation was added by the operator. Such operations may only be
+    /// replaced by another operator operation with the same sender and nonce.
+    pub no_replacement: bool,
 }
 
 impl PoolOperation {
@@ -193,6 +193,11 @@ impl ValidTimeRange {
         self.valid_after <= timestamp && (timestamp + buffer) <= self.valid_until
     }
 
+    /// Returns true if this time range ended before the given timestamp.
+    pub fn is_expired(self, timestamp: Timestamp) -> bool {
+        self.valid_until < timestamp
+    }
+
     /// Intersect two time ranges into a single time range that is valid whenever both are valid
     pub fn intersect(self, other: Self) -> Self {
         Self {
@@ -11,7 +11,10 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use std::{fmt::Debug, time::Duration};
+use std::{
+    fmt::{self, Debug, Display},
+    time::Duration,
+};
 
 use ethers::{
     abi::AbiEncode,
@@ -52,6 +55,16 @@ pub enum EntryPointVersion {
     V0_7,
 }
 
+impl Display for EntryPointVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryPointVersion::Unspecified => write!(f, "unspecified"),
+            EntryPointVersion::V0_6 => write!(f, "v0.6"),
+            EntryPointVersion::V0_7 => write!(f, "v0.7"),
+        }
+    }
+}
+
 /// Unique identifier for a user operation from a given sender
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct UserOperationId {
@@ -81,15 +94,54 @@ pub trait UserOperation: Debug + Clone + Send + Sync + 'static {
     /// Get the user operation nonce
     fn nonce(&self) -> U256;
 
+    /// Get the key portion of the 2D nonce, the upper 192 bits
+    fn nonce_key(&self) -> U256 {
+        split_nonce(self.nonce()).0
+    }
+
+    /// Get the sequence portion of the 2D nonce, the lower 64 bits
+    fn nonce_sequence(&self) -> u64 {
+        split_nonce(self.nonce()).1
+    }
+
     /// Get the user operation paymaster address, if any
     fn paymaster(&self) -> Option<Address>;
 
+    /// Get the data passed to the paymaster, empty if no paymaster is used
+    fn paymaster_data(&self) -> &[u8];
+
+    /// Get the ERC-20 token charged by a token paymaster, if any
+    ///
+    /// Assumes the layout used by token paymasters, paymaster data that starts with the
+    /// non-zero address of the token they charge the sender in. Other paymasters may use
+    /// the same bytes for anything, so only call this for known token paymasters.
+    fn paymaster_token(&self) -> Option<Address> {
+        self.paymaster()?;
+        let data = self.paymaster_data();
+        if data.len() < 20 {
+            return None;
+        }
+        let token = Address::from_slice(&data[..20]);
+        (!token.is_zero()).then_some(token)
+    }
+
     /// Get the user operation factory address, if any
     fn factory(&self) -> Option<Address>;
 
+    /// Returns true if the user operation deploys its sender account, i.e. it has a factory
+    fn is_deployment(&self) -> bool {
+        self.factory().is_some()
+    }
+
     /// Get the user operation calldata
     fn call_data(&self) -> &Bytes;
 
+    /// Get the user operation signature
+    fn signature(&self) -> &Bytes;
+
+    /// Returns the length in bytes of the init code, the factory address followed by its data
+    fn init_code_len(&self) -> usize;
+
     /// Returns the call gas limit
     fn call_gas_limit(&self) -> U256;
 
@@ -103,7 +155,7 @@ pub trait UserOperation: Debug + Clone + Send + Sync + 'static {
     fn max_priority_fee_per_gas(&self) -> U256;
 
     /// Returns the maximum cost, in wei, of this user operation
-    fn max_gas_cost(&self) -> U256;
+    fn max_gas_cost(&self, chain_spec: &ChainSpec) -> U256;
 
     /*
      * Enhanced functions
@@ -125,7 +177,7 @@ pub trait UserOperation: Debug + Clone + Send + Sync + 'static {
     fn heap_size(&self) -> usize;
 
     /// Returns the total verification gas limit
-    fn total_verification_gas_limit(&self) -> U256;
+    fn total_verification_gas_limit(&self, chain_spec: &ChainSpec) -> U256;
 
     /// Returns the required pre-execution buffer
     ///
@@ -152,6 +204,12 @@ pub trait UserOperation: Debug + Clone + Send + Sync + 'static {
     /// Abi encode size of the user operation
     fn abi_encoded_size(&self) -> usize;
 
+    /// Returns the `handleOps([op], beneficiary)` calldata of a bundle containing only this operation
+    ///
+    /// This is the data posted to L1 for the operation on rollups, and should be used when
+    /// estimating its L1 data cost.
+    fn single_op_bundle_calldata(&self, beneficiary: Address) -> Bytes;
+
     /// Calculate the size of the user operation in single UO bundle in bytes
     fn single_uo_bundle_size_bytes(&self) -> usize {
         self.abi_encoded_size() + BUNDLE_BYTE_OVERHEAD + USER_OP_OFFSET_WORD_SIZE
@@ -209,6 +267,13 @@ impl UserOperation for UserOperationVariant {
         }
     }
 
+    fn paymaster_data(&self) -> &[u8] {
+        match self {
+            UserOperationVariant::V0_6(op) => op.paymaster_data(),
+            UserOperationVariant::V0_7(op) => op.paymaster_data(),
+        }
+    }
+
     fn factory(&self) -> Option<Address> {
         match self {
             UserOperationVariant::V0_6(op) => op.factory(),
@@ -223,10 +288,24 @@ impl UserOperation for UserOperationVariant {
         }
     }
 
-    fn max_gas_cost(&self) -> U256 {
+    fn signature(&self) -> &Bytes {
         match self {
-            UserOperationVariant::V0_6(op) => op.max_gas_cost(),
-            UserOperationVariant::V0_7(op) => op.max_gas_cost(),
+            UserOperationVariant::V0_6(op) => op.signature(),
+            UserOperationVariant::V0_7(op) => op.signature(),
+        }
+    }
+
+    fn init_code_len(&self) -> usize {
+        match self {
+            UserOperationVariant::V0_6(op) => op.init_code_len(),
+            UserOperationVariant::V0_7(op) => op.init_code_len(),
+        }
+    }
+
+    fn max_gas_cost(&self, chain_spec: &ChainSpec) -> U256 {
+        match self {
+            UserOperationVariant::V0_6(op) => op.max_gas_cost(chain_spec),
+            UserOperationVariant::V0_7(op) => op.max_gas_cost(chain_spec),
         }
     }
 
@@ -258,10 +337,10 @@ impl UserOperation for UserOperationVariant {
         }
     }
 
-    fn total_verification_gas_limit(&self) -> U256 {
+    fn total_verification_gas_limit(&self, chain_spec: &ChainSpec) -> U256 {
         match self {
-            UserOperationVariant::V0_6(op) => op.total_verification_gas_limit(),
-            UserOperationVariant::V0_7(op) => op.total_verification_gas_limit(),
+            UserOperationVariant::V0_6(op) => op.total_verification_gas_limit(chain_spec),
+            UserOperationVariant::V0_7(op) => op.total_verification_gas_limit(chain_spec),
         }
     }
 
@@ -321,6 +400,13 @@ impl UserOperation for UserOperationVariant {
             UserOperationVariant::V0_7(op) => op.abi_encoded_size(),
         }
     }
+
+    fn single_op_bundle_calldata(&self, beneficiary: Address) -> Bytes {
+        match self {
+            UserOperationVariant::V0_6(op) => op.single_op_bundle_calldata(beneficiary),
+            UserOperationVariant::V0_7(op) => op.single_op_bundle_calldata(beneficiary),
+        }
+    }
 }
 
 impl UserOperationVariant {
@@ -338,13 +424,19 @@ impl UserOperationVariant {
         }
     }
 
-    /// Returns the user operation type
-    pub fn uo_type(&self) -> EntryPointVersion {
+    /// Returns the entry point version of the user operation
+    pub fn version(&self) -> EntryPointVersion {
         match self {
             UserOperationVariant::V0_6(_) => EntryPointVersion::V0_6,
             UserOperationVariant::V0_7(_) => EntryPointVersion::V0_7,
         }
     }
+
+    /// Returns the user operation type
+    #[deprecated(note = "use `version` instead")]
+    pub fn uo_type(&self) -> EntryPointVersion {
+        self.version()
+    }
 }
 
 /// User operation optional gas enum
@@ -382,6 +474,8 @@ pub struct GasEstimate {
     ///
     /// v0.7: populated only if the user operation has a paymaster
     pub paymaster_verification_gas_limit: Option<U256>,
+    /// Warnings about the estimate, e.g. a gas limit that was clamped to its configured max
+    pub warnings: Vec<String>,
 }
 
 /// User operations per aggregator
@@ -395,6 +489,41 @@ pub struct UserOpsPerAggregator<UO: UserOperation> {
     pub signature: Bytes,
 }
 
+/// Zero and non-zero byte counts of calldata, used to price its data cost
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CalldataByteCounts {
+    /// Number of zero bytes
+    pub zero_bytes: usize,
+    /// Number of non-zero bytes
+    pub non_zero_bytes: usize,
+}
+
+impl CalldataByteCounts {
+    /// Count the zero and non-zero bytes of the calldata
+    pub fn new(data: &[u8]) -> Self {
+        let zero_bytes = data.iter().filter(|&&b| b == 0).count();
+        Self {
+            zero_bytes,
+            non_zero_bytes: data.len() - zero_bytes,
+        }
+    }
+
+    /// Total length of the calldata in bytes
+    pub fn len(&self) -> usize {
+        self.zero_bytes + self.non_zero_bytes
+    }
+
+    /// Returns true if the calldata is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gas cost of the calldata given the per byte costs
+    pub fn gas_cost(&self, zero_byte_cost: U256, non_zero_byte_cost: U256) -> U256 {
+        zero_byte_cost * self.zero_bytes + non_zero_byte_cost * self.non_zero_bytes
+    }
+}
+
 pub(crate) fn op_calldata_gas_cost<UO: AbiEncode>(
     uo: UO,
     zero_byte_cost: U256,
@@ -418,6 +547,11 @@ pub(crate) fn op_calldata_gas_cost<UO: AbiEncode>(
     call_data_cost + per_word_cost * length_in_words
 }
 
+/// Splits a 2D nonce into its key, the upper 192 bits, and its sequence, the lower 64 bits
+pub fn split_nonce(nonce: U256) -> (U256, u64) {
+    (nonce >> 64, nonce.low_u64())
+}
+
 /// Calculates the size a byte array padded to the next largest multiple of 32
 pub(crate) fn byte_array_abi_len(b: &Bytes) -> usize {
     (b.len() + 31) & !31
@@ -436,6 +570,62 @@ pub(crate) fn default_if_none_or_equal<V: Copy + PartialEq>(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_variant_version() {
+        let op: UserOperationVariant = v0_6::UserOperation::default().into();
+        assert_eq!(op.version(), EntryPointVersion::V0_6);
+        assert_eq!(op.version().to_string(), "v0.6");
+    }
+
+    #[test]
+    fn test_nonce_key_and_sequence() {
+        let op = v0_6::UserOperation {
+            nonce: (U256::from(7) << 64) + U256::from(3),
+            ..Default::default()
+        };
+        assert_eq!(op.nonce_key(), 7.into());
+        assert_eq!(op.nonce_sequence(), 3);
+        assert_eq!(split_nonce(op.nonce), (7.into(), 3));
+    }
+
+    #[test]
+    fn test_paymaster_token() {
+        let paymaster = Address::random();
+        let token = Address::random();
+        let token_paymaster_op = |data: &[u8]| v0_6::UserOperation {
+            paymaster_and_data: [paymaster.as_bytes(), data].concat().into(),
+            ..Default::default()
+        };
+
+        // token address followed by a max token amount and signature
+        let op = token_paymaster_op(&[token.as_bytes(), &[1; 32], &[2; 65]].concat());
+        assert_eq!(op.paymaster_token(), Some(token));
+
+        // verifying paymasters start with abi encoded timestamps, leaving the address empty
+        let op = token_paymaster_op(&[0; 64]);
+        assert_eq!(op.paymaster_token(), None);
+
+        let op = token_paymaster_op(&[]);
+        assert_eq!(op.paymaster_token(), None);
+
+        assert_eq!(v0_6::UserOperation::default().paymaster_token(), None);
+    }
+
+    #[test]
+    fn test_is_deployment() {
+        let factory = Address::random();
+        let op = v0_6::UserOperation {
+            init_code: [factory.as_bytes(), &[1; 4]].concat().into(),
+            ..Default::default()
+        };
+        assert!(op.is_deployment());
+        assert!(UserOperationVariant::from(op).is_deployment());
+
+        let op = v0_6::UserOperation::default();
+        assert!(!op.is_deployment());
+        assert!(!UserOperationVariant::from(op).is_deployment());
+    }
+
     #[test]
     fn test_byte_array_abi_len() {
         let b = Bytes::from(vec![0u8; 32]);
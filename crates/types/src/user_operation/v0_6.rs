@@ -12,7 +12,7 @@
 // If not, see https://www.gnu.org/licenses/.
 
 use ethers::{
-    abi::{encode, Token},
+    abi::{encode, AbiEncode, Token},
     types::{Address, Bytes, H256, U256},
     utils::keccak256,
 };
@@ -21,6 +21,7 @@ use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 
 use super::{UserOperation as UserOperationTrait, UserOperationId, UserOperationVariant};
+use crate::contracts::v0_6::i_entry_point::HandleOpsCall;
 pub use crate::contracts::v0_6::i_entry_point::{UserOperation, UserOpsPerAggregator};
 use crate::{
     chain::ChainSpec,
@@ -86,12 +87,28 @@ impl UserOperationTrait for UserOperation {
         Self::get_address_from_field(&self.paymaster_and_data)
     }
 
+    fn paymaster_data(&self) -> &[u8] {
+        self.paymaster_and_data.get(20..).unwrap_or_default()
+    }
+
     fn call_data(&self) -> &Bytes {
         &self.call_data
     }
 
-    fn max_gas_cost(&self) -> U256 {
-        let mul = if self.paymaster().is_some() { 3 } else { 1 };
+    fn signature(&self) -> &Bytes {
+        &self.signature
+    }
+
+    fn init_code_len(&self) -> usize {
+        self.init_code.len()
+    }
+
+    fn max_gas_cost(&self, chain_spec: &ChainSpec) -> U256 {
+        let mul = if self.paymaster().is_some() {
+            chain_spec.paymaster_gas_policy_v0_6.max_gas_cost_multiplier
+        } else {
+            1
+        };
         self.max_fee_per_gas
             * (self.pre_verification_gas + self.call_gas_limit + self.verification_gas_limit * mul)
     }
@@ -132,8 +149,14 @@ impl UserOperationTrait for UserOperation {
         self.verification_gas_limit
     }
 
-    fn total_verification_gas_limit(&self) -> U256 {
-        let mul = if self.paymaster().is_some() { 2 } else { 1 };
+    fn total_verification_gas_limit(&self, chain_spec: &ChainSpec) -> U256 {
+        let mul = if self.paymaster().is_some() {
+            chain_spec
+                .paymaster_gas_policy_v0_6
+                .verification_gas_limit_multiplier
+        } else {
+            1
+        };
         self.verification_gas_limit * mul
     }
 
@@ -152,7 +175,7 @@ impl UserOperationTrait for UserOperation {
             chain_spec.calldata_non_zero_byte_gas,
             chain_spec.per_user_op_word_gas,
         ) + chain_spec.per_user_op_v0_6_gas
-            + (if self.factory().is_some() {
+            + (if self.is_deployment() {
                 chain_spec.per_user_op_deploy_overhead_gas
             } else {
                 U256::zero()
@@ -175,6 +198,15 @@ impl UserOperationTrait for UserOperation {
             + super::byte_array_abi_len(&self.paymaster_and_data)
             + super::byte_array_abi_len(&self.signature)
     }
+
+    fn single_op_bundle_calldata(&self, beneficiary: Address) -> Bytes {
+        HandleOpsCall {
+            ops: vec![self.clone()],
+            beneficiary,
+        }
+        .encode()
+        .into()
+    }
 }
 
 impl UserOperation {
@@ -358,7 +390,8 @@ impl UserOperationOptionalGas {
             verification_gas_limit: vgl,
             call_gas_limit: cgl,
             pre_verification_gas: pvg,
-            // These aren't used in gas estimation, set to if unset 0 so that there are no payment attempts during gas estimation
+            // These aren't used in gas estimation, set to if unset 0 so that there are no payment attempts during gas estimation.
+            // Fees provided by the client are kept so estimate-then-submit flows see the submitted fees.
             max_fee_per_gas: self.max_fee_per_gas.unwrap_or_default(),
             max_priority_fee_per_gas: self.max_priority_fee_per_gas.unwrap_or_default(),
         }
@@ -399,6 +432,7 @@ mod tests {
     use ethers::types::{Bytes, U256};
 
     use super::*;
+    use crate::chain::PaymasterGasPolicy;
 
     #[test]
     fn test_hash_zeroed() {
@@ -509,6 +543,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_into_user_operation_keeps_provided_fees() {
+        let op = UserOperationOptionalGas {
+            sender: Address::random(),
+            nonce: 1.into(),
+            init_code: Bytes::default(),
+            call_data: Bytes::default(),
+            call_gas_limit: None,
+            verification_gas_limit: None,
+            pre_verification_gas: None,
+            max_fee_per_gas: Some(100.into()),
+            max_priority_fee_per_gas: Some(10.into()),
+            paymaster_and_data: Bytes::default(),
+            signature: Bytes::default(),
+        };
+
+        let uo = op
+            .clone()
+            .into_user_operation(1_000_000.into(), 500_000.into());
+        assert_eq!(uo.max_fee_per_gas, 100.into());
+        assert_eq!(uo.max_priority_fee_per_gas, 10.into());
+        assert_eq!(uo.call_gas_limit, 1_000_000.into());
+        assert_eq!(uo.verification_gas_limit, 500_000.into());
+
+        let unset = UserOperationOptionalGas {
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            ..op
+        }
+        .into_user_operation(1_000_000.into(), 500_000.into());
+        assert_eq!(unset.max_fee_per_gas, U256::zero());
+        assert_eq!(unset.max_priority_fee_per_gas, U256::zero());
+    }
+
     #[test]
     fn test_get_address_from_field() {
         let paymaster_and_data: Bytes =
@@ -523,4 +591,84 @@ mod tests {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn test_single_op_bundle_calldata() {
+        // selector, ops offset, beneficiary, ops length, and the offset of the op
+        let bundle_overhead = 4 + 4 * 32;
+        let beneficiary = Address::random();
+
+        let small = UserOperation::default();
+        let small_calldata = small.single_op_bundle_calldata(beneficiary);
+        assert_eq!(
+            small_calldata.len(),
+            bundle_overhead + small.abi_encoded_size()
+        );
+
+        let large = UserOperation {
+            call_data: vec![1; 1000].into(),
+            signature: vec![1; 65].into(),
+            ..Default::default()
+        };
+        let large_calldata = large.single_op_bundle_calldata(beneficiary);
+        assert_eq!(
+            large_calldata.len(),
+            bundle_overhead + large.abi_encoded_size()
+        );
+        // 1000 bytes padded to 1024, 65 bytes padded to 96
+        assert_eq!(large_calldata.len() - small_calldata.len(), 1024 + 96);
+
+        let small_counts = CalldataByteCounts::new(&small_calldata);
+        let large_counts = CalldataByteCounts::new(&large_calldata);
+        assert_eq!(small_counts.len(), small_calldata.len());
+        assert_eq!(large_counts.len(), large_calldata.len());
+        assert_eq!(
+            large_counts.non_zero_bytes - small_counts.non_zero_bytes,
+            // data bytes, plus the non-zero bytes of the lengths 0x03e8 and 0x41
+            1000 + 65 + 3
+        );
+        assert!(
+            large_counts.gas_cost(4.into(), 16.into()) > small_counts.gas_cost(4.into(), 16.into())
+        );
+    }
+
+    #[test]
+    fn test_paymaster_gas_multipliers() {
+        let op = UserOperation {
+            call_gas_limit: 100.into(),
+            verification_gas_limit: 1_000.into(),
+            pre_verification_gas: 10.into(),
+            max_fee_per_gas: 2.into(),
+            paymaster_and_data: Address::random().as_bytes().to_vec().into(),
+            ..Default::default()
+        };
+        let cs = ChainSpec::default();
+        assert_eq!(op.max_gas_cost(&cs), U256::from(2 * (10 + 100 + 3 * 1_000)));
+        assert_eq!(op.total_verification_gas_limit(&cs), U256::from(2 * 1_000));
+
+        // a custom entry point's multipliers
+        let custom = ChainSpec {
+            paymaster_gas_policy_v0_6: PaymasterGasPolicy {
+                max_gas_cost_multiplier: 4,
+                verification_gas_limit_multiplier: 3,
+            },
+            ..ChainSpec::default()
+        };
+        assert_eq!(
+            op.max_gas_cost(&custom),
+            U256::from(2 * (10 + 100 + 4 * 1_000))
+        );
+        assert_eq!(
+            op.total_verification_gas_limit(&custom),
+            U256::from(3 * 1_000)
+        );
+
+        // multipliers only apply to ops with a paymaster
+        let op = UserOperation {
+            paymaster_and_data: Bytes::default(),
+            ..op
+        };
+        assert_eq!(op.max_gas_cost(&custom), U256::from(2 * (10 + 100 + 1_000)));
+        assert_eq!(op.total_verification_gas_limit(&custom), U256::from(1_000));
+    }
 }
@@ -12,7 +12,7 @@
 // If not, see https://www.gnu.org/licenses/.
 
 use ethers::{
-    abi::{encode, Token},
+    abi::{encode, AbiEncode, Token},
     types::{Address, Bytes, H256, U128, U256},
     utils::keccak256,
 };
@@ -20,7 +20,9 @@ use rand::RngCore;
 
 use super::{UserOperation as UserOperationTrait, UserOperationId, UserOperationVariant};
 use crate::{
-    chain::ChainSpec, contracts::v0_7::shared_types::PackedUserOperation, Entity, EntryPointVersion,
+    chain::ChainSpec,
+    contracts::v0_7::{i_entry_point::HandleOpsCall, shared_types::PackedUserOperation},
+    Entity, EntryPointVersion,
 };
 
 /// Gas overhead required by the entry point contract for the inner call
@@ -129,6 +131,10 @@ impl UserOperationTrait for UserOperation {
         self.paymaster
     }
 
+    fn paymaster_data(&self) -> &[u8] {
+        &self.paymaster_data
+    }
+
     fn factory(&self) -> Option<Address> {
         self.factory
     }
@@ -137,7 +143,16 @@ impl UserOperationTrait for UserOperation {
         &self.call_data
     }
 
-    fn max_gas_cost(&self) -> U256 {
+    fn signature(&self) -> &Bytes {
+        &self.signature
+    }
+
+    fn init_code_len(&self) -> usize {
+        self.factory
+            .map_or(0, |_| Address::len_bytes() + self.factory_data.len())
+    }
+
+    fn max_gas_cost(&self, _chain_spec: &ChainSpec) -> U256 {
         U256::from(self.max_fee_per_gas)
             * (self.pre_verification_gas
                 + self.call_gas_limit
@@ -185,7 +200,7 @@ impl UserOperationTrait for UserOperation {
         U256::from(self.verification_gas_limit)
     }
 
-    fn total_verification_gas_limit(&self) -> U256 {
+    fn total_verification_gas_limit(&self, _chain_spec: &ChainSpec) -> U256 {
         U256::from(self.verification_gas_limit) + U256::from(self.paymaster_verification_gas_limit)
     }
 
@@ -238,6 +253,15 @@ impl UserOperationTrait for UserOperation {
             + super::byte_array_abi_len(&self.packed.paymaster_and_data)
             + super::byte_array_abi_len(&self.packed.signature)
     }
+
+    fn single_op_bundle_calldata(&self, beneficiary: Address) -> Bytes {
+        HandleOpsCall {
+            ops: vec![self.packed.clone()],
+            beneficiary,
+        }
+        .encode()
+        .into()
+    }
 }
 
 impl UserOperation {
@@ -39,6 +39,19 @@ use crate::{
 const SIG_VALIDATION_FAILED: Address =
     H160([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
 
+/// The maximum value of the 6-byte `validUntil` field, used by the entry point when `validUntil` is zero
+const MAX_VALID_UNTIL: u64 = 0xffff_ffff_ffff;
+
+/// A `validUntil` of zero means "no expiry", not "expired at epoch". This is the only place
+/// it is normalized, every validity window is decoded through here.
+fn normalize_valid_until(valid_until: u64) -> u64 {
+    if valid_until == 0 {
+        MAX_VALID_UNTIL
+    } else {
+        valid_until
+    }
+}
+
 /// Error during validation simulation
 #[derive(Clone, Debug, thiserror::Error, Ord, PartialOrd, Eq, PartialEq)]
 pub enum ValidationRevert {
@@ -286,7 +299,7 @@ impl From<(U256, U256, bool, u64, u64, Bytes)> for ValidationReturnInfo {
             account_sig_failed: sig_failed,
             paymaster_sig_failed: sig_failed,
             valid_after: valid_after.into(),
-            valid_until: valid_until.into(),
+            valid_until: normalize_valid_until(valid_until).into(),
             paymaster_context,
         }
     }
@@ -350,14 +363,16 @@ impl ValidationData {
 
 /// Parse the validation data from a U256
 ///
-/// Works for both v0.6 and v0.7 validation data
+/// Works for both v0.6 and v0.7 validation data. The packed layout is
+/// `validAfter (6 bytes) | validUntil (6 bytes) | authorizer (20 bytes)`, and a
+/// `validUntil` of zero means the data never expires.
 pub fn parse_validation_data(data: U256) -> ValidationData {
     let slice: [u8; 32] = data.into();
     let aggregator = Address::from_slice(&slice[12..]);
 
     let mut buf = [0; 8];
     buf[2..8].copy_from_slice(&slice[6..12]);
-    let valid_until = u64::from_be_bytes(buf);
+    let valid_until = normalize_valid_until(u64::from_be_bytes(buf));
 
     let mut buf = [0; 8];
     buf[2..8].copy_from_slice(&slice[..6]);
@@ -434,7 +449,15 @@ impl From<AggregatorStakeInfoV0_7> for AggregatorInfo {
 
 #[cfg(test)]
 mod tests {
-    use super::parse_validation_data;
+    use super::*;
+
+    fn validation_data(valid_after: u64, valid_until: u64, aggregator: Address) -> U256 {
+        let mut data = [0; 32];
+        data[..6].copy_from_slice(&valid_after.to_be_bytes()[2..]);
+        data[6..12].copy_from_slice(&valid_until.to_be_bytes()[2..]);
+        data[12..].copy_from_slice(aggregator.as_bytes());
+        U256::from_big_endian(&data)
+    }
 
     #[test]
     fn test_parse_validation_data() {
@@ -450,4 +473,58 @@ mod tests {
         assert_eq!(parsed.valid_until, 0x66778899aabb);
         assert_eq!(parsed.valid_after, 0x001122334455);
     }
+
+    #[test]
+    fn test_parse_validation_data_zero_valid_until() {
+        let parsed = parse_validation_data(validation_data(100, 0, Address::zero()));
+        assert_eq!(parsed.valid_after, 100);
+        assert_eq!(parsed.valid_until, MAX_VALID_UNTIL);
+        assert!(parsed.signature_valid());
+        assert_eq!(parsed.aggregator(), None);
+    }
+
+    #[test]
+    fn test_return_info_v0_6_zero_valid_until() {
+        let return_info = ValidationReturnInfo::from((
+            U256::zero(),
+            U256::zero(),
+            false,
+            100,
+            0,
+            Bytes::default(),
+        ));
+        assert_eq!(return_info.valid_until, Timestamp::new(MAX_VALID_UNTIL));
+        assert!(return_info.is_valid_time_range());
+    }
+
+    #[test]
+    fn test_return_info_intersects_account_and_paymaster_windows() {
+        let return_info = ValidationReturnInfo::from(ReturnInfoV0_7 {
+            pre_op_gas: U256::zero(),
+            prefund: U256::zero(),
+            account_validation_data: validation_data(100, 1000, Address::zero()),
+            paymaster_validation_data: validation_data(500, 2000, Address::zero()),
+            paymaster_context: Bytes::default(),
+        });
+
+        assert_eq!(return_info.valid_after, Timestamp::new(500));
+        assert_eq!(return_info.valid_until, Timestamp::new(1000));
+        assert!(!return_info.account_sig_failed);
+        assert!(!return_info.paymaster_sig_failed);
+    }
+
+    #[test]
+    fn test_return_info_paymaster_without_expiry() {
+        let return_info = ValidationReturnInfo::from(ReturnInfoV0_7 {
+            pre_op_gas: U256::zero(),
+            prefund: U256::zero(),
+            account_validation_data: validation_data(100, 1000, Address::zero()),
+            paymaster_validation_data: validation_data(0, 0, SIG_VALIDATION_FAILED),
+            paymaster_context: Bytes::default(),
+        });
+
+        assert_eq!(return_info.valid_after, Timestamp::new(100));
+        assert_eq!(return_info.valid_until, Timestamp::new(1000));
+        assert!(return_info.paymaster_sig_failed);
+    }
 }